@@ -0,0 +1,22 @@
+-- Audit log of resolved tool-call approvals
+CREATE TABLE approval_events (
+    id                   BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL,
+    execution_process_id BLOB NOT NULL,
+    executor             TEXT,
+    tool_name            TEXT NOT NULL,
+    tool_call_id         TEXT NOT NULL,
+    tool_summary         TEXT NOT NULL,
+    decision             TEXT NOT NULL
+                            CHECK (decision IN ('approved', 'denied', 'timed_out')),
+    decider              TEXT NOT NULL
+                            CHECK (decider IN ('user', 'auto_rule', 'timeout')),
+    reason               TEXT,
+    requested_at         TEXT NOT NULL,
+    resolved_at          TEXT NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_approval_events_workspace_id ON approval_events(workspace_id);
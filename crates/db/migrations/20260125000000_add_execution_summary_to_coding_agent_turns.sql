@@ -0,0 +1,2 @@
+-- JSON ExecutionSummary of the finished run (files changed, commands, exit code)
+ALTER TABLE coding_agent_turns ADD COLUMN execution_summary TEXT;
@@ -0,0 +1,12 @@
+-- blocker_id must be Done before blocked_id may start
+CREATE TABLE task_dependencies (
+    blocker_id BLOB NOT NULL,
+    blocked_id BLOB NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (blocker_id, blocked_id),
+    CHECK (blocker_id != blocked_id),
+    FOREIGN KEY (blocker_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (blocked_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_dependencies_blocked_id ON task_dependencies(blocked_id);
@@ -0,0 +1,4 @@
+-- Archived tasks are hidden from the board and read-only, but keep their history
+ALTER TABLE tasks ADD COLUMN archived_at DATETIME;
+
+CREATE INDEX idx_tasks_project_id_archived_at ON tasks(project_id, archived_at);
@@ -0,0 +1,11 @@
+-- Deleted tasks are kept, hidden, until the purge job removes them after the
+-- retention period
+ALTER TABLE tasks ADD COLUMN deleted_at DATETIME;
+
+CREATE INDEX idx_tasks_deleted_at ON tasks(deleted_at);
+
+-- A deleted task must not stop the same shared task from being linked again
+DROP INDEX IF EXISTS idx_tasks_shared_task_unique;
+CREATE UNIQUE INDEX idx_tasks_shared_task_unique
+    ON tasks(shared_task_id)
+    WHERE shared_task_id IS NOT NULL AND deleted_at IS NULL;
@@ -0,0 +1,14 @@
+-- Audit trail of task mutations, newest read first
+CREATE TABLE task_events (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id     BLOB NOT NULL,
+    event_type  TEXT NOT NULL,
+    old_value   TEXT,
+    new_value   TEXT,
+    actor       TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_events_task_id_id ON task_events(task_id, id);
+CREATE INDEX idx_task_events_created_at ON task_events(created_at);
@@ -0,0 +1,28 @@
+-- Full-text index over task titles and descriptions, kept in sync by triggers
+CREATE VIRTUAL TABLE tasks_fts USING fts5(
+    title,
+    description,
+    content = 'tasks',
+    content_rowid = 'rowid',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO tasks_fts(rowid, title, description)
+    SELECT rowid, title, description FROM tasks;
+
+CREATE TRIGGER tasks_fts_after_insert AFTER INSERT ON tasks BEGIN
+    INSERT INTO tasks_fts(rowid, title, description)
+        VALUES (new.rowid, new.title, new.description);
+END;
+
+CREATE TRIGGER tasks_fts_after_delete AFTER DELETE ON tasks BEGIN
+    INSERT INTO tasks_fts(tasks_fts, rowid, title, description)
+        VALUES ('delete', old.rowid, old.title, old.description);
+END;
+
+CREATE TRIGGER tasks_fts_after_update AFTER UPDATE OF title, description ON tasks BEGIN
+    INSERT INTO tasks_fts(tasks_fts, rowid, title, description)
+        VALUES ('delete', old.rowid, old.title, old.description);
+    INSERT INTO tasks_fts(rowid, title, description)
+        VALUES (new.rowid, new.title, new.description);
+END;
@@ -0,0 +1,5 @@
+-- How urgent a task is relative to others in its project; the board sorts by it
+ALTER TABLE tasks ADD COLUMN priority TEXT NOT NULL DEFAULT 'medium'
+    CHECK (priority IN ('low', 'medium', 'high', 'urgent'));
+
+CREATE INDEX idx_tasks_project_id_priority ON tasks(project_id, priority);
@@ -0,0 +1,17 @@
+-- Manual order of a task within its board column; ranks compare as strings
+-- so a card can move between two others without renumbering the column
+ALTER TABLE tasks ADD COLUMN position TEXT;
+
+-- Existing columns keep their newest-first order
+UPDATE tasks
+SET position = ranked.position
+FROM (
+    SELECT id,
+           printf('%08dV', ROW_NUMBER() OVER (
+               PARTITION BY project_id, status ORDER BY created_at DESC
+           )) AS position
+      FROM tasks
+) AS ranked
+WHERE ranked.id = tasks.id;
+
+CREATE INDEX idx_tasks_project_id_status_position ON tasks(project_id, status, position);
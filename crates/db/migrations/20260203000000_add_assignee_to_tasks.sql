@@ -0,0 +1,4 @@
+-- Who owns a task, as a free-form name; NULL when nobody has picked it up
+ALTER TABLE tasks ADD COLUMN assignee TEXT;
+
+CREATE INDEX idx_tasks_project_id_assignee ON tasks(project_id, assignee);
@@ -0,0 +1,7 @@
+-- Calendar date a task should be finished by, and its share of the parent's
+-- schedule when due dates are spread over the tasks created from it
+ALTER TABLE tasks ADD COLUMN due_date DATE;
+ALTER TABLE tasks ADD COLUMN schedule_weight REAL NOT NULL DEFAULT 1.0
+    CHECK (schedule_weight > 0);
+
+CREATE INDEX idx_tasks_due_date ON tasks(due_date) WHERE due_date IS NOT NULL;
@@ -0,0 +1,30 @@
+-- Reusable task payloads, optionally recreated on a schedule
+CREATE TABLE task_templates (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB NOT NULL,
+    title       TEXT NOT NULL,
+    description TEXT,
+    priority    TEXT NOT NULL DEFAULT 'medium'
+                   CHECK (priority IN ('low', 'medium', 'high', 'urgent')),
+    assignee    TEXT,
+    recurrence  TEXT,
+    enabled     INTEGER NOT NULL DEFAULT 1,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_templates_project_id ON task_templates(project_id);
+
+-- Tasks created from a template. Scheduled runs carry the occurrence they
+-- were created for, which may only be claimed once; manual runs have none.
+CREATE TABLE task_template_runs (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    template_id   BLOB NOT NULL,
+    occurrence_at TEXT,
+    task_id       BLOB NOT NULL,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (template_id) REFERENCES task_templates(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    UNIQUE (template_id, occurrence_at)
+);
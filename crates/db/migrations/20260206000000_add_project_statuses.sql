@@ -0,0 +1,107 @@
+-- Board columns per project. Each maps onto one of the core task statuses,
+-- its category, which keeps driving transitions and rollups. The five
+-- built-in columns use the category as their key.
+CREATE TABLE project_statuses (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB NOT NULL,
+    key         TEXT NOT NULL,
+    name        TEXT NOT NULL,
+    category    TEXT NOT NULL
+                   CHECK (category IN ('todo', 'inprogress', 'inreview', 'done', 'cancelled')),
+    sort_order  INTEGER NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    UNIQUE (project_id, key)
+);
+
+CREATE TABLE default_project_statuses (
+    key        TEXT PRIMARY KEY,
+    name       TEXT NOT NULL,
+    sort_order INTEGER NOT NULL
+);
+
+INSERT INTO default_project_statuses (key, name, sort_order) VALUES
+    ('todo', 'To Do', 0),
+    ('inprogress', 'In Progress', 1),
+    ('inreview', 'In Review', 2),
+    ('done', 'Done', 3),
+    ('cancelled', 'Cancelled', 4);
+
+INSERT INTO project_statuses (id, project_id, key, name, category, sort_order)
+SELECT randomblob(16), p.id, d.key, d.name, d.key, d.sort_order
+FROM projects p CROSS JOIN default_project_statuses d;
+
+CREATE TRIGGER seed_project_statuses
+AFTER INSERT ON projects
+BEGIN
+    INSERT INTO project_statuses (id, project_id, key, name, category, sort_order)
+    SELECT randomblob(16), NEW.id, d.key, d.name, d.key, d.sort_order
+    FROM default_project_statuses d;
+END;
+
+-- The project status a task sits in, built-in columns included, so the
+-- board is laid out from project statuses alone. `status` stays as the
+-- category of that column.
+ALTER TABLE tasks ADD COLUMN status_key TEXT;
+
+UPDATE tasks SET status_key = status;
+
+CREATE INDEX idx_tasks_project_id_status_key ON tasks(project_id, status_key)
+    WHERE status_key IS NOT NULL;
+
+-- Tasks created without a column start in the built-in one of their status
+CREATE TRIGGER default_task_status_key
+AFTER INSERT ON tasks
+WHEN NEW.status_key IS NULL
+BEGIN
+    UPDATE tasks SET status_key = NEW.status WHERE id = NEW.id;
+END;
+
+-- A status change keeps the task in its column only while the column is of
+-- the new status, as when both are changed together
+CREATE TRIGGER reset_task_status_key
+AFTER UPDATE OF status ON tasks
+WHEN NEW.status != OLD.status
+ AND NOT EXISTS (
+     SELECT 1 FROM project_statuses ps
+      WHERE ps.project_id = NEW.project_id
+        AND ps.key = NEW.status_key
+        AND ps.category = NEW.status
+ )
+BEGIN
+    UPDATE tasks SET status_key = NEW.status WHERE id = NEW.id;
+END;
+
+-- The key must be a status of the task's project with the task's status as
+-- its category
+CREATE TRIGGER check_task_status_key_insert
+BEFORE INSERT ON tasks
+WHEN NEW.status_key IS NOT NULL
+ AND NOT EXISTS (
+     SELECT 1 FROM project_statuses ps
+      WHERE ps.project_id = NEW.project_id
+        AND ps.key = NEW.status_key
+        AND ps.category = NEW.status
+ )
+BEGIN
+    SELECT RAISE(ABORT, 'status_key must be a project status of the task''s status');
+END;
+
+-- A status change that leaves the key alone is moved to a matching column by
+-- reset_task_status_key afterwards, so only that case goes unchecked here
+CREATE TRIGGER check_task_status_key_update
+BEFORE UPDATE OF status, status_key ON tasks
+WHEN (NEW.status_key IS NOT OLD.status_key OR NEW.status = OLD.status)
+ AND (
+     NEW.status_key IS NULL
+  OR NOT EXISTS (
+     SELECT 1 FROM project_statuses ps
+      WHERE ps.project_id = NEW.project_id
+        AND ps.key = NEW.status_key
+        AND ps.category = NEW.status
+  )
+ )
+BEGIN
+    SELECT RAISE(ABORT, 'status_key must be a project status of the task''s status');
+END;
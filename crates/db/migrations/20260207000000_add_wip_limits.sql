@@ -0,0 +1,3 @@
+-- Most tasks a status column should hold at once; unlimited when NULL
+ALTER TABLE project_statuses ADD COLUMN wip_limit INTEGER
+    CHECK (wip_limit IS NULL OR wip_limit > 0);
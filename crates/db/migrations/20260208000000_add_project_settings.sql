@@ -0,0 +1,23 @@
+-- Per-project settings as one JSON object, so adding a setting needs no
+-- migration. Keys a build does not know are left in place when it writes.
+ALTER TABLE projects ADD COLUMN settings TEXT NOT NULL DEFAULT '{}'
+    CHECK (json_valid(settings) AND json_type(settings) = 'object');
+
+-- Projects that predate the task status transition rules keep moving tasks
+-- freely, as they did before; only projects created from now on get the
+-- rules by default
+UPDATE projects
+   SET settings = json_set(settings, '$.relaxed_status_transitions', json('true'));
+
+-- Audit trail of project level changes such as settings updates
+CREATE TABLE project_events (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id  BLOB NOT NULL,
+    event_type  TEXT NOT NULL,
+    old_value   TEXT,
+    new_value   TEXT,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_project_events_project_id_id ON project_events(project_id, id);
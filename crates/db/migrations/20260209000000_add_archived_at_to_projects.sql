@@ -0,0 +1,3 @@
+-- Archived projects drop out of listings and accept no new tasks or
+-- attempts. Their tasks are left as they are, so unarchiving restores them.
+ALTER TABLE projects ADD COLUMN archived_at TEXT;
@@ -0,0 +1,22 @@
+-- Tasks imported from an external tracker keep a link to their issue, so
+-- status changes can be pushed back. synced_event_id is the last status
+-- change event already pushed, and remote_state the issue state as last
+-- seen, to tell changes made upstream from our own.
+CREATE TABLE task_external_links (
+    id              BLOB PRIMARY KEY,
+    task_id         BLOB NOT NULL,
+    provider        TEXT NOT NULL CHECK (provider IN ('github')),
+    repository      TEXT NOT NULL,
+    external_id     TEXT NOT NULL,
+    url             TEXT NOT NULL,
+    remote_state    TEXT NOT NULL DEFAULT 'open'
+                       CHECK (remote_state IN ('open', 'closed')),
+    synced_event_id INTEGER NOT NULL DEFAULT 0,
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    UNIQUE (task_id, provider)
+);
+
+CREATE INDEX idx_task_external_links_issue
+    ON task_external_links(provider, repository, external_id);
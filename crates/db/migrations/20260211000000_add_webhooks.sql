@@ -0,0 +1,82 @@
+-- Outbound webhooks of a project. events lists the event types to send,
+-- an empty list sends all of them.
+CREATE TABLE webhooks (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB NOT NULL,
+    url         TEXT NOT NULL,
+    secret      TEXT NOT NULL,
+    events      TEXT NOT NULL DEFAULT '[]'
+                   CHECK (json_valid(events) AND json_type(events) = 'array'),
+    enabled     INTEGER NOT NULL DEFAULT 1,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhooks_project_id ON webhooks(project_id);
+
+-- Events waiting to be turned into deliveries. Triggers only queue them
+-- while the project has an enabled webhook, and the dispatcher picks them
+-- up outside the transaction that caused them.
+CREATE TABLE webhook_outbox (
+    id                   INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id           BLOB NOT NULL,
+    task_id              BLOB NOT NULL,
+    event_type           TEXT NOT NULL,
+    old_value            TEXT,
+    new_value            TEXT,
+    execution_process_id BLOB,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+-- One payload sent, or to be sent, to one webhook
+CREATE TABLE webhook_deliveries (
+    id              BLOB PRIMARY KEY,
+    webhook_id      BLOB NOT NULL,
+    event_type      TEXT NOT NULL,
+    payload         TEXT NOT NULL,
+    status          TEXT NOT NULL DEFAULT 'pending'
+                       CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    response_status INTEGER,
+    error           TEXT,
+    next_attempt_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhook_deliveries_pending ON webhook_deliveries(next_attempt_at)
+    WHERE status = 'pending';
+CREATE INDEX idx_webhook_deliveries_webhook_id ON webhook_deliveries(webhook_id, created_at);
+
+CREATE TRIGGER queue_task_webhook_events
+AFTER INSERT ON task_events
+WHEN NEW.event_type IN ('created', 'status_change', 'attempt_started')
+BEGIN
+    INSERT INTO webhook_outbox (project_id, task_id, event_type, old_value, new_value)
+    SELECT t.project_id, t.id,
+           CASE NEW.event_type
+               WHEN 'created' THEN 'task.created'
+               WHEN 'status_change' THEN 'task.status_changed'
+               ELSE 'attempt.started'
+           END,
+           NEW.old_value, NEW.new_value
+    FROM tasks t
+    WHERE t.id = NEW.task_id
+      AND EXISTS (SELECT 1 FROM webhooks w WHERE w.project_id = t.project_id AND w.enabled);
+END;
+
+CREATE TRIGGER queue_failed_attempt_webhook_events
+AFTER UPDATE OF status ON execution_processes
+WHEN NEW.status = 'failed' AND OLD.status != 'failed' AND NEW.run_reason = 'codingagent'
+BEGIN
+    INSERT INTO webhook_outbox (project_id, task_id, event_type, new_value, execution_process_id)
+    SELECT t.project_id, t.id, 'attempt.failed',
+           json_object('exit_code', NEW.exit_code), NEW.id
+    FROM sessions s
+    JOIN workspaces ws ON ws.id = s.workspace_id
+    JOIN tasks t ON t.id = ws.task_id
+    WHERE s.id = NEW.session_id
+      AND EXISTS (SELECT 1 FROM webhooks w WHERE w.project_id = t.project_id AND w.enabled);
+END;
@@ -0,0 +1,14 @@
+-- Log the end of coding agent attempts next to their start, so the task
+-- activity log covers the whole attempt lifecycle
+CREATE TRIGGER log_attempt_finished
+AFTER UPDATE OF status ON execution_processes
+WHEN OLD.status = 'running' AND NEW.status != 'running' AND NEW.run_reason = 'codingagent'
+BEGIN
+    INSERT INTO task_events (task_id, event_type, new_value, actor)
+    SELECT ws.task_id, 'attempt_finished',
+           json_object('status', NEW.status, 'exit_code', NEW.exit_code),
+           'system'
+    FROM sessions s
+    JOIN workspaces ws ON ws.id = s.workspace_id
+    WHERE s.id = NEW.session_id;
+END;
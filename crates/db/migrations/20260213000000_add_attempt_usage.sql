@@ -0,0 +1,10 @@
+-- What a finished coding agent run used, written with its execution
+-- summary. total_tokens is NULL when the executor reported no usage, and
+-- runs without a row at all are counted as unreported by the rollups.
+CREATE TABLE attempt_usage (
+    execution_process_id BLOB PRIMARY KEY,
+    total_tokens         INTEGER,
+    duration_ms          INTEGER NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
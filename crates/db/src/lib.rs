@@ -1,13 +1,50 @@
-use std::{str::FromStr, sync::Arc};
+use std::{path::Path, str::FromStr, sync::Arc, time::Duration};
 
 use sqlx::{
-    Error, Pool, Sqlite, SqlitePool,
+    Error, Pool, Sqlite,
     migrate::MigrateError,
     sqlite::{SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePoolOptions},
 };
 use utils::assets::asset_dir;
 
 pub mod models;
+pub mod retry;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_support;
+
+/// How connections to the app database are opened and pooled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DBOptions {
+    pub max_connections: u32,
+    /// How long a statement waits for another connection's write lock
+    /// before failing with `database is locked`
+    pub busy_timeout: Duration,
+}
+
+impl Default for DBOptions {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            busy_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// WAL lets readers carry on while one connection writes, and the busy
+/// timeout makes concurrent writers queue instead of erroring out
+fn connect_options(path: &Path, options: &DBOptions) -> Result<SqliteConnectOptions, Error> {
+    Ok(
+        SqliteConnectOptions::from_str(&format!("sqlite://{}", path.to_string_lossy()))?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .busy_timeout(options.busy_timeout)
+            .foreign_keys(true),
+    )
+}
+
+fn pool_options(options: &DBOptions) -> SqlitePoolOptions {
+    SqlitePoolOptions::new().max_connections(options.max_connections.max(1))
+}
 
 async fn run_migrations(pool: &Pool<Sqlite>) -> Result<(), Error> {
     use std::collections::HashSet;
@@ -73,20 +110,18 @@ pub struct DBService {
 }
 
 impl DBService {
-    pub async fn new() -> Result<DBService, Error> {
-        let database_url = format!(
-            "sqlite://{}",
-            asset_dir().join("db.sqlite").to_string_lossy()
-        );
-        let options = SqliteConnectOptions::from_str(&database_url)?
-            .create_if_missing(true)
-            .journal_mode(SqliteJournalMode::Delete);
-        let pool = SqlitePool::connect_with(options).await?;
+    pub async fn new(options: &DBOptions) -> Result<DBService, Error> {
+        let pool = pool_options(options)
+            .connect_with(connect_options(&asset_dir().join("db.sqlite"), options)?)
+            .await?;
         run_migrations(&pool).await?;
         Ok(DBService { pool })
     }
 
-    pub async fn new_with_after_connect<F>(after_connect: F) -> Result<DBService, Error>
+    pub async fn new_with_after_connect<F>(
+        options: &DBOptions,
+        after_connect: F,
+    ) -> Result<DBService, Error>
     where
         F: for<'a> Fn(
                 &'a mut SqliteConnection,
@@ -96,11 +131,14 @@ impl DBService {
             + Sync
             + 'static,
     {
-        let pool = Self::create_pool(Some(Arc::new(after_connect))).await?;
+        let pool = Self::create_pool(options, Some(Arc::new(after_connect))).await?;
         Ok(DBService { pool })
     }
 
-    async fn create_pool<F>(after_connect: Option<Arc<F>>) -> Result<Pool<Sqlite>, Error>
+    async fn create_pool<F>(
+        options: &DBOptions,
+        after_connect: Option<Arc<F>>,
+    ) -> Result<Pool<Sqlite>, Error>
     where
         F: for<'a> Fn(
                 &'a mut SqliteConnection,
@@ -110,16 +148,10 @@ impl DBService {
             + Sync
             + 'static,
     {
-        let database_url = format!(
-            "sqlite://{}",
-            asset_dir().join("db.sqlite").to_string_lossy()
-        );
-        let options = SqliteConnectOptions::from_str(&database_url)?
-            .create_if_missing(true)
-            .journal_mode(SqliteJournalMode::Delete);
+        let connect_options = connect_options(&asset_dir().join("db.sqlite"), options)?;
 
         let pool = if let Some(hook) = after_connect {
-            SqlitePoolOptions::new()
+            pool_options(options)
                 .after_connect(move |conn, _meta| {
                     let hook = hook.clone();
                     Box::pin(async move {
@@ -127,13 +159,102 @@ impl DBService {
                         Ok(())
                     })
                 })
-                .connect_with(options)
+                .connect_with(connect_options)
                 .await?
         } else {
-            SqlitePool::connect_with(options).await?
+            pool_options(options).connect_with(connect_options).await?
         };
 
         run_migrations(&pool).await?;
         Ok(pool)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        models::{
+            project::{CreateProject, Project},
+            task::{CreateTask, Task, TaskStatus},
+            task_event::{CreateTaskEvent, TaskEvent, TaskEventActor, TaskEventType},
+        },
+        retry::retry_on_busy,
+    };
+
+    #[tokio::test]
+    async fn concurrent_status_updates_do_not_lock_each_other_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = DBOptions {
+            max_connections: 8,
+            ..DBOptions::default()
+        };
+        let pool = pool_options(&options)
+            .connect_with(connect_options(&dir.path().join("db.sqlite"), &options).unwrap())
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+        let foreign_keys: bool = sqlx::query_scalar("PRAGMA foreign_keys")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(foreign_keys);
+
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "Busy".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let mut tasks = Vec::new();
+        for i in 0..8 {
+            let data = CreateTask::from_title_description(project_id, format!("Task {i}"), None);
+            tasks.push(Task::create(&pool, &data, Uuid::new_v4()).await.unwrap());
+        }
+
+        // Every task flips between two statuses from its own writer, each
+        // change followed by its log entry
+        const CHANGES: usize = 25;
+        let writers: Vec<_> = tasks
+            .iter()
+            .map(|task| {
+                let pool = pool.clone();
+                let task_id = task.id;
+                tokio::spawn(async move {
+                    let statuses = [TaskStatus::InProgress, TaskStatus::InReview];
+                    for status in statuses.into_iter().cycle().take(CHANGES) {
+                        retry_on_busy(|| Task::update_status(&pool, task_id, status.clone()))
+                            .await?;
+                        let event = CreateTaskEvent::new(
+                            task_id,
+                            TaskEventType::StatusChange,
+                            TaskEventActor::System,
+                        )
+                        .with_change(None, Some(serde_json::Value::from(status.to_string())));
+                        retry_on_busy(|| TaskEvent::create(&pool, &event)).await?;
+                    }
+                    Ok::<_, Error>(())
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap().unwrap();
+        }
+
+        let changes: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM task_events WHERE event_type = 'status_change'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(changes, (tasks.len() * CHANGES) as i64);
+    }
+}
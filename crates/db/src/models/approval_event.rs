@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "approval_decision", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    Approved,
+    Denied,
+    TimedOut,
+}
+
+/// Who or what resolved an approval request
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "approval_decider", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecider {
+    User,
+    AutoRule,
+    Timeout,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ApprovalEvent {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub execution_process_id: Uuid,
+    pub executor: Option<String>,
+    pub tool_name: String,
+    pub tool_call_id: String,
+    pub tool_summary: String,
+    pub decision: ApprovalDecision,
+    pub decider: ApprovalDecider,
+    pub reason: Option<String>,
+    #[ts(type = "Date")]
+    pub requested_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub resolved_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateApprovalEvent {
+    pub workspace_id: Uuid,
+    pub execution_process_id: Uuid,
+    pub executor: Option<String>,
+    pub tool_name: String,
+    pub tool_call_id: String,
+    pub tool_summary: String,
+    pub decision: ApprovalDecision,
+    pub decider: ApprovalDecider,
+    pub reason: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub resolved_at: DateTime<Utc>,
+}
+
+impl ApprovalEvent {
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateApprovalEvent,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        sqlx::query_as!(
+            ApprovalEvent,
+            r#"INSERT INTO approval_events (
+                    id, workspace_id, execution_process_id, executor, tool_name, tool_call_id,
+                    tool_summary, decision, decider, reason, requested_at, resolved_at, created_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                RETURNING id as "id!: Uuid",
+                          workspace_id as "workspace_id!: Uuid",
+                          execution_process_id as "execution_process_id!: Uuid",
+                          executor,
+                          tool_name,
+                          tool_call_id,
+                          tool_summary,
+                          decision as "decision!: ApprovalDecision",
+                          decider as "decider!: ApprovalDecider",
+                          reason,
+                          requested_at as "requested_at!: DateTime<Utc>",
+                          resolved_at as "resolved_at!: DateTime<Utc>",
+                          created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.workspace_id,
+            data.execution_process_id,
+            data.executor,
+            data.tool_name,
+            data.tool_call_id,
+            data.tool_summary,
+            data.decision,
+            data.decider,
+            data.reason,
+            data.requested_at,
+            data.resolved_at,
+            now
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// All approval events of a task attempt (workspace), oldest first
+    pub async fn find_by_task_attempt(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalEvent,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      executor,
+                      tool_name,
+                      tool_call_id,
+                      tool_summary,
+                      decision as "decision!: ApprovalDecision",
+                      decider as "decider!: ApprovalDecider",
+                      reason,
+                      requested_at as "requested_at!: DateTime<Utc>",
+                      resolved_at as "resolved_at!: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM approval_events
+               WHERE workspace_id = $1
+               ORDER BY resolved_at ASC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,365 @@
+//! What finished coding agent runs used, rolled up per attempt, task and
+//! project. Runs whose executor reported no token usage, or that have no
+//! usage row at all, are counted as unreported and left out of the sums
+//! rather than counted as zero.
+
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, PartialEq, FromRow, Serialize, TS)]
+pub struct UsageTotals {
+    /// Finished coding agent runs
+    #[ts(type = "number")]
+    pub runs: i64,
+    /// Runs without reported token usage, left out of the sums below
+    #[ts(type = "number")]
+    pub unreported_runs: i64,
+    /// Tokens of the runs that reported usage, none when no run did
+    #[ts(type = "number | null")]
+    pub total_tokens: Option<i64>,
+    /// Time the runs with a usage row spent running
+    #[ts(type = "number | null")]
+    pub duration_ms: Option<i64>,
+}
+
+/// Usage of one attempt at a task
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, TS)]
+pub struct AttemptUsage {
+    pub workspace_id: Uuid,
+    pub branch: String,
+    #[sqlx(flatten)]
+    pub usage: UsageTotals,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct TaskUsage {
+    /// All attempts added up
+    pub total: UsageTotals,
+    /// Attempts with finished runs, oldest first
+    pub attempts: Vec<AttemptUsage>,
+}
+
+/// Usage of the runs started in a calendar month, in UTC
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, TS)]
+pub struct MonthlyUsage {
+    /// `YYYY-MM`
+    pub month: String,
+    #[sqlx(flatten)]
+    pub usage: UsageTotals,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct ProjectUsage {
+    /// All tasks added up, archived and deleted ones included
+    pub total: UsageTotals,
+    /// Months with finished runs, oldest first
+    pub months: Vec<MonthlyUsage>,
+}
+
+/// Store what a finished run used, replacing an earlier record of the run
+pub async fn record(
+    pool: &SqlitePool,
+    execution_process_id: Uuid,
+    total_tokens: Option<i64>,
+    duration_ms: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"INSERT INTO attempt_usage (execution_process_id, total_tokens, duration_ms)
+           VALUES ($1, $2, $3)
+           ON CONFLICT (execution_process_id) DO UPDATE
+           SET total_tokens = excluded.total_tokens, duration_ms = excluded.duration_ms"#,
+        execution_process_id,
+        total_tokens,
+        duration_ms
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn task_usage(pool: &SqlitePool, task_id: Uuid) -> Result<TaskUsage, sqlx::Error> {
+    let total = sqlx::query_as!(
+        UsageTotals,
+        r#"SELECT COUNT(*) AS "runs!: i64",
+                 COUNT(*) - COUNT(u.total_tokens) AS "unreported_runs!: i64",
+                 SUM(u.total_tokens) AS "total_tokens: i64",
+                 SUM(u.duration_ms) AS "duration_ms: i64"
+           FROM execution_processes ep
+           JOIN sessions s ON s.id = ep.session_id
+           JOIN workspaces w ON w.id = s.workspace_id
+           JOIN tasks t ON t.id = w.task_id
+           LEFT JOIN attempt_usage u ON u.execution_process_id = ep.id
+           WHERE ep.run_reason = 'codingagent' AND ep.status != 'running'
+             AND t.id = $1"#,
+        task_id
+    )
+    .fetch_one(pool)
+    .await?;
+    let attempts = sqlx::query!(
+        r#"SELECT w.id AS "workspace_id!: Uuid",
+                  w.branch,
+                  COUNT(*) AS "runs!: i64",
+                  COUNT(*) - COUNT(u.total_tokens) AS "unreported_runs!: i64",
+                  SUM(u.total_tokens) AS "total_tokens: i64",
+                  SUM(u.duration_ms) AS "duration_ms: i64"
+           FROM execution_processes ep
+           JOIN sessions s ON s.id = ep.session_id
+           JOIN workspaces w ON w.id = s.workspace_id
+           JOIN tasks t ON t.id = w.task_id
+           LEFT JOIN attempt_usage u ON u.execution_process_id = ep.id
+           WHERE ep.run_reason = 'codingagent' AND ep.status != 'running'
+             AND t.id = $1
+           GROUP BY w.id
+           ORDER BY w.created_at, w.rowid"#,
+        task_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|rec| AttemptUsage {
+        workspace_id: rec.workspace_id,
+        branch: rec.branch,
+        usage: UsageTotals {
+            runs: rec.runs,
+            unreported_runs: rec.unreported_runs,
+            total_tokens: rec.total_tokens,
+            duration_ms: rec.duration_ms,
+        },
+    })
+    .collect();
+
+    Ok(TaskUsage { total, attempts })
+}
+
+pub async fn project_usage(
+    pool: &SqlitePool,
+    project_id: Uuid,
+) -> Result<ProjectUsage, sqlx::Error> {
+    let total = sqlx::query_as!(
+        UsageTotals,
+        r#"SELECT COUNT(*) AS "runs!: i64",
+                 COUNT(*) - COUNT(u.total_tokens) AS "unreported_runs!: i64",
+                 SUM(u.total_tokens) AS "total_tokens: i64",
+                 SUM(u.duration_ms) AS "duration_ms: i64"
+           FROM execution_processes ep
+           JOIN sessions s ON s.id = ep.session_id
+           JOIN workspaces w ON w.id = s.workspace_id
+           JOIN tasks t ON t.id = w.task_id
+           LEFT JOIN attempt_usage u ON u.execution_process_id = ep.id
+           WHERE ep.run_reason = 'codingagent' AND ep.status != 'running'
+             AND t.project_id = $1"#,
+        project_id
+    )
+    .fetch_one(pool)
+    .await?;
+    let months = sqlx::query!(
+        r#"SELECT strftime('%Y-%m', ep.started_at) AS "month!: String",
+                  COUNT(*) AS "runs!: i64",
+                  COUNT(*) - COUNT(u.total_tokens) AS "unreported_runs!: i64",
+                  SUM(u.total_tokens) AS "total_tokens: i64",
+                  SUM(u.duration_ms) AS "duration_ms: i64"
+           FROM execution_processes ep
+           JOIN sessions s ON s.id = ep.session_id
+           JOIN workspaces w ON w.id = s.workspace_id
+           JOIN tasks t ON t.id = w.task_id
+           LEFT JOIN attempt_usage u ON u.execution_process_id = ep.id
+           WHERE ep.run_reason = 'codingagent' AND ep.status != 'running'
+             AND t.project_id = $1
+           GROUP BY 1
+           ORDER BY 1"#,
+        project_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|rec| MonthlyUsage {
+        month: rec.month,
+        usage: UsageTotals {
+            runs: rec.runs,
+            unreported_runs: rec.unreported_runs,
+            total_tokens: rec.total_tokens,
+            duration_ms: rec.duration_ms,
+        },
+    })
+    .collect();
+
+    Ok(ProjectUsage { total, months })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::workspace::{CreateWorkspace, Workspace},
+        test_support::{create_task, pool_with_project},
+    };
+
+    async fn create_workspace(pool: &SqlitePool, task_id: Uuid, branch: &str) -> Workspace {
+        let data = CreateWorkspace {
+            branch: branch.to_string(),
+            agent_working_dir: None,
+        };
+        let workspace = Workspace::create(pool, &data, Uuid::new_v4(), task_id)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO sessions (id, workspace_id, executor) VALUES ($1, $2, 'CLAUDE_CODE')",
+        )
+        .bind(workspace.id)
+        .bind(workspace.id)
+        .execute(pool)
+        .await
+        .unwrap();
+        workspace
+    }
+
+    const MARCH: &str = "2026-03-10 09:00:00.000";
+    const APRIL: &str = "2026-04-02 09:00:00.000";
+
+    async fn seed_process(
+        pool: &SqlitePool,
+        workspace: &Workspace,
+        run_reason: &str,
+        status: &str,
+        started_at: &str,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            r#"INSERT INTO execution_processes (id, session_id, run_reason, status, started_at)
+               VALUES ($1, $2, $3, $4, $5)"#,
+        )
+        .bind(id)
+        .bind(workspace.id)
+        .bind(run_reason)
+        .bind(status)
+        .bind(started_at)
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    /// A coding agent run, with usage recorded when `usage` is set
+    async fn seed_run(
+        pool: &SqlitePool,
+        workspace: &Workspace,
+        status: &str,
+        started_at: &str,
+        usage: Option<(Option<i64>, i64)>,
+    ) -> Uuid {
+        let id = seed_process(pool, workspace, "codingagent", status, started_at).await;
+        if let Some((total_tokens, duration_ms)) = usage {
+            record(pool, id, total_tokens, duration_ms).await.unwrap();
+        }
+        id
+    }
+
+    fn totals(
+        runs: i64,
+        unreported_runs: i64,
+        total_tokens: Option<i64>,
+        duration_ms: Option<i64>,
+    ) -> UsageTotals {
+        UsageTotals {
+            runs,
+            unreported_runs,
+            total_tokens,
+            duration_ms,
+        }
+    }
+
+    #[tokio::test]
+    async fn rolls_up_attempts_tasks_and_months() {
+        let (pool, project_id) = pool_with_project().await;
+        let task = create_task(&pool, project_id, "Measured").await;
+        let first = create_workspace(&pool, task.id, "first").await;
+        let second = create_workspace(&pool, task.id, "second").await;
+        seed_run(
+            &pool,
+            &first,
+            "completed",
+            MARCH,
+            Some((Some(1_000), 60_000)),
+        )
+        .await;
+        // Reported no tokens
+        seed_run(&pool, &first, "failed", MARCH, Some((None, 30_000))).await;
+        seed_run(
+            &pool,
+            &second,
+            "completed",
+            APRIL,
+            Some((Some(2_000), 90_000)),
+        )
+        .await;
+        // Finished without a usage row
+        seed_run(&pool, &second, "killed", APRIL, None).await;
+        // Neither still running nor non-agent runs count
+        seed_run(&pool, &second, "running", APRIL, None).await;
+        let setup = seed_process(&pool, &second, "setupscript", "completed", APRIL).await;
+        record(&pool, setup, None, 5_000).await.unwrap();
+
+        let unmeasured = create_task(&pool, project_id, "Unmeasured").await;
+        let only = create_workspace(&pool, unmeasured.id, "only").await;
+        seed_run(&pool, &only, "completed", APRIL, None).await;
+
+        let usage = task_usage(&pool, task.id).await.unwrap();
+        assert_eq!(usage.total, totals(4, 2, Some(3_000), Some(180_000)));
+        assert_eq!(
+            usage.attempts,
+            [
+                AttemptUsage {
+                    workspace_id: first.id,
+                    branch: "first".to_string(),
+                    usage: totals(2, 1, Some(1_000), Some(90_000)),
+                },
+                AttemptUsage {
+                    workspace_id: second.id,
+                    branch: "second".to_string(),
+                    usage: totals(2, 1, Some(2_000), Some(90_000)),
+                },
+            ]
+        );
+
+        // Unknown usage is not zero usage
+        let usage = task_usage(&pool, unmeasured.id).await.unwrap();
+        assert_eq!(usage.total, totals(1, 1, None, None));
+
+        let usage = project_usage(&pool, project_id).await.unwrap();
+        assert_eq!(usage.total, totals(5, 3, Some(3_000), Some(180_000)));
+        assert_eq!(
+            usage.months,
+            [
+                MonthlyUsage {
+                    month: "2026-03".to_string(),
+                    usage: totals(2, 1, Some(1_000), Some(90_000)),
+                },
+                MonthlyUsage {
+                    month: "2026-04".to_string(),
+                    usage: totals(3, 2, Some(2_000), Some(90_000)),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn record_replaces_earlier_usage() {
+        let (pool, project_id) = pool_with_project().await;
+        let task = create_task(&pool, project_id, "Rerun").await;
+        let workspace = create_workspace(&pool, task.id, "rerun").await;
+        let id = seed_run(&pool, &workspace, "completed", MARCH, Some((None, 1_000))).await;
+
+        record(&pool, id, Some(500), 2_000).await.unwrap();
+        let usage = task_usage(&pool, task.id).await.unwrap();
+        assert_eq!(usage.total, totals(1, 0, Some(500), Some(2_000)));
+        assert!(
+            task_usage(&pool, Uuid::new_v4())
+                .await
+                .unwrap()
+                .attempts
+                .is_empty()
+        );
+    }
+}
@@ -1,9 +1,12 @@
 use chrono::{DateTime, Utc};
+use executors::logs::summary::ExecutionSummary;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, SqlitePool, types::Json};
 use ts_rs::TS;
 use uuid::Uuid;
 
+use super::task::TaskStatus;
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct CodingAgentTurn {
     pub id: Uuid,
@@ -157,6 +160,44 @@ impl CodingAgentTurn {
         Ok(())
     }
 
+    /// Store the structured summary of the turn's finished run
+    pub async fn update_execution_summary(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        summary: &ExecutionSummary,
+    ) -> Result<(), sqlx::Error> {
+        let summary = Json(summary);
+        let now = Utc::now();
+        sqlx::query!(
+            r#"UPDATE coding_agent_turns
+               SET execution_summary = $1, updated_at = $2
+               WHERE execution_process_id = $3"#,
+            summary,
+            now,
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_execution_summary(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<ExecutionSummary>, sqlx::Error> {
+        let summary = sqlx::query_scalar!(
+            r#"SELECT execution_summary as "execution_summary: Json<ExecutionSummary>"
+               FROM coding_agent_turns
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(summary.flatten().map(|Json(summary)| summary))
+    }
+
     /// Mark all coding agent turns for a workspace as seen
     pub async fn mark_seen_by_workspace_id(
         pool: &SqlitePool,
@@ -219,4 +260,39 @@ impl CodingAgentTurn {
 
         Ok(result.into_iter().collect())
     }
+
+    /// All agent session ids with the workspace and task they belong to,
+    /// newest first
+    pub async fn find_agent_session_refs(
+        pool: &SqlitePool,
+    ) -> Result<Vec<AgentSessionRef>, sqlx::Error> {
+        sqlx::query_as!(
+            AgentSessionRef,
+            r#"SELECT
+                cat.agent_session_id as "agent_session_id!",
+                s.workspace_id as "workspace_id!: Uuid",
+                w.task_id as "task_id!: Uuid",
+                t.status as "task_status!: TaskStatus",
+                cat.created_at as "created_at!: DateTime<Utc>"
+               FROM coding_agent_turns cat
+               JOIN execution_processes ep ON cat.execution_process_id = ep.id
+               JOIN sessions s ON ep.session_id = s.id
+               JOIN workspaces w ON s.workspace_id = w.id
+               JOIN tasks t ON w.task_id = t.id
+               WHERE cat.agent_session_id IS NOT NULL
+               ORDER BY cat.created_at DESC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+/// An agent session id together with the workspace and task that produced it
+#[derive(Debug, Clone)]
+pub struct AgentSessionRef {
+    pub agent_session_id: String,
+    pub workspace_id: Uuid,
+    pub task_id: Uuid,
+    pub task_status: TaskStatus,
+    pub created_at: DateTime<Utc>,
 }
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{Acquire, Executor, FromRow, Sqlite, SqlitePool};
 use ts_rs::TS;
 use uuid::Uuid;
 
@@ -178,11 +178,15 @@ impl Image {
 
 impl TaskImage {
     /// Associate multiple images with a task, skipping duplicates.
-    pub async fn associate_many_dedup(
-        pool: &SqlitePool,
+    pub async fn associate_many_dedup<'a, A>(
+        conn: A,
         task_id: Uuid,
         image_ids: &[Uuid],
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), sqlx::Error>
+    where
+        A: Acquire<'a, Database = Sqlite>,
+    {
+        let mut conn = conn.acquire().await?;
         for &image_id in image_ids {
             let id = Uuid::new_v4();
             sqlx::query!(
@@ -195,15 +199,18 @@ impl TaskImage {
                 task_id,
                 image_id
             )
-            .execute(pool)
+            .execute(&mut *conn)
             .await?;
         }
         Ok(())
     }
 
-    pub async fn delete_by_task_id(pool: &SqlitePool, task_id: Uuid) -> Result<(), sqlx::Error> {
+    pub async fn delete_by_task_id<'e, E>(executor: E, task_id: Uuid) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query!(r#"DELETE FROM task_images WHERE task_id = $1"#, task_id)
-            .execute(pool)
+            .execute(executor)
             .await?;
         Ok(())
     }
@@ -1,3 +1,5 @@
+pub mod approval_event;
+pub mod attempt_usage;
 pub mod coding_agent_turn;
 pub mod execution_process;
 pub mod execution_process_logs;
@@ -5,11 +7,24 @@ pub mod execution_process_repo_state;
 pub mod image;
 pub mod merge;
 pub mod project;
+pub mod project_bundle;
+pub mod project_event;
 pub mod project_repo;
+pub mod project_settings;
+pub mod project_stats;
+pub mod project_status;
 pub mod repo;
 pub mod scratch;
 pub mod session;
 pub mod tag;
 pub mod task;
+pub mod task_dependency;
+pub mod task_event;
+pub mod task_external_link;
+pub mod task_rank;
+pub mod task_recurrence;
+pub mod task_schedule;
+pub mod task_template;
+pub mod webhook;
 pub mod workspace;
 pub mod workspace_repo;
@@ -1,11 +1,16 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::project_repo::CreateProjectRepo;
+use super::{
+    project_event::{ProjectEvent, ProjectEventType},
+    project_repo::CreateProjectRepo,
+    project_settings::{self, ProjectSettings, ProjectSettingsError},
+};
 
 #[derive(Debug, Error)]
 pub enum ProjectError {
@@ -38,6 +43,14 @@ pub struct CreateProject {
 #[derive(Debug, Deserialize, TS)]
 pub struct UpdateProject {
     pub name: Option<String>,
+    /// Let tasks move between any statuses instead of following the lifecycle
+    #[serde(default)]
+    #[ts(optional)]
+    pub relaxed_status_transitions: Option<bool>,
+    /// Leave tasks created from another task's attempts out of WIP limits
+    #[serde(default)]
+    #[ts(optional)]
+    pub wip_limit_excludes_child_tasks: Option<bool>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -80,6 +93,42 @@ impl Project {
         .await
     }
 
+    /// Projects that are not archived, newest first
+    pub async fn find_unarchived(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Project,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      default_agent_working_dir,
+                      remote_project_id as "remote_project_id: Uuid",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM projects
+               WHERE archived_at IS NULL
+               ORDER BY created_at DESC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Archived projects, most recently archived first
+    pub async fn find_archived(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Project,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      default_agent_working_dir,
+                      remote_project_id as "remote_project_id: Uuid",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM projects
+               WHERE archived_at IS NOT NULL
+               ORDER BY archived_at DESC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Find the most actively used projects based on recent task activity
     pub async fn find_most_active(pool: &SqlitePool, limit: i32) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
@@ -185,6 +234,138 @@ impl Project {
         .await
     }
 
+    /// Id of a project called `name`, if there is one
+    pub async fn find_id_by_name<'e, E>(
+        executor: E,
+        name: &str,
+    ) -> Result<Option<Uuid>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_scalar!(
+            r#"SELECT id as "id!: Uuid" FROM projects WHERE name = $1 ORDER BY created_at LIMIT 1"#,
+            name
+        )
+        .fetch_optional(executor)
+        .await
+    }
+
+    /// Archive a project. Its tasks are not touched. Already archived
+    /// projects keep their original timestamp.
+    pub async fn archive(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE projects
+               SET archived_at = datetime('now', 'subsec')
+               WHERE id = $1 AND archived_at IS NULL"#,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn unarchive(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE projects SET archived_at = NULL WHERE id = $1 AND archived_at IS NOT NULL",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn is_archived<'e, E>(executor: E, id: Uuid) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_scalar!(
+            r#"SELECT archived_at IS NOT NULL as "archived!: bool" FROM projects WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(executor)
+        .await
+        .map(|archived| archived.unwrap_or(false))
+    }
+
+    pub async fn settings<'e, E>(executor: E, id: Uuid) -> Result<ProjectSettings, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let stored = sqlx::query_scalar!(
+            r#"SELECT settings as "settings!: Value" FROM projects WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(executor)
+        .await?;
+        Ok(stored
+            .map(|stored| ProjectSettings::from_stored(settings_object(stored)))
+            .unwrap_or_default())
+    }
+
+    /// Merge `patch` into the project's settings, see
+    /// [`project_settings::merge`], and log the keys that changed
+    pub async fn update_settings(
+        pool: &SqlitePool,
+        id: Uuid,
+        patch: &Map<String, Value>,
+    ) -> Result<ProjectSettings, ProjectSettingsError> {
+        let mut tx = pool.begin().await?;
+        let stored = sqlx::query_scalar!(
+            r#"SELECT settings as "settings!: Value" FROM projects WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ProjectSettingsError::ProjectNotFound)?;
+        let stored = settings_object(stored);
+        let (merged, settings) = project_settings::merge(&stored, patch)?;
+
+        if let Some((old, new)) = project_settings::changes(&stored, &merged, patch) {
+            let merged = Value::Object(merged);
+            sqlx::query!(
+                "UPDATE projects SET settings = $2 WHERE id = $1",
+                id,
+                merged
+            )
+            .execute(&mut *tx)
+            .await?;
+            ProjectEvent::create(
+                &mut *tx,
+                id,
+                ProjectEventType::SettingsChange,
+                Some(&old),
+                Some(&new),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(settings)
+    }
+
+    pub async fn relaxed_status_transitions<'e, E>(
+        executor: E,
+        id: Uuid,
+    ) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        Ok(Self::settings(executor, id)
+            .await?
+            .relaxed_status_transitions)
+    }
+
+    pub async fn wip_limit_excludes_child_tasks<'e, E>(
+        executor: E,
+        id: Uuid,
+    ) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        Ok(Self::settings(executor, id)
+            .await?
+            .wip_limit_excludes_child_tasks)
+    }
+
     pub async fn update(
         pool: &SqlitePool,
         id: Uuid,
@@ -261,3 +442,194 @@ impl Project {
         Ok(result.rows_affected())
     }
 }
+
+/// The settings column always holds an object; anything else reads as empty
+fn settings_object(stored: Value) -> Map<String, Value> {
+    match stored {
+        Value::Object(settings) => settings,
+        _ => Map::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::{
+        models::task::Task,
+        test_support::{create_project, create_task, pool_with_project},
+    };
+
+    fn patch(value: Value) -> Map<String, Value> {
+        settings_object(value)
+    }
+
+    async fn stored_settings(pool: &SqlitePool, id: Uuid) -> Value {
+        sqlx::query_scalar("SELECT settings FROM projects WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn settings_default_until_set() {
+        let (pool, project_id) = pool_with_project().await;
+        assert_eq!(
+            Project::settings(&pool, project_id).await.unwrap(),
+            ProjectSettings::default()
+        );
+        assert!(
+            !Project::relaxed_status_transitions(&pool, project_id)
+                .await
+                .unwrap()
+        );
+
+        let settings = Project::update_settings(
+            &pool,
+            project_id,
+            &patch(json!({ "relaxed_status_transitions": true })),
+        )
+        .await
+        .unwrap();
+        assert!(settings.relaxed_status_transitions);
+        assert!(!settings.wip_limit_excludes_child_tasks);
+        assert!(
+            Project::relaxed_status_transitions(&pool, project_id)
+                .await
+                .unwrap()
+        );
+
+        assert!(matches!(
+            Project::update_settings(
+                &pool,
+                project_id,
+                &patch(json!({ "wip_limit_excludes_child_tasks": "yes" })),
+            )
+            .await,
+            Err(ProjectSettingsError::Invalid(_))
+        ));
+        assert!(matches!(
+            Project::update_settings(&pool, Uuid::new_v4(), &Map::new()).await,
+            Err(ProjectSettingsError::ProjectNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn projects_from_before_the_transition_rules_stay_relaxed() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let migrator = sqlx::migrate!("./migrations");
+        let (before, after): (Vec<_>, Vec<_>) = migrator
+            .iter()
+            .partition(|migration| migration.version < 20260208000000);
+
+        for migration in before {
+            sqlx::raw_sql(&migration.sql).execute(&pool).await.unwrap();
+        }
+        let old_project = Uuid::new_v4();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ($1, 'Old')")
+            .bind(old_project)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for migration in after {
+            sqlx::raw_sql(&migration.sql).execute(&pool).await.unwrap();
+        }
+        let new_project = create_project(&pool, "New").await;
+
+        assert!(
+            Project::relaxed_status_transitions(&pool, old_project)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !Project::relaxed_status_transitions(&pool, new_project)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn updates_keep_unknown_keys_and_log_changes() {
+        let (pool, project_id) = pool_with_project().await;
+        sqlx::query("UPDATE projects SET settings = $2 WHERE id = $1")
+            .bind(project_id)
+            .bind(json!({ "phase_template": "bmad" }))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let update = patch(json!({ "wip_limit_excludes_child_tasks": true }));
+        Project::update_settings(&pool, project_id, &update)
+            .await
+            .unwrap();
+        assert_eq!(
+            stored_settings(&pool, project_id).await,
+            json!({ "phase_template": "bmad", "wip_limit_excludes_child_tasks": true })
+        );
+        let settings = Project::settings(&pool, project_id).await.unwrap();
+        assert!(settings.wip_limit_excludes_child_tasks);
+        assert_eq!(settings.unknown["phase_template"], json!("bmad"));
+
+        // Repeating the update changes nothing, so nothing is logged
+        Project::update_settings(&pool, project_id, &update)
+            .await
+            .unwrap();
+        let events = ProjectEvent::find_by_project(&pool, project_id, 10)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, ProjectEventType::SettingsChange);
+        assert_eq!(
+            events[0].old_value,
+            Some(json!({ "wip_limit_excludes_child_tasks": null }))
+        );
+        assert_eq!(
+            events[0].new_value,
+            Some(json!({ "wip_limit_excludes_child_tasks": true }))
+        );
+    }
+
+    #[tokio::test]
+    async fn archiving_hides_the_project_but_not_its_tasks() {
+        let (pool, project_id) = pool_with_project().await;
+        let task = create_task(&pool, project_id, "Release notes").await;
+        let today = Utc::now().date_naive();
+        Task::set_due_date(&pool, task.id, Some(today))
+            .await
+            .unwrap();
+        let listed = |projects: Vec<Project>| projects.iter().any(|p| p.id == project_id);
+
+        assert_eq!(Project::archive(&pool, project_id).await.unwrap(), 1);
+        assert_eq!(Project::archive(&pool, project_id).await.unwrap(), 0);
+        assert!(Project::is_archived(&pool, project_id).await.unwrap());
+        assert!(!listed(Project::find_unarchived(&pool).await.unwrap()));
+        assert!(listed(Project::find_archived(&pool).await.unwrap()));
+        // The tasks stay as they were, but global views leave them out
+        assert!(!Task::is_archived(&pool, task.id).await.unwrap());
+        assert!(Task::find_due(&pool, today, 0).await.unwrap().is_empty());
+        assert!(
+            Task::open_counts_by_assignee(&pool, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            Task::open_counts_by_assignee(&pool, Some(project_id))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        assert_eq!(Project::unarchive(&pool, project_id).await.unwrap(), 1);
+        assert!(listed(Project::find_unarchived(&pool).await.unwrap()));
+        assert_eq!(Task::find_due(&pool, today, 0).await.unwrap().len(), 1);
+    }
+}
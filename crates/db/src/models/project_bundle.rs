@@ -0,0 +1,544 @@
+//! Everything that makes up a project, gathered so it can be recreated in
+//! another database. Rows keep their original ids in the bundle and get
+//! new ones when inserted, so a bundle can be imported next to the project
+//! it came from.
+//!
+//! Repositories and worktrees are left out since their paths only make
+//! sense on the machine that exported them, as are task images and links
+//! to shared remote tasks. Workspaces are kept so tasks created from them
+//! stay attached to their parent, but come back without a container.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use super::{
+    execution_process::{
+        ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus, ExecutorActionField,
+    },
+    execution_process_logs::ExecutionProcessLogs,
+    project_event::{ProjectEvent, ProjectEventType},
+    project_status::ProjectStatus,
+    session::Session,
+    task::{TaskPriority, TaskStatus},
+    task_dependency::TaskDependency,
+    task_event::{TaskEvent, TaskEventActor, TaskEventType},
+    task_template::TaskTemplate,
+    workspace::Workspace,
+};
+
+/// Bumped whenever a change to the bundle format would break older readers
+pub const PROJECT_BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectBundle {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub project: BundledProject,
+    pub statuses: Vec<ProjectStatus>,
+    pub tasks: Vec<BundledTask>,
+    pub workspaces: Vec<Workspace>,
+    pub dependencies: Vec<TaskDependency>,
+    pub task_events: Vec<TaskEvent>,
+    pub project_events: Vec<ProjectEvent>,
+    pub templates: Vec<TaskTemplate>,
+    /// Only present when the export asked for it, as logs can be large
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_history: Option<ExecutionHistory>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct BundledProject {
+    pub id: Uuid,
+    pub name: String,
+    pub default_agent_working_dir: Option<String>,
+    pub settings: Value,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct BundledTask {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: TaskStatus,
+    pub status_key: Option<String>,
+    pub priority: TaskPriority,
+    pub position: Option<String>,
+    pub assignee: Option<String>,
+    pub due_date: Option<NaiveDate>,
+    pub schedule_weight: f64,
+    pub parent_workspace_id: Option<Uuid>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionHistory {
+    pub sessions: Vec<Session>,
+    pub processes: Vec<ExecutionProcess>,
+    pub logs: Vec<ExecutionProcessLogs>,
+}
+
+impl ProjectBundle {
+    /// Gather the project's rows, oldest first within each kind
+    pub async fn load(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        include_execution_history: bool,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let Some(project) = sqlx::query_as!(
+            BundledProject,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      default_agent_working_dir,
+                      settings as "settings!: Value",
+                      archived_at as "archived_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM projects
+               WHERE id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let workspaces = sqlx::query_as!(
+            Workspace,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      container_ref,
+                      branch,
+                      agent_working_dir,
+                      setup_completed_at as "setup_completed_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>",
+                      archived as "archived!: bool",
+                      pinned as "pinned!: bool",
+                      name
+               FROM workspaces
+               WHERE task_id IN (SELECT id FROM tasks WHERE project_id = $1)
+               ORDER BY created_at, rowid"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        // Links to workspaces outside the project cannot be carried over
+        let tasks = sqlx::query_as!(
+            BundledTask,
+            r#"SELECT t.id as "id!: Uuid",
+                      t.title,
+                      t.description,
+                      t.status as "status!: TaskStatus",
+                      t.status_key,
+                      t.priority as "priority!: TaskPriority",
+                      t.position,
+                      t.assignee,
+                      t.due_date as "due_date: NaiveDate",
+                      t.schedule_weight as "schedule_weight!: f64",
+                      CASE WHEN w.task_id IN (SELECT id FROM tasks WHERE project_id = $1)
+                           THEN t.parent_workspace_id END AS "parent_workspace_id: Uuid",
+                      t.archived_at as "archived_at: DateTime<Utc>",
+                      t.deleted_at as "deleted_at: DateTime<Utc>",
+                      t.created_at as "created_at!: DateTime<Utc>",
+                      t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks t
+               LEFT JOIN workspaces w ON w.id = t.parent_workspace_id
+               WHERE t.project_id = $1
+               ORDER BY t.created_at, t.rowid"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let dependencies = sqlx::query_as!(
+            TaskDependency,
+            r#"SELECT blocker_id as "blocker_id!: Uuid",
+                      blocked_id as "blocked_id!: Uuid",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_dependencies
+               WHERE blocker_id IN (SELECT id FROM tasks WHERE project_id = $1)
+                 AND blocked_id IN (SELECT id FROM tasks WHERE project_id = $1)
+               ORDER BY created_at, rowid"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let task_events = sqlx::query_as!(
+            TaskEvent,
+            r#"SELECT id as "id!: i64",
+                      task_id as "task_id!: Uuid",
+                      event_type as "event_type!: TaskEventType",
+                      old_value as "old_value: Value",
+                      new_value as "new_value: Value",
+                      actor as "actor!: TaskEventActor",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_events
+               WHERE task_id IN (SELECT id FROM tasks WHERE project_id = $1)
+               ORDER BY id"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let project_events = sqlx::query_as!(
+            ProjectEvent,
+            r#"SELECT id as "id!: i64",
+                      project_id as "project_id!: Uuid",
+                      event_type as "event_type!: ProjectEventType",
+                      old_value as "old_value: Value",
+                      new_value as "new_value: Value",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM project_events
+               WHERE project_id = $1
+               ORDER BY id"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let execution_history = if include_execution_history {
+            Some(ExecutionHistory::load(pool, project_id).await?)
+        } else {
+            None
+        };
+
+        Ok(Some(Self {
+            version: PROJECT_BUNDLE_VERSION,
+            exported_at: Utc::now(),
+            statuses: ProjectStatus::find_by_project_id(pool, project_id).await?,
+            templates: TaskTemplate::find_by_project_id(pool, project_id).await?,
+            project,
+            tasks,
+            workspaces,
+            dependencies,
+            task_events,
+            project_events,
+            execution_history,
+        }))
+    }
+
+    /// Recreate the project as `name`, with new ids throughout, and return
+    /// the new project id. Processes that were still running when exported
+    /// come back as killed.
+    pub async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        name: &str,
+    ) -> Result<Uuid, sqlx::Error> {
+        let mut ids = IdMap::default();
+        let project_id = ids.get(self.project.id);
+
+        sqlx::query!(
+            r#"INSERT INTO projects (id, name, default_agent_working_dir, settings, archived_at, created_at)
+               VALUES ($1, $2, $3, $4, $5, $6)"#,
+            project_id,
+            name,
+            self.project.default_agent_working_dir,
+            self.project.settings,
+            self.project.archived_at,
+            self.project.created_at
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // Built-in statuses were seeded along with the project
+        for status in &self.statuses {
+            let id = Uuid::new_v4();
+            sqlx::query!(
+                r#"INSERT INTO project_statuses (id, project_id, key, name, category, sort_order, wip_limit, created_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                   ON CONFLICT (project_id, key) DO UPDATE
+                   SET name = excluded.name,
+                       sort_order = excluded.sort_order,
+                       wip_limit = excluded.wip_limit"#,
+                id,
+                project_id,
+                status.key,
+                status.name,
+                status.category,
+                status.sort_order,
+                status.wip_limit,
+                status.created_at
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        // Tasks first, then the workspaces they own, then the links back
+        for task in &self.tasks {
+            let id = ids.get(task.id);
+            sqlx::query!(
+                r#"INSERT INTO tasks (id, project_id, title, description, status, status_key,
+                                      priority, position, assignee, due_date, schedule_weight,
+                                      archived_at, deleted_at, created_at, updated_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)"#,
+                id,
+                project_id,
+                task.title,
+                task.description,
+                task.status,
+                task.status_key,
+                task.priority,
+                task.position,
+                task.assignee,
+                task.due_date,
+                task.schedule_weight,
+                task.archived_at,
+                task.deleted_at,
+                task.created_at,
+                task.updated_at
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        for workspace in &self.workspaces {
+            let id = ids.get(workspace.id);
+            let task_id = ids.get(workspace.task_id);
+            sqlx::query!(
+                r#"INSERT INTO workspaces (id, task_id, branch, agent_working_dir, archived, pinned,
+                                           name, created_at, updated_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#,
+                id,
+                task_id,
+                workspace.branch,
+                workspace.agent_working_dir,
+                workspace.archived,
+                workspace.pinned,
+                workspace.name,
+                workspace.created_at,
+                workspace.updated_at
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        for task in &self.tasks {
+            let Some(parent_workspace_id) = task.parent_workspace_id else {
+                continue;
+            };
+            let id = ids.get(task.id);
+            let parent_workspace_id = ids.get(parent_workspace_id);
+            sqlx::query!(
+                "UPDATE tasks SET parent_workspace_id = $2 WHERE id = $1",
+                id,
+                parent_workspace_id
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        for dependency in &self.dependencies {
+            let blocker_id = ids.get(dependency.blocker_id);
+            let blocked_id = ids.get(dependency.blocked_id);
+            sqlx::query!(
+                r#"INSERT INTO task_dependencies (blocker_id, blocked_id, created_at)
+                   VALUES ($1, $2, $3)"#,
+                blocker_id,
+                blocked_id,
+                dependency.created_at
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        for event in &self.task_events {
+            let task_id = ids.get(event.task_id);
+            sqlx::query!(
+                r#"INSERT INTO task_events (task_id, event_type, old_value, new_value, actor, created_at)
+                   VALUES ($1, $2, $3, $4, $5, $6)"#,
+                task_id,
+                event.event_type,
+                event.old_value,
+                event.new_value,
+                event.actor,
+                event.created_at
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        for event in &self.project_events {
+            sqlx::query!(
+                r#"INSERT INTO project_events (project_id, event_type, old_value, new_value, created_at)
+                   VALUES ($1, $2, $3, $4, $5)"#,
+                project_id,
+                event.event_type,
+                event.old_value,
+                event.new_value,
+                event.created_at
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        // Templates start their schedule afresh from the import
+        for template in &self.templates {
+            let id = Uuid::new_v4();
+            sqlx::query!(
+                r#"INSERT INTO task_templates (id, project_id, title, description, priority,
+                                               assignee, recurrence, enabled)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+                id,
+                project_id,
+                template.title,
+                template.description,
+                template.priority,
+                template.assignee,
+                template.recurrence,
+                template.enabled
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        if let Some(history) = &self.execution_history {
+            history.insert(conn, &mut ids).await?;
+        }
+        Ok(project_id)
+    }
+}
+
+impl ExecutionHistory {
+    async fn load(pool: &SqlitePool, project_id: Uuid) -> Result<Self, sqlx::Error> {
+        let sessions = sqlx::query_as!(
+            Session,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      executor,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM sessions
+               WHERE id IN (SELECT s.id FROM sessions s
+                              JOIN workspaces w ON w.id = s.workspace_id
+                             WHERE w.task_id IN (SELECT id FROM tasks WHERE project_id = $1))
+               ORDER BY created_at, rowid"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+        let processes = sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT id as "id!: Uuid",
+                      session_id as "session_id!: Uuid",
+                      run_reason as "run_reason!: ExecutionProcessRunReason",
+                      executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
+                      status as "status!: ExecutionProcessStatus",
+                      exit_code,
+                      dropped as "dropped!: bool",
+                      started_at as "started_at!: DateTime<Utc>",
+                      completed_at as "completed_at?: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_processes
+               WHERE session_id IN (SELECT s.id FROM sessions s
+                                      JOIN workspaces w ON w.id = s.workspace_id
+                                     WHERE w.task_id IN (SELECT id FROM tasks WHERE project_id = $1))
+               ORDER BY created_at, rowid"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+        let logs = sqlx::query_as!(
+            ExecutionProcessLogs,
+            r#"SELECT execution_id as "execution_id!: Uuid",
+                      logs,
+                      byte_size,
+                      inserted_at as "inserted_at!: DateTime<Utc>"
+               FROM execution_process_logs
+               WHERE execution_id IN (
+                   SELECT id FROM execution_processes
+                    WHERE session_id IN (SELECT s.id FROM sessions s
+                                           JOIN workspaces w ON w.id = s.workspace_id
+                                          WHERE w.task_id IN (SELECT id FROM tasks WHERE project_id = $1))
+               )
+               ORDER BY inserted_at, rowid"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(Self {
+            sessions,
+            processes,
+            logs,
+        })
+    }
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        ids: &mut IdMap,
+    ) -> Result<(), sqlx::Error> {
+        for session in &self.sessions {
+            let id = ids.get(session.id);
+            let workspace_id = ids.get(session.workspace_id);
+            sqlx::query!(
+                r#"INSERT INTO sessions (id, workspace_id, executor, created_at, updated_at)
+                   VALUES ($1, $2, $3, $4, $5)"#,
+                id,
+                workspace_id,
+                session.executor,
+                session.created_at,
+                session.updated_at
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        for process in &self.processes {
+            let id = ids.get(process.id);
+            let session_id = ids.get(process.session_id);
+            sqlx::query!(
+                r#"INSERT INTO execution_processes (id, session_id, run_reason, executor_action,
+                                                    status, exit_code, dropped, started_at,
+                                                    completed_at, created_at, updated_at)
+                   VALUES ($1, $2, $3, $4, CASE WHEN $5 = 'running' THEN 'killed' ELSE $5 END,
+                           $6, $7, $8, $9, $10, $11)"#,
+                id,
+                session_id,
+                process.run_reason,
+                process.executor_action,
+                process.status,
+                process.exit_code,
+                process.dropped,
+                process.started_at,
+                process.completed_at,
+                process.created_at,
+                process.updated_at
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        for logs in &self.logs {
+            let execution_id = ids.get(logs.execution_id);
+            sqlx::query!(
+                r#"INSERT INTO execution_process_logs (execution_id, logs, byte_size, inserted_at)
+                   VALUES ($1, $2, $3, $4)"#,
+                execution_id,
+                logs.logs,
+                logs.byte_size,
+                logs.inserted_at
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// New ids for the bundle's rows, assigned on first use
+#[derive(Default)]
+struct IdMap(HashMap<Uuid, Uuid>);
+
+impl IdMap {
+    fn get(&mut self, old: Uuid) -> Uuid {
+        *self.0.entry(old).or_insert_with(Uuid::new_v4)
+    }
+}
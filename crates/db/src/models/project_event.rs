@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "project_event_type", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectEventType {
+    SettingsChange,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectEvent {
+    #[ts(type = "number")]
+    pub id: i64,
+    pub project_id: Uuid,
+    pub event_type: ProjectEventType,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl ProjectEvent {
+    pub async fn create<'e, E>(
+        executor: E,
+        project_id: Uuid,
+        event_type: ProjectEventType,
+        old_value: Option<&Value>,
+        new_value: Option<&Value>,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"INSERT INTO project_events (project_id, event_type, old_value, new_value)
+               VALUES ($1, $2, $3, $4)"#,
+            project_id,
+            event_type,
+            old_value,
+            new_value
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Events of a project, newest first
+    pub async fn find_by_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectEvent,
+            r#"SELECT id as "id!: i64",
+                      project_id as "project_id!: Uuid",
+                      event_type as "event_type!: ProjectEventType",
+                      old_value as "old_value: Value",
+                      new_value as "new_value: Value",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM project_events
+               WHERE project_id = $1
+               ORDER BY id DESC
+               LIMIT $2"#,
+            project_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
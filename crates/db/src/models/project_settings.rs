@@ -0,0 +1,156 @@
+//! Per-project configuration, stored as one JSON object on the project.
+//! Missing keys take their defaults, and keys this build does not know are
+//! written back as they were found, so older and newer builds can share a
+//! database.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+use ts_rs::TS;
+
+#[derive(Debug, Error)]
+pub enum ProjectSettingsError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Project not found")]
+    ProjectNotFound,
+    #[error("Invalid project settings: {0}")]
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct ProjectSettings {
+    /// Let tasks move between any statuses instead of following the lifecycle
+    pub relaxed_status_transitions: bool,
+    /// Leave tasks created from another task's attempts out of WIP limits
+    pub wip_limit_excludes_child_tasks: bool,
+    /// Keys this build does not know about
+    #[serde(flatten)]
+    #[ts(skip)]
+    pub unknown: Map<String, Value>,
+}
+
+impl ProjectSettings {
+    /// Read a stored settings object. One a newer build wrote in a shape
+    /// this one cannot read gives the defaults rather than failing.
+    pub fn from_stored(stored: Map<String, Value>) -> Self {
+        serde_json::from_value(Value::Object(stored)).unwrap_or_else(|e| {
+            tracing::warn!("Unreadable project settings, using defaults: {}", e);
+            Self::default()
+        })
+    }
+}
+
+/// Apply `patch` over the `stored` settings object. Keys set to `null`
+/// return to their default, any other value replaces the stored one.
+/// Returns the object to store along with the settings it holds; values
+/// of known keys must have the right type, unknown keys are kept as given.
+pub fn merge(
+    stored: &Map<String, Value>,
+    patch: &Map<String, Value>,
+) -> Result<(Map<String, Value>, ProjectSettings), ProjectSettingsError> {
+    let mut merged = stored.clone();
+    for (key, value) in patch {
+        if value.is_null() {
+            merged.remove(key);
+        } else {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    let settings = serde_json::from_value(Value::Object(merged.clone()))
+        .map_err(|e| ProjectSettingsError::Invalid(e.to_string()))?;
+    Ok((merged, settings))
+}
+
+/// The patched keys whose stored value `merge` changed, as objects of old
+/// and new values (`null` where a key was unset); `None` if none did
+pub fn changes(
+    stored: &Map<String, Value>,
+    merged: &Map<String, Value>,
+    patch: &Map<String, Value>,
+) -> Option<(Value, Value)> {
+    let mut old = Map::new();
+    let mut new = Map::new();
+    for key in patch.keys() {
+        let (before, after) = (stored.get(key), merged.get(key));
+        if before != after {
+            old.insert(key.clone(), before.cloned().unwrap_or(Value::Null));
+            new.insert(key.clone(), after.cloned().unwrap_or(Value::Null));
+        }
+    }
+    (!new.is_empty()).then(|| (Value::Object(old), Value::Object(new)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("not an object"),
+        }
+    }
+
+    #[test]
+    fn missing_keys_take_defaults() {
+        assert_eq!(
+            ProjectSettings::from_stored(Map::new()),
+            ProjectSettings::default()
+        );
+        let settings = ProjectSettings::from_stored(object(json!({
+            "relaxed_status_transitions": "sometimes",
+        })));
+        assert!(!settings.relaxed_status_transitions);
+    }
+
+    #[test]
+    fn merges_patches_and_validates_known_keys() {
+        let stored = object(json!({
+            "relaxed_status_transitions": true,
+            "added_in_a_later_version": { "depth": 2 },
+        }));
+        let (merged, settings) = merge(
+            &stored,
+            &object(json!({ "wip_limit_excludes_child_tasks": true })),
+        )
+        .unwrap();
+        assert!(settings.relaxed_status_transitions);
+        assert!(settings.wip_limit_excludes_child_tasks);
+        assert_eq!(merged["added_in_a_later_version"], json!({ "depth": 2 }));
+
+        let (merged, settings) = merge(
+            &merged,
+            &object(json!({ "relaxed_status_transitions": null })),
+        )
+        .unwrap();
+        assert!(!settings.relaxed_status_transitions);
+        assert!(!merged.contains_key("relaxed_status_transitions"));
+
+        assert!(matches!(
+            merge(&merged, &object(json!({ "relaxed_status_transitions": 1 }))),
+            Err(ProjectSettingsError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn reports_only_changed_keys() {
+        let stored = object(json!({ "relaxed_status_transitions": true }));
+        let patch = object(json!({
+            "relaxed_status_transitions": true,
+            "wip_limit_excludes_child_tasks": true,
+        }));
+        let (merged, _) = merge(&stored, &patch).unwrap();
+        assert_eq!(
+            changes(&stored, &merged, &patch),
+            Some((
+                json!({ "wip_limit_excludes_child_tasks": null }),
+                json!({ "wip_limit_excludes_child_tasks": true }),
+            ))
+        );
+        assert_eq!(changes(&merged, &merged, &patch), None);
+    }
+}
@@ -0,0 +1,425 @@
+//! Progress statistics of a project, computed in SQL from the tasks and
+//! the status changes in their activity log. Results are ordered by status
+//! the way the board is, and ranges bound as `$2`..`$3` may leave out
+//! either end.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// Optional bounds on when the counted changes happened
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StatsRange {
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, TS)]
+pub struct StatusCount {
+    pub status: TaskStatus,
+    #[ts(type = "number")]
+    pub count: i64,
+}
+
+/// Time tasks spent in a status before moving on. Tasks that skipped the
+/// status do not count; a task that came back to it counts once, with the
+/// time of all its visits added up.
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, TS)]
+pub struct StatusDuration {
+    pub status: TaskStatus,
+    /// Tasks that left the status
+    #[ts(type = "number")]
+    pub tasks: i64,
+    /// Times tasks left the status, more than `tasks` when some came back
+    #[ts(type = "number")]
+    pub visits: i64,
+    /// Average per task
+    pub avg_seconds: f64,
+}
+
+/// Nearest-rank percentiles of a duration, empty when nothing was measured
+#[derive(Debug, Clone, Default, PartialEq, FromRow, Serialize, TS)]
+pub struct DurationPercentiles {
+    #[ts(type = "number")]
+    pub count: i64,
+    pub mean_seconds: Option<f64>,
+    pub p50_seconds: Option<f64>,
+    pub p75_seconds: Option<f64>,
+    pub p90_seconds: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, TS)]
+pub struct WeeklyThroughput {
+    /// Monday the week starts on, in UTC
+    pub week_start: NaiveDate,
+    /// Tasks that reached Done in the week and are still there
+    #[ts(type = "number")]
+    pub completed: i64,
+}
+
+/// Current tasks per status, leaving out archived and deleted ones
+pub async fn status_counts(
+    pool: &SqlitePool,
+    project_id: Uuid,
+) -> Result<Vec<StatusCount>, sqlx::Error> {
+    sqlx::query_as!(
+        StatusCount,
+        r#"SELECT status as "status!: TaskStatus", COUNT(*) AS "count!: i64"
+           FROM tasks
+           WHERE project_id = $1 AND deleted_at IS NULL AND archived_at IS NULL
+           GROUP BY status
+           ORDER BY CASE status WHEN 'todo' THEN 0 WHEN 'inprogress' THEN 1
+                         WHEN 'inreview' THEN 2 WHEN 'done' THEN 3 ELSE 4 END"#,
+        project_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Time spent in each status, from the status changes that ended a stay
+/// within the range. A task's first stay starts when it was created.
+/// Durations are rounded to whole seconds.
+pub async fn status_durations(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    range: StatsRange,
+) -> Result<Vec<StatusDuration>, sqlx::Error> {
+    sqlx::query_as!(
+        StatusDuration,
+        r#"WITH stays AS (
+               SELECT e.task_id,
+                      json_extract(e.old_value, '$') AS status,
+                      COALESCE(LAG(e.created_at) OVER (PARTITION BY e.task_id ORDER BY e.id),
+                               t.created_at) AS entered_at,
+                      e.created_at AS left_at
+               FROM task_events e
+               JOIN tasks t ON t.id = e.task_id
+               WHERE t.project_id = $1 AND t.deleted_at IS NULL
+                 AND e.event_type = 'status_change'
+           ),
+           per_task AS (
+               SELECT task_id, status, COUNT(*) AS visits,
+                      ROUND(SUM(julianday(left_at) - julianday(entered_at)) * 86400.0) AS seconds
+               FROM stays
+               WHERE status IS NOT NULL
+                 AND ($2 IS NULL OR julianday(left_at) >= julianday($2))
+                 AND ($3 IS NULL OR julianday(left_at) < julianday($3))
+               GROUP BY task_id, status
+           )
+           SELECT status as "status!: TaskStatus",
+                  COUNT(*) AS "tasks!: i64",
+                  SUM(visits) AS "visits!: i64",
+                  AVG(seconds) AS "avg_seconds!: f64"
+           FROM per_task
+           GROUP BY status
+           ORDER BY CASE status WHEN 'todo' THEN 0 WHEN 'inprogress' THEN 1
+                         WHEN 'inreview' THEN 2 WHEN 'done' THEN 3 ELSE 4 END"#,
+        project_id,
+        range.from,
+        range.to
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Percentiles of the time until Done over the tasks completed in the
+/// range, measured from when they were first started with `from_start`
+/// and from creation otherwise. Completion comes from the activity log, so
+/// tasks finished before it existed are left out.
+async fn completion_percentiles(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    range: StatsRange,
+    from_start: bool,
+) -> Result<DurationPercentiles, sqlx::Error> {
+    sqlx::query_as!(
+        DurationPercentiles,
+        r#"WITH completed AS (
+               SELECT julianday(t.created_at) AS created_jd,
+                      (SELECT MIN(julianday(e.created_at)) FROM task_events e
+                        WHERE e.task_id = t.id AND e.event_type = 'status_change'
+                          AND json_extract(e.new_value, '$') = 'inprogress') AS started_jd,
+                      (SELECT MAX(julianday(e.created_at)) FROM task_events e
+                        WHERE e.task_id = t.id AND e.event_type = 'status_change'
+                          AND json_extract(e.new_value, '$') = 'done') AS done_jd
+               FROM tasks t
+               WHERE t.project_id = $1 AND t.deleted_at IS NULL AND t.status = 'done'
+           ),
+           durations AS (
+               SELECT ROUND((done_jd - start_jd) * 86400.0) AS seconds
+               FROM (
+                   SELECT done_jd, CASE WHEN $4 THEN started_jd ELSE created_jd END AS start_jd
+                   FROM completed
+               )
+               WHERE done_jd IS NOT NULL AND start_jd <= done_jd
+                 AND ($2 IS NULL OR julianday(done_jd) >= julianday($2))
+                 AND ($3 IS NULL OR julianday(done_jd) < julianday($3))
+           ),
+           ranked AS (
+               SELECT seconds,
+                      ROW_NUMBER() OVER (ORDER BY seconds) AS position,
+                      COUNT(*) OVER () AS total
+               FROM durations
+           )
+           SELECT COUNT(*) AS "count!: i64",
+                  AVG(seconds) AS "mean_seconds: f64",
+                  MIN(CASE WHEN position >= 0.50 * total THEN seconds END) AS "p50_seconds: f64",
+                  MIN(CASE WHEN position >= 0.75 * total THEN seconds END) AS "p75_seconds: f64",
+                  MIN(CASE WHEN position >= 0.90 * total THEN seconds END) AS "p90_seconds: f64"
+           FROM ranked"#,
+        project_id,
+        range.from,
+        range.to,
+        from_start
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// From first moving to In Progress until last reaching Done. Tasks that
+/// went to Done without being started are left out.
+pub async fn cycle_times(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    range: StatsRange,
+) -> Result<DurationPercentiles, sqlx::Error> {
+    completion_percentiles(pool, project_id, range, true).await
+}
+
+/// From creation until last reaching Done
+pub async fn lead_times(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    range: StatsRange,
+) -> Result<DurationPercentiles, sqlx::Error> {
+    completion_percentiles(pool, project_id, range, false).await
+}
+
+/// Completed tasks per week, only listing weeks with completions
+pub async fn weekly_throughput(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    range: StatsRange,
+) -> Result<Vec<WeeklyThroughput>, sqlx::Error> {
+    sqlx::query_as!(
+        WeeklyThroughput,
+        r#"WITH completed AS (
+               SELECT julianday(t.created_at) AS created_jd,
+                      (SELECT MIN(julianday(e.created_at)) FROM task_events e
+                        WHERE e.task_id = t.id AND e.event_type = 'status_change'
+                          AND json_extract(e.new_value, '$') = 'inprogress') AS started_jd,
+                      (SELECT MAX(julianday(e.created_at)) FROM task_events e
+                        WHERE e.task_id = t.id AND e.event_type = 'status_change'
+                          AND json_extract(e.new_value, '$') = 'done') AS done_jd
+               FROM tasks t
+               WHERE t.project_id = $1 AND t.deleted_at IS NULL AND t.status = 'done'
+           )
+           SELECT date(done_jd, 'weekday 0', '-6 days') AS "week_start!: NaiveDate",
+                  COUNT(*) AS "completed!: i64"
+           FROM completed
+           WHERE done_jd IS NOT NULL
+             AND ($2 IS NULL OR julianday(done_jd) >= julianday($2))
+             AND ($3 IS NULL OR julianday(done_jd) < julianday($3))
+           GROUP BY 1
+           ORDER BY 1"#,
+        project_id,
+        range.from,
+        range.to
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{
+        models::{task::Task, task_event::TaskEventActor},
+        test_support::{create_task, pool_with_project},
+    };
+
+    const HOUR: f64 = 3600.0;
+    const DAY: f64 = 24.0 * HOUR;
+
+    /// Day `day` of March 2026, a Sunday the 1st, at `hour`
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn sql_time(time: DateTime<Utc>) -> String {
+        time.format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+    }
+
+    /// A task created at `created` that went through `moves`
+    async fn seed_task(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        created: DateTime<Utc>,
+        moves: &[(TaskStatus, DateTime<Utc>)],
+    ) -> Task {
+        let task = create_task(pool, project_id, "Seeded").await;
+        sqlx::query("UPDATE tasks SET created_at = $2 WHERE id = $1")
+            .bind(task.id)
+            .bind(sql_time(created))
+            .execute(pool)
+            .await
+            .unwrap();
+        let mut from = TaskStatus::Todo;
+        for (to, time) in moves {
+            sqlx::query(
+                r#"INSERT INTO task_events (task_id, event_type, old_value, new_value, actor, created_at)
+                   VALUES ($1, 'status_change', json_quote($2), json_quote($3), $4, $5)"#,
+            )
+            .bind(task.id)
+            .bind(from.to_string())
+            .bind(to.to_string())
+            .bind(TaskEventActor::User)
+            .bind(sql_time(*time))
+            .execute(pool)
+            .await
+            .unwrap();
+            from = to.clone();
+        }
+        Task::update_status(pool, task.id, from).await.unwrap();
+        task
+    }
+
+    fn duration(rows: &[StatusDuration], status: TaskStatus) -> Option<&StatusDuration> {
+        rows.iter().find(|row| row.status == status)
+    }
+
+    #[tokio::test]
+    async fn durations_follow_the_activity_log() {
+        use TaskStatus::*;
+        let (pool, project_id) = pool_with_project().await;
+        // The straight path: a day waiting, two in progress, one in review
+        seed_task(
+            &pool,
+            project_id,
+            at(2, 0),
+            &[
+                (InProgress, at(3, 0)),
+                (InReview, at(5, 0)),
+                (Done, at(6, 0)),
+            ],
+        )
+        .await;
+        // Skips review, then gets reopened for 4 more hours of work
+        seed_task(
+            &pool,
+            project_id,
+            at(2, 0),
+            &[
+                (InProgress, at(2, 12)),
+                (Done, at(3, 0)),
+                (InProgress, at(4, 0)),
+                (Done, at(4, 4)),
+            ],
+        )
+        .await;
+        // Done without ever being started
+        seed_task(&pool, project_id, at(9, 0), &[(Done, at(9, 6))]).await;
+        // Still in progress
+        seed_task(&pool, project_id, at(9, 0), &[(InProgress, at(10, 0))]).await;
+
+        let counts = status_counts(&pool, project_id).await.unwrap();
+        let counts: Vec<_> = counts.iter().map(|c| (c.status.clone(), c.count)).collect();
+        assert_eq!(counts, [(InProgress, 1), (Done, 3)]);
+
+        let rows = status_durations(&pool, project_id, StatsRange::default())
+            .await
+            .unwrap();
+        let todo = duration(&rows, Todo).unwrap();
+        assert_eq!((todo.tasks, todo.visits), (4, 4));
+        assert_eq!(
+            todo.avg_seconds,
+            (DAY + 12.0 * HOUR + 6.0 * HOUR + DAY) / 4.0
+        );
+        let in_progress = duration(&rows, InProgress).unwrap();
+        assert_eq!((in_progress.tasks, in_progress.visits), (2, 3));
+        assert_eq!(in_progress.avg_seconds, (2.0 * DAY + 16.0 * HOUR) / 2.0);
+        let review = duration(&rows, InReview).unwrap();
+        assert_eq!((review.tasks, review.avg_seconds), (1, DAY));
+        // Reopening counts as time spent in Done
+        assert_eq!(duration(&rows, Done).unwrap().avg_seconds, DAY);
+
+        let cycle = cycle_times(&pool, project_id, StatsRange::default())
+            .await
+            .unwrap();
+        // From the first start: 3 days, and 1 day 16 hours
+        assert_eq!(cycle.count, 2);
+        assert_eq!(cycle.p50_seconds, Some(DAY + 16.0 * HOUR));
+        assert_eq!(cycle.p90_seconds, Some(3.0 * DAY));
+        let lead = lead_times(&pool, project_id, StatsRange::default())
+            .await
+            .unwrap();
+        assert_eq!(lead.count, 3);
+        assert_eq!(lead.p50_seconds, Some(2.0 * DAY + 4.0 * HOUR));
+        assert_eq!(
+            lead.mean_seconds,
+            Some((4.0 * DAY + 2.0 * DAY + 4.0 * HOUR + 6.0 * HOUR) / 3.0)
+        );
+
+        let weeks = weekly_throughput(&pool, project_id, StatsRange::default())
+            .await
+            .unwrap();
+        let weeks: Vec<_> = weeks
+            .iter()
+            .map(|w| (w.week_start.to_string(), w.completed))
+            .collect();
+        assert_eq!(
+            weeks,
+            [("2026-03-02".to_string(), 2), ("2026-03-09".to_string(), 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn ranges_bound_when_changes_happened() {
+        use TaskStatus::*;
+        let (pool, project_id) = pool_with_project().await;
+        seed_task(
+            &pool,
+            project_id,
+            at(2, 0),
+            &[(InProgress, at(3, 0)), (Done, at(4, 0))],
+        )
+        .await;
+        seed_task(
+            &pool,
+            project_id,
+            at(9, 0),
+            &[(InProgress, at(9, 6)), (Done, at(11, 6))],
+        )
+        .await;
+
+        let second_week = StatsRange {
+            from: Some(at(9, 0)),
+            to: Some(at(16, 0)),
+        };
+        let cycle = cycle_times(&pool, project_id, second_week).await.unwrap();
+        assert_eq!(cycle.count, 1);
+        assert_eq!(cycle.mean_seconds, Some(2.0 * DAY));
+        let rows = status_durations(&pool, project_id, second_week)
+            .await
+            .unwrap();
+        assert_eq!(duration(&rows, Todo).unwrap().avg_seconds, 6.0 * HOUR);
+        let weeks = weekly_throughput(&pool, project_id, second_week)
+            .await
+            .unwrap();
+        assert_eq!(weeks.len(), 1);
+
+        let empty = StatsRange {
+            from: Some(at(20, 0)),
+            to: None,
+        };
+        let none = cycle_times(&pool, project_id, empty).await.unwrap();
+        assert_eq!(none, DurationPercentiles::default());
+    }
+}
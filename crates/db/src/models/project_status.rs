@@ -0,0 +1,544 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::rust::double_option;
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+#[derive(Debug, Error)]
+pub enum ProjectStatusError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Status not found")]
+    NotFound,
+    #[error("Status keys may only contain lowercase letters, digits, '-' and '_'")]
+    InvalidKey,
+    #[error("A status with key '{0}' already exists in this project")]
+    DuplicateKey(String),
+    #[error("WIP limit must be at least 1")]
+    InvalidWipLimit,
+    #[error("Built-in statuses cannot be removed")]
+    BuiltIn,
+    #[error("Status is still used by {0} tasks")]
+    InUse(i64),
+    #[error("Status '{key}' is a {category} status, not {status}")]
+    CategoryMismatch {
+        key: String,
+        category: TaskStatus,
+        status: TaskStatus,
+    },
+}
+
+/// A board column of a project. Its `category` decides how tasks in it
+/// behave; the built-in columns use their category as key.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectStatus {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub key: String,
+    pub name: String,
+    pub category: TaskStatus,
+    pub sort_order: i32,
+    /// Most tasks the column should hold at once; unlimited when unset
+    pub wip_limit: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateProjectStatus {
+    pub key: String,
+    pub name: String,
+    pub category: TaskStatus,
+    /// Defaults to after the last column
+    #[serde(default)]
+    #[ts(optional)]
+    pub sort_order: Option<i32>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub wip_limit: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateProjectStatus {
+    pub name: Option<String>,
+    pub sort_order: Option<i32>,
+    /// `null` removes the limit
+    #[serde(default, with = "double_option")]
+    #[ts(optional, type = "number | null")]
+    pub wip_limit: Option<Option<i32>>,
+}
+
+/// A task counted against a column's WIP limit
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct StatusOccupant {
+    pub id: Uuid,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct StatusOccupancy {
+    pub status_key: String,
+    pub name: String,
+    pub wip_limit: Option<i32>,
+    #[ts(type = "number")]
+    pub task_count: i64,
+}
+
+fn check_wip_limit(limit: Option<i32>) -> Result<(), ProjectStatusError> {
+    match limit {
+        Some(limit) if limit < 1 => Err(ProjectStatusError::InvalidWipLimit),
+        _ => Ok(()),
+    }
+}
+
+impl ProjectStatus {
+    pub fn is_built_in(&self) -> bool {
+        self.key == self.category.to_string()
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectStatus,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      key,
+                      name,
+                      category as "category!: TaskStatus",
+                      sort_order as "sort_order!: i32",
+                      wip_limit as "wip_limit: i32",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_statuses
+               WHERE project_id = $1
+               ORDER BY sort_order ASC, created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_key<'e, E>(
+        executor: E,
+        project_id: Uuid,
+        key: &str,
+    ) -> Result<Option<Self>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            ProjectStatus,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      key,
+                      name,
+                      category as "category!: TaskStatus",
+                      sort_order as "sort_order!: i32",
+                      wip_limit as "wip_limit: i32",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_statuses
+               WHERE project_id = $1 AND key = $2"#,
+            project_id,
+            key
+        )
+        .fetch_optional(executor)
+        .await
+    }
+
+    /// The column `key` in the project, checked to belong to `status`
+    pub async fn resolve(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        key: &str,
+        status: &TaskStatus,
+    ) -> Result<Self, ProjectStatusError> {
+        let project_status = Self::find_by_key(pool, project_id, key)
+            .await?
+            .ok_or(ProjectStatusError::NotFound)?;
+        if &project_status.category != status {
+            return Err(ProjectStatusError::CategoryMismatch {
+                key: project_status.key,
+                category: project_status.category,
+                status: status.clone(),
+            });
+        }
+        Ok(project_status)
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateProjectStatus,
+    ) -> Result<Self, ProjectStatusError> {
+        let key = data.key.trim();
+        let valid_key = !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid_key {
+            return Err(ProjectStatusError::InvalidKey);
+        }
+        check_wip_limit(data.wip_limit)?;
+
+        let id = Uuid::new_v4();
+        let name = data.name.trim();
+        let inserted = sqlx::query!(
+            r#"INSERT INTO project_statuses (id, project_id, key, name, category, sort_order, wip_limit)
+               VALUES ($1, $2, $3, $4, $5, COALESCE(
+                   $6,
+                   (SELECT MAX(sort_order) + 1 FROM project_statuses WHERE project_id = $2),
+                   0
+               ), $7)
+               ON CONFLICT (project_id, key) DO NOTHING"#,
+            id,
+            project_id,
+            key,
+            name,
+            data.category,
+            data.sort_order,
+            data.wip_limit
+        )
+        .execute(pool)
+        .await?;
+        if inserted.rows_affected() == 0 {
+            return Err(ProjectStatusError::DuplicateKey(key.to_string()));
+        }
+        Self::find_by_key(pool, project_id, key)
+            .await?
+            .ok_or(ProjectStatusError::NotFound)
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        key: &str,
+        data: &UpdateProjectStatus,
+    ) -> Result<Self, ProjectStatusError> {
+        check_wip_limit(data.wip_limit.flatten())?;
+        let set_wip_limit = data.wip_limit.is_some();
+        let wip_limit = data.wip_limit.flatten();
+        let result = sqlx::query!(
+            r#"UPDATE project_statuses
+               SET name = COALESCE(NULLIF(TRIM($3), ''), name),
+                   sort_order = COALESCE($4, sort_order),
+                   wip_limit = CASE WHEN $5 THEN $6 ELSE wip_limit END,
+                   updated_at = datetime('now', 'subsec')
+               WHERE project_id = $1 AND key = $2"#,
+            project_id,
+            key,
+            data.name,
+            data.sort_order,
+            set_wip_limit,
+            wip_limit
+        )
+        .execute(pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(ProjectStatusError::NotFound);
+        }
+        Self::find_by_key(pool, project_id, key)
+            .await?
+            .ok_or(ProjectStatusError::NotFound)
+    }
+
+    /// Tasks counted against the WIP limit of column `key`, top first. Open
+    /// tasks count, leaving out those created from attempts when
+    /// `exclude_child_tasks` is set.
+    pub async fn occupants<'e, E>(
+        executor: E,
+        project_id: Uuid,
+        key: &str,
+        exclude_child_tasks: bool,
+    ) -> Result<Vec<StatusOccupant>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            StatusOccupant,
+            r#"SELECT t.id as "id!: Uuid", t.title
+               FROM tasks t
+               WHERE t.project_id = $1
+                 AND t.status_key = $2
+                 AND t.archived_at IS NULL
+                 AND t.deleted_at IS NULL
+                 AND (NOT $3 OR t.parent_workspace_id IS NULL)
+               ORDER BY t.position IS NULL, t.position, t.created_at DESC"#,
+            project_id,
+            key,
+            exclude_child_tasks
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// How full each column of the project is, in board order
+    pub async fn occupancy(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        exclude_child_tasks: bool,
+    ) -> Result<Vec<StatusOccupancy>, sqlx::Error> {
+        sqlx::query_as!(
+            StatusOccupancy,
+            r#"SELECT ps.key AS status_key, ps.name, ps.wip_limit as "wip_limit: i32",
+                      (SELECT COUNT(*) FROM tasks t
+                        WHERE t.project_id = $1
+                          AND t.status_key = ps.key
+                          AND t.archived_at IS NULL
+                          AND t.deleted_at IS NULL
+                          AND (NOT $2 OR t.parent_workspace_id IS NULL)) AS "task_count!: i64"
+               FROM project_statuses ps
+               WHERE ps.project_id = $1
+               ORDER BY ps.sort_order ASC, ps.created_at ASC"#,
+            project_id,
+            exclude_child_tasks
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Remove a custom status. Built-in ones, and any still holding tasks
+    /// (archived and deleted ones included), are kept.
+    pub async fn delete(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        key: &str,
+    ) -> Result<(), ProjectStatusError> {
+        let mut tx = pool.begin().await?;
+        let category = sqlx::query_scalar!(
+            r#"SELECT category as "category!: TaskStatus"
+               FROM project_statuses
+               WHERE project_id = $1 AND key = $2"#,
+            project_id,
+            key
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ProjectStatusError::NotFound)?;
+        if category.to_string() == key {
+            return Err(ProjectStatusError::BuiltIn);
+        }
+
+        let tasks = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM tasks WHERE project_id = $1 AND status_key = $2"#,
+            project_id,
+            key
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if tasks > 0 {
+            return Err(ProjectStatusError::InUse(tasks));
+        }
+
+        sqlx::query!(
+            "DELETE FROM project_statuses WHERE project_id = $1 AND key = $2",
+            project_id,
+            key
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::task::{Task, tests::reload},
+        test_support::{create_task, pool_with_project},
+    };
+
+    fn blocked() -> CreateProjectStatus {
+        CreateProjectStatus {
+            key: "blocked".to_string(),
+            name: "Blocked".to_string(),
+            category: TaskStatus::InProgress,
+            sort_order: None,
+            wip_limit: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn new_projects_get_the_built_in_statuses() {
+        let (pool, project_id) = pool_with_project().await;
+        let statuses = ProjectStatus::find_by_project_id(&pool, project_id)
+            .await
+            .unwrap();
+        let keys: Vec<_> = statuses.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec!["todo", "inprogress", "inreview", "done", "cancelled"]
+        );
+        assert!(statuses.iter().all(ProjectStatus::is_built_in));
+
+        let status = ProjectStatus::create(&pool, project_id, &blocked())
+            .await
+            .unwrap();
+        assert_eq!(status.sort_order, 5);
+        assert!(!status.is_built_in());
+        assert!(matches!(
+            ProjectStatus::create(&pool, project_id, &blocked()).await,
+            Err(ProjectStatusError::DuplicateKey(_))
+        ));
+        let invalid = CreateProjectStatus {
+            key: "Ready for review".to_string(),
+            ..blocked()
+        };
+        assert!(matches!(
+            ProjectStatus::create(&pool, project_id, &invalid).await,
+            Err(ProjectStatusError::InvalidKey)
+        ));
+    }
+
+    #[tokio::test]
+    async fn statuses_in_use_are_kept() {
+        let (pool, project_id) = pool_with_project().await;
+        ProjectStatus::create(&pool, project_id, &blocked())
+            .await
+            .unwrap();
+        let task = create_task(&pool, project_id, "Waiting on design").await;
+        Task::update_status(&pool, task.id, TaskStatus::InProgress)
+            .await
+            .unwrap();
+        Task::set_status_key(&pool, task.id, Some("blocked"))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            ProjectStatus::delete(&pool, project_id, "todo").await,
+            Err(ProjectStatusError::BuiltIn)
+        ));
+        assert!(matches!(
+            ProjectStatus::delete(&pool, project_id, "blocked").await,
+            Err(ProjectStatusError::InUse(1))
+        ));
+        assert!(matches!(
+            ProjectStatus::resolve(&pool, project_id, "blocked", &TaskStatus::Done).await,
+            Err(ProjectStatusError::CategoryMismatch { .. })
+        ));
+
+        Task::set_status_key(&pool, task.id, None).await.unwrap();
+        ProjectStatus::delete(&pool, project_id, "blocked")
+            .await
+            .unwrap();
+        assert!(
+            ProjectStatus::find_by_key(&pool, project_id, "blocked")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn status_changes_leave_custom_columns() {
+        let (pool, project_id) = pool_with_project().await;
+        ProjectStatus::create(&pool, project_id, &blocked())
+            .await
+            .unwrap();
+        let task = create_task(&pool, project_id, "Flaky test").await;
+        Task::update_status(&pool, task.id, TaskStatus::InProgress)
+            .await
+            .unwrap();
+
+        // Every task names its column, built-in ones included
+        assert_eq!(reload(&pool, task.id).await.status_key, "inprogress");
+
+        Task::set_status_key(&pool, task.id, Some("blocked"))
+            .await
+            .unwrap();
+        assert_eq!(reload(&pool, task.id).await.status_key, "blocked");
+
+        // Only columns of the task's status hold it
+        assert!(
+            Task::set_status_key(&pool, task.id, Some("done"))
+                .await
+                .is_err()
+        );
+        assert!(
+            Task::set_status_key(&pool, task.id, Some("missing"))
+                .await
+                .is_err()
+        );
+
+        Task::update_status(&pool, task.id, TaskStatus::InReview)
+            .await
+            .unwrap();
+        assert_eq!(reload(&pool, task.id).await.status_key, "inreview");
+        Task::set_status_key(&pool, task.id, None).await.unwrap();
+        assert_eq!(reload(&pool, task.id).await.status_key, "inreview");
+    }
+
+    #[tokio::test]
+    async fn occupancy_counts_open_tasks() {
+        let (pool, project_id) = pool_with_project().await;
+        let mut tasks = Vec::new();
+        for title in ["Parser", "Lexer", "Docs"] {
+            let task = create_task(&pool, project_id, title).await;
+            Task::update_status(&pool, task.id, TaskStatus::InProgress)
+                .await
+                .unwrap();
+            tasks.push(task);
+        }
+        Task::archive(&pool, tasks[2].id).await.unwrap();
+
+        let occupants = ProjectStatus::occupants(&pool, project_id, "inprogress", false)
+            .await
+            .unwrap();
+        assert_eq!(occupants.len(), 2);
+        assert!(occupants.iter().all(|o| o.id != tasks[2].id));
+
+        let status = ProjectStatus::update(
+            &pool,
+            project_id,
+            "inprogress",
+            &UpdateProjectStatus {
+                name: None,
+                sort_order: None,
+                wip_limit: Some(Some(2)),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(status.wip_limit, Some(2));
+        let occupancy = ProjectStatus::occupancy(&pool, project_id, false)
+            .await
+            .unwrap();
+        let in_progress = occupancy
+            .iter()
+            .find(|o| o.status_key == "inprogress")
+            .unwrap();
+        assert_eq!(
+            (in_progress.wip_limit, in_progress.task_count),
+            (Some(2), 2)
+        );
+
+        let with_limit = |wip_limit| UpdateProjectStatus {
+            name: None,
+            sort_order: None,
+            wip_limit,
+        };
+        assert!(matches!(
+            ProjectStatus::update(&pool, project_id, "inprogress", &with_limit(Some(Some(0))))
+                .await,
+            Err(ProjectStatusError::InvalidWipLimit)
+        ));
+        // Leaving the field out keeps the limit, `null` removes it
+        let status = ProjectStatus::update(&pool, project_id, "inprogress", &with_limit(None))
+            .await
+            .unwrap();
+        assert_eq!(status.wip_limit, Some(2));
+        let status =
+            ProjectStatus::update(&pool, project_id, "inprogress", &with_limit(Some(None)))
+                .await
+                .unwrap();
+        assert_eq!(status.wip_limit, None);
+    }
+}
@@ -1,11 +1,21 @@
-use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
+use serde_with::rust::double_option;
+use sqlx::{Acquire, Executor, FromRow, Sqlite, SqliteConnection, SqlitePool, Type};
 use strum_macros::{Display, EnumString};
+use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::{project::Project, workspace::Workspace};
+use super::{
+    project::Project,
+    task_dependency::{TaskDependency, TaskDependencyError},
+    task_rank::{self, MAX_RANK_LEN},
+    task_schedule::{self, TaskScheduleError},
+    workspace::Workspace,
+};
 
 #[derive(
     Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS, EnumString, Display, Default,
@@ -22,6 +32,35 @@ pub enum TaskStatus {
     Cancelled,
 }
 
+/// Declared from least to most urgent, so `Ord` follows urgency
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Type,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    TS,
+    EnumString,
+    Display,
+    Default,
+)]
+#[sqlx(type_name = "task_priority", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum TaskPriority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Urgent,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct Task {
     pub id: Uuid,
@@ -31,6 +70,15 @@ pub struct Task {
     pub status: TaskStatus,
     pub parent_workspace_id: Option<Uuid>, // Foreign key to parent Workspace
     pub shared_task_id: Option<Uuid>,
+    pub priority: TaskPriority,
+    /// Manual order within the status column, compared as a plain string
+    pub position: Option<String>,
+    pub assignee: Option<String>,
+    pub due_date: Option<NaiveDate>,
+    /// Share of the parent's schedule when due dates are distributed
+    pub schedule_weight: f64,
+    /// Project status the task sits in, one whose category is `status`
+    pub status_key: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -43,6 +91,10 @@ pub struct TaskWithAttemptStatus {
     pub has_in_progress_attempt: bool,
     pub last_attempt_failed: bool,
     pub executor: String,
+    /// Some task blocking this one is not Done yet
+    pub blocked: bool,
+    /// Set while the task is archived and read-only
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl std::ops::Deref for TaskWithAttemptStatus {
@@ -58,6 +110,24 @@ impl std::ops::DerefMut for TaskWithAttemptStatus {
     }
 }
 
+/// A full-text match from [`Task::search`]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskSearchResult {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    #[sqlx(flatten)]
+    pub task: Task,
+    /// Task owning the workspace this one was created from, to group
+    /// results under their parent
+    pub parent_task_id: Option<Uuid>,
+    /// Title with matches wrapped in `<mark>` tags
+    pub title_highlight: String,
+    /// Excerpt of the description around the matches, if any matched
+    pub description_snippet: Option<String>,
+    /// BM25 score, lower is a better match
+    pub rank: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct TaskRelationships {
     pub parent_task: Option<Task>, // The task that owns the parent workspace
@@ -74,6 +144,20 @@ pub struct CreateTask {
     pub parent_workspace_id: Option<Uuid>,
     pub image_ids: Option<Vec<Uuid>>,
     pub shared_task_id: Option<Uuid>,
+    /// Defaults to the parent task's priority, or Medium without a parent
+    #[serde(default)]
+    #[ts(optional)]
+    pub priority: Option<TaskPriority>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub assignee: Option<String>,
+    /// Must not be in the past
+    #[serde(default)]
+    #[ts(optional)]
+    pub due_date: Option<NaiveDate>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub schedule_weight: Option<f64>,
 }
 
 impl CreateTask {
@@ -90,6 +174,10 @@ impl CreateTask {
             parent_workspace_id: None,
             image_ids: None,
             shared_task_id: None,
+            priority: None,
+            assignee: None,
+            due_date: None,
+            schedule_weight: None,
         }
     }
 
@@ -108,17 +196,216 @@ impl CreateTask {
             parent_workspace_id: None,
             image_ids: None,
             shared_task_id: Some(shared_task_id),
+            priority: None,
+            assignee: None,
+            due_date: None,
+            schedule_weight: None,
+        }
+    }
+
+    /// A fresh Todo task with the priority, assignee and schedule weight
+    /// [`Task::duplicate`] carries over from `task`
+    fn copy_of(task: &Task, title: String, description: Option<String>) -> Self {
+        Self {
+            priority: Some(task.priority),
+            assignee: task.assignee.clone(),
+            schedule_weight: Some(task.schedule_weight),
+            ..Self::from_title_description(task.project_id, title, description)
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, TS)]
+/// Turn free text into an FTS5 query: each word is quoted so punctuation
+/// can't be read as query syntax, and the last one matches as a prefix
+fn fts_match_query(input: &str) -> Option<String> {
+    let words: Vec<String> = input
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .collect();
+    let (last, rest) = words.split_last()?;
+    let mut terms: Vec<String> = rest.iter().map(|word| format!("\"{word}\"")).collect();
+    terms.push(format!("\"{last}\"*"));
+    Some(terms.join(" "))
+}
+
+pub const DEFAULT_SCHEDULE_WEIGHT: f64 = 1.0;
+
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct AssigneeTaskCount {
+    pub project_id: Uuid,
+    /// `None` counts the project's unassigned tasks
+    pub assignee: Option<String>,
+    #[ts(type = "number")]
+    pub open_tasks: i64,
+}
+
+/// A due date set on a task by [`Task::distribute_due_dates`]
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DueDateChange {
+    pub task_id: Uuid,
+    pub previous: Option<NaiveDate>,
+    pub due_date: NaiveDate,
+}
+
+/// An open task with a due date, for the cross-project dashboard
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct DueTask {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    #[sqlx(flatten)]
+    pub task: Task,
+    pub project_name: String,
+}
+
+#[derive(Debug, Error)]
+pub enum TaskMoveError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Task not found")]
+    NotFound,
+    #[error("Task {0} is not in the target column")]
+    AnchorNotInColumn(Uuid),
+}
+
+#[derive(Debug, Error)]
+pub enum TaskProjectMoveError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Task not found")]
+    NotFound,
+    #[error("Project {0} not found")]
+    ProjectNotFound(Uuid),
+    #[error("Task is already in this project")]
+    SameProject,
+    #[error("Task was created from another task's attempt, move that task instead")]
+    ChildTask,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct MoveTaskToProject {
+    pub project_id: Uuid,
+    /// Drop dependencies on tasks outside the target project and unlink
+    /// shared tasks; both are kept otherwise
+    #[serde(default)]
+    #[ts(optional)]
+    pub sever_links: bool,
+}
+
+/// What [`Task::move_to_project`] changed
+#[derive(Debug, Clone)]
+pub struct ProjectMove {
+    pub previous_project_id: Uuid,
+    /// The moved task first, then the tasks created from it
+    pub task_ids: Vec<Uuid>,
+    /// Dependencies removed because they would have crossed projects
+    pub severed_dependencies: Vec<(Uuid, Uuid)>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct MoveTask {
+    pub status: TaskStatus,
+    /// Task to place this one directly below; the top of the column if unset
+    pub after_task_id: Option<Uuid>,
+    /// Custom project status within `status` to put the task in
+    #[serde(default)]
+    #[ts(optional)]
+    pub status_key: Option<String>,
+    /// Move even when the target column is at its WIP limit
+    #[serde(default)]
+    #[ts(optional)]
+    pub override_wip_limit: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct DuplicateTask {
+    /// Also recreate the tasks created from this task's attempts
+    #[serde(default)]
+    pub include_children: bool,
+    /// Carry each child's description over; otherwise children start blank
+    #[serde(default)]
+    pub copy_child_descriptions: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DuplicatedTask {
+    pub task: Task,
+    /// In the order the originals were created
+    pub children: Vec<Task>,
+}
+
+#[derive(Debug, Error)]
+pub enum TaskRestoreError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Task not found")]
+    NotFound,
+    #[error("Task {0} it was created from is deleted, restore it first")]
+    ParentDeleted(Uuid),
+}
+
+/// Largest number of tasks accepted by [`Task::create_many`]
+pub const MAX_TASK_BATCH_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TaskBatchItemError {
+    /// Position of the rejected task in the submitted batch
+    pub index: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Error)]
+pub enum TaskBatchError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Batch of {0} tasks exceeds the limit of {MAX_TASK_BATCH_SIZE}")]
+    TooLarge(usize),
+    #[error("{} tasks in the batch are invalid", .0.len())]
+    Invalid(Vec<TaskBatchItemError>),
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, TS)]
 pub struct UpdateTask {
     pub title: Option<String>,
     pub description: Option<String>,
     pub status: Option<TaskStatus>,
     pub parent_workspace_id: Option<Uuid>,
     pub image_ids: Option<Vec<Uuid>>,
+    /// Move to In Progress even while blockers are not Done
+    #[serde(default)]
+    #[ts(optional)]
+    pub ignore_blockers: Option<bool>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub priority: Option<TaskPriority>,
+    /// Also apply a priority change to tasks created from this one's
+    /// workspaces, and to theirs in turn
+    #[serde(default)]
+    #[ts(optional)]
+    pub propagate_priority: Option<bool>,
+    /// An empty string unassigns the task
+    #[serde(default)]
+    #[ts(optional)]
+    pub assignee: Option<String>,
+    /// `null` clears the due date
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "string | null")]
+    pub due_date: Option<Option<NaiveDate>>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub schedule_weight: Option<f64>,
+    /// With a new due date, spread it over the tasks created from this one
+    #[serde(default)]
+    #[ts(optional)]
+    pub redistribute_due_dates: Option<bool>,
+    /// Change status even when the target column is at its WIP limit
+    #[serde(default)]
+    #[ts(optional)]
+    pub override_wip_limit: Option<bool>,
 }
 
 impl Task {
@@ -137,6 +424,7 @@ impl Task {
     pub async fn find_by_project_id_with_attempt_status(
         pool: &SqlitePool,
         project_id: Uuid,
+        include_archived: bool,
     ) -> Result<Vec<TaskWithAttemptStatus>, sqlx::Error> {
         let records = sqlx::query!(
             r#"SELECT
@@ -147,8 +435,24 @@ impl Task {
   t.status                        AS "status!: TaskStatus",
   t.parent_workspace_id           AS "parent_workspace_id: Uuid",
   t.shared_task_id                AS "shared_task_id: Uuid",
+  t.priority                      AS "priority!: TaskPriority",
+  t.position,
+  t.assignee,
+  t.due_date                      AS "due_date: NaiveDate",
+  t.schedule_weight               AS "schedule_weight!: f64",
+  t.status_key                    AS "status_key!",
   t.created_at                    AS "created_at!: DateTime<Utc>",
   t.updated_at                    AS "updated_at!: DateTime<Utc>",
+  t.archived_at                   AS "archived_at: DateTime<Utc>",
+
+  EXISTS (
+    SELECT 1
+      FROM task_dependencies d
+      JOIN tasks blocker ON blocker.id = d.blocker_id
+     WHERE d.blocked_id = t.id
+       AND blocker.status != 'done'
+       AND blocker.deleted_at IS NULL
+  )                               AS "blocked!: bool",
 
   CASE WHEN EXISTS (
     SELECT 1
@@ -183,13 +487,24 @@ impl Task {
 
 FROM tasks t
 WHERE t.project_id = $1
-ORDER BY t.created_at DESC"#,
-            project_id
+  AND t.deleted_at IS NULL
+  AND ($2 OR t.archived_at IS NULL)
+ORDER BY CASE t.priority
+           WHEN 'urgent' THEN 0
+           WHEN 'high'   THEN 1
+           WHEN 'medium' THEN 2
+           ELSE 3
+         END,
+         t.position IS NULL,
+         t.position,
+         t.created_at DESC"#,
+            project_id,
+            include_archived
         )
         .fetch_all(pool)
         .await?;
 
-        let tasks = records
+        Ok(records
             .into_iter()
             .map(|rec| TaskWithAttemptStatus {
                 task: Task {
@@ -200,34 +515,59 @@ ORDER BY t.created_at DESC"#,
                     status: rec.status,
                     parent_workspace_id: rec.parent_workspace_id,
                     shared_task_id: rec.shared_task_id,
+                    priority: rec.priority,
+                    position: rec.position,
+                    assignee: rec.assignee,
+                    due_date: rec.due_date,
+                    schedule_weight: rec.schedule_weight,
+                    status_key: rec.status_key,
                     created_at: rec.created_at,
                     updated_at: rec.updated_at,
                 },
                 has_in_progress_attempt: rec.has_in_progress_attempt != 0,
                 last_attempt_failed: rec.last_attempt_failed != 0,
                 executor: rec.executor,
+                blocked: rec.blocked,
+                archived_at: rec.archived_at,
             })
-            .collect();
-
-        Ok(tasks)
+            .collect())
     }
 
-    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+    pub async fn find_by_id<'e, E>(executor: E, id: Uuid) -> Result<Option<Self>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", priority as "priority!: TaskPriority", position, assignee, due_date as "due_date: NaiveDate", schedule_weight as "schedule_weight!: f64", status_key as "status_key!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE id = $1"#,
             id
         )
-        .fetch_optional(pool)
+        .fetch_optional(executor)
+        .await
+    }
+
+    /// Like [`Task::find_by_id`], but a deleted task is not found
+    pub async fn find_live_by_id<'e, E>(executor: E, id: Uuid) -> Result<Option<Self>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", priority as "priority!: TaskPriority", position, assignee, due_date as "due_date: NaiveDate", schedule_weight as "schedule_weight!: f64", status_key as "status_key!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE id = $1 AND deleted_at IS NULL"#,
+            id
+        )
+        .fetch_optional(executor)
         .await
     }
 
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", priority as "priority!: TaskPriority", position, assignee, due_date as "due_date: NaiveDate", schedule_weight as "schedule_weight!: f64", status_key as "status_key!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE rowid = $1"#,
             rowid
@@ -245,9 +585,9 @@ ORDER BY t.created_at DESC"#,
     {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", priority as "priority!: TaskPriority", position, assignee, due_date as "due_date: NaiveDate", schedule_weight as "schedule_weight!: f64", status_key as "status_key!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
-               WHERE shared_task_id = $1
+               WHERE shared_task_id = $1 AND deleted_at IS NULL
                LIMIT 1"#,
             shared_task_id
         )
@@ -258,7 +598,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_all_shared(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", priority as "priority!: TaskPriority", position, assignee, due_date as "due_date: NaiveDate", schedule_weight as "schedule_weight!: f64", status_key as "status_key!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE shared_task_id IS NOT NULL"#
         )
@@ -271,193 +611,1203 @@ ORDER BY t.created_at DESC"#,
         data: &CreateTask,
         task_id: Uuid,
     ) -> Result<Self, sqlx::Error> {
-        let status = data.status.clone().unwrap_or_default();
-        sqlx::query_as!(
-            Task,
-            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_workspace_id, shared_task_id)
-               VALUES ($1, $2, $3, $4, $5, $6, $7)
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
-            task_id,
-            data.project_id,
-            data.title,
-            data.description,
-            status,
-            data.parent_workspace_id,
-            data.shared_task_id
+        let mut tx = pool.begin().await?;
+        let task = Self::insert(&mut tx, task_id, data).await?;
+        tx.commit().await?;
+        Ok(task)
+    }
+
+    /// Put the task above every other task in its column
+    async fn place_at_top(conn: &mut SqliteConnection, id: Uuid) -> Result<(), sqlx::Error> {
+        let column = sqlx::query!(
+            r#"SELECT t.project_id as "project_id!: Uuid",
+                      t.status as "status!: TaskStatus",
+                      (SELECT MIN(other.position)
+                         FROM tasks other
+                        WHERE other.project_id = t.project_id
+                          AND other.status = t.status
+                          AND other.id != t.id) as "first?: String"
+               FROM tasks t
+               WHERE t.id = $1"#,
+            id
         )
-        .fetch_one(pool)
-        .await
+        .fetch_one(&mut *conn)
+        .await?;
+        let rank = task_rank::rank_between(None, column.first.as_deref());
+        Self::set_position(conn, id, column.project_id, column.status, rank).await
     }
 
-    pub async fn update(
-        pool: &SqlitePool,
+    async fn set_position(
+        conn: &mut SqliteConnection,
         id: Uuid,
         project_id: Uuid,
-        title: String,
-        description: Option<String>,
         status: TaskStatus,
-        parent_workspace_id: Option<Uuid>,
-    ) -> Result<Self, sqlx::Error> {
-        sqlx::query_as!(
-            Task,
-            r#"UPDATE tasks
-               SET title = $3, description = $4, status = $5, parent_workspace_id = $6
-               WHERE id = $1 AND project_id = $2
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
-            id,
-            project_id,
-            title,
-            description,
-            status,
-            parent_workspace_id
-        )
-        .fetch_one(pool)
-        .await
+        rank: String,
+    ) -> Result<(), sqlx::Error> {
+        let too_long = rank.len() > MAX_RANK_LEN;
+        sqlx::query!("UPDATE tasks SET position = $2 WHERE id = $1", id, rank)
+            .execute(&mut *conn)
+            .await?;
+        if too_long {
+            Self::rebalance_column(conn, project_id, status).await?;
+        }
+        Ok(())
     }
 
-    pub async fn update_status(
-        pool: &SqlitePool,
-        id: Uuid,
+    /// Renumber a column with evenly spaced ranks, keeping its order
+    async fn rebalance_column(
+        conn: &mut SqliteConnection,
+        project_id: Uuid,
         status: TaskStatus,
     ) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            "UPDATE tasks SET status = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
-            id,
+        let ids = sqlx::query_scalar!(
+            r#"SELECT id as "id!: Uuid" FROM tasks
+               WHERE project_id = $1 AND status = $2
+               ORDER BY position IS NULL, position, created_at DESC"#,
+            project_id,
             status
         )
-        .execute(pool)
+        .fetch_all(&mut *conn)
         .await?;
+        for (id, rank) in ids.iter().zip(task_rank::spread_ranks(ids.len())) {
+            sqlx::query!("UPDATE tasks SET position = $2 WHERE id = $1", id, rank)
+                .execute(&mut *conn)
+                .await?;
+        }
         Ok(())
     }
 
-    /// Update the parent_workspace_id field for a task
-    pub async fn update_parent_workspace_id(
-        pool: &SqlitePool,
-        task_id: Uuid,
-        parent_workspace_id: Option<Uuid>,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            "UPDATE tasks SET parent_workspace_id = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
-            task_id,
-            parent_workspace_id
+    /// Move the task into the `status` column directly below `after_task_id`,
+    /// or to the top of it without one
+    pub async fn move_to<'a, A>(
+        conn: A,
+        id: Uuid,
+        status: TaskStatus,
+        after_task_id: Option<Uuid>,
+    ) -> Result<Self, TaskMoveError>
+    where
+        A: Acquire<'a, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+
+        // Writing first takes the database write lock, so moves racing for the
+        // same gap see each other's ranks rather than computing the same one
+        let project_id = sqlx::query_scalar!(
+            r#"UPDATE tasks SET status = $2 WHERE id = $1 RETURNING project_id as "project_id!: Uuid""#,
+            id,
+            status
         )
-        .execute(pool)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(TaskMoveError::NotFound)?;
+
+        let before = match after_task_id {
+            Some(after_id) => sqlx::query_scalar!(
+                r#"SELECT position FROM tasks
+                   WHERE id = $1 AND project_id = $2 AND status = $3 AND id != $4"#,
+                after_id,
+                project_id,
+                status,
+                id
+            )
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(TaskMoveError::AnchorNotInColumn(after_id))?,
+            None => None,
+        };
+        let before = before.as_deref();
+        let after = sqlx::query_scalar!(
+            r#"SELECT MIN(position) as "position: String" FROM tasks
+               WHERE project_id = $1 AND status = $2 AND id != $3
+                 AND ($4 IS NULL OR position > $4)"#,
+            project_id,
+            status,
+            id,
+            before
+        )
+        .fetch_one(&mut *tx)
         .await?;
-        Ok(())
+
+        let rank = task_rank::rank_between(before, after.as_deref());
+        Self::set_position(&mut tx, id, project_id, status, rank).await?;
+        let task = Self::find_by_id(&mut *tx, id)
+            .await?
+            .ok_or(TaskMoveError::NotFound)?;
+        tx.commit().await?;
+        Ok(task)
     }
 
-    /// Nullify parent_workspace_id for all tasks that reference the given workspace ID
-    /// This breaks parent-child relationships before deleting a parent task
-    pub async fn nullify_children_by_workspace_id<'e, E>(
-        executor: E,
-        workspace_id: Uuid,
-    ) -> Result<u64, sqlx::Error>
+    /// Move a task, and every task created from its attempts, to another
+    /// project. Each lands at the top of its column there, in the order it
+    /// had before. Dependencies and shared-task links are kept unless
+    /// `sever_links` is set, in which case those reaching outside the
+    /// target project are removed.
+    pub async fn move_to_project<'a, A>(
+        conn: A,
+        id: Uuid,
+        target_project_id: Uuid,
+        sever_links: bool,
+    ) -> Result<ProjectMove, TaskProjectMoveError>
     where
-        E: Executor<'e, Database = Sqlite>,
+        A: Acquire<'a, Database = Sqlite>,
     {
-        let result = sqlx::query!(
-            "UPDATE tasks SET parent_workspace_id = NULL WHERE parent_workspace_id = $1",
-            workspace_id
+        let mut tx = conn.begin().await?;
+
+        let moving = sqlx::query!(
+            r#"SELECT project_id as "project_id!: Uuid",
+                      parent_workspace_id as "parent_workspace_id: Uuid"
+               FROM tasks
+               WHERE id = $1"#,
+            id
         )
-        .execute(executor)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(TaskProjectMoveError::NotFound)?;
+        let previous_project_id = moving.project_id;
+        if previous_project_id == target_project_id {
+            return Err(TaskProjectMoveError::SameProject);
+        }
+        if moving.parent_workspace_id.is_some() {
+            return Err(TaskProjectMoveError::ChildTask);
+        }
+        let project_exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM projects WHERE id = $1) as "exists!: bool""#,
+            target_project_id
+        )
+        .fetch_one(&mut *tx)
         .await?;
-        Ok(result.rows_affected())
+        if !project_exists {
+            return Err(TaskProjectMoveError::ProjectNotFound(target_project_id));
+        }
+
+        let mut task_ids = sqlx::query_scalar!(
+            r#"WITH RECURSIVE task_tree(id) AS (
+                   SELECT id FROM tasks WHERE id = $1
+                   UNION
+                   SELECT t.id
+                     FROM tasks t
+                     JOIN workspaces w ON t.parent_workspace_id = w.id
+                     JOIN task_tree tt ON w.task_id = tt.id
+               )
+               UPDATE tasks
+               SET project_id = $2, status_key = status, updated_at = datetime('now', 'subsec')
+               WHERE id IN (SELECT id FROM task_tree)
+               RETURNING id as "id!: Uuid""#,
+            id,
+            target_project_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        task_ids.sort_by_key(|moved| *moved != id);
+
+        // Placing each at the top from the bottom up keeps their order
+        let by_position = sqlx::query_scalar!(
+            r#"WITH RECURSIVE task_tree(id) AS (
+                   SELECT id FROM tasks WHERE id = $1
+                   UNION
+                   SELECT t.id
+                     FROM tasks t
+                     JOIN workspaces w ON t.parent_workspace_id = w.id
+                     JOIN task_tree tt ON w.task_id = tt.id
+               )
+               SELECT id as "id!: Uuid" FROM tasks
+               WHERE id IN (SELECT id FROM task_tree)
+               ORDER BY position DESC"#,
+            id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for moved in by_position {
+            Self::place_at_top(&mut tx, moved).await?;
+        }
+
+        let mut severed_dependencies = Vec::new();
+        if sever_links {
+            severed_dependencies = sqlx::query!(
+                r#"WITH RECURSIVE task_tree(id) AS (
+                       SELECT id FROM tasks WHERE id = $1
+                       UNION
+                       SELECT t.id
+                         FROM tasks t
+                         JOIN workspaces w ON t.parent_workspace_id = w.id
+                         JOIN task_tree tt ON w.task_id = tt.id
+                   )
+                   DELETE FROM task_dependencies
+                   WHERE (blocker_id IN (SELECT id FROM task_tree)
+                          OR blocked_id IN (SELECT id FROM task_tree))
+                     AND EXISTS (
+                         SELECT 1 FROM tasks t
+                         WHERE t.id IN (blocker_id, blocked_id) AND t.project_id != $2
+                     )
+                   RETURNING blocker_id as "blocker_id!: Uuid", blocked_id as "blocked_id!: Uuid""#,
+                id,
+                target_project_id
+            )
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .map(|rec| (rec.blocker_id, rec.blocked_id))
+            .collect();
+            sqlx::query!(
+                r#"WITH RECURSIVE task_tree(id) AS (
+                       SELECT id FROM tasks WHERE id = $1
+                       UNION
+                       SELECT t.id
+                         FROM tasks t
+                         JOIN workspaces w ON t.parent_workspace_id = w.id
+                         JOIN task_tree tt ON w.task_id = tt.id
+                   )
+                   UPDATE tasks SET shared_task_id = NULL
+                   WHERE id IN (SELECT id FROM task_tree)"#,
+                id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(ProjectMove {
+            previous_project_id,
+            task_ids,
+            severed_dependencies,
+        })
     }
 
-    /// Clear shared_task_id for all tasks that reference shared tasks belonging to a remote project
-    /// This breaks the link between local tasks and shared tasks when a project is unlinked
-    pub async fn clear_shared_task_ids_for_remote_project<'e, E>(
+    /// Put the task in a status column of its project, or back in the
+    /// built-in column of its status with `None`. A status change returns
+    /// the task to the built-in column of its new status.
+    pub async fn set_status_key<'e, E>(
         executor: E,
-        remote_project_id: Uuid,
-    ) -> Result<u64, sqlx::Error>
+        id: Uuid,
+        key: Option<&str>,
+    ) -> Result<(), sqlx::Error>
     where
         E: Executor<'e, Database = Sqlite>,
     {
-        let result = sqlx::query!(
-            r#"UPDATE tasks
-               SET shared_task_id = NULL
-               WHERE project_id IN (
-                   SELECT id FROM projects WHERE remote_project_id = $1
-               )"#,
-            remote_project_id
+        sqlx::query!(
+            "UPDATE tasks SET status_key = COALESCE($2, status) WHERE id = $1",
+            id,
+            key
         )
         .execute(executor)
         .await?;
-        Ok(result.rows_affected())
+        Ok(())
     }
 
-    pub async fn delete<'e, E>(executor: E, id: Uuid) -> Result<u64, sqlx::Error>
+    pub async fn set_due_date<'e, E>(
+        executor: E,
+        id: Uuid,
+        due_date: Option<NaiveDate>,
+    ) -> Result<(), sqlx::Error>
     where
         E: Executor<'e, Database = Sqlite>,
     {
-        let result = sqlx::query!("DELETE FROM tasks WHERE id = $1", id)
+        sqlx::query!("UPDATE tasks SET due_date = $2 WHERE id = $1", id, due_date)
             .execute(executor)
             .await?;
-        Ok(result.rows_affected())
+        Ok(())
     }
 
-    pub async fn set_shared_task_id<'e, E>(
+    pub async fn set_schedule_weight<'e, E>(
         executor: E,
         id: Uuid,
-        shared_task_id: Option<Uuid>,
-    ) -> Result<(), sqlx::Error>
+        weight: f64,
+    ) -> Result<(), TaskScheduleError>
     where
         E: Executor<'e, Database = Sqlite>,
     {
+        task_schedule::check_weight(weight)?;
         sqlx::query!(
-            "UPDATE tasks SET shared_task_id = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            "UPDATE tasks SET schedule_weight = $2 WHERE id = $1",
             id,
-            shared_task_id
+            weight
         )
         .execute(executor)
         .await?;
         Ok(())
     }
 
-    pub async fn batch_unlink_shared_tasks<'e, E>(
-        executor: E,
-        shared_task_ids: &[Uuid],
-    ) -> Result<u64, sqlx::Error>
+    /// Spread the task's due date over the tasks created from its workspaces,
+    /// in creation order and in proportion to their schedule weights, starting
+    /// from `today`. Returns the due dates that changed.
+    pub async fn distribute_due_dates<'a, A>(
+        conn: A,
+        id: Uuid,
+        today: NaiveDate,
+    ) -> Result<Vec<DueDateChange>, TaskScheduleError>
     where
-        E: Executor<'e, Database = Sqlite>,
+        A: Acquire<'a, Database = Sqlite>,
     {
-        if shared_task_ids.is_empty() {
-            return Ok(0);
+        let mut tx = conn.begin().await?;
+
+        let due_date = sqlx::query_scalar!(
+            r#"SELECT due_date as "due_date: NaiveDate" FROM tasks WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .flatten();
+        let due_date = due_date.ok_or(TaskScheduleError::NoDueDate)?;
+        if due_date < today {
+            return Err(TaskScheduleError::DeadlinePassed(due_date));
         }
 
-        let mut query_builder = sqlx::QueryBuilder::new(
-            "UPDATE tasks SET shared_task_id = NULL, updated_at = CURRENT_TIMESTAMP WHERE shared_task_id IN (",
-        );
+        let children = sqlx::query!(
+            r#"SELECT t.id as "id!: Uuid",
+                      t.due_date as "due_date: NaiveDate",
+                      t.schedule_weight as "schedule_weight!: f64"
+               FROM tasks t
+               JOIN workspaces w ON w.id = t.parent_workspace_id
+               WHERE w.task_id = $1 AND t.deleted_at IS NULL
+               ORDER BY t.created_at ASC, t.rowid ASC"#,
+            id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let weights: Vec<f64> = children.iter().map(|child| child.schedule_weight).collect();
+        let dates = task_schedule::distribute_due_dates(today, due_date, &weights);
 
-        let mut separated = query_builder.separated(", ");
-        for id in shared_task_ids {
-            separated.push_bind(id);
+        let mut changes = Vec::new();
+        for (child, date) in children.into_iter().zip(dates) {
+            if child.due_date == Some(date) {
+                continue;
+            }
+            sqlx::query!(
+                "UPDATE tasks SET due_date = $2 WHERE id = $1",
+                child.id,
+                date
+            )
+            .execute(&mut *tx)
+            .await?;
+            changes.push(DueDateChange {
+                task_id: child.id,
+                previous: child.due_date,
+                due_date: date,
+            });
         }
-        separated.push_unseparated(")");
-
-        let result = query_builder.build().execute(executor).await?;
-        Ok(result.rows_affected())
+        tx.commit().await?;
+        Ok(changes)
     }
 
-    pub async fn find_children_by_workspace_id(
+    /// Open tasks across all unarchived projects due on or before `today`
+    /// plus `within_days`, overdue ones included, soonest first
+    pub async fn find_due(
         pool: &SqlitePool,
-        workspace_id: Uuid,
-    ) -> Result<Vec<Self>, sqlx::Error> {
-        // Find only child tasks that have this workspace as their parent
-        sqlx::query_as!(
-            Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
-               FROM tasks
-               WHERE parent_workspace_id = $1
-               ORDER BY created_at DESC"#,
-            workspace_id,
+        today: NaiveDate,
+        within_days: u32,
+    ) -> Result<Vec<DueTask>, sqlx::Error> {
+        let horizon = today
+            .checked_add_days(chrono::Days::new(within_days.into()))
+            .unwrap_or(NaiveDate::MAX);
+        let records = sqlx::query!(
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.shared_task_id as "shared_task_id: Uuid", t.priority as "priority!: TaskPriority", t.position, t.assignee, t.due_date as "due_date: NaiveDate", t.schedule_weight as "schedule_weight!: f64", t.status_key as "status_key!", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>",
+                      p.name as project_name
+               FROM tasks t
+               JOIN projects p ON p.id = t.project_id
+               WHERE t.due_date IS NOT NULL
+                 AND t.due_date <= $1
+                 AND t.status NOT IN ('done', 'cancelled')
+                 AND t.archived_at IS NULL
+                 AND t.deleted_at IS NULL
+                 AND p.archived_at IS NULL
+               ORDER BY t.due_date ASC, t.created_at ASC"#,
+            horizon
         )
         .fetch_all(pool)
-        .await
-    }
+        .await?;
 
-    pub async fn find_relationships_for_workspace(
-        pool: &SqlitePool,
+        Ok(records
+            .into_iter()
+            .map(|rec| DueTask {
+                task: Task {
+                    id: rec.id,
+                    project_id: rec.project_id,
+                    title: rec.title,
+                    description: rec.description,
+                    status: rec.status,
+                    parent_workspace_id: rec.parent_workspace_id,
+                    shared_task_id: rec.shared_task_id,
+                    priority: rec.priority,
+                    position: rec.position,
+                    assignee: rec.assignee,
+                    due_date: rec.due_date,
+                    schedule_weight: rec.schedule_weight,
+                    status_key: rec.status_key,
+                    created_at: rec.created_at,
+                    updated_at: rec.updated_at,
+                },
+                project_name: rec.project_name,
+            })
+            .collect())
+    }
+
+    /// Assign the task, or unassign it with `None` or a blank name. Returns
+    /// the assignee as stored.
+    pub async fn set_assignee<'e, E>(
+        executor: E,
+        id: Uuid,
+        assignee: Option<&str>,
+    ) -> Result<Option<String>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_scalar!(
+            "UPDATE tasks SET assignee = NULLIF(TRIM($2), '') WHERE id = $1 RETURNING assignee",
+            id,
+            assignee
+        )
+        .fetch_optional(executor)
+        .await
+        .map(Option::flatten)
+    }
+
+    /// Tasks not yet Done or Cancelled per assignee, for every unarchived
+    /// project or just `project_id`. Archived and deleted tasks are not
+    /// counted.
+    pub async fn open_counts_by_assignee(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+    ) -> Result<Vec<AssigneeTaskCount>, sqlx::Error> {
+        sqlx::query_as!(
+            AssigneeTaskCount,
+            r#"SELECT project_id as "project_id!: Uuid", assignee as "assignee?", COUNT(*) as "open_tasks!: i64"
+               FROM tasks
+               WHERE (project_id = $1
+                      OR ($1 IS NULL
+                          AND project_id NOT IN
+                              (SELECT id FROM projects WHERE archived_at IS NOT NULL)))
+                 AND status NOT IN ('done', 'cancelled')
+                 AND archived_at IS NULL
+                 AND deleted_at IS NULL
+               GROUP BY project_id, assignee
+               ORDER BY project_id, assignee IS NULL, assignee"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Set the task's priority, and with `propagate` that of every task
+    /// created from its workspaces. Returns the ids of the tasks that changed.
+    pub async fn set_priority<'e, E>(
+        executor: E,
+        id: Uuid,
+        priority: TaskPriority,
+        propagate: bool,
+    ) -> Result<Vec<Uuid>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        if propagate {
+            sqlx::query_scalar!(
+                r#"WITH RECURSIVE task_tree(id) AS (
+                       SELECT id FROM tasks WHERE id = $1
+                       UNION
+                       SELECT t.id
+                         FROM tasks t
+                         JOIN workspaces w ON t.parent_workspace_id = w.id
+                         JOIN task_tree tt ON w.task_id = tt.id
+                   )
+                   UPDATE tasks
+                   SET priority = $2
+                   WHERE id IN (SELECT id FROM task_tree) AND deleted_at IS NULL AND priority != $2
+                   RETURNING id as "id!: Uuid""#,
+                id,
+                priority
+            )
+            .fetch_all(executor)
+            .await
+        } else {
+            sqlx::query_scalar!(
+                r#"UPDATE tasks
+                   SET priority = $2
+                   WHERE id = $1 AND priority != $2
+                   RETURNING id as "id!: Uuid""#,
+                id,
+                priority
+            )
+            .fetch_all(executor)
+            .await
+        }
+    }
+
+    /// Create every task in `data`, with its images, in a single transaction.
+    /// Any invalid item rejects the whole batch, reporting each bad index.
+    pub async fn create_many<'a, A>(
+        conn: A,
+        data: &[CreateTask],
+    ) -> Result<Vec<Self>, TaskBatchError>
+    where
+        A: Acquire<'a, Database = Sqlite>,
+    {
+        if data.len() > MAX_TASK_BATCH_SIZE {
+            return Err(TaskBatchError::TooLarge(data.len()));
+        }
+
+        let mut tx = conn.begin().await?;
+
+        let mut project_exists: HashMap<Uuid, bool> = HashMap::new();
+        let mut errors = Vec::new();
+        for (index, item) in data.iter().enumerate() {
+            if item.title.trim().is_empty() {
+                errors.push(TaskBatchItemError {
+                    index,
+                    message: "Title must not be empty".to_string(),
+                });
+                continue;
+            }
+            let schedule = item
+                .due_date
+                .map_or(Ok(()), |due| task_schedule::check_due_date(due, Utc::now()))
+                .and(
+                    item.schedule_weight
+                        .map_or(Ok(()), task_schedule::check_weight),
+                );
+            if let Err(err) = schedule {
+                errors.push(TaskBatchItemError {
+                    index,
+                    message: err.to_string(),
+                });
+                continue;
+            }
+            let exists = match project_exists.get(&item.project_id) {
+                Some(exists) => *exists,
+                None => {
+                    let exists = sqlx::query_scalar!(
+                        r#"SELECT EXISTS(SELECT 1 FROM projects WHERE id = $1) as "exists!: bool""#,
+                        item.project_id
+                    )
+                    .fetch_one(&mut *tx)
+                    .await?;
+                    project_exists.insert(item.project_id, exists);
+                    exists
+                }
+            };
+            if !exists {
+                errors.push(TaskBatchItemError {
+                    index,
+                    message: format!("Project {} not found", item.project_id),
+                });
+            }
+        }
+        if !errors.is_empty() {
+            return Err(TaskBatchError::Invalid(errors));
+        }
+
+        let mut tasks = Vec::with_capacity(data.len());
+        for item in data {
+            tasks.push(Self::insert(&mut tx, Uuid::new_v4(), item).await?);
+        }
+
+        tx.commit().await?;
+        Ok(tasks)
+    }
+
+    /// Copy a task as a fresh Todo task titled "<title> (copy)", keeping its
+    /// description, priority, assignee and schedule weight. Its attempts,
+    /// activity and dependencies are not copied. With `include_children`,
+    /// every task created from its attempts is recreated the same way in
+    /// Todo; the copy has no attempts for them to hang from, so they become
+    /// top-level tasks listed under the copy.
+    pub async fn duplicate<'a, A>(
+        conn: A,
+        id: Uuid,
+        options: &DuplicateTask,
+    ) -> Result<Option<DuplicatedTask>, sqlx::Error>
+    where
+        A: Acquire<'a, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+
+        let Some(source) = sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", priority as "priority!: TaskPriority", position, assignee, due_date as "due_date: NaiveDate", schedule_weight as "schedule_weight!: f64", status_key as "status_key!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE id = $1 AND deleted_at IS NULL"#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let children = if options.include_children {
+            sqlx::query_as!(
+                Task,
+                r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.shared_task_id as "shared_task_id: Uuid", t.priority as "priority!: TaskPriority", t.position, t.assignee, t.due_date as "due_date: NaiveDate", t.schedule_weight as "schedule_weight!: f64", t.status_key as "status_key!", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+                   FROM tasks t
+                   JOIN workspaces w ON w.id = t.parent_workspace_id
+                   WHERE w.task_id = $1 AND t.deleted_at IS NULL
+                   ORDER BY t.created_at ASC, t.rowid ASC"#,
+                id
+            )
+            .fetch_all(&mut *tx)
+            .await?
+        } else {
+            Vec::new()
+        };
+
+        // Each insert lands at the top of Todo, so children go in last to
+        // first and the copy goes in last, above them
+        let mut copied_children = Vec::with_capacity(children.len());
+        for child in children.iter().rev() {
+            let description = child
+                .description
+                .clone()
+                .filter(|_| options.copy_child_descriptions);
+            let data = CreateTask::copy_of(child, child.title.clone(), description);
+            copied_children.push(Self::insert(&mut tx, Uuid::new_v4(), &data).await?);
+        }
+        copied_children.reverse();
+
+        let data = CreateTask::copy_of(
+            &source,
+            format!("{} (copy)", source.title),
+            source.description.clone(),
+        );
+        let task = Self::insert(&mut tx, Uuid::new_v4(), &data).await?;
+
+        tx.commit().await?;
+        Ok(Some(DuplicatedTask {
+            task,
+            children: copied_children,
+        }))
+    }
+
+    /// Insert a task with its images on an open connection, at the top of
+    /// its column, so callers can create it as part of a larger
+    /// transaction. Priority falls back to that of the task owning the parent
+    /// workspace and then to Medium; the assignee is never inherited.
+    pub async fn insert(
+        conn: &mut SqliteConnection,
+        id: Uuid,
+        item: &CreateTask,
+    ) -> Result<Self, sqlx::Error> {
+        let status = item.status.clone().unwrap_or_default();
+        let assignee = item.assignee.as_deref();
+        // Writing first takes the database write lock, so concurrent inserts
+        // into the same column see each other's ranks
+        sqlx::query!(
+            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_workspace_id, shared_task_id,
+                                  priority, assignee, due_date, schedule_weight)
+               VALUES ($1, $2, $3, $4, $5, $6, $7,
+                       COALESCE(
+                           $8,
+                           (SELECT p.priority
+                              FROM workspaces w
+                              JOIN tasks p ON p.id = w.task_id
+                             WHERE w.id = $6),
+                           'medium'
+                       ),
+                       NULLIF(TRIM($9), ''), $10, COALESCE($11, 1.0))"#,
+            id,
+            item.project_id,
+            item.title,
+            item.description,
+            status,
+            item.parent_workspace_id,
+            item.shared_task_id,
+            item.priority,
+            assignee,
+            item.due_date,
+            item.schedule_weight
+        )
+        .execute(&mut *conn)
+        .await?;
+        Self::place_at_top(conn, id).await?;
+
+        for image_id in item.image_ids.iter().flatten() {
+            let task_image_id = Uuid::new_v4();
+            sqlx::query!(
+                r#"INSERT INTO task_images (id, task_id, image_id)
+                   SELECT $1, $2, $3
+                   WHERE NOT EXISTS (
+                       SELECT 1 FROM task_images WHERE task_id = $2 AND image_id = $3
+                   )"#,
+                task_image_id,
+                id,
+                image_id
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Self::find_by_id(&mut *conn, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    /// Archive a task along with every task created from its workspaces,
+    /// recursively. Already archived tasks keep their original timestamp.
+    pub async fn archive<'e, E>(executor: E, id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            r#"WITH RECURSIVE task_tree(id) AS (
+                   SELECT id FROM tasks WHERE id = $1
+                   UNION
+                   SELECT t.id
+                     FROM tasks t
+                     JOIN workspaces w ON t.parent_workspace_id = w.id
+                     JOIN task_tree tt ON w.task_id = tt.id
+               )
+               UPDATE tasks
+               SET archived_at = datetime('now', 'subsec')
+               WHERE id IN (SELECT id FROM task_tree) AND archived_at IS NULL"#,
+            id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Restore a task and the tasks created from its workspaces
+    pub async fn unarchive<'e, E>(executor: E, id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            r#"WITH RECURSIVE task_tree(id) AS (
+                   SELECT id FROM tasks WHERE id = $1
+                   UNION
+                   SELECT t.id
+                     FROM tasks t
+                     JOIN workspaces w ON t.parent_workspace_id = w.id
+                     JOIN task_tree tt ON w.task_id = tt.id
+               )
+               UPDATE tasks
+               SET archived_at = NULL
+               WHERE id IN (SELECT id FROM task_tree) AND archived_at IS NOT NULL"#,
+            id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Whether the task, or a task it was created from, is archived
+    pub async fn is_archived<'e, E>(executor: E, id: Uuid) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_scalar!(
+            r#"WITH RECURSIVE lineage(parent_workspace_id, archived_at) AS (
+                   SELECT parent_workspace_id, archived_at FROM tasks WHERE id = $1
+                   UNION
+                   SELECT t.parent_workspace_id, t.archived_at
+                     FROM lineage l
+                     JOIN workspaces w ON w.id = l.parent_workspace_id
+                     JOIN tasks t ON t.id = w.task_id
+               )
+               SELECT EXISTS (SELECT 1 FROM lineage WHERE archived_at IS NOT NULL) as "archived!: bool""#,
+            id
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Make `blocker_id` block `task_id`, see [`TaskDependency::create`]
+    pub async fn add_dependency<'a, A>(
+        conn: A,
+        task_id: Uuid,
+        blocker_id: Uuid,
+    ) -> Result<TaskDependency, TaskDependencyError>
+    where
+        A: Acquire<'a, Database = Sqlite>,
+    {
+        TaskDependency::create(conn, blocker_id, task_id).await
+    }
+
+    /// Tasks that must be Done before `task_id` can start
+    pub async fn blockers(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Task>, sqlx::Error> {
+        TaskDependency::find_blockers(pool, task_id).await
+    }
+
+    /// Tasks waiting on `task_id`
+    pub async fn dependents(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Task>, sqlx::Error> {
+        TaskDependency::find_dependents(pool, task_id).await
+    }
+
+    /// Hide a task and every task created from its workspaces until they are
+    /// restored or purged
+    pub async fn soft_delete<'e, E>(executor: E, id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            r#"WITH RECURSIVE task_tree(id) AS (
+                   SELECT id FROM tasks WHERE id = $1
+                   UNION
+                   SELECT t.id
+                     FROM tasks t
+                     JOIN workspaces w ON t.parent_workspace_id = w.id
+                     JOIN task_tree tt ON w.task_id = tt.id
+               )
+               UPDATE tasks
+               SET deleted_at = datetime('now', 'subsec')
+               WHERE id IN (SELECT id FROM task_tree) AND deleted_at IS NULL"#,
+            id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Bring back a deleted task together with the descendants deleted
+    /// alongside it. Fails while the task it was created from is deleted.
+    pub async fn restore<'a, A>(conn: A, id: Uuid) -> Result<u64, TaskRestoreError>
+    where
+        A: Acquire<'a, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+
+        // Kept as stored text so it matches the descendants' timestamps exactly
+        let deleted_at = sqlx::query_scalar!(
+            r#"SELECT deleted_at as "deleted_at: String" FROM tasks WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(TaskRestoreError::NotFound)?;
+        let Some(deleted_at) = deleted_at else {
+            return Ok(0);
+        };
+
+        let deleted_parent = sqlx::query_scalar!(
+            r#"SELECT parent.id as "id!: Uuid"
+               FROM tasks t
+               JOIN workspaces w ON t.parent_workspace_id = w.id
+               JOIN tasks parent ON parent.id = w.task_id
+               WHERE t.id = $1 AND parent.deleted_at IS NOT NULL"#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(parent_id) = deleted_parent {
+            return Err(TaskRestoreError::ParentDeleted(parent_id));
+        }
+
+        let result = sqlx::query!(
+            r#"WITH RECURSIVE task_tree(id) AS (
+                   SELECT id FROM tasks WHERE id = $1
+                   UNION
+                   SELECT t.id
+                     FROM tasks t
+                     JOIN workspaces w ON t.parent_workspace_id = w.id
+                     JOIN task_tree tt ON w.task_id = tt.id
+               )
+               UPDATE tasks
+               SET deleted_at = NULL
+               WHERE id IN (SELECT id FROM task_tree) AND deleted_at = $2"#,
+            id,
+            deleted_at
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn is_deleted(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT deleted_at IS NOT NULL as "deleted!: bool" FROM tasks WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+        .map(|deleted| deleted.unwrap_or(false))
+    }
+
+    /// Rank the project's tasks against `query`, weighting title matches above
+    /// description matches. Every word must match, and the last one may be a
+    /// prefix. Deleted tasks are never returned.
+    pub async fn search(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        query: &str,
+        limit: i64,
+        include_archived: bool,
+    ) -> Result<Vec<TaskSearchResult>, sqlx::Error> {
+        let Some(match_query) = fts_match_query(query) else {
+            return Ok(Vec::new());
+        };
+        let records = sqlx::query!(
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.shared_task_id as "shared_task_id: Uuid", t.priority as "priority!: TaskPriority", t.position, t.assignee, t.due_date as "due_date: NaiveDate", t.schedule_weight as "schedule_weight!: f64", t.status_key as "status_key!", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>",
+                      w.task_id as "parent_task_id: Uuid",
+                      highlight(tasks_fts, 0, '<mark>', '</mark>') as "title_highlight!: String",
+                      NULLIF(snippet(tasks_fts, 1, '<mark>', '</mark>', '…', 16), '')
+                          as "description_snippet: String",
+                      bm25(tasks_fts, 10.0, 1.0) as "rank!: f64"
+               FROM tasks_fts
+               JOIN tasks t ON t.rowid = tasks_fts.rowid
+               LEFT JOIN workspaces w ON w.id = t.parent_workspace_id
+               WHERE tasks_fts MATCH $1
+                 AND t.project_id = $2
+                 AND t.deleted_at IS NULL
+                 AND ($3 OR t.archived_at IS NULL)
+               ORDER BY bm25(tasks_fts, 10.0, 1.0)
+               LIMIT $4"#,
+            match_query,
+            project_id,
+            include_archived,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|rec| TaskSearchResult {
+                task: Task {
+                    id: rec.id,
+                    project_id: rec.project_id,
+                    title: rec.title,
+                    description: rec.description,
+                    status: rec.status,
+                    parent_workspace_id: rec.parent_workspace_id,
+                    shared_task_id: rec.shared_task_id,
+                    priority: rec.priority,
+                    position: rec.position,
+                    assignee: rec.assignee,
+                    due_date: rec.due_date,
+                    schedule_weight: rec.schedule_weight,
+                    status_key: rec.status_key,
+                    created_at: rec.created_at,
+                    updated_at: rec.updated_at,
+                },
+                parent_task_id: rec.parent_task_id,
+                title_highlight: rec.title_highlight,
+                description_snippet: rec.description_snippet,
+                rank: rec.rank,
+            })
+            .collect())
+    }
+
+    /// Archived or deleted, and so left out of the board
+    pub async fn is_hidden(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT archived_at IS NOT NULL OR deleted_at IS NOT NULL as "hidden!: bool"
+               FROM tasks
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+        .map(|hidden| hidden.unwrap_or(false))
+    }
+
+    /// Deleted tasks of a project, most recently deleted first
+    pub async fn find_deleted_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", priority as "priority!: TaskPriority", position, assignee, due_date as "due_date: NaiveDate", schedule_weight as "schedule_weight!: f64", status_key as "status_key!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE project_id = $1 AND deleted_at IS NOT NULL
+               ORDER BY deleted_at DESC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Tasks deleted before `cutoff`, ready to be removed for good
+    pub async fn find_deleted_before(
+        pool: &SqlitePool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", priority as "priority!: TaskPriority", position, assignee, due_date as "due_date: NaiveDate", schedule_weight as "schedule_weight!: f64", status_key as "status_key!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE deleted_at IS NOT NULL AND julianday(deleted_at) < julianday($1)
+               ORDER BY deleted_at ASC"#,
+            cutoff
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn update<'e, E>(
+        executor: E,
+        id: Uuid,
+        project_id: Uuid,
+        title: String,
+        description: Option<String>,
+        status: TaskStatus,
+        parent_workspace_id: Option<Uuid>,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            Task,
+            r#"UPDATE tasks
+               SET title = $3, description = $4, status = $5, parent_workspace_id = $6
+               WHERE id = $1 AND project_id = $2
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", priority as "priority!: TaskPriority", position, assignee, due_date as "due_date: NaiveDate", schedule_weight as "schedule_weight!: f64", status_key as "status_key!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            title,
+            description,
+            status,
+            parent_workspace_id
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    pub async fn update_status<'e, E>(
+        executor: E,
+        id: Uuid,
+        status: TaskStatus,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            "UPDATE tasks SET status = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            id,
+            status
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Update the parent_workspace_id field for a task
+    pub async fn update_parent_workspace_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        parent_workspace_id: Option<Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE tasks SET parent_workspace_id = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            task_id,
+            parent_workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Nullify parent_workspace_id for all tasks that reference the given workspace ID
+    /// This breaks parent-child relationships before deleting a parent task
+    pub async fn nullify_children_by_workspace_id<'e, E>(
+        executor: E,
+        workspace_id: Uuid,
+    ) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            "UPDATE tasks SET parent_workspace_id = NULL WHERE parent_workspace_id = $1",
+            workspace_id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Clear shared_task_id for all tasks that reference shared tasks belonging to a remote project
+    /// This breaks the link between local tasks and shared tasks when a project is unlinked
+    pub async fn clear_shared_task_ids_for_remote_project<'e, E>(
+        executor: E,
+        remote_project_id: Uuid,
+    ) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            r#"UPDATE tasks
+               SET shared_task_id = NULL
+               WHERE project_id IN (
+                   SELECT id FROM projects WHERE remote_project_id = $1
+               )"#,
+            remote_project_id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn delete<'e, E>(executor: E, id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!("DELETE FROM tasks WHERE id = $1", id)
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn set_shared_task_id<'e, E>(
+        executor: E,
+        id: Uuid,
+        shared_task_id: Option<Uuid>,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            "UPDATE tasks SET shared_task_id = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            id,
+            shared_task_id
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn batch_unlink_shared_tasks<'e, E>(
+        executor: E,
+        shared_task_ids: &[Uuid],
+    ) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        if shared_task_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "UPDATE tasks SET shared_task_id = NULL, updated_at = CURRENT_TIMESTAMP WHERE shared_task_id IN (",
+        );
+
+        let mut separated = query_builder.separated(", ");
+        for id in shared_task_ids {
+            separated.push_bind(id);
+        }
+        separated.push_unseparated(")");
+
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn find_children_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        // Find only child tasks that have this workspace as their parent
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", priority as "priority!: TaskPriority", position, assignee, due_date as "due_date: NaiveDate", schedule_weight as "schedule_weight!: f64", status_key as "status_key!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE parent_workspace_id = $1 AND deleted_at IS NULL AND archived_at IS NULL
+               ORDER BY created_at DESC"#,
+            workspace_id,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_relationships_for_workspace(
+        pool: &SqlitePool,
         workspace: &Workspace,
     ) -> Result<TaskRelationships, sqlx::Error> {
         // 1. Get the current task (task that owns this workspace)
@@ -490,3 +1840,854 @@ ORDER BY t.created_at DESC"#,
         })
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            task_dependency::TaskDependency,
+            workspace::{CreateWorkspace, Workspace},
+        },
+        test_support::{create_project, create_task, pool_with_project},
+    };
+
+    pub(crate) async fn reload(pool: &SqlitePool, id: Uuid) -> Task {
+        Task::find_by_id(pool, id).await.unwrap().unwrap()
+    }
+
+    /// A task and a task created from one of its workspaces
+    async fn parent_with_child(pool: &SqlitePool, project_id: Uuid) -> (Task, Task) {
+        let parent = create_task(pool, project_id, "Parent").await;
+        let workspace = Workspace::create(
+            pool,
+            &CreateWorkspace {
+                branch: "parent".to_string(),
+                agent_working_dir: None,
+            },
+            Uuid::new_v4(),
+            parent.id,
+        )
+        .await
+        .unwrap();
+        let mut child = CreateTask::from_title_description(project_id, "Child".into(), None);
+        child.parent_workspace_id = Some(workspace.id);
+        let child = Task::create(pool, &child, Uuid::new_v4()).await.unwrap();
+        (parent, child)
+    }
+
+    async fn task_count(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn creates_a_full_batch_in_order() {
+        let (pool, project_id) = pool_with_project().await;
+        let batch: Vec<_> = (0..100)
+            .map(|i| CreateTask::from_title_description(project_id, format!("Task {i}"), None))
+            .collect();
+
+        let tasks = Task::create_many(&pool, &batch).await.unwrap();
+
+        assert_eq!(tasks.len(), 100);
+        assert_eq!(tasks[42].title, "Task 42");
+        assert!(tasks.iter().all(|t| t.status == TaskStatus::Todo));
+        assert_eq!(task_count(&pool).await, 100);
+    }
+
+    #[tokio::test]
+    async fn invalid_items_reject_the_whole_batch() {
+        let (pool, project_id) = pool_with_project().await;
+        let mut batch: Vec<_> = (0..10)
+            .map(|i| CreateTask::from_title_description(project_id, format!("Task {i}"), None))
+            .collect();
+        batch[3].title = "  ".to_string();
+        batch[7].project_id = Uuid::new_v4();
+
+        let Err(TaskBatchError::Invalid(errors)) = Task::create_many(&pool, &batch).await else {
+            panic!("expected the batch to be rejected");
+        };
+
+        let indices: Vec<_> = errors.iter().map(|e| e.index).collect();
+        assert_eq!(indices, vec![3, 7]);
+        assert_eq!(task_count(&pool).await, 0);
+    }
+
+    #[tokio::test]
+    async fn database_failure_rolls_back_earlier_inserts() {
+        let (pool, project_id) = pool_with_project().await;
+        let mut batch: Vec<_> = (0..100)
+            .map(|i| CreateTask::from_title_description(project_id, format!("Task {i}"), None))
+            .collect();
+        // Unknown image ids violate the task_images foreign key
+        batch[99].image_ids = Some(vec![Uuid::new_v4()]);
+
+        let result = Task::create_many(&pool, &batch).await;
+
+        assert!(matches!(result, Err(TaskBatchError::Database(_))));
+        assert_eq!(task_count(&pool).await, 0);
+    }
+
+    #[tokio::test]
+    async fn tasks_created_under_an_archived_task_count_as_archived() {
+        let (pool, project_id) = pool_with_project().await;
+        let parent = create_task(&pool, project_id, "Parent").await;
+        let workspace = Workspace::create(
+            &pool,
+            &CreateWorkspace {
+                branch: "parent".to_string(),
+                agent_working_dir: None,
+            },
+            Uuid::new_v4(),
+            parent.id,
+        )
+        .await
+        .unwrap();
+        Task::archive(&pool, parent.id).await.unwrap();
+
+        // Created by the parent's attempt after the parent was archived
+        let mut child = CreateTask::from_title_description(project_id, "Late".into(), None);
+        child.parent_workspace_id = Some(workspace.id);
+        let child = Task::create(&pool, &child, Uuid::new_v4()).await.unwrap();
+        assert!(Task::is_archived(&pool, child.id).await.unwrap());
+
+        Task::unarchive(&pool, parent.id).await.unwrap();
+        assert!(!Task::is_archived(&pool, child.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn archiving_cascades_to_child_tasks_and_hides_them() {
+        let (pool, project_id) = pool_with_project().await;
+        let (parent, child) = parent_with_child(&pool, project_id).await;
+        let other = create_task(&pool, project_id, "Other").await;
+
+        assert_eq!(Task::archive(&pool, parent.id).await.unwrap(), 2);
+        assert!(Task::is_archived(&pool, child.id).await.unwrap());
+        assert!(!Task::is_archived(&pool, other.id).await.unwrap());
+
+        let visible = Task::find_by_project_id_with_attempt_status(&pool, project_id, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            visible.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![other.id]
+        );
+        let all = Task::find_by_project_id_with_attempt_status(&pool, project_id, true)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(
+            all.iter()
+                .filter(|t| t.id != other.id)
+                .all(|t| t.archived_at.is_some())
+        );
+
+        assert_eq!(Task::unarchive(&pool, parent.id).await.unwrap(), 2);
+        assert!(!Task::is_archived(&pool, child.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn soft_delete_cascades_and_restores_together() {
+        let (pool, project_id) = pool_with_project().await;
+        let (parent, child) = parent_with_child(&pool, project_id).await;
+
+        assert_eq!(Task::soft_delete(&pool, parent.id).await.unwrap(), 2);
+        assert!(Task::is_deleted(&pool, child.id).await.unwrap());
+        assert!(
+            Task::find_live_by_id(&pool, child.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        for include_archived in [false, true] {
+            let listed =
+                Task::find_by_project_id_with_attempt_status(&pool, project_id, include_archived)
+                    .await
+                    .unwrap();
+            assert!(listed.is_empty());
+        }
+
+        // The child can't come back on its own while its parent is deleted
+        assert!(matches!(
+            Task::restore(&pool, child.id).await,
+            Err(TaskRestoreError::ParentDeleted(id)) if id == parent.id
+        ));
+        assert_eq!(Task::restore(&pool, parent.id).await.unwrap(), 2);
+        assert!(!Task::is_deleted(&pool, child.id).await.unwrap());
+        assert!(
+            Task::find_live_by_id(&pool, child.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_leaves_separately_deleted_children_deleted() {
+        let (pool, project_id) = pool_with_project().await;
+        let (parent, child) = parent_with_child(&pool, project_id).await;
+
+        Task::soft_delete(&pool, child.id).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        Task::soft_delete(&pool, parent.id).await.unwrap();
+
+        assert_eq!(Task::restore(&pool, parent.id).await.unwrap(), 1);
+        assert!(Task::is_deleted(&pool, child.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn only_tasks_past_the_cutoff_are_purgeable() {
+        let (pool, project_id) = pool_with_project().await;
+        let deleted = create_task(&pool, project_id, "Deleted").await;
+        create_task(&pool, project_id, "Kept").await;
+        Task::soft_delete(&pool, deleted.id).await.unwrap();
+
+        let past = Utc::now() - chrono::Duration::days(1);
+        assert!(
+            Task::find_deleted_before(&pool, past)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let future = Utc::now() + chrono::Duration::minutes(1);
+        let purgeable = Task::find_deleted_before(&pool, future).await.unwrap();
+        assert_eq!(
+            purgeable.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![deleted.id]
+        );
+        Task::delete(&pool, deleted.id).await.unwrap();
+        assert_eq!(task_count(&pool).await, 1);
+    }
+
+    #[tokio::test]
+    async fn deleted_tasks_do_not_block_relinking_their_shared_task() {
+        let (pool, project_id) = pool_with_project().await;
+        let shared_task_id = Uuid::new_v4();
+        let shared = |title: &str| {
+            CreateTask::from_shared_task(
+                project_id,
+                title.to_string(),
+                None,
+                TaskStatus::Todo,
+                shared_task_id,
+            )
+        };
+
+        let first = Task::create(&pool, &shared("First"), Uuid::new_v4())
+            .await
+            .unwrap();
+        assert!(
+            Task::create(&pool, &shared("Duplicate"), Uuid::new_v4())
+                .await
+                .is_err()
+        );
+
+        // The deleted task keeps its link for a restore or the purge
+        Task::soft_delete(&pool, first.id).await.unwrap();
+        assert!(
+            Task::find_by_shared_task_id(&pool, shared_task_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let second = Task::create(&pool, &shared("Second"), Uuid::new_v4())
+            .await
+            .unwrap();
+        let linked = Task::find_by_shared_task_id(&pool, shared_task_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(linked.id, second.id);
+        assert_eq!(
+            reload(&pool, first.id).await.shared_task_id,
+            Some(shared_task_id)
+        );
+    }
+
+    #[test]
+    fn match_query_quotes_words_and_prefixes_the_last() {
+        assert_eq!(
+            fts_match_query("webhook retr").as_deref(),
+            Some("\"webhook\" \"retr\"*")
+        );
+        assert_eq!(
+            fts_match_query(" \"OR\" NEAR( ").as_deref(),
+            Some("\"OR\" \"NEAR(\"*")
+        );
+        assert_eq!(fts_match_query("  \" "), None);
+    }
+
+    async fn create_described(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        title: &str,
+        description: &str,
+    ) -> Task {
+        let data = CreateTask::from_title_description(
+            project_id,
+            title.to_string(),
+            Some(description.to_string()),
+        );
+        Task::create(pool, &data, Uuid::new_v4()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn search_ranks_title_matches_first_and_matches_prefixes() {
+        let (pool, project_id) = pool_with_project().await;
+        let in_description = create_described(
+            &pool,
+            project_id,
+            "Harden delivery",
+            "Add webhook retries with backoff",
+        )
+        .await;
+        let in_title = create_described(&pool, project_id, "Webhook retries", "Use a queue").await;
+        create_described(&pool, project_id, "Unrelated", "Nothing to see").await;
+
+        let results = Task::search(&pool, project_id, "webhook retr", 10, false)
+            .await
+            .unwrap();
+        let ids: Vec<_> = results.iter().map(|r| r.task.id).collect();
+        assert_eq!(ids, vec![in_title.id, in_description.id]);
+        assert_eq!(
+            results[0].title_highlight,
+            "<mark>Webhook</mark> <mark>retries</mark>"
+        );
+        assert!(
+            results[1]
+                .description_snippet
+                .as_deref()
+                .unwrap()
+                .contains("<mark>webhook</mark>")
+        );
+    }
+
+    #[tokio::test]
+    async fn search_follows_edits_and_skips_hidden_tasks() {
+        let (pool, project_id) = pool_with_project().await;
+        let (parent, child) = parent_with_child(&pool, project_id).await;
+        let archived = create_task(&pool, project_id, "Child archived").await;
+        let deleted = create_task(&pool, project_id, "Child deleted").await;
+        Task::archive(&pool, archived.id).await.unwrap();
+        Task::soft_delete(&pool, deleted.id).await.unwrap();
+
+        let results = Task::search(&pool, project_id, "child", 10, false)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].task.id, child.id);
+        assert_eq!(results[0].parent_task_id, Some(parent.id));
+
+        let with_archived = Task::search(&pool, project_id, "child", 10, true)
+            .await
+            .unwrap();
+        assert_eq!(with_archived.len(), 2);
+
+        Task::update(
+            &pool,
+            child.id,
+            project_id,
+            "Renamed".to_string(),
+            None,
+            TaskStatus::Todo,
+            child.parent_workspace_id,
+        )
+        .await
+        .unwrap();
+        assert!(
+            Task::search(&pool, project_id, "child", 10, false)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            Task::search(&pool, project_id, "renamed", 10, false)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn children_inherit_priority_and_follow_it_on_request() {
+        let (pool, project_id) = pool_with_project().await;
+        let parent = create_task(&pool, project_id, "Parent").await;
+        Task::set_priority(&pool, parent.id, TaskPriority::High, false)
+            .await
+            .unwrap();
+        let workspace = Workspace::create(
+            &pool,
+            &CreateWorkspace {
+                branch: "parent".to_string(),
+                agent_working_dir: None,
+            },
+            Uuid::new_v4(),
+            parent.id,
+        )
+        .await
+        .unwrap();
+        let mut data = CreateTask::from_title_description(project_id, "Child".into(), None);
+        data.parent_workspace_id = Some(workspace.id);
+        let child = Task::create(&pool, &data, Uuid::new_v4()).await.unwrap();
+        data.priority = Some(TaskPriority::Low);
+        let explicit = Task::create(&pool, &data, Uuid::new_v4()).await.unwrap();
+        assert_eq!(reload(&pool, child.id).await.priority, TaskPriority::High);
+        assert_eq!(reload(&pool, explicit.id).await.priority, TaskPriority::Low);
+
+        let changed = Task::set_priority(&pool, parent.id, TaskPriority::Urgent, false)
+            .await
+            .unwrap();
+        assert_eq!(changed, vec![parent.id]);
+        assert_eq!(reload(&pool, child.id).await.priority, TaskPriority::High);
+
+        let mut changed = Task::set_priority(&pool, parent.id, TaskPriority::Low, true)
+            .await
+            .unwrap();
+        changed.sort();
+        let mut expected = vec![parent.id, child.id];
+        expected.sort();
+        assert_eq!(changed, expected);
+        assert_eq!(reload(&pool, child.id).await.priority, TaskPriority::Low);
+    }
+
+    #[tokio::test]
+    async fn board_lists_by_priority_then_newest() {
+        let (pool, project_id) = pool_with_project().await;
+        let older_high = create_task(&pool, project_id, "Older high").await;
+        let medium = create_task(&pool, project_id, "Medium").await;
+        let newer_high = create_task(&pool, project_id, "Newer high").await;
+        let urgent = create_task(&pool, project_id, "Urgent").await;
+        sqlx::query("UPDATE tasks SET created_at = datetime('now', '-1 hour') WHERE id = $1")
+            .bind(older_high.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (task, priority) in [
+            (&older_high, TaskPriority::High),
+            (&newer_high, TaskPriority::High),
+            (&urgent, TaskPriority::Urgent),
+        ] {
+            Task::set_priority(&pool, task.id, priority, false)
+                .await
+                .unwrap();
+        }
+
+        let ids: Vec<_> = Task::find_by_project_id_with_attempt_status(&pool, project_id, false)
+            .await
+            .unwrap()
+            .iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(
+            ids,
+            vec![urgent.id, newer_high.id, older_high.id, medium.id]
+        );
+    }
+
+    async fn column(pool: &SqlitePool, project_id: Uuid, status: TaskStatus) -> Vec<Uuid> {
+        sqlx::query_scalar(
+            "SELECT id FROM tasks WHERE project_id = $1 AND status = $2 ORDER BY position",
+        )
+        .bind(project_id)
+        .bind(status)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn moves_between_neighbours_and_across_columns() {
+        let (pool, project_id) = pool_with_project().await;
+        let a = create_task(&pool, project_id, "A").await;
+        let b = create_task(&pool, project_id, "B").await;
+        let c = create_task(&pool, project_id, "C").await;
+        assert_eq!(
+            column(&pool, project_id, TaskStatus::Todo).await,
+            vec![c.id, b.id, a.id]
+        );
+
+        Task::move_to(&pool, a.id, TaskStatus::Todo, Some(c.id))
+            .await
+            .unwrap();
+        assert_eq!(
+            column(&pool, project_id, TaskStatus::Todo).await,
+            vec![c.id, a.id, b.id]
+        );
+
+        let moved = Task::move_to(&pool, b.id, TaskStatus::InProgress, None)
+            .await
+            .unwrap();
+        assert_eq!(moved.status, TaskStatus::InProgress);
+        assert!(matches!(
+            Task::move_to(&pool, c.id, TaskStatus::InProgress, Some(a.id)).await,
+            Err(TaskMoveError::AnchorNotInColumn(id)) if id == a.id
+        ));
+    }
+
+    #[tokio::test]
+    async fn concurrent_moves_into_the_same_gap_both_land_in_it() {
+        let (pool, project_id) = pool_with_project().await;
+        let below = create_task(&pool, project_id, "Below").await;
+        let above = create_task(&pool, project_id, "Above").await;
+        let first = create_task(&pool, project_id, "First").await;
+        let second = create_task(&pool, project_id, "Second").await;
+        for task in [&first, &second] {
+            Task::move_to(&pool, task.id, TaskStatus::InProgress, None)
+                .await
+                .unwrap();
+        }
+
+        let (moved_first, moved_second) = tokio::join!(
+            Task::move_to(&pool, first.id, TaskStatus::Todo, Some(above.id)),
+            Task::move_to(&pool, second.id, TaskStatus::Todo, Some(above.id)),
+        );
+        moved_first.unwrap();
+        moved_second.unwrap();
+
+        let order = column(&pool, project_id, TaskStatus::Todo).await;
+        assert_eq!(order.len(), 4);
+        assert_eq!(order[0], above.id);
+        assert_eq!(order[3], below.id);
+        let first_rank = reload(&pool, first.id).await.position;
+        let second_rank = reload(&pool, second.id).await.position;
+        assert_ne!(first_rank, second_rank);
+    }
+
+    #[tokio::test]
+    async fn dense_gaps_are_rebalanced() {
+        let (pool, project_id) = pool_with_project().await;
+        let tail = create_task(&pool, project_id, "Tail").await;
+        let left = create_task(&pool, project_id, "Left").await;
+        let right = create_task(&pool, project_id, "Right").await;
+        let anchor = create_task(&pool, project_id, "Anchor").await;
+
+        // Each move splits the gap just below the anchor in half
+        for i in 0..200 {
+            let moving = if i % 2 == 0 { right.id } else { left.id };
+            Task::move_to(&pool, moving, TaskStatus::Todo, Some(anchor.id))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            column(&pool, project_id, TaskStatus::Todo).await,
+            vec![anchor.id, left.id, right.id, tail.id]
+        );
+        for task in [&tail, &left, &right, &anchor] {
+            let rank = reload(&pool, task.id).await.position.unwrap();
+            assert!(rank.len() <= MAX_RANK_LEN, "{rank}");
+        }
+    }
+
+    #[tokio::test]
+    async fn children_are_assigned_independently_of_their_parent() {
+        let (pool, project_id) = pool_with_project().await;
+        let (parent, child) = parent_with_child(&pool, project_id).await;
+        Task::set_assignee(&pool, parent.id, Some("ana"))
+            .await
+            .unwrap();
+        assert_eq!(reload(&pool, child.id).await.assignee, None);
+
+        Task::set_assignee(&pool, child.id, Some(" bo "))
+            .await
+            .unwrap();
+        assert_eq!(
+            reload(&pool, child.id).await.assignee.as_deref(),
+            Some("bo")
+        );
+        assert_eq!(
+            reload(&pool, parent.id).await.assignee.as_deref(),
+            Some("ana")
+        );
+
+        Task::set_assignee(&pool, child.id, Some("  "))
+            .await
+            .unwrap();
+        assert_eq!(reload(&pool, child.id).await.assignee, None);
+    }
+
+    #[tokio::test]
+    async fn counts_open_tasks_per_assignee() {
+        let (pool, project_id) = pool_with_project().await;
+        let mut data = CreateTask::from_title_description(project_id, "Mine".into(), None);
+        data.assignee = Some("ana".to_string());
+        Task::create(&pool, &data, Uuid::new_v4()).await.unwrap();
+        Task::create(&pool, &data, Uuid::new_v4()).await.unwrap();
+        let done = Task::create(&pool, &data, Uuid::new_v4()).await.unwrap();
+        Task::update_status(&pool, done.id, TaskStatus::Done)
+            .await
+            .unwrap();
+        let archived = Task::create(&pool, &data, Uuid::new_v4()).await.unwrap();
+        Task::archive(&pool, archived.id).await.unwrap();
+        create_task(&pool, project_id, "Nobody's").await;
+
+        let counts = Task::open_counts_by_assignee(&pool, Some(project_id))
+            .await
+            .unwrap();
+        let counts: Vec<_> = counts
+            .iter()
+            .map(|c| (c.assignee.as_deref(), c.open_tasks))
+            .collect();
+        assert_eq!(counts, vec![(Some("ana"), 2), (None, 1)]);
+    }
+
+    #[tokio::test]
+    async fn distributes_the_parent_deadline_by_weight() {
+        let (pool, project_id) = pool_with_project().await;
+        let (parent, first) = parent_with_child(&pool, project_id).await;
+        let mut data = CreateTask::from_title_description(project_id, "Second".into(), None);
+        data.parent_workspace_id = first.parent_workspace_id;
+        data.schedule_weight = Some(3.0);
+        let second = Task::create(&pool, &data, Uuid::new_v4()).await.unwrap();
+
+        let today = NaiveDate::from_ymd_opt(2026, 5, 1).unwrap();
+        assert!(matches!(
+            Task::distribute_due_dates(&pool, parent.id, today).await,
+            Err(TaskScheduleError::NoDueDate)
+        ));
+
+        let deadline = NaiveDate::from_ymd_opt(2026, 5, 9).unwrap();
+        Task::set_due_date(&pool, parent.id, Some(deadline))
+            .await
+            .unwrap();
+        let changes = Task::distribute_due_dates(&pool, parent.id, today)
+            .await
+            .unwrap();
+        let changes: Vec<_> = changes.iter().map(|c| (c.task_id, c.due_date)).collect();
+        assert_eq!(
+            changes,
+            vec![
+                (first.id, NaiveDate::from_ymd_opt(2026, 5, 3).unwrap()),
+                (second.id, deadline),
+            ]
+        );
+
+        // Nothing moves when run again
+        assert!(
+            Task::distribute_due_dates(&pool, parent.id, today)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let after_deadline = NaiveDate::from_ymd_opt(2026, 5, 10).unwrap();
+        assert!(matches!(
+            Task::distribute_due_dates(&pool, parent.id, after_deadline).await,
+            Err(TaskScheduleError::DeadlinePassed(date)) if date == deadline
+        ));
+    }
+
+    #[tokio::test]
+    async fn finds_open_tasks_due_up_to_the_horizon() {
+        let (pool, project_id) = pool_with_project().await;
+        let today = NaiveDate::from_ymd_opt(2026, 5, 10).unwrap();
+        for (title, offset) in [("Late", -1), ("Today", 0), ("Edge", 7), ("Later", 8)] {
+            let task = create_task(&pool, project_id, title).await;
+            let date = today + chrono::Duration::days(offset);
+            Task::set_due_date(&pool, task.id, Some(date))
+                .await
+                .unwrap();
+        }
+        let done = create_task(&pool, project_id, "Done late").await;
+        Task::set_due_date(&pool, done.id, Some(today - chrono::Duration::days(3)))
+            .await
+            .unwrap();
+        Task::update_status(&pool, done.id, TaskStatus::Done)
+            .await
+            .unwrap();
+
+        let titles: Vec<_> = Task::find_due(&pool, today, 7)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.task.title)
+            .collect();
+        assert_eq!(titles, vec!["Late", "Today", "Edge"]);
+    }
+
+    #[tokio::test]
+    async fn duplicates_with_each_option() {
+        let (pool, project_id) = pool_with_project().await;
+        let (parent, child) = parent_with_child(&pool, project_id).await;
+        let blocker = create_task(&pool, project_id, "Blocker").await;
+        TaskDependency::create(&pool, blocker.id, parent.id)
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"UPDATE tasks
+               SET status = 'done', priority = 'urgent',
+                   description = CASE WHEN id = $1 THEN 'Spec' ELSE 'Edited phase' END
+               WHERE id IN ($1, $2)"#,
+        )
+        .bind(parent.id)
+        .bind(child.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        for (include_children, copy_child_descriptions) in
+            [(false, false), (false, true), (true, false), (true, true)]
+        {
+            let options = DuplicateTask {
+                include_children,
+                copy_child_descriptions,
+            };
+            let copy = Task::duplicate(&pool, parent.id, &options)
+                .await
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(copy.task.title, "Parent (copy)");
+            assert_eq!(copy.task.status, TaskStatus::Todo);
+            assert_eq!(copy.task.description.as_deref(), Some("Spec"));
+            assert_eq!(
+                reload(&pool, copy.task.id).await.priority,
+                TaskPriority::Urgent
+            );
+            assert!(
+                TaskDependency::find_blockers(&pool, copy.task.id)
+                    .await
+                    .unwrap()
+                    .is_empty()
+            );
+
+            assert_eq!(copy.children.len(), usize::from(include_children));
+            if let Some(phase) = copy.children.first() {
+                assert_eq!(phase.title, "Child");
+                assert_eq!(phase.status, TaskStatus::Todo);
+                assert_eq!(
+                    phase.description.as_deref(),
+                    copy_child_descriptions.then_some("Edited phase")
+                );
+                assert_eq!(reload(&pool, phase.id).await.priority, TaskPriority::Urgent);
+                // The copy sits directly above its children
+                let (copy_rank, phase_rank) = (
+                    reload(&pool, copy.task.id).await.position,
+                    reload(&pool, phase.id).await.position,
+                );
+                assert!(copy_rank < phase_rank);
+            }
+        }
+
+        // Nothing of the original was touched, and the copies start with no
+        // attempts of their own
+        assert_eq!(task_count(&pool).await, 3 + 4 + 2);
+        let workspaces: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM workspaces")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(workspaces, 1);
+        assert!(
+            Task::duplicate(&pool, Uuid::new_v4(), &DuplicateTask::default())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn moving_to_a_project_takes_children_along() {
+        let (pool, project_id) = pool_with_project().await;
+        let target = create_project(&pool, "Elsewhere").await;
+        let (parent, child) = parent_with_child(&pool, project_id).await;
+        let neighbour = create_task(&pool, project_id, "Neighbour").await;
+        TaskDependency::create(&pool, neighbour.id, child.id)
+            .await
+            .unwrap();
+
+        let moved = Task::move_to_project(&pool, parent.id, target, false)
+            .await
+            .unwrap();
+
+        assert_eq!(moved.previous_project_id, project_id);
+        assert_eq!(moved.task_ids, vec![parent.id, child.id]);
+        assert!(moved.severed_dependencies.is_empty());
+        for id in [parent.id, child.id] {
+            let task = Task::find_by_id(&pool, id).await.unwrap().unwrap();
+            assert_eq!(task.project_id, target);
+        }
+        // Both keep their order at the top of the target column
+        let ranks = (
+            reload(&pool, parent.id).await.position,
+            reload(&pool, child.id).await.position,
+        );
+        assert!(ranks.0.is_some() && ranks.0 > ranks.1);
+        // The dependency now crosses projects and is kept
+        assert_eq!(
+            TaskDependency::find_blockers(&pool, child.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn moving_to_a_project_can_sever_links() {
+        let (pool, project_id) = pool_with_project().await;
+        let target = create_project(&pool, "Elsewhere").await;
+        let (parent, child) = parent_with_child(&pool, project_id).await;
+        let neighbour = create_task(&pool, project_id, "Neighbour").await;
+        TaskDependency::create(&pool, neighbour.id, child.id)
+            .await
+            .unwrap();
+        // Links within the moved tree survive
+        TaskDependency::create(&pool, child.id, parent.id)
+            .await
+            .unwrap();
+        Task::set_shared_task_id(&pool, parent.id, Some(Uuid::new_v4()))
+            .await
+            .unwrap();
+
+        let moved = Task::move_to_project(&pool, parent.id, target, true)
+            .await
+            .unwrap();
+
+        assert_eq!(moved.severed_dependencies, vec![(neighbour.id, child.id)]);
+        assert!(
+            TaskDependency::find_blockers(&pool, child.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            TaskDependency::find_blockers(&pool, parent.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        let parent = Task::find_by_id(&pool, parent.id).await.unwrap().unwrap();
+        assert_eq!(parent.shared_task_id, None);
+    }
+
+    #[tokio::test]
+    async fn moving_to_a_project_is_rejected() {
+        let (pool, project_id) = pool_with_project().await;
+        let target = create_project(&pool, "Elsewhere").await;
+        let (parent, child) = parent_with_child(&pool, project_id).await;
+
+        assert!(matches!(
+            Task::move_to_project(&pool, parent.id, Uuid::new_v4(), false).await,
+            Err(TaskProjectMoveError::ProjectNotFound(_))
+        ));
+        assert!(matches!(
+            Task::move_to_project(&pool, parent.id, project_id, false).await,
+            Err(TaskProjectMoveError::SameProject)
+        ));
+        assert!(matches!(
+            Task::move_to_project(&pool, child.id, target, false).await,
+            Err(TaskProjectMoveError::ChildTask)
+        ));
+        assert!(matches!(
+            Task::move_to_project(&pool, Uuid::new_v4(), target, false).await,
+            Err(TaskProjectMoveError::NotFound)
+        ));
+        let child = Task::find_by_id(&pool, child.id).await.unwrap().unwrap();
+        assert_eq!(child.project_id, project_id);
+    }
+}
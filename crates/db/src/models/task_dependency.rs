@@ -0,0 +1,339 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Acquire, Executor, FromRow, Sqlite, SqlitePool};
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::{Task, TaskPriority, TaskStatus};
+
+#[derive(Debug, Error)]
+pub enum TaskDependencyError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("A task cannot be blocked by itself")]
+    SelfDependency,
+    #[error("Blocking task not found")]
+    BlockerNotFound,
+    #[error("Dependency would create a cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+/// `blocker_id` must be Done before `blocked_id` may move to In Progress
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskDependency {
+    pub blocker_id: Uuid,
+    pub blocked_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskDependency {
+    pub blocker_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TaskDependencies {
+    /// Tasks that must be Done before this one can start
+    pub blockers: Vec<Task>,
+    /// Tasks waiting on this one
+    pub dependents: Vec<Task>,
+}
+
+impl TaskDependency {
+    /// Record that `blocker_id` blocks `blocked_id`. Adding an existing
+    /// dependency is a no-op; one that closes a loop is rejected with the
+    /// titles along the loop.
+    pub async fn create<'a, A>(
+        conn: A,
+        blocker_id: Uuid,
+        blocked_id: Uuid,
+    ) -> Result<Self, TaskDependencyError>
+    where
+        A: Acquire<'a, Database = Sqlite>,
+    {
+        if blocker_id == blocked_id {
+            return Err(TaskDependencyError::SelfDependency);
+        }
+
+        let mut tx = conn.begin().await?;
+        let edges: Vec<(Uuid, Uuid)> = sqlx::query!(
+            r#"SELECT blocker_id as "blocker_id!: Uuid", blocked_id as "blocked_id!: Uuid"
+               FROM task_dependencies"#
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|edge| (edge.blocker_id, edge.blocked_id))
+        .collect();
+        if let Some(path) = dependency_path(&edges, blocked_id, blocker_id) {
+            let mut titles = Vec::with_capacity(path.len() + 1);
+            for id in std::iter::once(blocker_id).chain(path) {
+                let title = sqlx::query_scalar!("SELECT title FROM tasks WHERE id = $1", id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                titles.push(title.unwrap_or_else(|| id.to_string()));
+            }
+            return Err(TaskDependencyError::Cycle(titles));
+        }
+
+        sqlx::query!(
+            r#"INSERT INTO task_dependencies (blocker_id, blocked_id)
+               VALUES ($1, $2)
+               ON CONFLICT (blocker_id, blocked_id) DO NOTHING"#,
+            blocker_id,
+            blocked_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        let dependency = sqlx::query_as!(
+            TaskDependency,
+            r#"SELECT blocker_id as "blocker_id!: Uuid",
+                      blocked_id as "blocked_id!: Uuid",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_dependencies
+               WHERE blocker_id = $1 AND blocked_id = $2"#,
+            blocker_id,
+            blocked_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(dependency)
+    }
+
+    pub async fn delete<'e, E>(
+        executor: E,
+        blocker_id: Uuid,
+        blocked_id: Uuid,
+    ) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            "DELETE FROM task_dependencies WHERE blocker_id = $1 AND blocked_id = $2",
+            blocker_id,
+            blocked_id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Tasks that block `task_id`
+    pub async fn find_blockers(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.shared_task_id as "shared_task_id: Uuid", t.priority as "priority!: TaskPriority", t.position, t.assignee, t.due_date as "due_date: NaiveDate", t.schedule_weight as "schedule_weight!: f64", t.status_key as "status_key!", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks t
+               JOIN task_dependencies d ON d.blocker_id = t.id
+               WHERE d.blocked_id = $1 AND t.deleted_at IS NULL
+               ORDER BY t.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Blockers of `task_id` that are not Done yet
+    pub async fn find_open_blockers<'e, E>(
+        executor: E,
+        task_id: Uuid,
+    ) -> Result<Vec<Task>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.shared_task_id as "shared_task_id: Uuid", t.priority as "priority!: TaskPriority", t.position, t.assignee, t.due_date as "due_date: NaiveDate", t.schedule_weight as "schedule_weight!: f64", t.status_key as "status_key!", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks t
+               JOIN task_dependencies d ON d.blocker_id = t.id
+               WHERE d.blocked_id = $1 AND t.status != 'done' AND t.deleted_at IS NULL
+               ORDER BY t.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Tasks blocked by `task_id`
+    pub async fn find_dependents(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.shared_task_id as "shared_task_id: Uuid", t.priority as "priority!: TaskPriority", t.position, t.assignee, t.due_date as "due_date: NaiveDate", t.schedule_weight as "schedule_weight!: f64", t.status_key as "status_key!", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks t
+               JOIN task_dependencies d ON d.blocked_id = t.id
+               WHERE d.blocker_id = $1 AND t.deleted_at IS NULL
+               ORDER BY t.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+/// Path from `from` to `to` following blocker -> blocked edges, both ends
+/// included, if one exists
+fn dependency_path(edges: &[(Uuid, Uuid)], from: Uuid, to: Uuid) -> Option<Vec<Uuid>> {
+    let mut blocked_by: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for (blocker, blocked) in edges {
+        blocked_by.entry(*blocker).or_default().push(*blocked);
+    }
+
+    // Breadth-first, remembering how each task was reached
+    let mut reached_from: HashMap<Uuid, Uuid> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(current) = queue.pop_front() {
+        if current == to {
+            let mut path = vec![to];
+            while let Some(prev) = reached_from.get(path.last().unwrap()) {
+                path.push(*prev);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in blocked_by.get(&current).into_iter().flatten() {
+            if *next != from && !reached_from.contains_key(next) {
+                reached_from.insert(*next, current);
+                queue.push_back(*next);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::task::TaskStatus,
+        test_support::{create_task, pool_with_project},
+    };
+
+    /// Whether each task shows as blocked on the board, by id
+    async fn blocked(pool: &SqlitePool, project_id: Uuid) -> HashMap<Uuid, bool> {
+        Task::find_by_project_id_with_attempt_status(pool, project_id, false)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|task| (task.id, task.blocked))
+            .collect()
+    }
+
+    #[test]
+    fn finds_transitive_paths_only_along_edges() {
+        let [a, b, c, d] = [
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        ];
+        // a blocks b and c, both block d
+        let edges = [(a, b), (a, c), (b, d), (c, d)];
+
+        let path = dependency_path(&edges, a, d).unwrap();
+        assert_eq!(path.len(), 3);
+        assert_eq!((path[0], path[2]), (a, d));
+
+        // Adding "d blocks a" closes a loop through a -> b -> d; neither
+        // "b blocks c" nor the redundant "a blocks d" does
+        assert!(dependency_path(&edges, c, b).is_none());
+        assert!(dependency_path(&edges, d, a).is_none());
+    }
+
+    #[test]
+    fn reports_the_chain_of_a_long_cycle() {
+        let ids: Vec<Uuid> = (0..5).map(|_| Uuid::new_v4()).collect();
+        let edges: Vec<_> = ids.windows(2).map(|w| (w[0], w[1])).collect();
+
+        // Adding "last blocks first" must walk the whole chain
+        assert_eq!(dependency_path(&edges, ids[0], ids[4]), Some(ids.clone()));
+    }
+
+    #[tokio::test]
+    async fn cycles_are_rejected_with_the_loop_titles() {
+        let (pool, project_id) = pool_with_project().await;
+        let a = create_task(&pool, project_id, "A").await;
+        let b = create_task(&pool, project_id, "B").await;
+        let c = create_task(&pool, project_id, "C").await;
+        Task::add_dependency(&pool, b.id, a.id).await.unwrap();
+        Task::add_dependency(&pool, c.id, b.id).await.unwrap();
+
+        assert!(matches!(
+            Task::add_dependency(&pool, a.id, a.id).await,
+            Err(TaskDependencyError::SelfDependency)
+        ));
+        let Err(TaskDependencyError::Cycle(titles)) = Task::add_dependency(&pool, a.id, c.id).await
+        else {
+            panic!("C blocking A closes A -> B -> C");
+        };
+        assert_eq!(titles, ["C", "A", "B", "C"]);
+        assert!(Task::blockers(&pool, a.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn chains_link_only_direct_neighbours() {
+        let (pool, project_id) = pool_with_project().await;
+        let a = create_task(&pool, project_id, "A").await;
+        let b = create_task(&pool, project_id, "B").await;
+        let c = create_task(&pool, project_id, "C").await;
+        Task::add_dependency(&pool, b.id, a.id).await.unwrap();
+        Task::add_dependency(&pool, c.id, b.id).await.unwrap();
+
+        let ids = |tasks: Vec<Task>| tasks.into_iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids(Task::blockers(&pool, c.id).await.unwrap()), [b.id]);
+        assert_eq!(ids(Task::dependents(&pool, a.id).await.unwrap()), [b.id]);
+
+        // A shortcut along the chain is no loop, and adding it twice is one edge
+        Task::add_dependency(&pool, c.id, a.id).await.unwrap();
+        Task::add_dependency(&pool, c.id, a.id).await.unwrap();
+        let mut blockers = ids(Task::blockers(&pool, c.id).await.unwrap());
+        let mut expected = vec![a.id, b.id];
+        blockers.sort();
+        expected.sort();
+        assert_eq!(blockers, expected);
+    }
+
+    #[tokio::test]
+    async fn finishing_a_blocker_unblocks_only_the_next_task() {
+        let (pool, project_id) = pool_with_project().await;
+        let a = create_task(&pool, project_id, "A").await;
+        let b = create_task(&pool, project_id, "B").await;
+        let c = create_task(&pool, project_id, "C").await;
+        Task::add_dependency(&pool, b.id, a.id).await.unwrap();
+        Task::add_dependency(&pool, c.id, b.id).await.unwrap();
+        let board = blocked(&pool, project_id).await;
+        assert_eq!(
+            (board[&a.id], board[&b.id], board[&c.id]),
+            (false, true, true)
+        );
+
+        Task::update_status(&pool, a.id, TaskStatus::Done)
+            .await
+            .unwrap();
+        let board = blocked(&pool, project_id).await;
+        assert_eq!((board[&b.id], board[&c.id]), (false, true));
+        let open = TaskDependency::find_open_blockers(&pool, c.id)
+            .await
+            .unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].id, b.id);
+
+        Task::update_status(&pool, b.id, TaskStatus::Done)
+            .await
+            .unwrap();
+        assert!(!blocked(&pool, project_id).await[&c.id]);
+        assert!(
+            TaskDependency::find_open_blockers(&pool, c.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "task_event_type", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TaskEventType {
+    Created,
+    StatusChange,
+    PriorityChange,
+    AssigneeChange,
+    DueDateChange,
+    ProjectChange,
+    AttemptStarted,
+    /// A coding agent attempt stopped running; logged by a database trigger
+    AttemptFinished,
+    DependencyAdded,
+    DependencyRemoved,
+    Archived,
+    Unarchived,
+    Deleted,
+    Restored,
+    WipLimitOverride,
+    /// A linked issue changed upstream in a way that disagrees with the task
+    ExternalConflict,
+}
+
+/// Who or what caused a task mutation
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "task_event_actor", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TaskEventActor {
+    /// Someone acting through the UI, API or MCP
+    User,
+    /// The app reacting to an attempt starting, finishing or merging
+    System,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskEvent {
+    #[ts(type = "number")]
+    pub id: i64,
+    pub task_id: Uuid,
+    pub event_type: TaskEventType,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+    pub actor: TaskEventActor,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// An event with the project its task belongs to
+#[derive(Debug, Clone, FromRow)]
+pub struct ProjectTaskEvent {
+    #[sqlx(flatten)]
+    pub event: TaskEvent,
+    pub project_id: Uuid,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateTaskEvent {
+    pub task_id: Uuid,
+    pub event_type: TaskEventType,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+    pub actor: TaskEventActor,
+}
+
+impl CreateTaskEvent {
+    pub fn new(task_id: Uuid, event_type: TaskEventType, actor: TaskEventActor) -> Self {
+        Self {
+            task_id,
+            event_type,
+            old_value: None,
+            new_value: None,
+            actor,
+        }
+    }
+
+    pub fn with_change(mut self, old_value: Option<Value>, new_value: Option<Value>) -> Self {
+        self.old_value = old_value;
+        self.new_value = new_value;
+        self
+    }
+}
+
+impl TaskEvent {
+    pub async fn create<'e, E>(executor: E, data: &CreateTaskEvent) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"INSERT INTO task_events (task_id, event_type, old_value, new_value, actor)
+               VALUES ($1, $2, $3, $4, $5)"#,
+            data.task_id,
+            data.event_type,
+            data.old_value,
+            data.new_value,
+            data.actor
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Events of a task, newest first. Pass the last `id` of a page as
+    /// `before` to get the next one.
+    pub async fn find_by_task(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskEvent,
+            r#"SELECT id as "id!: i64",
+                      task_id as "task_id!: Uuid",
+                      event_type as "event_type!: TaskEventType",
+                      old_value as "old_value: Value",
+                      new_value as "new_value: Value",
+                      actor as "actor!: TaskEventActor",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_events
+               WHERE task_id = $1 AND ($2 IS NULL OR id < $2)
+               ORDER BY id DESC
+               LIMIT $3"#,
+            task_id,
+            before,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Events of all tasks logged after the event `after_id`, oldest first.
+    /// Ids grow in commit order, so a reader following the last id it saw
+    /// misses nothing.
+    pub async fn find_after(
+        pool: &SqlitePool,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<ProjectTaskEvent>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT e.id as "id!: i64",
+                      e.task_id as "task_id!: Uuid",
+                      e.event_type as "event_type!: TaskEventType",
+                      e.old_value as "old_value: Value",
+                      e.new_value as "new_value: Value",
+                      e.actor as "actor!: TaskEventActor",
+                      e.created_at as "created_at!: DateTime<Utc>",
+                      t.project_id as "project_id!: Uuid"
+               FROM task_events e
+               JOIN tasks t ON t.id = e.task_id
+               WHERE e.id > $1
+               ORDER BY e.id
+               LIMIT $2"#,
+            after_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(records
+            .into_iter()
+            .map(|rec| ProjectTaskEvent {
+                event: TaskEvent {
+                    id: rec.id,
+                    task_id: rec.task_id,
+                    event_type: rec.event_type,
+                    old_value: rec.old_value,
+                    new_value: rec.new_value,
+                    actor: rec.actor,
+                    created_at: rec.created_at,
+                },
+                project_id: rec.project_id,
+            })
+            .collect())
+    }
+
+    /// Id of the newest event, 0 when none was logged
+    pub async fn latest_id(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(r#"SELECT COALESCE(MAX(id), 0) as "latest!: i64" FROM task_events"#)
+            .fetch_one(pool)
+            .await
+    }
+
+    /// Drop events recorded before `cutoff`
+    pub async fn delete_older_than(
+        pool: &SqlitePool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM task_events WHERE julianday(created_at) < julianday($1)",
+            cutoff
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_task, pool_with_project};
+
+    #[tokio::test]
+    async fn pages_through_events_newest_first() {
+        let (pool, project_id) = pool_with_project().await;
+        let task = create_task(&pool, project_id, "Audited").await;
+        for status in ["inprogress", "inreview", "done"] {
+            let event =
+                CreateTaskEvent::new(task.id, TaskEventType::StatusChange, TaskEventActor::User)
+                    .with_change(None, Some(Value::from(status)));
+            TaskEvent::create(&pool, &event).await.unwrap();
+        }
+
+        let first = TaskEvent::find_by_task(&pool, task.id, None, 2)
+            .await
+            .unwrap();
+        let values: Vec<_> = first.iter().map(|e| e.new_value.clone().unwrap()).collect();
+        assert_eq!(values, vec![Value::from("done"), Value::from("inreview")]);
+
+        let rest = TaskEvent::find_by_task(&pool, task.id, Some(first[1].id), 2)
+            .await
+            .unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].new_value, Some(Value::from("inprogress")));
+        assert_eq!(rest[0].actor, TaskEventActor::User);
+    }
+
+    #[tokio::test]
+    async fn prunes_only_events_before_the_cutoff() {
+        let (pool, project_id) = pool_with_project().await;
+        let task = create_task(&pool, project_id, "Audited").await;
+        let event = CreateTaskEvent::new(task.id, TaskEventType::Created, TaskEventActor::User);
+        TaskEvent::create(&pool, &event).await.unwrap();
+
+        let past = Utc::now() - chrono::Duration::days(1);
+        assert_eq!(TaskEvent::delete_older_than(&pool, past).await.unwrap(), 0);
+
+        let future = Utc::now() + chrono::Duration::minutes(1);
+        assert_eq!(
+            TaskEvent::delete_older_than(&pool, future).await.unwrap(),
+            1
+        );
+        assert!(
+            TaskEvent::find_by_task(&pool, task.id, None, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
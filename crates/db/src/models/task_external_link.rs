@@ -0,0 +1,281 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
+use strum_macros::Display;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS, Display)]
+#[sqlx(type_name = "external_provider", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ExternalProvider {
+    GitHub,
+}
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS, Display)]
+#[sqlx(type_name = "external_issue_state", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ExternalIssueState {
+    Open,
+    Closed,
+}
+
+/// A task tied to the issue in an external tracker it was imported from
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskExternalLink {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub provider: ExternalProvider,
+    /// `owner/name` for GitHub
+    pub repository: String,
+    /// The issue number for GitHub
+    pub external_id: String,
+    pub url: String,
+    /// Issue state as of the last import or sync
+    pub remote_state: ExternalIssueState,
+    /// Last status change of the task pushed to the issue
+    #[ts(type = "number")]
+    pub synced_event_id: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A link whose task changed status since it was last synced
+#[derive(Debug, Clone, FromRow)]
+pub struct UnsyncedLink {
+    #[sqlx(flatten)]
+    pub link: TaskExternalLink,
+    /// Newest status change event of the task
+    pub event_id: i64,
+}
+
+impl TaskExternalLink {
+    /// Link a task to an issue. Status changes already logged for the task
+    /// count as synced.
+    pub async fn create<'e, E>(
+        executor: E,
+        task_id: Uuid,
+        provider: ExternalProvider,
+        repository: &str,
+        external_id: &str,
+        url: &str,
+        remote_state: ExternalIssueState,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            TaskExternalLink,
+            r#"INSERT INTO task_external_links
+                   (id, task_id, provider, repository, external_id, url, remote_state, synced_event_id)
+               VALUES ($1, $2, $3, $4, $5, $6, $7,
+                       (SELECT COALESCE(MAX(id), 0) FROM task_events WHERE task_id = $2))
+               RETURNING id as "id!: Uuid",
+                         task_id as "task_id!: Uuid",
+                         provider as "provider!: ExternalProvider",
+                         repository,
+                         external_id,
+                         url,
+                         remote_state as "remote_state!: ExternalIssueState",
+                         synced_event_id as "synced_event_id!: i64",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            task_id,
+            provider,
+            repository,
+            external_id,
+            url,
+            remote_state
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    pub async fn find_by_task(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskExternalLink,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      provider as "provider!: ExternalProvider",
+                      repository,
+                      external_id,
+                      url,
+                      remote_state as "remote_state!: ExternalIssueState",
+                      synced_event_id as "synced_event_id!: i64",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_external_links
+               WHERE task_id = $1
+               ORDER BY created_at"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Ids of the issues in `repository` already linked to a task of the
+    /// project
+    pub async fn find_linked_ids(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        provider: ExternalProvider,
+        repository: &str,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT l.external_id
+               FROM task_external_links l
+               JOIN tasks t ON t.id = l.task_id
+               WHERE t.project_id = $1 AND l.provider = $2 AND l.repository = $3"#,
+            project_id,
+            provider,
+            repository
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Links of `provider` whose task has a status change not yet pushed,
+    /// leaving out deleted tasks
+    pub async fn find_unsynced(
+        pool: &SqlitePool,
+        provider: ExternalProvider,
+    ) -> Result<Vec<UnsyncedLink>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      provider as "provider!: ExternalProvider",
+                      repository,
+                      external_id,
+                      url,
+                      remote_state as "remote_state!: ExternalIssueState",
+                      synced_event_id as "synced_event_id!: i64",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>",
+                      event_id as "event_id!: i64"
+               FROM (
+                   SELECT l.*, MAX(e.id) AS event_id
+                   FROM task_external_links l
+                   JOIN tasks t ON t.id = l.task_id AND t.deleted_at IS NULL
+                   JOIN task_events e ON e.task_id = l.task_id AND e.event_type = 'status_change'
+                   WHERE l.provider = $1
+                   GROUP BY l.id
+               )
+               WHERE event_id > synced_event_id
+               ORDER BY event_id"#,
+            provider
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(records
+            .into_iter()
+            .map(|rec| UnsyncedLink {
+                link: TaskExternalLink {
+                    id: rec.id,
+                    task_id: rec.task_id,
+                    provider: rec.provider,
+                    repository: rec.repository,
+                    external_id: rec.external_id,
+                    url: rec.url,
+                    remote_state: rec.remote_state,
+                    synced_event_id: rec.synced_event_id,
+                    created_at: rec.created_at,
+                    updated_at: rec.updated_at,
+                },
+                event_id: rec.event_id,
+            })
+            .collect())
+    }
+
+    /// Record that the task's changes up to `event_id` were pushed, and the
+    /// issue state that resulted
+    pub async fn mark_synced(
+        pool: &SqlitePool,
+        id: Uuid,
+        event_id: i64,
+        remote_state: ExternalIssueState,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE task_external_links
+               SET synced_event_id = $2, remote_state = $3,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            event_id,
+            remote_state
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            task::{Task, TaskStatus},
+            task_event::{CreateTaskEvent, TaskEvent, TaskEventActor, TaskEventType},
+        },
+        test_support::{create_task, pool_with_project},
+    };
+
+    async fn log_status_change(pool: &SqlitePool, task_id: Uuid) {
+        let event =
+            CreateTaskEvent::new(task_id, TaskEventType::StatusChange, TaskEventActor::User);
+        TaskEvent::create(pool, &event).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_later_status_changes_are_unsynced() {
+        let (pool, project_id) = pool_with_project().await;
+        let task = create_task(&pool, project_id, "Issue 7").await;
+        log_status_change(&pool, task.id).await;
+        let link = TaskExternalLink::create(
+            &pool,
+            task.id,
+            ExternalProvider::GitHub,
+            "octo/repo",
+            "7",
+            "https://github.com/octo/repo/issues/7",
+            ExternalIssueState::Open,
+        )
+        .await
+        .unwrap();
+        let unsynced = || TaskExternalLink::find_unsynced(&pool, ExternalProvider::GitHub);
+        assert!(unsynced().await.unwrap().is_empty());
+
+        Task::update_status(&pool, task.id, TaskStatus::Done)
+            .await
+            .unwrap();
+        log_status_change(&pool, task.id).await;
+        let pending = unsynced().await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].link.id, link.id);
+
+        TaskExternalLink::mark_synced(
+            &pool,
+            link.id,
+            pending[0].event_id,
+            ExternalIssueState::Closed,
+        )
+        .await
+        .unwrap();
+        assert!(unsynced().await.unwrap().is_empty());
+        assert_eq!(
+            TaskExternalLink::find_linked_ids(
+                &pool,
+                project_id,
+                ExternalProvider::GitHub,
+                "octo/repo"
+            )
+            .await
+            .unwrap(),
+            ["7"]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Ranks that order tasks within a board column. They compare as plain
+//! strings, and a new rank always fits between two neighbours, so a move
+//! only rewrites the moved task.
+
+const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Ranks grow by a digit each time a gap is split; past this length the
+/// column is renumbered
+pub const MAX_RANK_LEN: usize = 24;
+
+fn digit_value(digit: u8) -> usize {
+    DIGITS.iter().position(|d| *d == digit).unwrap_or(0)
+}
+
+/// A rank that sorts after `before` and before `after`, where `None` leaves
+/// that side open. Never ends in the lowest digit, so there is always room
+/// in front of it.
+pub fn rank_between(before: Option<&str>, after: Option<&str>) -> String {
+    midpoint(
+        before.unwrap_or_default().as_bytes(),
+        after.map(str::as_bytes),
+    )
+}
+
+fn midpoint(low: &[u8], high: Option<&[u8]>) -> String {
+    if let Some(high) = high {
+        // Digits shared by both, reading digits missing from `low` as zero
+        let shared = high
+            .iter()
+            .enumerate()
+            .take_while(|(i, digit)| low.get(*i).copied().unwrap_or(DIGITS[0]) == **digit)
+            .count();
+        if shared > 0 {
+            let rest = midpoint(low.get(shared..).unwrap_or_default(), Some(&high[shared..]));
+            return format!("{}{rest}", String::from_utf8_lossy(&high[..shared]));
+        }
+    }
+
+    let low_digit = low.first().map_or(0, |digit| digit_value(*digit));
+    let high_digit = high
+        .and_then(|high| high.first())
+        .map_or(DIGITS.len(), |digit| digit_value(*digit));
+    if high_digit > low_digit + 1 {
+        return char::from(DIGITS[(low_digit + high_digit) / 2]).to_string();
+    }
+    match high {
+        // The first digit of `high` alone is already between the two
+        Some(high) if high.len() > 1 => char::from(high[0]).to_string(),
+        _ => format!(
+            "{}{}",
+            char::from(DIGITS[low_digit]),
+            midpoint(low.get(1..).unwrap_or_default(), None)
+        ),
+    }
+}
+
+/// Evenly spaced ranks for a column of `count` tasks, in order
+pub fn spread_ranks(count: usize) -> Vec<String> {
+    (1..=count).map(|i| format!("{i:08}V")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_fit_between_neighbours() {
+        let cases = [
+            (None, None),
+            (None, Some("V")),
+            (Some("V"), None),
+            (Some("00000001V"), Some("00000002V")),
+            (Some("1"), Some("2")),
+            (Some("1z"), Some("2")),
+            (None, Some("01")),
+            (Some("zzz"), None),
+        ];
+        for (before, after) in cases {
+            let rank = rank_between(before, after);
+            assert!(
+                before.is_none_or(|b| b < rank.as_str()),
+                "{before:?} < {rank}"
+            );
+            assert!(
+                after.is_none_or(|a| rank.as_str() < a),
+                "{rank} < {after:?}"
+            );
+            assert!(!rank.ends_with('0'), "{rank}");
+        }
+    }
+
+    #[test]
+    fn repeated_splits_stay_ordered() {
+        let low = "00000001V".to_string();
+        let mut high = "00000002V".to_string();
+        for _ in 0..200 {
+            let rank = rank_between(Some(&low), Some(&high));
+            assert!(low < rank && rank < high);
+            high = rank;
+        }
+        assert!(high.len() > MAX_RANK_LEN);
+    }
+}
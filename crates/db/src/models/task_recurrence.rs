@@ -0,0 +1,257 @@
+//! Recurrence rules for task templates, written as five-field cron
+//! expressions (`minute hour day-of-month month day-of-week`) and evaluated
+//! in UTC. Fields take `*`, single values, `a-b` ranges, `/n` steps and
+//! comma separated lists. As in cron, when both day fields are restricted a
+//! day matches if either does.
+
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, Timelike, Utc};
+use thiserror::Error;
+
+/// How far back to look for an occurrence before giving up; rules such as
+/// `0 0 30 2 *` never match
+const MAX_LOOKBACK_DAYS: u64 = 5 * 366;
+
+#[derive(Debug, Error, PartialEq)]
+#[error("Invalid recurrence '{rule}': {reason}")]
+pub struct RecurrenceError {
+    pub rule: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recurrence {
+    rule: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl Recurrence {
+    /// The latest occurrence at or before `until`
+    pub fn latest_at_or_before(&self, until: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut date = until.date_naive();
+        for _ in 0..MAX_LOOKBACK_DAYS {
+            if self.matches_date(date) {
+                let last_hour = if date == until.date_naive() {
+                    until.hour()
+                } else {
+                    23
+                };
+                for hour in (0..=last_hour).rev().filter(|h| has(self.hours, *h)) {
+                    let last_minute = if date == until.date_naive() && hour == until.hour() {
+                        until.minute()
+                    } else {
+                        59
+                    };
+                    if let Some(minute) = (0..=last_minute).rev().find(|m| has(self.minutes, *m)) {
+                        return date.and_hms_opt(hour, minute, 0).map(|at| at.and_utc());
+                    }
+                }
+            }
+            date = date.checked_sub_days(Days::new(1))?;
+        }
+        None
+    }
+
+    /// The occurrence a scheduler should act on at `now`: the latest one
+    /// after `since`, the previous run or the template's creation. Missed
+    /// occurrences collapse into the newest, so a long downtime creates one
+    /// task rather than a backlog of them.
+    pub fn due_occurrence(
+        &self,
+        since: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        self.latest_at_or_before(now)
+            .filter(|occurrence| *occurrence > since)
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        let day_of_month = has(self.days_of_month, date.day());
+        let day_of_week = has(self.days_of_week, date.weekday().num_days_from_sunday());
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (true, false) => day_of_week,
+            (false, true) => day_of_month,
+            (false, false) => day_of_month || day_of_week,
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = RecurrenceError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let error = |reason: String| RecurrenceError {
+            rule: rule.to_string(),
+            reason,
+        };
+        let expanded = match rule.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(error(format!("expected 5 fields, got {}", fields.len())));
+        };
+
+        let mut days_of_week = parse_field(day_of_week, 0, 7).map_err(error)?;
+        // Both 0 and 7 mean Sunday
+        if has(days_of_week, 7) {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            rule: rule.trim().to_string(),
+            minutes: parse_field(minute, 0, 59).map_err(error)?,
+            hours: parse_field(hour, 0, 23).map_err(error)?,
+            days_of_month: parse_field(day_of_month, 1, 31).map_err(error)?,
+            months: parse_field(month, 1, 12).map_err(error)?,
+            days_of_week,
+            any_day_of_month: day_of_month == "*",
+            any_day_of_week: day_of_week == "*",
+        })
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rule)
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// The values a field allows, as a bit set
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("invalid step in '{part}'")),
+            },
+            None => (part, 1),
+        };
+        let value = |text: &str| match text.parse::<u32>() {
+            Ok(value) if (min..=max).contains(&value) => Ok(value),
+            _ => Err(format!("'{text}' is not between {min} and {max}")),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            // `5/15` runs from 5 to the end of the field
+            None if step > 1 => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if start > end {
+            return Err(format!("range '{range}' is reversed"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(2026, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+            .and_utc()
+    }
+
+    fn rule(rule: &str) -> Recurrence {
+        rule.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_fields() {
+        assert!("* * * * *".parse::<Recurrence>().is_ok());
+        assert!("*/15 9-17 * * 1-5".parse::<Recurrence>().is_ok());
+        assert!("0 0 1,15 * *".parse::<Recurrence>().is_ok());
+        assert!("@weekly".parse::<Recurrence>().is_ok());
+        for invalid in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            assert!(invalid.parse::<Recurrence>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn latest_occurrence() {
+        // 2026-03-02 is a Monday
+        assert_eq!(
+            rule("30 9 * * *").latest_at_or_before(at(2, 9, 30)),
+            Some(at(2, 9, 30))
+        );
+        assert_eq!(
+            rule("30 9 * * *").latest_at_or_before(at(2, 9, 29)),
+            Some(at(1, 9, 30))
+        );
+        assert_eq!(
+            rule("*/20 * * * *").latest_at_or_before(at(2, 10, 59)),
+            Some(at(2, 10, 40))
+        );
+        assert_eq!(
+            rule("0 8 * * 1-5").latest_at_or_before(at(8, 12, 0)),
+            Some(at(6, 8, 0))
+        );
+        // Sunday may be written as 7
+        assert_eq!(
+            rule("0 0 * * 7").latest_at_or_before(at(10, 0, 0)),
+            Some(at(8, 0, 0))
+        );
+        // Either day field matches when both are restricted
+        assert_eq!(
+            rule("0 0 20 * 1").latest_at_or_before(at(21, 0, 0)),
+            Some(at(20, 0, 0))
+        );
+        assert_eq!(
+            rule("0 0 20 * 1").latest_at_or_before(at(19, 0, 0)),
+            Some(at(16, 0, 0))
+        );
+        assert_eq!(rule("0 0 30 2 *").latest_at_or_before(at(1, 0, 0)), None);
+    }
+
+    #[test]
+    fn due_occurrence_window() {
+        let daily = rule("@daily");
+        // Nothing new since the last run
+        assert_eq!(daily.due_occurrence(at(5, 0, 0), at(5, 23, 59)), None);
+        // The window is open at the start
+        assert_eq!(
+            daily.due_occurrence(at(5, 0, 0), at(6, 0, 0)),
+            Some(at(6, 0, 0))
+        );
+        // Occurrences missed while offline collapse into the latest
+        assert_eq!(
+            daily.due_occurrence(at(1, 0, 0), at(9, 7, 0)),
+            Some(at(9, 0, 0))
+        );
+    }
+}
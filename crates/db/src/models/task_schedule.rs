@@ -0,0 +1,117 @@
+//! Spreading a parent task's deadline over the tasks created from it.
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TaskScheduleError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Due date {due} is before the task was created on {created}")]
+    DueBeforeCreation { due: NaiveDate, created: NaiveDate },
+    #[error("Schedule weight must be a positive number")]
+    InvalidWeight,
+    #[error("Task has no due date to distribute")]
+    NoDueDate,
+    #[error("Due date {0} has already passed")]
+    DeadlinePassed(NaiveDate),
+}
+
+/// A due date may not fall before the day the task was created
+pub fn check_due_date(due: NaiveDate, created_at: DateTime<Utc>) -> Result<(), TaskScheduleError> {
+    let created = created_at.date_naive();
+    if due < created {
+        return Err(TaskScheduleError::DueBeforeCreation { due, created });
+    }
+    Ok(())
+}
+
+pub fn check_weight(weight: f64) -> Result<(), TaskScheduleError> {
+    if !weight.is_finite() || weight <= 0.0 {
+        return Err(TaskScheduleError::InvalidWeight);
+    }
+    Ok(())
+}
+
+/// Due dates for consecutive phases that share the days from `start` to
+/// `end` in proportion to `weights`. Each phase is due where its cumulative
+/// share of the span ends, rounded down to a whole day, and the last one is
+/// always due on `end`.
+pub fn distribute_due_dates(start: NaiveDate, end: NaiveDate, weights: &[f64]) -> Vec<NaiveDate> {
+    let start = start.min(end);
+    let span = (end - start).num_days() as f64;
+    let total: f64 = weights.iter().sum();
+    let mut cumulative = 0.0;
+    weights
+        .iter()
+        .enumerate()
+        .map(|(i, weight)| {
+            cumulative += weight;
+            if i + 1 == weights.len() || total <= 0.0 {
+                return end;
+            }
+            let days = (span * cumulative / total).floor() as u64;
+            start
+                .checked_add_days(Days::new(days))
+                .unwrap_or(end)
+                .min(end)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    #[test]
+    fn splits_the_span_by_weight() {
+        assert_eq!(
+            distribute_due_dates(date(1), date(10), &[1.0, 1.0, 1.0]),
+            vec![date(4), date(7), date(10)]
+        );
+        assert_eq!(
+            distribute_due_dates(date(1), date(9), &[1.0, 2.0, 1.0]),
+            vec![date(3), date(7), date(9)]
+        );
+        // 10 days in thirds rounds each boundary down
+        assert_eq!(
+            distribute_due_dates(date(1), date(11), &[1.0, 1.0, 1.0]),
+            vec![date(4), date(7), date(11)]
+        );
+    }
+
+    #[test]
+    fn boundary_dates() {
+        assert_eq!(
+            distribute_due_dates(date(5), date(5), &[1.0, 3.0]),
+            vec![date(5), date(5)]
+        );
+        // A start after the deadline leaves nothing to share
+        assert_eq!(
+            distribute_due_dates(date(9), date(5), &[1.0, 1.0]),
+            vec![date(5), date(5)]
+        );
+        assert_eq!(
+            distribute_due_dates(date(1), date(31), &[2.5]),
+            vec![date(31)]
+        );
+        assert!(distribute_due_dates(date(1), date(31), &[]).is_empty());
+    }
+
+    #[test]
+    fn due_date_may_fall_on_the_creation_day() {
+        let created_at = date(10).and_hms_opt(23, 59, 0).unwrap().and_utc();
+        assert!(check_due_date(date(10), created_at).is_ok());
+        assert!(matches!(
+            check_due_date(date(9), created_at),
+            Err(TaskScheduleError::DueBeforeCreation { .. })
+        ));
+        assert!(check_weight(0.5).is_ok());
+        assert!(check_weight(0.0).is_err());
+        assert!(check_weight(f64::NAN).is_err());
+    }
+}
@@ -0,0 +1,402 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{
+    task::{CreateTask, Task, TaskPriority},
+    task_event::{CreateTaskEvent, TaskEvent, TaskEventActor, TaskEventType},
+    task_recurrence::{Recurrence, RecurrenceError},
+};
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskTemplate {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: TaskPriority,
+    pub assignee: Option<String>,
+    /// Five-field cron expression in UTC; templates without one are only
+    /// instantiated by hand
+    pub recurrence: Option<String>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// The latest scheduled occurrence a task was created for
+    pub last_occurrence_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskTemplate {
+    pub project_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub priority: Option<TaskPriority>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub assignee: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub recurrence: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateTaskTemplate {
+    pub title: Option<String>,
+    /// Empty string clears the description
+    pub description: Option<String>,
+    pub priority: Option<TaskPriority>,
+    /// Empty string clears the assignee
+    pub assignee: Option<String>,
+    /// Empty string removes the schedule
+    pub recurrence: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// Parse a recurrence rule, treating a blank one as no schedule
+pub fn parse_recurrence(rule: Option<&str>) -> Result<Option<Recurrence>, RecurrenceError> {
+    rule.map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .map(str::parse)
+        .transpose()
+}
+
+impl TaskTemplate {
+    pub fn schedule(&self) -> Result<Option<Recurrence>, RecurrenceError> {
+        parse_recurrence(self.recurrence.as_deref())
+    }
+
+    /// The occurrence a task should be created for at `now`, if any. The
+    /// window starts after the last scheduled run, or at creation for a
+    /// template that has never run.
+    pub fn due_occurrence(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if !self.enabled {
+            return None;
+        }
+        let since = self.last_occurrence_at.unwrap_or(self.created_at);
+        self.schedule().ok().flatten()?.due_occurrence(since, now)
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTemplate,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      title,
+                      description,
+                      priority as "priority!: TaskPriority",
+                      assignee,
+                      recurrence,
+                      enabled as "enabled!: bool",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>",
+                      (SELECT MAX(r.occurrence_at)
+                         FROM task_template_runs r
+                        WHERE r.template_id = task_templates.id) as "last_occurrence_at: DateTime<Utc>"
+               FROM task_templates
+               WHERE project_id = $1
+               ORDER BY title ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Enabled templates that have a recurrence rule, outside archived
+    /// projects
+    pub async fn find_scheduled(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTemplate,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      title,
+                      description,
+                      priority as "priority!: TaskPriority",
+                      assignee,
+                      recurrence,
+                      enabled as "enabled!: bool",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>",
+                      (SELECT MAX(r.occurrence_at)
+                         FROM task_template_runs r
+                        WHERE r.template_id = task_templates.id) as "last_occurrence_at: DateTime<Utc>"
+               FROM task_templates
+               WHERE enabled = 1
+                 AND recurrence IS NOT NULL
+                 AND project_id NOT IN (SELECT id FROM projects WHERE archived_at IS NOT NULL)"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTemplate,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      title,
+                      description,
+                      priority as "priority!: TaskPriority",
+                      assignee,
+                      recurrence,
+                      enabled as "enabled!: bool",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>",
+                      (SELECT MAX(r.occurrence_at)
+                         FROM task_template_runs r
+                        WHERE r.template_id = task_templates.id) as "last_occurrence_at: DateTime<Utc>"
+               FROM task_templates
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreateTaskTemplate) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let priority = data.priority.unwrap_or_default();
+        sqlx::query!(
+            r#"INSERT INTO task_templates (id, project_id, title, description, priority, assignee, recurrence)
+               VALUES ($1, $2, $3, $4, $5, NULLIF(TRIM($6), ''), NULLIF(TRIM($7), ''))"#,
+            id,
+            data.project_id,
+            data.title,
+            data.description,
+            priority,
+            data.assignee,
+            data.recurrence
+        )
+        .execute(pool)
+        .await?;
+        Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateTaskTemplate,
+    ) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE task_templates
+               SET title = COALESCE($2, title),
+                   description = CASE WHEN $3 IS NULL THEN description ELSE NULLIF($3, '') END,
+                   priority = COALESCE($4, priority),
+                   assignee = CASE WHEN $5 IS NULL THEN assignee ELSE NULLIF(TRIM($5), '') END,
+                   recurrence = CASE WHEN $6 IS NULL THEN recurrence ELSE NULLIF(TRIM($6), '') END,
+                   enabled = COALESCE($7, enabled),
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            data.title,
+            data.description,
+            data.priority,
+            data.assignee,
+            data.recurrence,
+            data.enabled
+        )
+        .execute(pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_templates WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Create a task from the template. A scheduled `occurrence` is claimed
+    /// in the same transaction as the task is created, so each occurrence
+    /// yields at most one task no matter how often it is attempted; `None`
+    /// is returned when it was already claimed. Manual runs pass no
+    /// occurrence and always create a task.
+    pub async fn instantiate(
+        pool: &SqlitePool,
+        template: &TaskTemplate,
+        occurrence: Option<DateTime<Utc>>,
+        actor: TaskEventActor,
+    ) -> Result<Option<Task>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let mut data = CreateTask::from_title_description(
+            template.project_id,
+            template.title.clone(),
+            template.description.clone(),
+        );
+        data.priority = Some(template.priority);
+        data.assignee = template.assignee.clone();
+        let task = Task::insert(&mut tx, Uuid::new_v4(), &data).await?;
+
+        let claimed = sqlx::query!(
+            r#"INSERT OR IGNORE INTO task_template_runs (template_id, occurrence_at, task_id)
+               VALUES ($1, $2, $3)"#,
+            template.id,
+            occurrence,
+            task.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        if claimed.rows_affected() == 0 {
+            // Dropping the transaction discards the task
+            return Ok(None);
+        }
+
+        TaskEvent::create(
+            &mut *tx,
+            &CreateTaskEvent::new(task.id, TaskEventType::Created, actor),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(task))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::{models::project::Project, test_support::pool_with_project};
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(2026, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+            .and_utc()
+    }
+
+    async fn daily_template() -> (SqlitePool, TaskTemplate) {
+        let (pool, project_id) = pool_with_project().await;
+        let template = TaskTemplate::create(
+            &pool,
+            &CreateTaskTemplate {
+                project_id,
+                title: "Standup notes".to_string(),
+                description: None,
+                priority: Some(TaskPriority::High),
+                assignee: None,
+                recurrence: Some("0 9 * * *".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+        sqlx::query("UPDATE task_templates SET created_at = $2 WHERE id = $1")
+            .bind(template.id)
+            .bind(at(1, 0))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let template = TaskTemplate::find_by_id(&pool, template.id)
+            .await
+            .unwrap()
+            .unwrap();
+        (pool, template)
+    }
+
+    async fn task_count(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn each_occurrence_creates_one_task() {
+        let (pool, template) = daily_template().await;
+        assert_eq!(template.due_occurrence(at(1, 8)), None);
+        let occurrence = template.due_occurrence(at(1, 10)).unwrap();
+        assert_eq!(occurrence, at(1, 9));
+
+        let task =
+            TaskTemplate::instantiate(&pool, &template, Some(occurrence), TaskEventActor::System)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(task.title, "Standup notes");
+        assert_eq!(task.priority, TaskPriority::High);
+
+        // A restart that still sees the stale template must not create another
+        let again =
+            TaskTemplate::instantiate(&pool, &template, Some(occurrence), TaskEventActor::System)
+                .await
+                .unwrap();
+        assert!(again.is_none());
+        assert_eq!(task_count(&pool).await, 1);
+
+        // Once reloaded, the window starts after the claimed occurrence
+        let template = TaskTemplate::find_by_id(&pool, template.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(template.last_occurrence_at, Some(at(1, 9)));
+        assert_eq!(template.due_occurrence(at(2, 8)), None);
+        assert_eq!(template.due_occurrence(at(4, 12)), Some(at(4, 9)));
+    }
+
+    #[tokio::test]
+    async fn manual_runs_do_not_claim_occurrences() {
+        let (pool, template) = daily_template().await;
+        for _ in 0..2 {
+            TaskTemplate::instantiate(&pool, &template, None, TaskEventActor::User)
+                .await
+                .unwrap()
+                .unwrap();
+        }
+        assert_eq!(task_count(&pool).await, 2);
+
+        let template = TaskTemplate::find_by_id(&pool, template.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(template.last_occurrence_at, None);
+        assert_eq!(template.due_occurrence(at(1, 10)), Some(at(1, 9)));
+
+        let disabled = TaskTemplate::update(
+            &pool,
+            template.id,
+            &UpdateTaskTemplate {
+                title: None,
+                description: None,
+                priority: None,
+                assignee: None,
+                recurrence: None,
+                enabled: Some(false),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(disabled.due_occurrence(at(1, 10)), None);
+    }
+
+    #[tokio::test]
+    async fn archived_projects_are_not_scheduled() {
+        let (pool, template) = daily_template().await;
+        let scheduled =
+            |templates: Vec<TaskTemplate>| templates.iter().any(|t| t.id == template.id);
+        assert!(scheduled(
+            TaskTemplate::find_scheduled(&pool).await.unwrap()
+        ));
+
+        Project::archive(&pool, template.project_id).await.unwrap();
+        assert!(!scheduled(
+            TaskTemplate::find_scheduled(&pool).await.unwrap()
+        ));
+    }
+}
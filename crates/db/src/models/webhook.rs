@@ -0,0 +1,527 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type, types::Json};
+use strum_macros::Display;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Events a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Display)]
+pub enum WebhookEventType {
+    #[serde(rename = "task.created")]
+    #[strum(serialize = "task.created")]
+    TaskCreated,
+    #[serde(rename = "task.status_changed")]
+    #[strum(serialize = "task.status_changed")]
+    TaskStatusChanged,
+    #[serde(rename = "attempt.started")]
+    #[strum(serialize = "attempt.started")]
+    AttemptStarted,
+    #[serde(rename = "attempt.failed")]
+    #[strum(serialize = "attempt.failed")]
+    AttemptFailed,
+    /// Sent by hand to check a webhook, whatever its filter
+    #[serde(rename = "webhook.test")]
+    #[strum(serialize = "webhook.test")]
+    Test,
+}
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS, Display)]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub url: String,
+    /// Key of the HMAC-SHA256 signature sent with every payload
+    pub secret: String,
+    /// Events sent to the webhook; empty sends all of them
+    #[ts(type = "WebhookEventType[]")]
+    pub events: Json<Vec<WebhookEventType>>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateWebhook {
+    pub url: String,
+    /// Generated when left out
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub events: Vec<WebhookEventType>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpdateWebhook {
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub events: Option<Vec<WebhookEventType>>,
+    pub enabled: Option<bool>,
+}
+
+/// A task or attempt change queued by the database triggers, waiting to
+/// be turned into deliveries
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookOutboxEvent {
+    pub id: i64,
+    pub project_id: Uuid,
+    pub task_id: Uuid,
+    pub event_type: String,
+    pub old_value: Option<Json<Value>>,
+    pub new_value: Option<Json<Value>>,
+    pub execution_process_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One payload for one webhook, kept after it is sent for debugging
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_type: String,
+    #[ts(type = "JsonValue")]
+    pub payload: Json<Value>,
+    pub status: WebhookDeliveryStatus,
+    #[ts(type = "number")]
+    pub attempts: i64,
+    /// HTTP status of the last attempt, when the receiver answered
+    #[ts(type = "number | null")]
+    pub response_status: Option<i64>,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    /// Whether the webhook wants events of `event_type`
+    pub fn subscribes_to(&self, event_type: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e.to_string() == event_type)
+    }
+
+    pub async fn find_by_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      url,
+                      secret,
+                      events as "events!: Json<Vec<WebhookEventType>>",
+                      enabled as "enabled!: bool",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhooks
+               WHERE project_id = $1
+               ORDER BY created_at"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_enabled_by_project<'e, E>(
+        executor: E,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            Webhook,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      url,
+                      secret,
+                      events as "events!: Json<Vec<WebhookEventType>>",
+                      enabled as "enabled!: bool",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhooks
+               WHERE project_id = $1 AND enabled
+               ORDER BY created_at"#,
+            project_id
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// The webhook, if it belongs to the project
+    pub async fn find_in_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      url,
+                      secret,
+                      events as "events!: Json<Vec<WebhookEventType>>",
+                      enabled as "enabled!: bool",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhooks
+               WHERE id = $1 AND project_id = $2"#,
+            id,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      url,
+                      secret,
+                      events as "events!: Json<Vec<WebhookEventType>>",
+                      enabled as "enabled!: bool",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhooks
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateWebhook,
+        secret: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let events = Json(&data.events);
+        let enabled = data.enabled.unwrap_or(true);
+        sqlx::query_as!(
+            Webhook,
+            r#"INSERT INTO webhooks (id, project_id, url, secret, events, enabled)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         url,
+                         secret,
+                         events as "events!: Json<Vec<WebhookEventType>>",
+                         enabled as "enabled!: bool",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.url,
+            secret,
+            events,
+            enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Apply the fields set in `data`, leaving the others alone
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateWebhook,
+    ) -> Result<Self, sqlx::Error> {
+        let events = data.events.as_ref().map(Json);
+        sqlx::query_as!(
+            Webhook,
+            r#"UPDATE webhooks
+               SET url = COALESCE($2, url),
+                   secret = COALESCE($3, secret),
+                   events = COALESCE($4, events),
+                   enabled = COALESCE($5, enabled),
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         url,
+                         secret,
+                         events as "events!: Json<Vec<WebhookEventType>>",
+                         enabled as "enabled!: bool",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.url,
+            data.secret,
+            events,
+            data.enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM webhooks WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl WebhookOutboxEvent {
+    /// The oldest queued events
+    pub async fn find_batch(pool: &SqlitePool, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookOutboxEvent,
+            r#"SELECT id as "id!: i64",
+                      project_id as "project_id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      event_type,
+                      old_value as "old_value: Json<Value>",
+                      new_value as "new_value: Json<Value>",
+                      execution_process_id as "execution_process_id: Uuid",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM webhook_outbox
+               ORDER BY id
+               LIMIT $1"#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn delete<'e, E>(executor: E, id: i64) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!("DELETE FROM webhook_outbox WHERE id = $1", id)
+            .execute(executor)
+            .await?;
+        Ok(())
+    }
+}
+
+impl WebhookDelivery {
+    /// Queue a payload for the webhook, due right away
+    pub async fn create<'e, E>(
+        executor: E,
+        webhook_id: Uuid,
+        event_type: &str,
+        payload: &Value,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"INSERT INTO webhook_deliveries (id, webhook_id, event_type, payload)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid",
+                         webhook_id as "webhook_id!: Uuid",
+                         event_type,
+                         payload as "payload!: Json<Value>",
+                         status as "status!: WebhookDeliveryStatus",
+                         attempts as "attempts!: i64",
+                         response_status,
+                         error,
+                         next_attempt_at as "next_attempt_at!: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            webhook_id,
+            event_type,
+            payload
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Pending deliveries whose next attempt is due at `now`, oldest first
+    pub async fn find_due(
+        pool: &SqlitePool,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"SELECT id as "id!: Uuid",
+                      webhook_id as "webhook_id!: Uuid",
+                      event_type,
+                      payload as "payload!: Json<Value>",
+                      status as "status!: WebhookDeliveryStatus",
+                      attempts as "attempts!: i64",
+                      response_status,
+                      error,
+                      next_attempt_at as "next_attempt_at!: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhook_deliveries
+               WHERE status = 'pending' AND julianday(next_attempt_at) <= julianday($1)
+               ORDER BY next_attempt_at
+               LIMIT $2"#,
+            now,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Newest deliveries of the webhook first
+    pub async fn find_by_webhook(
+        pool: &SqlitePool,
+        webhook_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"SELECT id as "id!: Uuid",
+                      webhook_id as "webhook_id!: Uuid",
+                      event_type,
+                      payload as "payload!: Json<Value>",
+                      status as "status!: WebhookDeliveryStatus",
+                      attempts as "attempts!: i64",
+                      response_status,
+                      error,
+                      next_attempt_at as "next_attempt_at!: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhook_deliveries
+               WHERE webhook_id = $1
+               ORDER BY created_at DESC
+               LIMIT $2"#,
+            webhook_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record the outcome of one more attempt
+    pub async fn record_attempt(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: WebhookDeliveryStatus,
+        response_status: Option<i64>,
+        error: Option<&str>,
+        next_attempt_at: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"UPDATE webhook_deliveries
+               SET status = $2, attempts = attempts + 1, response_status = $3,
+                   error = $4, next_attempt_at = $5,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         webhook_id as "webhook_id!: Uuid",
+                         event_type,
+                         payload as "payload!: Json<Value>",
+                         status as "status!: WebhookDeliveryStatus",
+                         attempts as "attempts!: i64",
+                         response_status,
+                         error,
+                         next_attempt_at as "next_attempt_at!: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            status,
+            response_status,
+            error,
+            next_attempt_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        models::task_event::{CreateTaskEvent, TaskEvent, TaskEventActor, TaskEventType},
+        test_support::{create_task, pool_with_project},
+    };
+
+    fn webhook(events: Vec<WebhookEventType>) -> CreateWebhook {
+        CreateWebhook {
+            url: "http://127.0.0.1:9/hook".to_string(),
+            secret: None,
+            events,
+            enabled: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn task_events_are_queued_only_with_an_enabled_webhook() {
+        let (pool, project_id) = pool_with_project().await;
+        let task = create_task(&pool, project_id, "Quiet").await;
+        let status_change =
+            CreateTaskEvent::new(task.id, TaskEventType::StatusChange, TaskEventActor::User)
+                .with_change(Some(json!("todo")), Some(json!("inprogress")));
+        TaskEvent::create(&pool, &status_change).await.unwrap();
+        assert!(
+            WebhookOutboxEvent::find_batch(&pool, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let hook = Webhook::create(&pool, project_id, &webhook(vec![]), "s3cret")
+            .await
+            .unwrap();
+        TaskEvent::create(&pool, &status_change).await.unwrap();
+        let priority =
+            CreateTaskEvent::new(task.id, TaskEventType::PriorityChange, TaskEventActor::User);
+        TaskEvent::create(&pool, &priority).await.unwrap();
+        let queued = WebhookOutboxEvent::find_batch(&pool, 10).await.unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].event_type, "task.status_changed");
+        assert_eq!(queued[0].new_value.as_deref(), Some(&json!("inprogress")));
+
+        let disabled = UpdateWebhook {
+            url: None,
+            secret: None,
+            events: None,
+            enabled: Some(false),
+        };
+        let hook = Webhook::update(&pool, hook.id, &disabled).await.unwrap();
+        assert!(!hook.enabled);
+        assert_eq!(hook.secret, "s3cret");
+        TaskEvent::create(&pool, &status_change).await.unwrap();
+        assert_eq!(
+            WebhookOutboxEvent::find_batch(&pool, 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn filters_match_event_names() {
+        let (pool, project_id) = pool_with_project().await;
+        let data = webhook(vec![WebhookEventType::AttemptFailed]);
+        let hook = Webhook::create(&pool, project_id, &data, "s")
+            .await
+            .unwrap();
+        assert!(hook.subscribes_to("attempt.failed"));
+        assert!(!hook.subscribes_to("task.created"));
+        let all = Webhook::create(&pool, project_id, &webhook(vec![]), "s")
+            .await
+            .unwrap();
+        assert!(all.subscribes_to("task.created"));
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -371,12 +371,15 @@ impl Workspace {
         .await
     }
 
-    pub async fn create(
-        pool: &SqlitePool,
+    pub async fn create<'e, E>(
+        executor: E,
         data: &CreateWorkspace,
         id: Uuid,
         task_id: Uuid,
-    ) -> Result<Self, WorkspaceError> {
+    ) -> Result<Self, WorkspaceError>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         Ok(sqlx::query_as!(
             Workspace,
             r#"INSERT INTO workspaces (id, task_id, container_ref, branch, agent_working_dir, setup_completed_at)
@@ -389,7 +392,7 @@ impl Workspace {
             data.agent_working_dir,
             Option::<DateTime<Utc>>::None
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?)
     }
 
@@ -0,0 +1,46 @@
+//! Retrying writes that lost a race for SQLite's single write lock.
+
+use std::{future::Future, time::Duration};
+
+use sqlx::Error;
+
+/// Attempts made before the busy error is returned
+const MAX_ATTEMPTS: u32 = 5;
+const FIRST_DELAY: Duration = Duration::from_millis(50);
+
+const SQLITE_BUSY: i32 = 5;
+const SQLITE_LOCKED: i32 = 6;
+
+/// Whether `err` is SQLite giving up on a lock held by another connection,
+/// extended codes such as `SQLITE_BUSY_SNAPSHOT` included
+pub fn is_busy(err: &Error) -> bool {
+    let Error::Database(err) = err else {
+        return false;
+    };
+    err.code()
+        .and_then(|code| code.parse::<i32>().ok())
+        .is_some_and(|code| matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED))
+}
+
+/// Run `write` again with a doubling delay while it fails because the
+/// database is locked. The busy timeout already makes most writers wait, so
+/// this is for the writes that still lose out, like a transaction that read
+/// before another connection wrote. `write` must be safe to repeat.
+pub async fn retry_on_busy<T, F, Fut>(mut write: F) -> Result<T, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let mut delay = FIRST_DELAY;
+    for _ in 1..MAX_ATTEMPTS {
+        match write().await {
+            Err(err) if is_busy(&err) => {
+                tracing::debug!("Database is locked, retrying in {:?}", delay);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            result => return result,
+        }
+    }
+    write().await
+}
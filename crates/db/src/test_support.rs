@@ -0,0 +1,48 @@
+//! Database fixtures for tests, shared with other crates through the
+//! `test-utils` feature
+
+use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        project::{CreateProject, Project},
+        task::{CreateTask, Task},
+    },
+    run_migrations,
+};
+
+/// Fresh in-memory database with every migration applied
+pub async fn test_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    run_migrations(&pool).await.unwrap();
+    pool
+}
+
+/// Add a project without repositories, returning its id
+pub async fn create_project(pool: &SqlitePool, name: &str) -> Uuid {
+    let project_id = Uuid::new_v4();
+    let project = CreateProject {
+        name: name.to_string(),
+        repositories: vec![],
+    };
+    Project::create(pool, &project, project_id).await.unwrap();
+    project_id
+}
+
+/// Fresh database holding a single project
+pub async fn pool_with_project() -> (SqlitePool, Uuid) {
+    let pool = test_pool().await;
+    let project_id = create_project(&pool, "Backlog").await;
+    (pool, project_id)
+}
+
+/// Add a Todo task to the project
+pub async fn create_task(pool: &SqlitePool, project_id: Uuid, title: &str) -> Task {
+    let data = CreateTask::from_title_description(project_id, title.to_string(), None);
+    Task::create(pool, &data, Uuid::new_v4()).await.unwrap()
+}
@@ -16,22 +16,27 @@ use futures::{StreamExt, TryStreamExt};
 use git2::Error as Git2Error;
 use serde_json::Value;
 use services::services::{
+    acp_sessions::AcpSessionGcService,
     analytics::{AnalyticsContext, AnalyticsService},
     approvals::Approvals,
     auth::AuthContext,
     config::{Config, ConfigError},
     container::{ContainerError, ContainerService},
+    event_bus::{EventBus, EventBusFeeder},
     events::{EventError, EventService},
     file_search::FileSearchCache,
     filesystem::{FilesystemError, FilesystemService},
     filesystem_watcher::FilesystemWatcherError,
     git::{GitService, GitServiceError},
+    github_issues::GitHubIssueSyncService,
     image::{ImageError, ImageService},
     pr_monitor::PrMonitorService,
     project::ProjectService,
     queued_message::QueuedMessageService,
     repo::RepoService,
     share::SharePublisher,
+    task_templates::TaskTemplateScheduler,
+    webhooks::WebhookDispatcher,
     worktree_manager::WorktreeError,
 };
 use sqlx::Error as SqlxError;
@@ -103,6 +108,8 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn events(&self) -> &EventService;
 
+    fn event_bus(&self) -> &EventBus;
+
     fn file_search_cache(&self) -> &Arc<FileSearchCache>;
 
     fn approvals(&self) -> &Approvals;
@@ -136,6 +143,26 @@ pub trait Deployment: Clone + Send + Sync + 'static {
         PrMonitorService::spawn(db, analytics, publisher).await
     }
 
+    async fn spawn_acp_session_gc_service(&self) -> tokio::task::JoinHandle<()> {
+        AcpSessionGcService::spawn(self.db().clone()).await
+    }
+
+    async fn spawn_task_template_scheduler(&self) -> tokio::task::JoinHandle<()> {
+        TaskTemplateScheduler::spawn(self.db().clone()).await
+    }
+
+    async fn spawn_github_issue_sync_service(&self) -> tokio::task::JoinHandle<()> {
+        GitHubIssueSyncService::spawn(self.db().clone(), self.config().clone()).await
+    }
+
+    async fn spawn_webhook_dispatcher(&self) -> tokio::task::JoinHandle<()> {
+        WebhookDispatcher::spawn(self.db().clone()).await
+    }
+
+    async fn spawn_event_bus_feeder(&self) -> tokio::task::JoinHandle<()> {
+        EventBusFeeder::spawn(self.db().clone(), self.event_bus().clone()).await
+    }
+
     async fn track_if_analytics_allowed(&self, event_name: &str, properties: Value) {
         let analytics_enabled = self.config().read().await.analytics_enabled;
         // Track events unless user has explicitly opted out
@@ -12,8 +12,10 @@ use crate::{
         script::ScriptRequest,
     },
     approvals::ExecutorApprovalService,
+    command::CmdOverrides,
     env::ExecutionEnv,
     executors::{BaseCodingAgent, ExecutorError, SpawnedChild},
+    profile::{ExecutorConfigs, ExecutorProfileId},
 };
 pub mod coding_agent_follow_up;
 pub mod coding_agent_initial;
@@ -69,6 +71,44 @@ impl ExecutorAction {
             ExecutorActionType::ScriptRequest(_) => None,
         }
     }
+
+    pub fn executor_profile_id(&self) -> Option<&ExecutorProfileId> {
+        match self.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                Some(&request.executor_profile_id)
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                Some(&request.executor_profile_id)
+            }
+            ExecutorActionType::ReviewRequest(request) => Some(&request.executor_profile_id),
+            ExecutorActionType::ScriptRequest(_) => None,
+        }
+    }
+
+    /// Command overrides of the profile this action runs, if any
+    fn cmd_overrides(&self) -> CmdOverrides {
+        self.executor_profile_id()
+            .and_then(|profile_id| ExecutorConfigs::get_cached().get_coding_agent(profile_id))
+            .and_then(|agent| agent.cmd_overrides().cloned())
+            .unwrap_or_default()
+    }
+
+    /// The profile's own cap on captured output, overriding the global one
+    pub fn output_limit_bytes(&self) -> Option<u64> {
+        self.cmd_overrides().output_limit_bytes
+    }
+
+    /// Values of sensitive environment variables the spawned process will see,
+    /// for masking in its logs
+    pub async fn secret_values(&self, env: &ExecutionEnv) -> Vec<String> {
+        let cmd = self.cmd_overrides();
+        let profile_env = env.clone().with_profile(&cmd).await.unwrap_or_else(|_| {
+            // Spawning fails on the same env file error; still cover inline values
+            env.clone()
+                .with_overrides(&cmd.env.clone().unwrap_or_default())
+        });
+        profile_env.secret_values(&cmd)
+    }
 }
 
 #[async_trait]
@@ -1,23 +1,33 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{cell::RefCell, collections::HashMap, path::PathBuf};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use ts_rs::TS;
-use workspace_utils::shell::resolve_executable_path;
+use workspace_utils::shell::{find_executable_in_dirs, resolve_executable_path};
 
-use crate::executors::ExecutorError;
+use crate::{env::ExecutionEnv, executors::ExecutorError};
 
 #[derive(Debug, Error)]
 pub enum CommandBuildError {
     #[error("base command cannot be parsed: {0}")]
     InvalidBase(String),
+    #[error("unbalanced {quote} quote at column {column} in: {input}")]
+    UnbalancedQuote {
+        input: String,
+        quote: char,
+        column: usize,
+    },
     #[error("base command is empty after parsing")]
     EmptyCommand,
     #[error("failed to quote command: {0}")]
     QuoteError(#[from] shlex::QuoteError),
     #[error("invalide shell parameters: {0}")]
     InvalidShellParams(String),
+    #[error("environment variable `{0}` is not set")]
+    UndefinedVariable(String),
+    #[error("unterminated `${{` in: {0}")]
+    UnterminatedVariable(String),
 }
 
 #[derive(Debug, Clone)]
@@ -31,11 +41,36 @@ impl CommandParts {
         Self { program, args }
     }
 
+    /// Expand `$VAR` and `${VAR}` references in the program and arguments
+    /// against `env`, which must already have the profile applied with
+    /// [`ExecutionEnv::with_profile`], so that variables resolve to the values
+    /// the agent process sees. `$$` yields a literal `$`.
+    /// `{scratch}` yields the workspace's scratch directory. A bare program
+    /// name found in the profile's `path_prepend` directories is replaced by
+    /// its full path.
+    pub fn expand_env(
+        self,
+        env: &ExecutionEnv,
+        cmd: &CmdOverrides,
+    ) -> Result<Self, CommandBuildError> {
+        let lookup = |name: &str| env.var(name);
+        let program = expand_env_vars(&env.expand_scratch(&self.program), lookup)?;
+        let program = find_executable_in_dirs(&program, &env.path_prepend_dirs(cmd))
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or(program);
+        let args = self
+            .args
+            .iter()
+            .map(|arg| expand_env_vars(&env.expand_scratch(arg), lookup))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { program, args })
+    }
+
     pub async fn into_resolved(self) -> Result<(PathBuf, Vec<String>), ExecutorError> {
         let CommandParts { program, args } = self;
         let executable = resolve_executable_path(&program)
             .await
-            .ok_or(ExecutorError::ExecutableNotFound { program })?;
+            .ok_or_else(|| ExecutorError::executable_not_found(program))?;
         Ok((executable, args))
     }
 }
@@ -60,6 +95,42 @@ pub struct CmdOverrides {
     )]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub env: Option<HashMap<String, String>>,
+    #[schemars(
+        title = "Environment File",
+        description = "Dotenv file loaded before Environment Variables; relative paths resolve against the worktree"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<PathBuf>,
+    #[schemars(
+        title = "Remove Environment Variables",
+        description = "Inherited environment variables to remove before Environment Variables are applied"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_remove: Option<Vec<String>>,
+    #[schemars(
+        title = "Clear Environment",
+        description = "Drop the server's environment except PATH and HOME instead of inheriting it"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_clear: Option<bool>,
+    #[schemars(
+        title = "Prepend to PATH",
+        description = "Directories to put in front of PATH and search for the command; {worktree} and {scratch} are substituted and relative paths resolve against the worktree"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_prepend: Option<Vec<PathBuf>>,
+    #[schemars(
+        title = "Redact",
+        description = "Extra environment variable names (* wildcards allowed) whose values are masked in logs; names ending in _KEY, _TOKEN or _SECRET are always masked"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact: Option<Vec<String>>,
+    #[schemars(
+        title = "Output Limit (bytes)",
+        description = "Stdout and stderr kept per execution before the rest is truncated; defaults to the global execution output limit"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_limit_bytes: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
@@ -168,10 +239,94 @@ fn split_command_line(input: &str) -> Result<Vec<String>, CommandBuildError> {
 
     #[cfg(not(windows))]
     {
-        shlex::split(input).ok_or_else(|| CommandBuildError::InvalidBase(input.to_string()))
+        shlex::split(input).ok_or_else(|| match unbalanced_quote(input) {
+            Some((quote, column)) => CommandBuildError::UnbalancedQuote {
+                input: input.to_string(),
+                quote,
+                column,
+            },
+            None => CommandBuildError::InvalidBase(input.to_string()),
+        })
     }
 }
 
+/// Check that a command line splits into arguments, so broken quoting is
+/// reported when a profile is saved rather than when an agent is spawned
+pub fn check_command_line(input: &str) -> Result<(), CommandBuildError> {
+    split_command_line(input).map(|_| ())
+}
+
+/// Find the quote left open under POSIX shell rules, with its 1-based column
+#[cfg(not(windows))]
+fn unbalanced_quote(input: &str) -> Option<(char, usize)> {
+    let mut open: Option<(char, usize)> = None;
+    let mut chars = input.chars().enumerate();
+    while let Some((i, c)) = chars.next() {
+        match (open, c) {
+            (None | Some(('"', _)), '\\') => {
+                chars.next();
+            }
+            (None, '\'' | '"') => open = Some((c, i + 1)),
+            (Some((quote, _)), c) if c == quote => open = None,
+            _ => {}
+        }
+    }
+    open
+}
+
+fn expand_env_vars(
+    input: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, CommandBuildError> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        if let Some(tail) = after.strip_prefix('$') {
+            out.push('$');
+            rest = tail;
+        } else if let Some(tail) = after.strip_prefix('{') {
+            let end = tail
+                .find('}')
+                .ok_or_else(|| CommandBuildError::UnterminatedVariable(input.to_string()))?;
+            let name = &tail[..end];
+            out.push_str(
+                &lookup(name).ok_or_else(|| CommandBuildError::UndefinedVariable(name.into()))?,
+            );
+            rest = &tail[end + 1..];
+        } else if after.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            let end = after.find(|c| !is_name_char(c)).unwrap_or(after.len());
+            let name = &after[..end];
+            out.push_str(
+                &lookup(name).ok_or_else(|| CommandBuildError::UndefinedVariable(name.into()))?,
+            );
+            rest = &after[end..];
+        } else {
+            out.push('$');
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Names of the variables referenced in `input` that `lookup` cannot
+/// resolve, in order of appearance
+pub fn undefined_variables(input: &str, lookup: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    let missing = RefCell::new(Vec::new());
+    let _ = expand_env_vars(input, |name| {
+        lookup(name).or_else(|| {
+            missing.borrow_mut().push(name.to_string());
+            Some(String::new())
+        })
+    });
+    missing.into_inner()
+}
+
 pub fn apply_overrides(
     builder: CommandBuilder,
     overrides: &CmdOverrides,
@@ -187,3 +342,208 @@ pub fn apply_overrides(
         Ok(builder)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::RepoContext;
+
+    fn env_with(vars: &[(&str, &str)]) -> ExecutionEnv {
+        let mut env = ExecutionEnv::new(RepoContext::default(), false);
+        for (k, v) in vars {
+            env.insert(*k, *v);
+        }
+        env
+    }
+
+    #[test]
+    fn expands_command_params_and_base_override() {
+        let env = env_with(&[("AGENT_HOME", "/opt/agent"), ("CFG", "/etc/agent")]);
+        let overrides = CmdOverrides {
+            base_command_override: Some("${AGENT_HOME}/bin/agent --fast".to_string()),
+            additional_params: Some(vec!["--config".into(), "$CFG/agent.toml".into()]),
+            ..Default::default()
+        };
+        let builder = CommandBuilder::new("agent").params(["--home", "$AGENT_HOME"]);
+        let parts = apply_overrides(builder, &overrides)
+            .unwrap()
+            .build_initial()
+            .unwrap()
+            .expand_env(&env, &overrides)
+            .unwrap();
+
+        assert_eq!(parts.program, "/opt/agent/bin/agent");
+        assert_eq!(
+            parts.args,
+            vec![
+                "--fast",
+                "--home",
+                "/opt/agent",
+                "--config",
+                "/etc/agent/agent.toml"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn profile_env_wins_and_dollar_dollar_escapes() {
+        let overrides = CmdOverrides {
+            env: Some(HashMap::from([("NAME".to_string(), "profile".to_string())])),
+            ..Default::default()
+        };
+        let env = env_with(&[("NAME", "runtime")])
+            .with_profile(&overrides)
+            .await
+            .unwrap();
+        let parts = CommandParts::new("echo".into(), vec!["$NAME-$$NAME-$1".into()])
+            .expand_env(&env, &overrides)
+            .unwrap();
+
+        assert_eq!(parts.args, vec!["profile-$NAME-$1"]);
+    }
+
+    #[tokio::test]
+    async fn env_file_variables_expand_and_removed_ones_do_not() {
+        let worktree = std::env::temp_dir().join(format!("vk-expand-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&worktree).unwrap();
+        std::fs::write(worktree.join(".agent.env"), "AGENT_HOME=/opt/from-file\n").unwrap();
+        let overrides = CmdOverrides {
+            env_file: Some(PathBuf::from(".agent.env")),
+            env_remove: Some(vec!["HOME".to_string()]),
+            ..Default::default()
+        };
+        let env = ExecutionEnv::new(RepoContext::new(worktree.clone(), vec![]), false)
+            .with_profile(&overrides)
+            .await;
+        std::fs::remove_dir_all(&worktree).unwrap();
+        let env = env.unwrap();
+
+        let parts = CommandParts::new("$AGENT_HOME/agent".into(), vec![])
+            .expand_env(&env, &overrides)
+            .unwrap();
+        assert_eq!(parts.program, "/opt/from-file/agent");
+
+        let err = CommandParts::new("agent".into(), vec!["$HOME".into()])
+            .expand_env(&env, &overrides)
+            .unwrap_err();
+        assert!(matches!(err, CommandBuildError::UndefinedVariable(name) if name == "HOME"));
+    }
+
+    #[test]
+    fn lists_undefined_variables() {
+        let lookup = |name: &str| (name == "KNOWN").then(|| "x".to_string());
+        assert_eq!(
+            undefined_variables("$KNOWN ${MISSING} $$ESCAPED $OTHER_MISSING", lookup),
+            vec!["MISSING", "OTHER_MISSING"]
+        );
+    }
+
+    #[test]
+    fn undefined_and_unterminated_variables_error() {
+        let env = env_with(&[]);
+        let overrides = CmdOverrides::default();
+
+        let err = CommandParts::new("${VK_SURELY_UNSET_VARIABLE}/agent".into(), vec![])
+            .expand_env(&env, &overrides)
+            .unwrap_err();
+        assert!(
+            matches!(err, CommandBuildError::UndefinedVariable(name) if name == "VK_SURELY_UNSET_VARIABLE")
+        );
+
+        let err = CommandParts::new("agent".into(), vec!["${HOME".into()])
+            .expand_env(&env, &overrides)
+            .unwrap_err();
+        assert!(matches!(err, CommandBuildError::UnterminatedVariable(_)));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn splits_command_lines_with_quotes_and_escapes() {
+        let cases: &[(&str, &[&str])] = &[
+            (
+                "npx -y @anthropic-ai/claude-code@latest",
+                &["npx", "-y", "@anthropic-ai/claude-code@latest"],
+            ),
+            (
+                "node /path/agent.js --flag 'two words'",
+                &["node", "/path/agent.js", "--flag", "two words"],
+            ),
+            (
+                r#"node "/path/with spaces/agent.js""#,
+                &["node", "/path/with spaces/agent.js"],
+            ),
+            (
+                r"node /path/with\ spaces/agent.js",
+                &["node", "/path/with spaces/agent.js"],
+            ),
+            (r#"agent --name '' """#, &["agent", "--name", "", ""]),
+            (
+                r"agent 'C:\Program Files\agent.exe'",
+                &["agent", r"C:\Program Files\agent.exe"],
+            ),
+            (
+                r"agent C:\\tools\\agent.exe",
+                &["agent", r"C:\tools\agent.exe"],
+            ),
+            (r#"agent "say \"hi\"""#, &["agent", r#"say "hi""#]),
+        ];
+        for (input, expected) in cases {
+            let parts = split_command_line(input).unwrap();
+            assert_eq!(parts, *expected, "splitting {input}");
+        }
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn reports_column_of_unbalanced_quote() {
+        let cases = [
+            ("agent 'open", '\'', 7),
+            (r#"agent "a" "b"#, '"', 11),
+            (r#"agent "it's" 'x"#, '\'', 14),
+        ];
+        for (input, quote, column) in cases {
+            let err = check_command_line(input).unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    CommandBuildError::UnbalancedQuote { quote: q, column: c, .. }
+                        if q == quote && c == column
+                ),
+                "checking {input}: {err}"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn resolves_program_from_path_prepend() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let worktree = std::env::temp_dir().join(format!("vk-path-{}", uuid::Uuid::new_v4()));
+        let bin_dir = worktree.join("tools");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        let program = bin_dir.join("vk-only-in-tools");
+        std::fs::write(&program, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let env = ExecutionEnv::new(RepoContext::new(worktree.clone(), vec![]), false);
+        let overrides = CmdOverrides {
+            path_prepend: Some(vec![PathBuf::from("{worktree}/missing"), "tools".into()]),
+            ..Default::default()
+        };
+        let resolved = CommandParts::new("vk-only-in-tools".into(), vec![])
+            .expand_env(&env, &overrides)
+            .unwrap()
+            .into_resolved()
+            .await;
+        let unresolved = CommandParts::new("vk-only-in-tools".into(), vec![])
+            .expand_env(&env, &CmdOverrides::default())
+            .unwrap()
+            .into_resolved()
+            .await;
+        std::fs::remove_dir_all(&worktree).unwrap();
+
+        assert_eq!(resolved.unwrap().0, program);
+        assert!(unresolved.is_err());
+    }
+}
@@ -1,6 +1,12 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
 
+use thiserror::Error;
 use tokio::process::Command;
+use workspace_utils::{path::expand_tilde, shell::merge_paths};
 
 use crate::command::CmdOverrides;
 
@@ -28,12 +34,37 @@ impl RepoContext {
     }
 }
 
+/// Failure to load a profile's `env_file`. Never carries variable values.
+#[derive(Debug, Error)]
+pub enum EnvFileError {
+    #[error("failed to read env file {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("malformed entry on line {line} of env file {}", path.display())]
+    Malformed { path: PathBuf, line: usize },
+}
+
+/// Names of variables whose values are always masked in executor logs
+pub const SECRET_NAME_PATTERNS: &[&str] = &["*_KEY", "*_TOKEN", "*_SECRET"];
+
+/// Inherited variables kept when a profile clears the environment, without
+/// which most agents cannot find their tools or config
+pub const ENV_CLEAR_KEEP: &[&str] = &["PATH", "HOME"];
+
+/// Prefix of the variables the deployment sets on every execution, such as
+/// `VK_TASK_ID`
+pub const EXECUTION_VAR_PREFIX: &str = "VK_";
+
 /// Environment variables to inject into executor processes
 #[derive(Debug, Clone)]
 pub struct ExecutionEnv {
     pub vars: HashMap<String, String>,
     pub repo_context: RepoContext,
     pub commit_reminder: bool,
+    env_remove: Vec<String>,
+    env_clear: bool,
 }
 
 impl ExecutionEnv {
@@ -42,6 +73,8 @@ impl ExecutionEnv {
             vars: HashMap::new(),
             repo_context,
             commit_reminder,
+            env_remove: Vec::new(),
+            env_clear: false,
         }
     }
 
@@ -62,29 +95,264 @@ impl ExecutionEnv {
         self
     }
 
-    /// Return a new env with profile env from CmdOverrides merged in.
-    pub fn with_profile(self, cmd: &CmdOverrides) -> Self {
+    /// Return a new env with the profile's removals, env file, env and PATH
+    /// prefix from CmdOverrides applied, in that order.
+    pub async fn with_profile(self, cmd: &CmdOverrides) -> Result<Self, EnvFileError> {
+        let file_vars = match cmd.env_file {
+            Some(ref env_file) => {
+                let path = self.resolve_env_file_path(env_file);
+                let contents = tokio::fs::read_to_string(&path).await.map_err(|source| {
+                    EnvFileError::Read {
+                        path: path.clone(),
+                        source,
+                    }
+                })?;
+                parse_env_file(&contents).map_err(|line| EnvFileError::Malformed { path, line })?
+            }
+            None => HashMap::new(),
+        };
+        Ok(self.apply_profile(cmd, &file_vars))
+    }
+
+    /// The env every execution of the profile starts from, as far as it is
+    /// known before a workspace exists. `None` when the env file resolves
+    /// against the worktree or cannot be read, as its variables are unknown.
+    pub fn for_profile_check(cmd: &CmdOverrides) -> Option<Self> {
+        let file_vars = match cmd.env_file {
+            Some(ref env_file) => {
+                let path = expand_tilde(&env_file.to_string_lossy());
+                if !path.is_absolute() {
+                    return None;
+                }
+                parse_env_file(&std::fs::read_to_string(path).ok()?).ok()?
+            }
+            None => HashMap::new(),
+        };
+        Some(Self::new(RepoContext::default(), false).apply_profile(cmd, &file_vars))
+    }
+
+    fn apply_profile(mut self, cmd: &CmdOverrides, file_vars: &HashMap<String, String>) -> Self {
+        self.env_clear |= cmd.env_clear.unwrap_or(false);
+        if let Some(ref keys) = cmd.env_remove {
+            for key in keys {
+                self.vars.remove(key);
+                self.env_remove.push(key.clone());
+            }
+        }
+        self.merge(file_vars);
         if let Some(ref profile_env) = cmd.env {
-            self.with_overrides(profile_env)
+            self.merge(profile_env);
+        }
+
+        let prepend = self.path_prepend_dirs(cmd);
+        if !prepend.is_empty() {
+            let base = match self.vars.get("PATH") {
+                Some(path) => OsString::from(path),
+                None => std::env::var_os("PATH").unwrap_or_default(),
+            };
+            let prepend = std::env::join_paths(&prepend).unwrap_or_default();
+            let path = merge_paths(prepend, base);
+            self.insert("PATH", path.to_string_lossy());
+        }
+        self
+    }
+
+    /// The profile's `path_prepend` entries with `{worktree}` and `{scratch}`
+    /// substituted and relative entries resolved against the worktree
+    pub fn path_prepend_dirs(&self, cmd: &CmdOverrides) -> Vec<PathBuf> {
+        let worktree = &self.repo_context.workspace_root;
+        cmd.path_prepend
+            .iter()
+            .flatten()
+            .map(|entry| {
+                let entry = PathBuf::from(
+                    self.expand_scratch(&entry.to_string_lossy())
+                        .replace("{worktree}", &worktree.to_string_lossy()),
+                );
+                if entry.is_absolute() {
+                    entry
+                } else {
+                    worktree.join(entry)
+                }
+            })
+            .collect()
+    }
+
+    fn resolve_env_file_path(&self, env_file: &Path) -> PathBuf {
+        let expanded = expand_tilde(&env_file.to_string_lossy());
+        if expanded.is_absolute() {
+            expanded
         } else {
-            self
+            self.repo_context.workspace_root.join(expanded)
         }
     }
 
-    /// Apply all environment variables to a Command
+    /// Apply all environment variables to a Command, after clearing or
+    /// removing inherited ones as requested by the profile. Clearing only
+    /// drops what the server inherited: variables the executor already set
+    /// on the command and [`ENV_CLEAR_KEEP`] survive it.
     pub fn apply_to_command(&self, command: &mut Command) {
+        if self.env_clear {
+            let explicit: Vec<(OsString, OsString)> = command
+                .as_std()
+                .get_envs()
+                .filter_map(|(key, value)| Some((key.to_owned(), value?.to_owned())))
+                .collect();
+            command.env_clear();
+            for key in ENV_CLEAR_KEEP {
+                if let Some(value) = std::env::var_os(key) {
+                    command.env(key, value);
+                }
+            }
+            command.envs(explicit);
+        }
+        for key in &self.env_remove {
+            command.env_remove(key);
+        }
         for (key, value) in &self.vars {
             command.env(key, value);
         }
     }
 
+    /// Values of the variables the child process will see whose names match
+    /// [`SECRET_NAME_PATTERNS`] or the profile's `redact` list
+    pub fn secret_values(&self, cmd: &CmdOverrides) -> Vec<String> {
+        let patterns: Vec<&str> = SECRET_NAME_PATTERNS
+            .iter()
+            .copied()
+            .chain(cmd.redact.iter().flatten().map(String::as_str))
+            .collect();
+        let inherited = std::env::vars().filter(|(key, _)| self.inherits(key));
+
+        inherited
+            .chain(self.vars.iter().map(|(k, v)| (k.clone(), v.clone())))
+            .filter(|(key, value)| {
+                !value.is_empty() && patterns.iter().any(|pattern| name_matches(pattern, key))
+            })
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    /// The workspace's scratch directory, when the deployment provides one
+    pub fn scratch_dir(&self) -> Option<&str> {
+        self.vars.get("VK_SCRATCH_DIR").map(String::as_str)
+    }
+
+    /// Substitute `{scratch}` with the scratch directory, if one is set
+    pub fn expand_scratch(&self, input: &str) -> String {
+        match self.scratch_dir() {
+            Some(dir) => input.replace("{scratch}", dir),
+            None => input.to_string(),
+        }
+    }
+
     pub fn contains_key(&self, key: &str) -> bool {
         self.vars.contains_key(key)
     }
+
+    /// Value of `key` in the child process: set on this env, or inherited
+    /// from the server unless the profile cleared or removed it
+    pub fn var(&self, key: &str) -> Option<String> {
+        match self.vars.get(key) {
+            Some(value) => Some(value.clone()),
+            None if self.inherits(key) => std::env::var(key).ok(),
+            None => None,
+        }
+    }
+
+    /// Whether the child process keeps the server's own value of `key`
+    fn inherits(&self, key: &str) -> bool {
+        (!self.env_clear || ENV_CLEAR_KEEP.contains(&key))
+            && !self.env_remove.iter().any(|k| k == key)
+    }
+
+    /// Resolve a variable usable in user-configured prompt text
+    pub fn prompt_variable(&self, name: &str) -> Option<String> {
+        match name {
+            "task_title" => self.vars.get("VK_TASK_TITLE").cloned(),
+            "task_id" => self.vars.get("VK_TASK_ID").cloned(),
+            "project_name" => self.vars.get("VK_PROJECT_NAME").cloned(),
+            "branch" => self.vars.get("VK_WORKSPACE_BRANCH").cloned(),
+            "scratch" => self.scratch_dir().map(str::to_string),
+            "worktree" => Some(
+                self.repo_context
+                    .workspace_root
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Case-insensitive match of a variable name against a pattern in which `*`
+/// stands for any run of characters
+fn name_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_uppercase();
+    let name = name.to_ascii_uppercase();
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = name.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard: exact match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Parse dotenv-style `KEY=VALUE` lines. Blank lines, `#` comments and an
+/// optional `export ` prefix are accepted; values may be single- or
+/// double-quoted. Returns the 1-based number of the first malformed line.
+fn parse_env_file(contents: &str) -> Result<HashMap<String, String>, usize> {
+    let mut vars = HashMap::new();
+
+    for (idx, raw) in contents.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line.split_once('=').ok_or(idx + 1)?;
+        let key = key.trim();
+        let valid_key = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_key {
+            return Err(idx + 1);
+        }
+
+        let value = value.trim();
+        let value = if let Some(inner) = value.strip_prefix('"') {
+            let inner = inner.strip_suffix('"').ok_or(idx + 1)?;
+            inner
+                .replace("\\n", "\n")
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\")
+        } else if let Some(inner) = value.strip_prefix('\'') {
+            inner.strip_suffix('\'').ok_or(idx + 1)?.to_string()
+        } else {
+            match value.find(" #") {
+                Some(pos) => value[..pos].trim_end().to_string(),
+                None => value.to_string(),
+            }
+        };
+        vars.insert(key.to_string(), value);
+    }
+
+    Ok(vars)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+
     use super::*;
 
     #[test]
@@ -103,4 +371,148 @@ mod tests {
         assert_eq!(merged.vars.get("FOO").unwrap(), "profile"); // overrides
         assert_eq!(merged.vars.get("BAR").unwrap(), "profile");
     }
+
+    #[tokio::test]
+    async fn secret_values_follow_name_patterns() {
+        assert!(name_matches("*_KEY", "openai_api_key"));
+        assert!(name_matches("DB_*_PASS*", "DB_MAIN_PASSWORD"));
+        assert!(name_matches("SESSION", "session"));
+        assert!(!name_matches("*_KEY", "KEYRING"));
+        assert!(!name_matches("SESSION", "SESSION_ID"));
+
+        let cmd = CmdOverrides {
+            env_clear: Some(true),
+            env: Some(HashMap::from([
+                ("API_KEY".to_string(), "key-value".to_string()),
+                ("GH_TOKEN".to_string(), "token-value".to_string()),
+                ("DB_PASSWORD".to_string(), "hunter2".to_string()),
+                ("LOG_LEVEL".to_string(), "debug".to_string()),
+            ])),
+            redact: Some(vec!["*_PASSWORD".to_string()]),
+            ..Default::default()
+        };
+        let env = ExecutionEnv::new(RepoContext::default(), false)
+            .with_profile(&cmd)
+            .await
+            .unwrap();
+        let mut secrets = env.secret_values(&cmd);
+        secrets.sort();
+
+        assert_eq!(secrets, vec!["hunter2", "key-value", "token-value"]);
+    }
+
+    #[tokio::test]
+    async fn env_file_is_overridden_by_inline_env() {
+        let dir = std::env::temp_dir().join(format!("vk-env-file-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("agent.env"),
+            "# keys\nexport API_KEY=\"from file\"\nSHARED=file # trailing\nVK_PROJECT_NAME=file\n",
+        )
+        .unwrap();
+
+        let mut base = ExecutionEnv::new(RepoContext::new(dir.clone(), vec![]), false);
+        base.insert("VK_PROJECT_NAME", "runtime");
+
+        let cmd = CmdOverrides {
+            env_file: Some(PathBuf::from("agent.env")),
+            env: Some(HashMap::from([(
+                "SHARED".to_string(),
+                "inline".to_string(),
+            )])),
+            ..Default::default()
+        };
+        let merged = base.with_profile(&cmd).await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(merged.vars.get("API_KEY").unwrap(), "from file");
+        assert_eq!(merged.vars.get("SHARED").unwrap(), "inline");
+        assert_eq!(merged.vars.get("VK_PROJECT_NAME").unwrap(), "file");
+    }
+
+    #[tokio::test]
+    async fn env_file_errors_name_path_and_line() {
+        let cmd = CmdOverrides {
+            env_file: Some(PathBuf::from("/nonexistent/vk/agent.env")),
+            ..Default::default()
+        };
+        let err = ExecutionEnv::new(RepoContext::default(), false)
+            .with_profile(&cmd)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("/nonexistent/vk/agent.env"));
+
+        assert_eq!(parse_env_file("A=1\n\nnot a pair\n"), Err(3));
+        assert_eq!(parse_env_file("A=\"unterminated\n"), Err(1));
+        assert_eq!(parse_env_file("1BAD=x\n"), Err(1));
+    }
+
+    #[cfg(unix)]
+    async fn child_env(env: &ExecutionEnv) -> HashMap<String, String> {
+        child_env_of(Command::new("/usr/bin/env"), env).await
+    }
+
+    /// Environment `command` runs with once `env` is applied to it
+    #[cfg(unix)]
+    async fn child_env_of(mut command: Command, env: &ExecutionEnv) -> HashMap<String, String> {
+        env.apply_to_command(&mut command);
+        let output = command.output().await.unwrap();
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|l| l.split_once('='))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn env_remove_strips_inherited_and_runtime_vars() {
+        let mut base = ExecutionEnv::new(RepoContext::default(), false);
+        base.insert("VK_DROP_ME", "runtime");
+
+        let cmd = CmdOverrides {
+            env_remove: Some(vec!["HOME".to_string(), "VK_DROP_ME".to_string()]),
+            env: Some(HashMap::from([("KEEP".to_string(), "1".to_string())])),
+            ..Default::default()
+        };
+        let vars = child_env(&base.with_profile(&cmd).await.unwrap()).await;
+
+        assert!(!vars.contains_key("HOME"));
+        assert!(!vars.contains_key("VK_DROP_ME"));
+        assert_eq!(vars.get("KEEP").unwrap(), "1");
+        assert!(vars.contains_key("PATH"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn env_clear_drops_only_inherited_vars() {
+        let cmd = CmdOverrides {
+            env_clear: Some(true),
+            env: Some(HashMap::from([("ONLY".to_string(), "1".to_string())])),
+            ..Default::default()
+        };
+        let env = ExecutionEnv::new(RepoContext::default(), false)
+            .with_profile(&cmd)
+            .await
+            .unwrap();
+        // Set by the executor before the profile is applied, as the harness does
+        let mut command = Command::new("/usr/bin/env");
+        command.env("NODE_NO_WARNINGS", "1");
+        let vars = child_env_of(command, &env).await;
+
+        let expected: BTreeSet<&str> = ENV_CLEAR_KEEP
+            .iter()
+            .copied()
+            .filter(|key| std::env::var_os(key).is_some())
+            .chain(["NODE_NO_WARNINGS", "ONLY"])
+            .collect();
+        let names: BTreeSet<&str> = vars.keys().map(String::as_str).collect();
+        assert_eq!(names, expected, "unexpected vars: {vars:?}");
+        assert_eq!(vars.get("NODE_NO_WARNINGS").unwrap(), "1");
+        assert_eq!(vars.get("ONLY").unwrap(), "1");
+        assert_eq!(
+            vars.get("PATH").map(String::as_str),
+            std::env::var("PATH").ok().as_deref()
+        );
+    }
 }
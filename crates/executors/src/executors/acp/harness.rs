@@ -1,12 +1,15 @@
 use std::{
-    path::{Path, PathBuf},
+    collections::HashSet,
+    path::{Component, Path, PathBuf},
     process::Stdio,
     rc::Rc,
     sync::Arc,
+    time::Duration,
 };
 
 use agent_client_protocol as proto;
 use agent_client_protocol::Agent as _;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use futures::StreamExt;
 use tokio::{io::AsyncWriteExt, process::Command, sync::mpsc};
@@ -15,9 +18,12 @@ use tokio_util::{
     io::ReaderStream,
 };
 use tracing::error;
-use workspace_utils::{approvals::ApprovalStatus, stream_lines::LinesStreamExt};
+use workspace_utils::{
+    approvals::ApprovalStatus, path::VIBE_IMAGES_DIR, redact::Redactor,
+    stream_lines::LinesStreamExt,
+};
 
-use super::{AcpClient, SessionManager};
+use super::{AcpClient, GEMINI_SESSION_NAMESPACE, SessionFileInfo, SessionManager};
 use crate::{
     approvals::ExecutorApprovalService,
     command::{CmdOverrides, CommandParts},
@@ -43,12 +49,39 @@ impl AcpAgentHarness {
     /// Create a harness with the default Gemini namespace
     pub fn new() -> Self {
         Self {
-            session_namespace: "gemini_sessions".to_string(),
+            session_namespace: GEMINI_SESSION_NAMESPACE.to_string(),
             model: None,
             mode: None,
         }
     }
 
+    /// Delete sessions in `namespace` not modified within `older_than`, except
+    /// the `protected` ones. Returns the ids of the deleted sessions.
+    pub fn cleanup_sessions(
+        namespace: &str,
+        older_than: Duration,
+        protected: &HashSet<String>,
+    ) -> std::io::Result<Vec<String>> {
+        SessionManager::new(namespace)?.cleanup_sessions(older_than, protected)
+    }
+
+    /// List the sessions stored in `namespace`, most recently used first
+    pub fn list_sessions(namespace: &str) -> std::io::Result<Vec<SessionFileInfo>> {
+        SessionManager::new(namespace)?.list_sessions()
+    }
+
+    /// Metadata and normalized transcript of a single stored session
+    pub fn read_session(
+        namespace: &str,
+        session_id: &str,
+    ) -> std::io::Result<Option<(SessionFileInfo, Vec<serde_json::Value>)>> {
+        let manager = SessionManager::new(namespace)?;
+        let Some(info) = manager.session_info(session_id)? else {
+            return Ok(None);
+        };
+        Ok(Some((info, manager.read_session_events(session_id)?)))
+    }
+
     /// Create a harness with a custom session namespace (e.g. for Qwen)
     pub fn with_session_namespace(namespace: impl Into<String>) -> Self {
         Self {
@@ -77,7 +110,11 @@ impl AcpAgentHarness {
         cmd_overrides: &CmdOverrides,
         approvals: Option<std::sync::Arc<dyn ExecutorApprovalService>>,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let (program_path, args) = command_parts.into_resolved().await?;
+        let profile_env = env.clone().with_profile(cmd_overrides).await?;
+        let (program_path, args) = command_parts
+            .expand_env(&profile_env, cmd_overrides)?
+            .into_resolved()
+            .await?;
         let mut command = Command::new(program_path);
         command
             .kill_on_drop(true)
@@ -89,30 +126,32 @@ impl AcpAgentHarness {
             .env("NPM_CONFIG_LOGLEVEL", "error")
             .env("NODE_NO_WARNINGS", "1");
 
-        env.clone()
-            .with_profile(cmd_overrides)
-            .apply_to_command(&mut command);
+        profile_env.apply_to_command(&mut command);
+        let redactor = Redactor::new(profile_env.secret_values(cmd_overrides));
 
         let mut child = command.group_spawn()?;
 
         let (exit_tx, exit_rx) = tokio::sync::oneshot::channel::<ExecutorExitResult>();
+        let (interrupt_tx, interrupt_rx) = tokio::sync::oneshot::channel::<()>();
         Self::bootstrap_acp_connection(
             &mut child,
             current_dir.to_path_buf(),
             None,
             prompt,
             Some(exit_tx),
+            interrupt_rx,
             self.session_namespace.clone(),
             self.model.clone(),
             self.mode.clone(),
             approvals,
+            redactor,
         )
         .await?;
 
         Ok(SpawnedChild {
             child,
             exit_signal: Some(exit_rx),
-            interrupt_sender: None,
+            interrupt_sender: Some(interrupt_tx),
         })
     }
 
@@ -127,7 +166,11 @@ impl AcpAgentHarness {
         cmd_overrides: &CmdOverrides,
         approvals: Option<std::sync::Arc<dyn ExecutorApprovalService>>,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let (program_path, args) = command_parts.into_resolved().await?;
+        let profile_env = env.clone().with_profile(cmd_overrides).await?;
+        let (program_path, args) = command_parts
+            .expand_env(&profile_env, cmd_overrides)?
+            .into_resolved()
+            .await?;
         let mut command = Command::new(program_path);
         command
             .kill_on_drop(true)
@@ -139,30 +182,32 @@ impl AcpAgentHarness {
             .env("NPM_CONFIG_LOGLEVEL", "error")
             .env("NODE_NO_WARNINGS", "1");
 
-        env.clone()
-            .with_profile(cmd_overrides)
-            .apply_to_command(&mut command);
+        profile_env.apply_to_command(&mut command);
+        let redactor = Redactor::new(profile_env.secret_values(cmd_overrides));
 
         let mut child = command.group_spawn()?;
 
         let (exit_tx, exit_rx) = tokio::sync::oneshot::channel::<ExecutorExitResult>();
+        let (interrupt_tx, interrupt_rx) = tokio::sync::oneshot::channel::<()>();
         Self::bootstrap_acp_connection(
             &mut child,
             current_dir.to_path_buf(),
             Some(session_id.to_string()),
             prompt,
             Some(exit_tx),
+            interrupt_rx,
             self.session_namespace.clone(),
             self.model.clone(),
             self.mode.clone(),
             approvals,
+            redactor,
         )
         .await?;
 
         Ok(SpawnedChild {
             child,
             exit_signal: Some(exit_rx),
-            interrupt_sender: None,
+            interrupt_sender: Some(interrupt_tx),
         })
     }
 
@@ -173,10 +218,12 @@ impl AcpAgentHarness {
         existing_session: Option<String>,
         prompt: String,
         exit_signal: Option<tokio::sync::oneshot::Sender<ExecutorExitResult>>,
+        interrupt_rx: tokio::sync::oneshot::Receiver<()>,
         session_namespace: String,
         model: Option<String>,
         mode: Option<String>,
         approvals: Option<std::sync::Arc<dyn ExecutorApprovalService>>,
+        redactor: Redactor,
     ) -> Result<(), ExecutorError> {
         // Take child's stdio for ACP wiring
         let orig_stdout = child.inner().stdout.take().ok_or_else(|| {
@@ -310,9 +357,10 @@ impl AcpAgentHarness {
                         });
 
                         // Initialize
-                        let _ = conn
+                        let supports_images = conn
                             .initialize(proto::InitializeRequest::new(proto::ProtocolVersion::V1))
-                            .await;
+                            .await
+                            .is_ok_and(|resp| resp.agent_capabilities.prompt_capabilities.image);
 
                         // Handle session creation/forking
                         let (acp_session_id, display_session_id, prompt_to_send) =
@@ -394,6 +442,7 @@ impl AcpAgentHarness {
                         let app_tx_clone = log_tx.clone();
                         let sess_id_for_writer = display_session_id.clone();
                         let sm_for_writer = session_manager.clone();
+                        let redactor_for_writer = redactor.clone();
                         let conn_for_cancel = conn.clone();
                         let acp_session_id_for_cancel = acp_session_id.clone();
                         tokio::task::spawn_local(async move {
@@ -413,7 +462,8 @@ impl AcpAgentHarness {
                                         .await;
                                 }
 
-                                let line = event.to_string();
+                                // Mask secrets before the event reaches the log or session file
+                                let line = redactor_for_writer.redact(&event.to_string());
                                 // Forward to stdout
                                 let _ = app_tx_clone.send(line.clone());
                                 // Persist to session file
@@ -424,24 +474,50 @@ impl AcpAgentHarness {
                         // Save prompt to session
                         let _ = session_manager.append_raw_line(
                             &display_session_id,
-                            &serde_json::to_string(&serde_json::json!({ "user": prompt_to_send }))
+                            &redactor.redact(
+                                &serde_json::to_string(
+                                    &serde_json::json!({ "user": prompt_to_send }),
+                                )
                                 .unwrap_or_default(),
+                            ),
                         );
 
                         // Build prompt request
                         let initial_req = proto::PromptRequest::new(
                             proto::SessionId::new(acp_session_id.clone()),
-                            vec![proto::ContentBlock::Text(proto::TextContent::new(
-                                prompt_to_send,
-                            ))],
+                            prompt_content_blocks(prompt_to_send, &cwd, supports_images),
                         );
 
                         let mut current_req = Some(initial_req);
+                        let mut interrupt_rx = interrupt_rx;
+                        let mut interrupt_rx_done = false;
+                        let mut interrupted = false;
 
                         while let Some(req) = current_req.take() {
                             tracing::trace!(?req, "sending ACP prompt request");
-                            // Send the prompt and await completion to obtain stop_reason
-                            match conn.prompt(req).await {
+                            // Send the prompt and await completion to obtain stop_reason.
+                            // On interrupt, cancel the in-flight turn and wait for the agent
+                            // to acknowledge so the session is left in a resumable state.
+                            let prompt_fut = conn.prompt(req);
+                            tokio::pin!(prompt_fut);
+                            let result = tokio::select! {
+                                res = &mut prompt_fut => res,
+                                signal = &mut interrupt_rx, if !interrupt_rx_done => {
+                                    interrupt_rx_done = true;
+                                    // A dropped sender is not an interrupt request
+                                    if signal.is_ok() {
+                                        interrupted = true;
+                                        tracing::debug!("Interrupting ACP prompt");
+                                        let _ = conn
+                                            .cancel(proto::CancelNotification::new(
+                                                proto::SessionId::new(acp_session_id.clone()),
+                                            ))
+                                            .await;
+                                    }
+                                    prompt_fut.await
+                                }
+                            };
+                            match result {
                                 Ok(resp) => {
                                     // Emit done with stop_reason
                                     let stop_reason = serde_json::to_string(&resp.stop_reason)
@@ -464,6 +540,10 @@ impl AcpAgentHarness {
                                 }
                             }
 
+                            if interrupted {
+                                break;
+                            }
+
                             // Flush any pending user feedback after finish
                             let feedback = client_feedback_handle
                                 .drain_feedback()
@@ -509,3 +589,128 @@ impl AcpAgentHarness {
         Ok(())
     }
 }
+
+/// Build the content blocks for a prompt. Images referenced from the
+/// worktree's `.vibe-images` directory are attached as image blocks when the
+/// agent accepts them; otherwise their filenames are listed in a text block.
+fn prompt_content_blocks(
+    prompt: String,
+    cwd: &Path,
+    supports_images: bool,
+) -> Vec<proto::ContentBlock> {
+    let image_paths = referenced_image_paths(&prompt);
+    let mut blocks = vec![proto::ContentBlock::Text(proto::TextContent::new(prompt))];
+    let mut not_attached = Vec::new();
+
+    for rel_path in image_paths {
+        let image = supports_images
+            .then(|| image_mime_type(&rel_path))
+            .flatten()
+            .and_then(|mime| match std::fs::read(cwd.join(&rel_path)) {
+                Ok(bytes) => Some(proto::ImageContent::new(BASE64.encode(bytes), mime)),
+                Err(err) => {
+                    tracing::warn!("Failed to read prompt image {}: {}", rel_path, err);
+                    None
+                }
+            });
+        match image {
+            Some(image) => blocks.push(proto::ContentBlock::Image(image)),
+            None => not_attached.push(rel_path),
+        }
+    }
+
+    if !not_attached.is_empty() {
+        blocks.push(proto::ContentBlock::Text(proto::TextContent::new(format!(
+            "[Attached images not sent to the agent: {}]",
+            not_attached.join(", ")
+        ))));
+    }
+    blocks
+}
+
+/// Relative paths of `.vibe-images` files referenced as markdown images, in
+/// order. Paths that could leave the directory, through `..` or otherwise,
+/// are dropped so the prompt cannot pull in other files from the machine.
+fn referenced_image_paths(prompt: &str) -> Vec<String> {
+    let marker = format!("]({VIBE_IMAGES_DIR}/");
+    let mut paths: Vec<String> = Vec::new();
+    let mut rest = prompt;
+
+    while let Some(start) = rest.find(&marker) {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find(')') else {
+            break;
+        };
+        let path = &after[..end];
+        let stays_inside = Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if stays_inside && !path.contains(char::is_whitespace) && !paths.iter().any(|p| p == path) {
+            paths.push(path.to_string());
+        }
+        rest = &after[end..];
+    }
+    paths
+}
+
+fn image_mime_type(path: &str) -> Option<&'static str> {
+    let ext = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROMPT: &str = "Fix the layout\n\n![shot](.vibe-images/a.png) and ![again](.vibe-images/a.png)\n![notes](.vibe-images/b.txt)";
+
+    #[test]
+    fn finds_referenced_images_once() {
+        assert_eq!(
+            referenced_image_paths(PROMPT),
+            vec![".vibe-images/a.png", ".vibe-images/b.txt"]
+        );
+    }
+
+    #[test]
+    fn ignores_images_outside_the_images_directory() {
+        let prompt = "![key](.vibe-images/../../.ssh/id_rsa.png) \
+            ![nested](.vibe-images/x/../../secret.png) ![ok](.vibe-images/shots/b.png)";
+        assert_eq!(
+            referenced_image_paths(prompt),
+            vec![".vibe-images/shots/b.png"]
+        );
+    }
+
+    #[test]
+    fn attaches_images_only_when_supported() {
+        let cwd = std::env::temp_dir().join(format!("vk-acp-images-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(cwd.join(VIBE_IMAGES_DIR)).unwrap();
+        std::fs::write(cwd.join(".vibe-images/a.png"), [0x89, b'P', b'N', b'G']).unwrap();
+
+        let with_images = prompt_content_blocks(PROMPT.to_string(), &cwd, true);
+        let without_images = prompt_content_blocks(PROMPT.to_string(), &cwd, false);
+        std::fs::remove_dir_all(&cwd).unwrap();
+
+        assert!(matches!(
+            &with_images[1],
+            proto::ContentBlock::Image(image) if image.mime_type == "image/png"
+        ));
+        assert!(matches!(
+            with_images.last(),
+            Some(proto::ContentBlock::Text(text)) if text.text.contains(".vibe-images/b.txt")
+        ));
+        assert_eq!(without_images.len(), 2);
+        assert!(matches!(
+            &without_images[1],
+            proto::ContentBlock::Text(text)
+                if text.text.contains(".vibe-images/a.png") && text.text.contains(".vibe-images/b.txt")
+        ));
+    }
+}
@@ -9,9 +9,14 @@ pub use client::AcpClient;
 pub use harness::AcpAgentHarness;
 pub use normalize_logs::*;
 use serde::{Deserialize, Serialize};
-pub use session::SessionManager;
+pub use session::{SessionFileInfo, SessionManager};
 use workspace_utils::approvals::ApprovalStatus;
 
+/// Session namespaces used by the built-in ACP executors
+pub const GEMINI_SESSION_NAMESPACE: &str = "gemini_sessions";
+pub const QWEN_SESSION_NAMESPACE: &str = "qwen_sessions";
+pub const SESSION_NAMESPACES: &[&str] = &[GEMINI_SESSION_NAMESPACE, QWEN_SESSION_NAMESPACE];
+
 /// Parsed event types for internal processing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AcpEvent {
@@ -1,11 +1,14 @@
 use std::{
+    collections::HashSet,
     fs::{self, OpenOptions},
     io::{self, Result, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     str::FromStr,
+    time::{Duration, SystemTime},
 };
 
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
 use crate::executors::acp::AcpEvent;
 
@@ -26,10 +29,11 @@ impl SessionManager {
             vk_dir = vk_dir.join("dev");
         }
 
-        let base_dir = vk_dir.join(&namespace);
+        Self::with_base_dir(vk_dir.join(&namespace))
+    }
 
+    fn with_base_dir(base_dir: PathBuf) -> Result<Self> {
         fs::create_dir_all(&base_dir)?;
-
         Ok(Self { base_dir })
     }
 
@@ -148,6 +152,82 @@ impl SessionManager {
         Ok(())
     }
 
+    /// List stored sessions, most recently modified first
+    pub fn list_sessions(&self) -> Result<Vec<SessionFileInfo>> {
+        let mut sessions = Vec::new();
+        for entry in fs::read_dir(&self.base_dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "jsonl") {
+                continue;
+            }
+            let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            sessions.push(Self::file_info(session_id, &path)?);
+        }
+        sessions.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+        Ok(sessions)
+    }
+
+    /// Metadata of a single stored session, if it exists
+    pub fn session_info(&self, session_id: &str) -> Result<Option<SessionFileInfo>> {
+        let path = self.session_file_path(session_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::file_info(session_id, &path).map(Some)
+    }
+
+    fn file_info(session_id: &str, path: &Path) -> Result<SessionFileInfo> {
+        let metadata = fs::metadata(path)?;
+        Ok(SessionFileInfo {
+            session_id: session_id.to_string(),
+            size_bytes: metadata.len(),
+            // Creation time is not available on every filesystem
+            created_at: metadata.created().ok().map(Into::into),
+            modified_at: metadata.modified()?.into(),
+        })
+    }
+
+    /// Read the stored (already normalized) events of a session, skipping
+    /// lines that are not valid JSON
+    pub fn read_session_events(&self, session_id: &str) -> Result<Vec<serde_json::Value>> {
+        Ok(self
+            .read_session_raw(session_id)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Delete sessions not modified within `older_than`, except those listed in
+    /// `protected`. Returns the ids of the deleted sessions.
+    pub fn cleanup_sessions(
+        &self,
+        older_than: Duration,
+        protected: &HashSet<String>,
+    ) -> Result<Vec<String>> {
+        let cutoff = SystemTime::now()
+            .checked_sub(older_than)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut deleted = Vec::new();
+
+        for session in self.list_sessions()? {
+            if protected.contains(&session.session_id)
+                || SystemTime::from(session.modified_at) >= cutoff
+            {
+                continue;
+            }
+            self.delete_session(&session.session_id)?;
+            tracing::info!(
+                "Deleted stale ACP session {} from {}",
+                session.session_id,
+                self.base_dir.display()
+            );
+            deleted.push(session.session_id);
+        }
+        Ok(deleted)
+    }
+
     /// Generate a resume prompt from session history
     pub fn generate_resume_prompt(&self, session_id: &str, current_prompt: &str) -> Result<String> {
         let session_context = self.read_session_raw(session_id)?;
@@ -170,6 +250,15 @@ impl SessionManager {
     }
 }
 
+/// A stored session file
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SessionFileInfo {
+    pub session_id: String,
+    pub size_bytes: u64,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub modified_at: chrono::DateTime<chrono::Utc>,
+}
+
 /// Session metadata stored separately from events
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionMetadata {
@@ -179,3 +268,59 @@ pub struct SessionMetadata {
     pub parent_session: Option<String>,
     pub tags: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_session(manager: &SessionManager, id: &str, age: Duration) {
+        let path = manager.session_file_path(id);
+        fs::write(&path, "{\"user\":\"hi\"}\n").unwrap();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    #[test]
+    fn cleanup_removes_only_old_unprotected_sessions() {
+        let dir = std::env::temp_dir().join(format!("vk-acp-sessions-{}", uuid::Uuid::new_v4()));
+        let manager = SessionManager::with_base_dir(dir.clone()).unwrap();
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        write_session(&manager, "fresh", Duration::ZERO);
+        write_session(&manager, "old", 10 * day);
+        write_session(&manager, "old-protected", 10 * day);
+        fs::write(dir.join("notes.txt"), "not a session").unwrap();
+
+        let protected = HashSet::from(["old-protected".to_string()]);
+        let deleted = manager.cleanup_sessions(7 * day, &protected).unwrap();
+        let remaining: Vec<_> = manager
+            .list_sessions()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.session_id)
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(deleted, vec!["old"]);
+        assert_eq!(remaining, vec!["fresh", "old-protected"]);
+    }
+
+    #[test]
+    fn reads_session_info_and_events() {
+        let dir = std::env::temp_dir().join(format!("vk-acp-sessions-{}", uuid::Uuid::new_v4()));
+        let manager = SessionManager::with_base_dir(dir.clone()).unwrap();
+
+        manager
+            .append_raw_line("abc", r#"{"User":"hello"}"#)
+            .unwrap();
+        let info = manager.session_info("abc").unwrap().unwrap();
+        let events = manager.read_session_events("abc").unwrap();
+        let missing = manager.session_info("missing").unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(info.session_id, "abc");
+        assert!(info.size_bytes > 0);
+        assert_eq!(events, vec![serde_json::json!({"user": "hello"})]);
+        assert!(missing.is_none());
+    }
+}
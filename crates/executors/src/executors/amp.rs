@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use command_group::AsyncCommandGroup;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::msg_store::MsgStore;
 
@@ -52,9 +52,13 @@ impl StandardCodingAgentExecutor for Amp {
         env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let command_parts = self.build_command_builder()?.build_initial()?;
-        let (executable_path, args) = command_parts.into_resolved().await?;
+        let profile_env = env.clone().with_profile(&self.cmd).await?;
+        let (executable_path, args) = command_parts
+            .expand_env(&profile_env, &self.cmd)?
+            .into_resolved()
+            .await?;
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
 
         let mut command = Command::new(executable_path);
         command
@@ -65,17 +69,12 @@ impl StandardCodingAgentExecutor for Amp {
             .current_dir(current_dir)
             .args(&args);
 
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+        profile_env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
         // Feed the prompt in, then close the pipe so amp sees EOF
-        if let Some(mut stdin) = child.inner().stdin.take() {
-            stdin.write_all(combined_prompt.as_bytes()).await?;
-            stdin.shutdown().await?;
-        }
+        crate::stdin::spawn_prompt_writer(&mut child, combined_prompt);
 
         Ok(child.into())
     }
@@ -94,7 +93,11 @@ impl StandardCodingAgentExecutor for Amp {
             "fork".to_string(),
             session_id.to_string(),
         ])?;
-        let (fork_program, fork_args) = fork_line.into_resolved().await?;
+        let profile_env = env.clone().with_profile(&self.cmd).await?;
+        let (fork_program, fork_args) = fork_line
+            .expand_env(&profile_env, &self.cmd)?
+            .into_resolved()
+            .await?;
         let fork_output = Command::new(fork_program)
             .kill_on_drop(true)
             .stdout(Stdio::piped())
@@ -125,9 +128,12 @@ impl StandardCodingAgentExecutor for Amp {
             "continue".to_string(),
             new_thread_id.clone(),
         ])?;
-        let (continue_program, continue_args) = continue_line.into_resolved().await?;
+        let (continue_program, continue_args) = continue_line
+            .expand_env(&profile_env, &self.cmd)?
+            .into_resolved()
+            .await?;
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
 
         let mut command = Command::new(continue_program);
         command
@@ -138,17 +144,12 @@ impl StandardCodingAgentExecutor for Amp {
             .current_dir(current_dir)
             .args(&continue_args);
 
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+        profile_env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
         // Feed the prompt in, then close the pipe so amp sees EOF
-        if let Some(mut stdin) = child.inner().stdin.take() {
-            stdin.write_all(combined_prompt.as_bytes()).await?;
-            stdin.shutdown().await?;
-        }
+        crate::stdin::spawn_prompt_writer(&mut child, combined_prompt);
 
         Ok(child.into())
     }
@@ -252,8 +252,12 @@ impl ClaudeCode {
         command_parts: CommandParts,
         env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let (program_path, args) = command_parts.into_resolved().await?;
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let profile_env = env.clone().with_profile(&self.cmd).await?;
+        let (program_path, args) = command_parts
+            .expand_env(&profile_env, &self.cmd)?
+            .into_resolved()
+            .await?;
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
 
         let mut command = Command::new(program_path);
         command
@@ -264,9 +268,7 @@ impl ClaudeCode {
             .current_dir(current_dir)
             .args(&args);
 
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+        profile_env.apply_to_command(&mut command);
 
         // Remove ANTHROPIC_API_KEY if disable_api_key is enabled
         if self.disable_api_key.unwrap_or(false) {
@@ -2147,6 +2149,12 @@ mod tests {
                 base_command_override: None,
                 additional_params: None,
                 env: None,
+                env_file: None,
+                env_remove: None,
+                env_clear: None,
+                path_prepend: None,
+                redact: None,
+                output_limit_bytes: None,
             },
             approvals_service: None,
             disable_api_key: None,
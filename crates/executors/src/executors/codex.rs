@@ -175,7 +175,7 @@ impl StandardCodingAgentExecutor for Codex {
         env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let command_parts = self.build_command_builder()?.build_initial()?;
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
         let action = CodexSessionAction::Chat {
             prompt: combined_prompt,
         };
@@ -191,7 +191,7 @@ impl StandardCodingAgentExecutor for Codex {
         env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let command_parts = self.build_command_builder()?.build_follow_up(&[])?;
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
         let action = CodexSessionAction::Chat {
             prompt: combined_prompt,
         };
@@ -345,7 +345,11 @@ impl Codex {
         resume_session: Option<&str>,
         env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let (program_path, args) = command_parts.into_resolved().await?;
+        let profile_env = env.clone().with_profile(&self.cmd).await?;
+        let (program_path, args) = command_parts
+            .expand_env(&profile_env, &self.cmd)?
+            .into_resolved()
+            .await?;
 
         let mut process = Command::new(program_path);
         process
@@ -359,9 +363,7 @@ impl Codex {
             .env("NO_COLOR", "1")
             .env("RUST_LOG", "error");
 
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut process);
+        profile_env.apply_to_command(&mut process);
 
         let mut child = process.group_spawn()?;
 
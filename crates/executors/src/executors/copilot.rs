@@ -13,7 +13,6 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tokio::{
     fs,
-    io::AsyncWriteExt,
     process::Command,
     time::{interval, timeout},
 };
@@ -108,9 +107,13 @@ impl StandardCodingAgentExecutor for Copilot {
         let command_parts = self
             .build_command_builder(&log_dir.to_string_lossy())?
             .build_initial()?;
-        let (program_path, args) = command_parts.into_resolved().await?;
+        let profile_env = env.clone().with_profile(&self.cmd).await?;
+        let (program_path, args) = command_parts
+            .expand_env(&profile_env, &self.cmd)?
+            .into_resolved()
+            .await?;
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
 
         let mut command = Command::new(program_path);
         command
@@ -122,17 +125,12 @@ impl StandardCodingAgentExecutor for Copilot {
             .args(&args)
             .env("NODE_NO_WARNINGS", "1");
 
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+        profile_env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
         // Write prompt to stdin
-        if let Some(mut stdin) = child.inner().stdin.take() {
-            stdin.write_all(combined_prompt.as_bytes()).await?;
-            stdin.shutdown().await?;
-        }
+        crate::stdin::spawn_prompt_writer(&mut child, combined_prompt);
 
         let (_, appender) = stdout_dup::tee_stdout_with_appender(&mut child)?;
         Self::send_session_id(log_dir, appender);
@@ -151,9 +149,13 @@ impl StandardCodingAgentExecutor for Copilot {
         let command_parts = self
             .build_command_builder(&log_dir.to_string_lossy())?
             .build_follow_up(&["--resume".to_string(), session_id.to_string()])?;
-        let (program_path, args) = command_parts.into_resolved().await?;
+        let profile_env = env.clone().with_profile(&self.cmd).await?;
+        let (program_path, args) = command_parts
+            .expand_env(&profile_env, &self.cmd)?
+            .into_resolved()
+            .await?;
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
 
         let mut command = Command::new(program_path);
 
@@ -166,17 +168,12 @@ impl StandardCodingAgentExecutor for Copilot {
             .args(&args)
             .env("NODE_NO_WARNINGS", "1");
 
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+        profile_env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
         // Write comprehensive prompt to stdin
-        if let Some(mut stdin) = child.inner().stdin.take() {
-            stdin.write_all(combined_prompt.as_bytes()).await?;
-            stdin.shutdown().await?;
-        }
+        crate::stdin::spawn_prompt_writer(&mut child, combined_prompt);
 
         let (_, appender) = stdout_dup::tee_stdout_with_appender(&mut child)?;
         Self::send_session_id(log_dir, appender);
@@ -6,7 +6,7 @@ use command_group::AsyncCommandGroup;
 use futures::StreamExt;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::{
     diff::{create_unified_diff, normalize_unified_diff},
@@ -81,9 +81,13 @@ impl StandardCodingAgentExecutor for CursorAgent {
 
         let command_parts = self.build_command_builder()?.build_initial()?;
 
-        let (executable_path, args) = command_parts.into_resolved().await?;
+        let profile_env = env.clone().with_profile(&self.cmd).await?;
+        let (executable_path, args) = command_parts
+            .expand_env(&profile_env, &self.cmd)?
+            .into_resolved()
+            .await?;
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
 
         let mut command = Command::new(executable_path);
         command
@@ -94,16 +98,11 @@ impl StandardCodingAgentExecutor for CursorAgent {
             .current_dir(current_dir)
             .args(&args);
 
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+        profile_env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
-        if let Some(mut stdin) = child.inner().stdin.take() {
-            stdin.write_all(combined_prompt.as_bytes()).await?;
-            stdin.shutdown().await?;
-        }
+        crate::stdin::spawn_prompt_writer(&mut child, combined_prompt);
 
         Ok(child.into())
     }
@@ -120,9 +119,13 @@ impl StandardCodingAgentExecutor for CursorAgent {
         let command_parts = self
             .build_command_builder()?
             .build_follow_up(&["--resume".to_string(), session_id.to_string()])?;
-        let (executable_path, args) = command_parts.into_resolved().await?;
+        let profile_env = env.clone().with_profile(&self.cmd).await?;
+        let (executable_path, args) = command_parts
+            .expand_env(&profile_env, &self.cmd)?
+            .into_resolved()
+            .await?;
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
 
         let mut command = Command::new(executable_path);
         command
@@ -133,16 +136,11 @@ impl StandardCodingAgentExecutor for CursorAgent {
             .current_dir(current_dir)
             .args(&args);
 
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+        profile_env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
-        if let Some(mut stdin) = child.inner().stdin.take() {
-            stdin.write_all(combined_prompt.as_bytes()).await?;
-            stdin.shutdown().await?;
-        }
+        crate::stdin::spawn_prompt_writer(&mut child, combined_prompt);
 
         Ok(child.into())
     }
@@ -5,7 +5,7 @@ use command_group::AsyncCommandGroup;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum_macros::AsRefStr;
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::msg_store::MsgStore;
 
@@ -112,7 +112,11 @@ async fn spawn_droid(
     env: &ExecutionEnv,
     cmd_overrides: &crate::command::CmdOverrides,
 ) -> Result<SpawnedChild, ExecutorError> {
-    let (program_path, args) = command_parts.into_resolved().await?;
+    let profile_env = env.clone().with_profile(cmd_overrides).await?;
+    let (program_path, args) = command_parts
+        .expand_env(&profile_env, cmd_overrides)?
+        .into_resolved()
+        .await?;
 
     let mut command = Command::new(program_path);
     command
@@ -123,16 +127,11 @@ async fn spawn_droid(
         .current_dir(current_dir)
         .args(args);
 
-    env.clone()
-        .with_profile(cmd_overrides)
-        .apply_to_command(&mut command);
+    profile_env.apply_to_command(&mut command);
 
     let mut child = command.group_spawn()?;
 
-    if let Some(mut stdin) = child.inner().stdin.take() {
-        stdin.write_all(prompt.as_bytes()).await?;
-        stdin.shutdown().await?;
-    }
+    crate::stdin::spawn_prompt_writer(&mut child, prompt.clone());
 
     Ok(child.into())
 }
@@ -146,7 +145,7 @@ impl StandardCodingAgentExecutor for Droid {
         env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let droid_command = self.build_command_builder()?.build_initial()?;
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
 
         spawn_droid(droid_command, &combined_prompt, current_dir, env, &self.cmd).await
     }
@@ -166,7 +165,7 @@ impl StandardCodingAgentExecutor for Droid {
         let continue_cmd = self
             .build_command_builder()?
             .build_follow_up(&["--session-id".to_string(), forked_session_id.clone()])?;
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
 
         spawn_droid(continue_cmd, &combined_prompt, current_dir, env, &self.cmd).await
     }
@@ -1,4 +1,7 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use command_group::AsyncGroupChild;
@@ -17,12 +20,13 @@ use crate::executors::qa_mock::QaMockExecutor;
 use crate::{
     actions::{ExecutorAction, review::RepoReviewContext},
     approvals::ExecutorApprovalService,
-    command::CommandBuildError,
+    command::{CmdOverrides, CommandBuildError},
     env::ExecutionEnv,
     executors::{
         amp::Amp, claude::ClaudeCode, codex::Codex, copilot::Copilot, cursor::CursorAgent,
         droid::Droid, gemini::Gemini, opencode::Opencode, qwen::QwenCode,
     },
+    logs::summary::ExecutionSummary,
     mcp_config::McpConfig,
 };
 
@@ -53,7 +57,11 @@ pub enum ExecutorError {
     #[error("Follow-up is not supported: {0}")]
     FollowUpNotSupported(String),
     #[error(transparent)]
-    SpawnError(#[from] FuturesIoError),
+    SpawnFailed { source: FuturesIoError },
+    #[error("Permission denied: {0}")]
+    PermissionDenied(FuturesIoError),
+    #[error("Invalid `{field}` in executor profile: {reason}")]
+    InvalidConfig { field: String, reason: String },
     #[error("Unknown executor type: {0}")]
     UnknownExecutorType(String),
     #[error("I/O error: {0}")]
@@ -67,15 +75,86 @@ pub enum ExecutorError {
     #[error(transparent)]
     ExecutorApprovalError(#[from] crate::approvals::ExecutorApprovalError),
     #[error(transparent)]
-    CommandBuild(#[from] CommandBuildError),
+    CommandBuild(CommandBuildError),
+    #[error(transparent)]
+    EnvFile(#[from] crate::env::EnvFileError),
     #[error("Executable `{program}` not found in PATH")]
-    ExecutableNotFound { program: String },
+    ExecutableNotFound {
+        program: String,
+        searched_paths: Vec<PathBuf>,
+    },
     #[error("Setup helper not supported")]
     SetupHelperNotSupported,
     #[error("Auth required: {0}")]
     AuthRequired(String),
 }
 
+/// Machine-readable category of an [`ExecutorError`], so clients can offer
+/// targeted remediation ("install the tool" vs "fix profiles.json")
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutorErrorKind {
+    InvalidConfig,
+    CommandNotFound,
+    PermissionDenied,
+    AuthRequired,
+    SpawnFailed,
+    Other,
+}
+
+impl ExecutorError {
+    /// `program` could not be found; records the PATH that was searched
+    pub fn executable_not_found(program: impl Into<String>) -> Self {
+        Self::ExecutableNotFound {
+            program: program.into(),
+            searched_paths: std::env::var_os("PATH")
+                .map(|path| std::env::split_paths(&path).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn kind(&self) -> ExecutorErrorKind {
+        match self {
+            Self::InvalidConfig { .. }
+            | Self::CommandBuild(_)
+            | Self::EnvFile(_)
+            | Self::UnknownExecutorType(_) => ExecutorErrorKind::InvalidConfig,
+            Self::ExecutableNotFound { .. } => ExecutorErrorKind::CommandNotFound,
+            Self::PermissionDenied(_) => ExecutorErrorKind::PermissionDenied,
+            Self::AuthRequired(_) => ExecutorErrorKind::AuthRequired,
+            Self::SpawnFailed { .. } => ExecutorErrorKind::SpawnFailed,
+            _ => ExecutorErrorKind::Other,
+        }
+    }
+}
+
+impl From<FuturesIoError> for ExecutorError {
+    fn from(err: FuturesIoError) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::PermissionDenied => Self::PermissionDenied(err),
+            _ => Self::SpawnFailed { source: err },
+        }
+    }
+}
+
+impl From<CommandBuildError> for ExecutorError {
+    fn from(err: CommandBuildError) -> Self {
+        // Built-in commands always parse, so parse failures point at the
+        // profile's command overrides
+        let field = match &err {
+            CommandBuildError::InvalidBase(_)
+            | CommandBuildError::UnbalancedQuote { .. }
+            | CommandBuildError::EmptyCommand => "base_command_override",
+            CommandBuildError::InvalidShellParams(_) => "additional_params",
+            _ => return Self::CommandBuild(err),
+        };
+        Self::InvalidConfig {
+            field: field.to_string(),
+            reason: err.to_string(),
+        }
+    }
+}
+
 #[enum_dispatch]
 #[derive(
     Debug, Clone, Serialize, Deserialize, PartialEq, TS, Display, EnumDiscriminants, VariantNames,
@@ -155,6 +234,23 @@ impl CodingAgent {
         }
     }
 
+    /// Command overrides of this profile
+    pub fn cmd_overrides(&self) -> Option<&CmdOverrides> {
+        match self {
+            Self::ClaudeCode(agent) => Some(&agent.cmd),
+            Self::Amp(agent) => Some(&agent.cmd),
+            Self::Gemini(agent) => Some(&agent.cmd),
+            Self::Codex(agent) => Some(&agent.cmd),
+            Self::Opencode(agent) => Some(&agent.cmd),
+            Self::CursorAgent(agent) => Some(&agent.cmd),
+            Self::QwenCode(agent) => Some(&agent.cmd),
+            Self::Copilot(agent) => Some(&agent.cmd),
+            Self::Droid(agent) => Some(&agent.cmd),
+            #[cfg(feature = "qa-mode")]
+            Self::QaMock(_) => None,
+        }
+    }
+
     pub fn supports_mcp(&self) -> bool {
         self.default_mcp_config_path().is_some()
     }
@@ -231,6 +327,11 @@ pub trait StandardCodingAgentExecutor {
 
     fn normalize_logs(&self, _raw_logs_event_store: Arc<MsgStore>, _worktree_path: &Path);
 
+    /// Summary of a finished run, read from the normalized entries in `msg_store`
+    fn summarize(&self, msg_store: Arc<MsgStore>) -> ExecutionSummary {
+        ExecutionSummary::from_msg_store(&msg_store)
+    }
+
     // MCP configuration methods
     fn default_mcp_config_path(&self) -> Option<std::path::PathBuf>;
 
@@ -279,6 +380,23 @@ pub struct SpawnedChild {
     pub interrupt_sender: Option<InterruptSender>,
 }
 
+impl SpawnedChild {
+    /// OS identifiers of the spawned process, while it has not been reaped yet.
+    pub fn process_ids(&mut self) -> Option<ProcessIds> {
+        let pid = self.child.inner().id()?;
+        // group_spawn makes the child the leader of a fresh process group on Unix
+        let pgid = cfg!(unix).then_some(pid);
+        Some(ProcessIds { pid, pgid })
+    }
+}
+
+/// PID and process-group ID of a running execution
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, TS)]
+pub struct ProcessIds {
+    pub pid: u32,
+    pub pgid: Option<u32>,
+}
+
 impl From<AsyncGroupChild> for SpawnedChild {
     fn from(child: AsyncGroupChild) -> Self {
         Self {
@@ -293,7 +411,7 @@ impl From<AsyncGroupChild> for SpawnedChild {
 #[serde(transparent)]
 #[schemars(
     title = "Append Prompt",
-    description = "Extra text appended to the prompt",
+    description = "Extra text appended to the prompt. Supports {task_title}, {task_id}, {project_name}, {worktree}, {branch} and {scratch}; use {{ and }} for literal braces",
     extend("format" = "textarea")
 )]
 #[derive(Default)]
@@ -304,14 +422,59 @@ impl AppendPrompt {
         self.0.clone()
     }
 
-    pub fn combine_prompt(&self, prompt: &str) -> String {
+    pub fn combine_prompt(&self, prompt: &str, env: &ExecutionEnv) -> String {
         match self {
-            AppendPrompt(Some(value)) => format!("{prompt}{value}"),
+            AppendPrompt(Some(value)) => {
+                format!("{prompt}{}", render_prompt_variables(value, env))
+            }
             AppendPrompt(None) => prompt.to_string(),
         }
     }
 }
 
+/// Substitute `{variable}` placeholders in user-configured prompt text with values
+/// from the execution environment. Unknown variables are left verbatim, `{{`
+/// escapes to a literal `{` and `}}` to a literal `}`.
+pub fn render_prompt_variables(template: &str, env: &ExecutionEnv) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        rendered.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if let Some(after_escape) = tail.strip_prefix("{{") {
+            rendered.push('{');
+            rest = after_escape;
+            continue;
+        }
+        if let Some(after_brace) = tail.strip_prefix('}') {
+            rendered.push('}');
+            rest = after_brace.strip_prefix('}').unwrap_or(after_brace);
+            continue;
+        }
+
+        let substitution = tail[1..].find('}').and_then(|end| {
+            env.prompt_variable(&tail[1..1 + end])
+                .map(|value| (value, end + 2))
+        });
+
+        match substitution {
+            Some((value, consumed)) => {
+                rendered.push_str(&value);
+                rest = &tail[consumed..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
 pub fn build_review_prompt(
     context: Option<&[RepoReviewContext]>,
     additional_prompt: Option<&str>,
@@ -342,9 +505,57 @@ pub fn build_review_prompt(
 
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
+    use std::{path::PathBuf, str::FromStr};
 
     use super::*;
+    use crate::env::RepoContext;
+
+    fn prompt_env() -> ExecutionEnv {
+        let mut env = ExecutionEnv::new(
+            RepoContext::new(PathBuf::from("/tmp/worktrees/abc"), vec![]),
+            false,
+        );
+        env.insert("VK_TASK_TITLE", "Fix login");
+        env.insert("VK_TASK_ID", "task-1");
+        env.insert("VK_PROJECT_NAME", "kanban");
+        env.insert("VK_WORKSPACE_BRANCH", "vk/fix-login");
+        env
+    }
+
+    #[test]
+    fn append_prompt_substitutes_known_variables() {
+        let append = AppendPrompt(Some(
+            "\nTask {task_title} ({task_id}) in {project_name} on {branch}; edit {worktree}/src"
+                .to_string(),
+        ));
+
+        assert_eq!(
+            append.combine_prompt("Do it", &prompt_env()),
+            "Do it\nTask Fix login (task-1) in kanban on vk/fix-login; edit /tmp/worktrees/abc/src"
+        );
+    }
+
+    #[test]
+    fn append_prompt_keeps_unknown_variables_and_unescapes_braces() {
+        let env = prompt_env();
+
+        assert_eq!(
+            render_prompt_variables("{unknown} {task_id", &env),
+            "{unknown} {task_id"
+        );
+        assert_eq!(
+            render_prompt_variables("{{task_id}} {{}", &env),
+            "{task_id} {}"
+        );
+        assert_eq!(
+            render_prompt_variables("{{{task_id}}} }}}", &env),
+            "{task-1} }}"
+        );
+        assert_eq!(
+            AppendPrompt(None).combine_prompt("{task_id}", &env),
+            "{task_id}"
+        );
+    }
 
     #[test]
     fn test_cursor_agent_deserialization() {
@@ -371,4 +582,78 @@ mod tests {
         assert!(result.is_ok(), "CURSOR should deserialize via serde");
         assert_eq!(result.unwrap(), BaseCodingAgent::CursorAgent);
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawned_child_records_pid_and_group() {
+        use command_group::AsyncCommandGroup;
+
+        let child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .kill_on_drop(true)
+            .group_spawn()
+            .unwrap();
+        let mut spawned = SpawnedChild::from(child);
+        let ids = spawned.process_ids().unwrap();
+
+        let ps = std::process::Command::new("ps")
+            .args(["-o", "pgid=", "-p", &ids.pid.to_string()])
+            .output()
+            .unwrap();
+        let real_pgid: u32 = String::from_utf8_lossy(&ps.stdout).trim().parse().unwrap();
+
+        assert_eq!(Some(ids.pid), spawned.child.inner().id());
+        assert_eq!(ids.pgid, Some(real_pgid));
+
+        spawned.child.kill().await.unwrap();
+        spawned.child.wait().await.unwrap();
+        assert_eq!(spawned.process_ids(), None);
+    }
+
+    #[test]
+    fn classifies_failures_by_kind() {
+        let denied =
+            ExecutorError::from(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert_eq!(denied.kind(), ExecutorErrorKind::PermissionDenied);
+
+        let broken_pipe = ExecutorError::from(std::io::Error::from(std::io::ErrorKind::BrokenPipe));
+        assert_eq!(broken_pipe.kind(), ExecutorErrorKind::SpawnFailed);
+
+        let bad_base = ExecutorError::from(CommandBuildError::UnbalancedQuote {
+            input: "agent 'x".to_string(),
+            quote: '\'',
+            column: 7,
+        });
+        assert!(
+            matches!(&bad_base, ExecutorError::InvalidConfig { field, .. } if field == "base_command_override")
+        );
+        assert_eq!(bad_base.kind(), ExecutorErrorKind::InvalidConfig);
+
+        let bad_params = ExecutorError::from(CommandBuildError::InvalidShellParams("'".into()));
+        assert!(
+            matches!(&bad_params, ExecutorError::InvalidConfig { field, .. } if field == "additional_params")
+        );
+
+        let unset = ExecutorError::from(CommandBuildError::UndefinedVariable("X".into()));
+        assert!(matches!(unset, ExecutorError::CommandBuild(_)));
+        assert_eq!(unset.kind(), ExecutorErrorKind::InvalidConfig);
+
+        assert_eq!(
+            ExecutorError::AuthRequired("login".into()).kind(),
+            ExecutorErrorKind::AuthRequired
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_program_reports_command_not_found() {
+        let err = crate::command::CommandParts::new("vk-surely-missing-program".into(), vec![])
+            .into_resolved()
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.kind(), ExecutorErrorKind::CommandNotFound);
+        assert!(
+            matches!(err, ExecutorError::ExecutableNotFound { program, .. } if program == "vk-surely-missing-program")
+        );
+    }
 }
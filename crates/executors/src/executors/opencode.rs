@@ -62,10 +62,14 @@ impl Opencode {
         resume_session: Option<&str>,
         env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
 
         let command_parts = self.build_command_builder()?.build_initial()?;
-        let (program_path, args) = command_parts.into_resolved().await?;
+        let profile_env = env.clone().with_profile(&self.cmd).await?;
+        let (program_path, args) = command_parts
+            .expand_env(&profile_env, &self.cmd)?
+            .into_resolved()
+            .await?;
 
         let mut command = Command::new(program_path);
         command
@@ -78,9 +82,7 @@ impl Opencode {
             .env("NODE_NO_WARNINGS", "1")
             .env("NO_COLOR", "1");
 
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+        profile_env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
         let server_stdout = child.inner().stdout.take().ok_or_else(|| {
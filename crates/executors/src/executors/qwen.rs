@@ -13,7 +13,7 @@ use crate::{
     env::ExecutionEnv,
     executors::{
         AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
-        gemini::AcpAgentHarness,
+        acp::QWEN_SESSION_NAMESPACE, gemini::AcpAgentHarness,
     },
 };
 
@@ -57,8 +57,8 @@ impl StandardCodingAgentExecutor for QwenCode {
         env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let qwen_command = self.build_command_builder()?.build_initial()?;
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
-        let harness = AcpAgentHarness::with_session_namespace("qwen_sessions");
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
+        let harness = AcpAgentHarness::with_session_namespace(QWEN_SESSION_NAMESPACE);
         let approvals = if self.yolo.unwrap_or(false) {
             None
         } else {
@@ -84,8 +84,8 @@ impl StandardCodingAgentExecutor for QwenCode {
         env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let qwen_command = self.build_command_builder()?.build_follow_up(&[])?;
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
-        let harness = AcpAgentHarness::with_session_namespace("qwen_sessions");
+        let combined_prompt = self.append_prompt.combine_prompt(prompt, env);
+        let harness = AcpAgentHarness::with_session_namespace(QWEN_SESSION_NAMESPACE);
         let approvals = if self.yolo.unwrap_or(false) {
             None
         } else {
@@ -5,6 +5,7 @@ use workspace_utils::approvals::ApprovalStatus;
 
 pub mod plain_text_processor;
 pub mod stderr_processor;
+pub mod summary;
 pub mod utils;
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -90,6 +91,8 @@ pub enum NormalizedEntryType {
     },
     Thinking,
     Loading,
+    /// Keep-alive shown while the agent is running but silent; never part of the transcript
+    Heartbeat,
     NextAction {
         failed: bool,
         execution_processes: usize,
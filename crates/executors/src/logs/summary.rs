@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use workspace_utils::{log_msg::LogMsg, msg_store::MsgStore, text::truncate_to_char_boundary};
+
+use crate::logs::{
+    ActionType, CommandExitStatus, FileChange, NormalizedEntry, NormalizedEntryType,
+    TokenUsageInfo, utils::patch::extract_normalized_entry_from_patch,
+};
+
+const MAX_FINAL_MESSAGE_LENGTH: usize = 4096;
+
+/// Machine-readable outcome of a finished run
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct ExecutionSummary {
+    /// Paths edited, written, deleted or renamed, in first-touched order
+    pub files_changed: Vec<String>,
+    pub commands: Vec<CommandSummary>,
+    pub final_message: Option<String>,
+    /// Set by the container once the process has exited
+    pub exit_code: Option<i64>,
+    /// Last token usage the agent reported, none for executors that don't
+    #[serde(default)]
+    pub token_usage: Option<TokenUsageInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct CommandSummary {
+    pub command: String,
+    pub exit_status: Option<CommandExitStatus>,
+}
+
+impl ExecutionSummary {
+    /// Summarize the final state of each normalized entry in `msg_store`.
+    /// Raw output is ignored, so a store without normalized entries yields an
+    /// empty summary.
+    pub fn from_msg_store(msg_store: &MsgStore) -> Self {
+        // Entries are re-sent as tool calls progress; keep the latest version
+        let mut entries = BTreeMap::new();
+        for msg in msg_store.get_history() {
+            if let LogMsg::JsonPatch(patch) = msg
+                && let Some((index, entry)) = extract_normalized_entry_from_patch(&patch)
+            {
+                entries.insert(index, entry);
+            }
+        }
+        Self::from_entries(entries.into_values())
+    }
+
+    pub fn from_entries(entries: impl IntoIterator<Item = NormalizedEntry>) -> Self {
+        let mut summary = Self::default();
+        for entry in entries {
+            match entry.entry_type {
+                NormalizedEntryType::AssistantMessage => {
+                    let content = entry.content.trim();
+                    if !content.is_empty() {
+                        summary.final_message = Some(truncate_final_message(content));
+                    }
+                }
+                NormalizedEntryType::ToolUse { action_type, .. } => match action_type {
+                    ActionType::FileEdit { path, changes } => {
+                        summary.add_file(path);
+                        for change in changes {
+                            if let FileChange::Rename { new_path } = change {
+                                summary.add_file(new_path);
+                            }
+                        }
+                    }
+                    ActionType::CommandRun { command, result } => {
+                        summary.commands.push(CommandSummary {
+                            command,
+                            exit_status: result.and_then(|r| r.exit_status),
+                        });
+                    }
+                    _ => {}
+                },
+                NormalizedEntryType::TokenUsageInfo(usage) => summary.token_usage = Some(usage),
+                _ => {}
+            }
+        }
+        summary
+    }
+
+    fn add_file(&mut self, path: String) {
+        if !self.files_changed.contains(&path) {
+            self.files_changed.push(path);
+        }
+    }
+}
+
+fn truncate_final_message(content: &str) -> String {
+    if content.len() > MAX_FINAL_MESSAGE_LENGTH {
+        format!(
+            "{}...",
+            truncate_to_char_boundary(content, MAX_FINAL_MESSAGE_LENGTH)
+        )
+    } else {
+        content.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::{CommandRunResult, ToolStatus, utils::patch::ConversationPatch};
+
+    fn tool_use(action_type: ActionType) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "tool".to_string(),
+                action_type,
+                status: ToolStatus::Success,
+            },
+            content: String::new(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn summarizes_latest_version_of_each_entry() {
+        let store = MsgStore::new();
+        let command = |result| {
+            tool_use(ActionType::CommandRun {
+                command: "cargo test".to_string(),
+                result,
+            })
+        };
+        let edit = tool_use(ActionType::FileEdit {
+            path: "src/lib.rs".to_string(),
+            changes: vec![],
+        });
+        let message = NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: "  All tests pass.\n".to_string(),
+            metadata: None,
+        };
+
+        store.push_stdout("{\"raw\":\"agent output\"}\n");
+        store.push_patch(ConversationPatch::add_normalized_entry(0, edit.clone()));
+        store.push_patch(ConversationPatch::add_normalized_entry(1, command(None)));
+        store.push_patch(ConversationPatch::replace(
+            1,
+            command(Some(CommandRunResult {
+                exit_status: Some(CommandExitStatus::ExitCode { code: 0 }),
+                output: None,
+            })),
+        ));
+        store.push_patch(ConversationPatch::add_normalized_entry(2, edit));
+        store.push_patch(ConversationPatch::add_normalized_entry(3, message));
+
+        let summary = ExecutionSummary::from_msg_store(&store);
+        assert_eq!(summary.files_changed, vec!["src/lib.rs"]);
+        assert_eq!(summary.commands.len(), 1);
+        assert!(matches!(
+            summary.commands[0].exit_status,
+            Some(CommandExitStatus::ExitCode { code: 0 })
+        ));
+        assert_eq!(summary.final_message.as_deref(), Some("All tests pass."));
+        assert_eq!(summary.exit_code, None);
+        assert!(summary.token_usage.is_none());
+    }
+
+    #[test]
+    fn keeps_last_token_usage() {
+        let usage = |total_tokens| NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::TokenUsageInfo(TokenUsageInfo {
+                total_tokens,
+                model_context_window: 200_000,
+            }),
+            content: String::new(),
+            metadata: None,
+        };
+
+        let summary = ExecutionSummary::from_entries([usage(1_200), usage(5_400)]);
+        assert_eq!(summary.token_usage.map(|u| u.total_tokens), Some(5_400));
+    }
+
+    #[test]
+    fn raw_output_yields_empty_summary() {
+        let store = MsgStore::new();
+        store.push_stdout("edited src/main.rs\n");
+        store.push_stderr("warning: unused variable\n");
+
+        let summary = ExecutionSummary::from_msg_store(&store);
+        assert!(summary.files_changed.is_empty());
+        assert!(summary.commands.is_empty());
+        assert!(summary.final_message.is_none());
+    }
+}
@@ -33,6 +33,8 @@ struct PatchEntry {
     value: PatchType,
 }
 
+const HEARTBEAT_PATH: &str = "/heartbeat";
+
 pub fn escape_json_pointer_segment(s: &str) -> String {
     s.replace('~', "~0").replace('/', "~1")
 }
@@ -123,6 +125,27 @@ impl ConversationPatch {
         }]))
         .unwrap()
     }
+
+    /// Create an ADD patch that sets the heartbeat entry. It lives outside
+    /// `/entries` so it never takes an index from the real conversation.
+    pub fn set_heartbeat(entry: NormalizedEntry) -> Patch {
+        let patch_entry = PatchEntry {
+            op: PatchOperation::Add,
+            path: HEARTBEAT_PATH.to_string(),
+            value: PatchType::NormalizedEntry(entry),
+        };
+
+        from_value(json!([patch_entry])).unwrap()
+    }
+
+    /// Create a REMOVE patch that clears the heartbeat entry
+    pub fn clear_heartbeat() -> Patch {
+        from_value(json!([{
+            "op": PatchOperation::Remove,
+            "path": HEARTBEAT_PATH,
+        }]))
+        .unwrap()
+    }
 }
 
 /// Extract the entry index and `NormalizedEntry` from a JsonPatch if it contains one
@@ -1,17 +1,21 @@
 use std::{
     collections::HashMap,
     fs,
+    path::Path,
     str::FromStr,
     sync::{LazyLock, RwLock},
 };
 
 use convert_case::{Case, Casing};
 use serde::{Deserialize, Deserializer, Serialize, de::Error as DeError};
+use serde_json::Value;
 use thiserror::Error;
 use ts_rs::TS;
 
-use crate::executors::{
-    AvailabilityInfo, BaseCodingAgent, CodingAgent, StandardCodingAgentExecutor,
+use crate::{
+    command::{check_command_line, undefined_variables},
+    env::{EXECUTION_VAR_PREFIX, ExecutionEnv},
+    executors::{AvailabilityInfo, BaseCodingAgent, CodingAgent, StandardCodingAgentExecutor},
 };
 
 /// Return the canonical form for variant keys.
@@ -54,6 +58,12 @@ pub enum ProfileError {
 static EXECUTOR_PROFILES_CACHE: LazyLock<RwLock<ExecutorConfigs>> =
     LazyLock::new(|| RwLock::new(ExecutorConfigs::load()));
 
+static LOAD_REPORT: LazyLock<RwLock<ProfilesReport>> =
+    LazyLock::new(|| RwLock::new(ProfilesReport::default()));
+
+/// Executor keys still accepted for compatibility, with their current names
+const DEPRECATED_EXECUTOR_NAMES: &[(&str, &str)] = &[("CURSOR", "CURSOR_AGENT")];
+
 // New format default profiles (v3 - flattened)
 const DEFAULT_PROFILES_JSON: &str = include_str!("../default_profiles.json");
 
@@ -206,38 +216,148 @@ impl ExecutorConfigs {
         *cache = Self::load();
     }
 
-    /// Load executor profiles from file or defaults
+    /// Load executor profiles from file or defaults, logging and recording
+    /// any problems found in profiles.json
     pub fn load() -> Self {
-        let profiles_path = workspace_utils::assets::profiles_path();
+        let (profiles, report) = Self::load_with_report();
+        report.log();
+        *LOAD_REPORT.write().unwrap() = report;
+        profiles
+    }
+
+    /// Problems found the last time profiles.json was loaded
+    pub fn load_report() -> ProfilesReport {
+        LOAD_REPORT.read().unwrap().clone()
+    }
+
+    /// Re-read profiles.json and make it active, unless it contains invalid
+    /// entries: then the current profiles stay in use. Executions already
+    /// running keep the configuration they were spawned with either way.
+    pub fn reload_checked() -> ProfilesReport {
+        Self::reload_checked_from(&workspace_utils::assets::profiles_path())
+    }
+
+    fn reload_checked_from(profiles_path: &Path) -> ProfilesReport {
+        let (profiles, report) = Self::load_with_report_from(profiles_path);
+        report.log();
+        if report.has_errors() {
+            tracing::warn!("Keeping current executor profiles, profiles.json has errors");
+        } else {
+            *EXECUTOR_PROFILES_CACHE.write().unwrap() = profiles;
+            tracing::info!("Reloaded executor profiles");
+        }
+        *LOAD_REPORT.write().unwrap() = report.clone();
+        report
+    }
 
+    fn load_with_report() -> (Self, ProfilesReport) {
+        Self::load_with_report_from(&workspace_utils::assets::profiles_path())
+    }
+
+    fn load_with_report_from(profiles_path: &Path) -> (Self, ProfilesReport) {
         // Load defaults first
         let mut defaults = Self::from_defaults();
         defaults.canonicalise();
 
         // Try to load user overrides
-        let content = match fs::read_to_string(&profiles_path) {
+        let content = match fs::read_to_string(profiles_path) {
             Ok(content) => content,
             Err(_) => {
                 tracing::info!("No user profiles.json found, using defaults only");
-                return defaults;
+                return (defaults, ProfilesReport::default());
             }
         };
 
-        // Parse user overrides
-        match serde_json::from_str::<Self>(&content) {
-            Ok(mut user_overrides) => {
-                tracing::info!("Loaded user profile overrides from profiles.json");
-                user_overrides.canonicalise();
-                Self::merge_with_defaults(defaults, user_overrides)
-            }
+        // Parse user overrides, skipping only the entries that are invalid
+        let (mut user_overrides, report) = Self::parse_overrides(&content);
+        tracing::info!("Loaded user profile overrides from profiles.json");
+        user_overrides.canonicalise();
+        (Self::merge_with_defaults(defaults, user_overrides), report)
+    }
+
+    /// Parse each executor and variant of a profiles document independently.
+    /// Invalid entries are left out and reported as errors; unknown fields and
+    /// deprecated names are kept and reported as warnings.
+    pub fn parse_overrides(content: &str) -> (Self, ProfilesReport) {
+        let mut overrides = Self {
+            executors: HashMap::new(),
+        };
+        let mut report = ProfilesReport::default();
+
+        let root = match serde_json::from_str::<Value>(content) {
+            Ok(root) => root,
             Err(e) => {
-                tracing::error!(
-                    "Failed to parse user profiles.json: {}, using defaults only",
-                    e
+                report.error(None, None, format!("is not valid JSON: {e}"));
+                return (overrides, report);
+            }
+        };
+        let Some(executors) = root.get("executors").and_then(Value::as_object) else {
+            report.error(None, None, "has no 'executors' object".to_string());
+            return (overrides, report);
+        };
+
+        for (executor_name, variants) in executors {
+            let Ok(executor_key) = BaseCodingAgent::from_str(executor_name) else {
+                report.error(
+                    Some(executor_name),
+                    None,
+                    "is not a known executor".to_string(),
+                );
+                continue;
+            };
+            if let Some(current) = DEPRECATED_EXECUTOR_NAMES
+                .iter()
+                .find_map(|(old, new)| (old == executor_name).then_some(new))
+            {
+                report.warning(
+                    Some(executor_name),
+                    None,
+                    format!("is a deprecated name, use '{current}' instead"),
+                );
+            }
+            let Some(variants) = variants.as_object() else {
+                report.error(
+                    Some(executor_name),
+                    None,
+                    "must be an object of variants".to_string(),
                 );
-                defaults
+                continue;
+            };
+
+            for (variant_name, raw) in variants {
+                let agent = match serde_json::from_value::<CodingAgent>(raw.clone()) {
+                    Ok(agent) => agent,
+                    Err(e) => {
+                        report.error(Some(executor_name), Some(variant_name), e.to_string());
+                        continue;
+                    }
+                };
+                if let Err(reason) = check_variant(executor_key, variant_name, &agent) {
+                    report.error(Some(executor_name), Some(variant_name), reason);
+                    continue;
+                }
+                if let Some(reason) = undefined_variables_reason(&agent) {
+                    report.warning(Some(executor_name), Some(variant_name), reason);
+                }
+                for field in unknown_fields(raw, &agent) {
+                    report.warning(
+                        Some(executor_name),
+                        Some(variant_name),
+                        format!("has unknown field '{field}', which is ignored"),
+                    );
+                }
+                overrides
+                    .executors
+                    .entry(executor_key)
+                    .or_insert_with(|| ExecutorConfig {
+                        configurations: HashMap::new(),
+                    })
+                    .configurations
+                    .insert(variant_name.clone(), agent);
             }
         }
+
+        (overrides, report)
     }
 
     /// Save user profile overrides to file (only saves what differs from defaults)
@@ -367,13 +487,14 @@ impl ExecutorConfigs {
                 )));
             }
 
-            // Ensure configuration names don't conflict with reserved words
-            for config_name in profile.configurations.keys() {
-                if config_name.starts_with("__") {
-                    return Err(ProfileError::Validation(format!(
-                        "Configuration name '{config_name}' is reserved (starts with '__')"
-                    )));
-                }
+            for (config_name, agent) in &profile.configurations {
+                check_variant(*executor_key, config_name, agent)
+                    .and_then(|()| undefined_variables_reason(agent).map_or(Ok(()), Err))
+                    .map_err(|reason| {
+                        ProfileError::Validation(format!(
+                            "Configuration '{executor_key}:{config_name}' {reason}"
+                        ))
+                    })?;
             }
         }
         Ok(())
@@ -476,9 +597,348 @@ impl ExecutorConfigs {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileIssueSeverity {
+    /// The entry was skipped
+    Error,
+    /// The entry was loaded, but may not behave as intended
+    Warning,
+}
+
+/// A problem with one entry of profiles.json
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProfileIssue {
+    pub severity: ProfileIssueSeverity,
+    /// Executor key as written in the file; `None` for document-level problems
+    pub executor: Option<String>,
+    pub variant: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ProfileIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.executor, &self.variant) {
+            (Some(executor), Some(variant)) => write!(f, "'{executor}:{variant}'")?,
+            (Some(executor), None) => write!(f, "'{executor}'")?,
+            _ => write!(f, "profiles.json")?,
+        }
+        write!(f, " {}", self.message)
+    }
+}
+
+/// Problems found while loading profiles.json. Valid entries are loaded
+/// regardless, so an empty report means every entry was applied as written.
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct ProfilesReport {
+    pub issues: Vec<ProfileIssue>,
+}
+
+impl ProfilesReport {
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == ProfileIssueSeverity::Error)
+    }
+
+    fn log(&self) {
+        for issue in &self.issues {
+            match issue.severity {
+                ProfileIssueSeverity::Error => tracing::error!("Skipped profile {}", issue),
+                ProfileIssueSeverity::Warning => tracing::warn!("Profile {}", issue),
+            }
+        }
+    }
+
+    fn push(
+        &mut self,
+        severity: ProfileIssueSeverity,
+        executor: Option<&String>,
+        variant: Option<&String>,
+        message: String,
+    ) {
+        self.issues.push(ProfileIssue {
+            severity,
+            executor: executor.cloned(),
+            variant: variant.cloned(),
+            message,
+        });
+    }
+
+    fn error(&mut self, executor: Option<&String>, variant: Option<&String>, message: String) {
+        self.push(ProfileIssueSeverity::Error, executor, variant, message);
+    }
+
+    fn warning(&mut self, executor: Option<&String>, variant: Option<&String>, message: String) {
+        self.push(ProfileIssueSeverity::Warning, executor, variant, message);
+    }
+}
+
+/// Checks shared by saving and loading: the variant must configure the
+/// executor it is listed under, use an allowed name and have command
+/// overrides that can be split into arguments
+fn check_variant(
+    executor_key: BaseCodingAgent,
+    config_name: &str,
+    agent: &CodingAgent,
+) -> Result<(), String> {
+    if BaseCodingAgent::from(agent) != executor_key {
+        return Err(format!(
+            "configures '{agent}' but is listed under '{executor_key}'"
+        ));
+    }
+
+    if config_name.starts_with("__") {
+        return Err("uses a reserved name (starts with '__')".to_string());
+    }
+
+    let Some(cmd) = agent.cmd_overrides() else {
+        return Ok(());
+    };
+    let checks = [
+        ("base_command_override", cmd.base_command_override.clone()),
+        (
+            "additional_params",
+            cmd.additional_params.as_ref().map(|p| p.join(" ")),
+        ),
+    ];
+    for (field, value) in checks {
+        if let Some(value) = value.filter(|v| !v.trim().is_empty())
+            && let Err(err) = check_command_line(&value)
+        {
+            return Err(format!("has an invalid {field}: {err}"));
+        }
+    }
+    Ok(())
+}
+
+/// Why the variant's command would fail to expand: the variables it references
+/// that neither its env, its env file nor the server environment define.
+/// Execution variables are set per run and always count as defined; nothing
+/// is reported when the env file can only be read from a worktree.
+fn undefined_variables_reason(agent: &CodingAgent) -> Option<String> {
+    let cmd = agent.cmd_overrides()?;
+    let env = ExecutionEnv::for_profile_check(cmd)?;
+    let lookup = |name: &str| {
+        if name.starts_with(EXECUTION_VAR_PREFIX) {
+            Some(String::new())
+        } else {
+            env.var(name)
+        }
+    };
+    let mut names: Vec<String> = Vec::new();
+    let values = cmd
+        .base_command_override
+        .iter()
+        .chain(cmd.additional_params.iter().flatten());
+    for name in values.flat_map(|value| undefined_variables(value, lookup)) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    if names.is_empty() {
+        return None;
+    }
+    let names = names
+        .iter()
+        .map(|name| format!("'{name}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(format!("references undefined variables: {names}"))
+}
+
+/// Fields set in `raw` that deserialization ignored. Found by comparing with
+/// the fields `agent` serializes back to; explicit nulls are not reported.
+fn unknown_fields(raw: &Value, agent: &CodingAgent) -> Vec<String> {
+    let known = serde_json::to_value(agent).unwrap_or_default();
+    let fields = |value: &Value| {
+        value
+            .as_object()
+            .and_then(|outer| outer.values().next())
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let known = fields(&known);
+    fields(raw)
+        .into_iter()
+        .filter(|(name, value)| !value.is_null() && !known.contains_key(name))
+        .map(|(name, _)| name)
+        .collect()
+}
+
 pub fn to_default_variant(id: &ExecutorProfileId) -> ExecutorProfileId {
     ExecutorProfileId {
         executor: id.executor,
         variant: None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue<'a>(report: &'a ProfilesReport, executor: &str, variant: &str) -> &'a ProfileIssue {
+        report
+            .issues
+            .iter()
+            .find(|i| {
+                i.executor.as_deref() == Some(executor) && i.variant.as_deref() == Some(variant)
+            })
+            .unwrap_or_else(|| panic!("no issue for {executor}:{variant}"))
+    }
+
+    #[test]
+    fn invalid_profiles_are_reported_without_rejecting_valid_ones() {
+        let content = serde_json::json!({
+            "executors": {
+                "CLAUDE_CODE": {
+                    "DEFAULT": {"CLAUDE_CODE": {"model": "opus"}},
+                    "TYPO": {"CLAUDE_CODE": {"modle": "opus", "model": null}},
+                    "BROKEN": {"CLAUDE_CODE": {"base_command_override": "claude \"oops"}},
+                    "WRONG_AGENT": {"AMP": {}}
+                },
+                "AMP": {
+                    "DEFAULT": {"AMP": {"dangerously_allow_all": "yes"}}
+                },
+                "CURSOR": {
+                    "DEFAULT": {"CURSOR_AGENT": {"force": true}}
+                },
+                "NOT_AN_AGENT": {
+                    "DEFAULT": {}
+                }
+            }
+        })
+        .to_string();
+
+        let (overrides, report) = ExecutorConfigs::parse_overrides(&content);
+
+        let claude = &overrides.executors[&BaseCodingAgent::ClaudeCode];
+        let mut loaded: Vec<_> = claude.configurations.keys().collect();
+        loaded.sort();
+        assert_eq!(loaded, vec!["DEFAULT", "TYPO"]);
+        assert!(!overrides.executors.contains_key(&BaseCodingAgent::Amp));
+        assert!(
+            overrides
+                .executors
+                .contains_key(&BaseCodingAgent::CursorAgent)
+        );
+
+        let typo = issue(&report, "CLAUDE_CODE", "TYPO");
+        assert_eq!(typo.severity, ProfileIssueSeverity::Warning);
+        assert!(typo.message.contains("'modle'"));
+        for variant in ["BROKEN", "WRONG_AGENT"] {
+            assert_eq!(
+                issue(&report, "CLAUDE_CODE", variant).severity,
+                ProfileIssueSeverity::Error
+            );
+        }
+        assert_eq!(
+            issue(&report, "AMP", "DEFAULT").severity,
+            ProfileIssueSeverity::Error
+        );
+
+        let executor_issue = |name: &str| {
+            report
+                .issues
+                .iter()
+                .find(|i| i.executor.as_deref() == Some(name) && i.variant.is_none())
+                .map(|i| i.severity)
+        };
+        assert_eq!(
+            executor_issue("CURSOR"),
+            Some(ProfileIssueSeverity::Warning)
+        );
+        assert_eq!(
+            executor_issue("NOT_AN_AGENT"),
+            Some(ProfileIssueSeverity::Error)
+        );
+        assert_eq!(report.issues.len(), 6);
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn undefined_command_variables_warn_on_load_and_fail_on_save() {
+        let content = serde_json::json!({
+            "executors": {
+                "CLAUDE_CODE": {
+                    "DEFAULT": {"CLAUDE_CODE": {
+                        "base_command_override": "$AGENT_HOME/claude",
+                        "env": {"AGENT_HOME": "/opt/agent"},
+                        "additional_params": ["--task", "$VK_TASK_ID"]
+                    }},
+                    "MISSING": {"CLAUDE_CODE": {
+                        "base_command_override": "${VK_SURELY_UNSET_HOME_DIR}/claude",
+                        "additional_params": ["--config", "$SURELY_UNSET_AGENT_CONFIG"]
+                    }}
+                }
+            }
+        })
+        .to_string();
+
+        let (overrides, report) = ExecutorConfigs::parse_overrides(&content);
+        assert_eq!(report.issues.len(), 1, "{:?}", report.issues);
+        let missing = issue(&report, "CLAUDE_CODE", "MISSING");
+        assert_eq!(missing.severity, ProfileIssueSeverity::Warning);
+        assert!(missing.message.contains("'SURELY_UNSET_AGENT_CONFIG'"));
+
+        let merged =
+            ExecutorConfigs::merge_with_defaults(ExecutorConfigs::from_defaults(), overrides);
+        let err = ExecutorConfigs::validate_merged(&merged).unwrap_err();
+        assert!(
+            matches!(err, ProfileError::Validation(ref msg) if msg.contains("CLAUDE_CODE:MISSING")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn reload_swaps_profiles_unless_invalid() {
+        let dir = std::env::temp_dir().join(format!("vk-profiles-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("profiles.json");
+        let write_command = |command: &str| {
+            let content = serde_json::json!({
+                "executors": {
+                    "CLAUDE_CODE": {
+                        "DEFAULT": {"CLAUDE_CODE": {"base_command_override": command}}
+                    }
+                }
+            });
+            fs::write(&path, content.to_string()).unwrap();
+        };
+        let spawned_command = || {
+            ExecutorConfigs::get_cached()
+                .get_coding_agent_or_default(&ExecutorProfileId::new(BaseCodingAgent::ClaudeCode))
+                .cmd_overrides()
+                .and_then(|cmd| cmd.base_command_override.clone())
+        };
+
+        write_command("my-claude --first");
+        assert!(
+            ExecutorConfigs::reload_checked_from(&path)
+                .issues
+                .is_empty()
+        );
+        let first = spawned_command();
+
+        write_command("my-claude --second");
+        ExecutorConfigs::reload_checked_from(&path);
+        let second = spawned_command();
+
+        write_command("my-claude \"unterminated");
+        let report = ExecutorConfigs::reload_checked_from(&path);
+        let after_invalid = spawned_command();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(first.as_deref(), Some("my-claude --first"));
+        assert_eq!(second.as_deref(), Some("my-claude --second"));
+        assert!(report.has_errors());
+        assert_eq!(after_invalid.as_deref(), Some("my-claude --second"));
+    }
+
+    #[test]
+    fn default_profiles_load_without_issues() {
+        let (_, report) = ExecutorConfigs::parse_overrides(DEFAULT_PROFILES_JSON);
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+}
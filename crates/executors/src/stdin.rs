@@ -0,0 +1,69 @@
+//! Background prompt delivery over a child process's stdin
+//!
+//! Writing a large prompt inline before `spawn` returns can deadlock: the child
+//! blocks on a full stdout pipe that is only drained once the container attaches
+//! its log forwarders, while the executor blocks on a full stdin pipe. Writing from
+//! a separate task lets `spawn` return so stdout/stderr are drained concurrently.
+
+use command_group::AsyncGroupChild;
+use tokio::{io::AsyncWriteExt, task::JoinHandle};
+
+/// Upper bound for a single write so one huge prompt never monopolises the pipe.
+const PROMPT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Take the child's stdin and stream `prompt` into it from a background task,
+/// closing the pipe afterwards so the child sees EOF.
+///
+/// Returns `None` if the child was not spawned with a piped stdin.
+pub fn spawn_prompt_writer(child: &mut AsyncGroupChild, prompt: String) -> Option<JoinHandle<()>> {
+    let mut stdin = child.inner().stdin.take()?;
+
+    Some(tokio::spawn(async move {
+        for chunk in prompt.as_bytes().chunks(PROMPT_CHUNK_BYTES) {
+            if let Err(err) = stdin.write_all(chunk).await {
+                tracing::warn!("Failed to write prompt to child stdin: {err}");
+                return;
+            }
+        }
+
+        if let Err(err) = stdin.shutdown().await {
+            tracing::debug!("Failed to close child stdin: {err}");
+        }
+    }))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{process::Stdio, time::Duration};
+
+    use command_group::AsyncCommandGroup;
+    use tokio::{io::AsyncReadExt, process::Command};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn large_prompt_round_trips_without_deadlock() {
+        let prompt = "0123456789abcdef\n".repeat(256 * 1024); // ~4 MiB
+
+        let mut child = Command::new("cat")
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .group_spawn()
+            .expect("spawn cat");
+
+        let writer = spawn_prompt_writer(&mut child, prompt.clone()).expect("piped stdin");
+        let mut stdout = child.inner().stdout.take().expect("piped stdout");
+
+        let mut echoed = String::new();
+        tokio::time::timeout(Duration::from_secs(30), stdout.read_to_string(&mut echoed))
+            .await
+            .expect("stdin/stdout pipes deadlocked")
+            .expect("read stdout");
+        writer.await.expect("writer task");
+
+        assert_eq!(echoed.len(), prompt.len());
+        assert!(echoed == prompt);
+    }
+}
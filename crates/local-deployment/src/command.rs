@@ -8,14 +8,32 @@ use services::services::container::ContainerError;
 #[cfg(unix)]
 use tokio::time::Duration;
 
-pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), ContainerError> {
+/// Stop the child's process group. `pgid` is the group recorded at spawn
+/// time; without it the group is derived from the leader. Once the leader has
+/// exited, the recorded group is only signalled while it still has members,
+/// such as grandchildren the leader left behind.
+pub async fn kill_process_group(
+    child: &mut AsyncGroupChild,
+    pgid: Option<u32>,
+) -> Result<(), ContainerError> {
     // hit the whole process group, not just the leader
     #[cfg(unix)]
     {
-        if let Some(pid) = child.inner().id() {
-            let pgid = getpgid(Some(Pid::from_raw(pid as i32)))
-                .map_err(|e| ContainerError::KillFailed(std::io::Error::other(e)))?;
-
+        let exited = child
+            .inner()
+            .try_wait()
+            .map_err(ContainerError::Io)?
+            .is_some();
+        let pgid = match (pgid, child.inner().id()) {
+            (Some(pgid), _) => Some(Pid::from_raw(pgid as i32)),
+            (None, Some(pid)) => Some(
+                getpgid(Some(Pid::from_raw(pid as i32)))
+                    .map_err(|e| ContainerError::KillFailed(std::io::Error::other(e)))?,
+            ),
+            (None, None) => None,
+        };
+        let pgid = pgid.filter(|pgid| !exited || group_alive(*pgid));
+        if let Some(pgid) = pgid {
             for sig in [Signal::SIGINT, Signal::SIGTERM, Signal::SIGKILL] {
                 if let Err(e) = killpg(pgid, sig) {
                     tracing::warn!(
@@ -26,19 +44,64 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
                     );
                 }
                 tokio::time::sleep(Duration::from_secs(2)).await;
-                if child
+                let leader_exited = child
                     .inner()
                     .try_wait()
                     .map_err(ContainerError::Io)?
-                    .is_some()
-                {
+                    .is_some();
+                if leader_exited && !group_alive(pgid) {
                     break;
                 }
             }
         }
     }
 
+    #[cfg(not(unix))]
+    let _ = pgid;
+
     let _ = child.kill().await;
     let _ = child.wait().await;
     Ok(())
 }
+
+/// Whether any process is left in the group
+#[cfg(unix)]
+fn group_alive(pgid: Pid) -> bool {
+    killpg(pgid, None).is_ok()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::process::Stdio;
+
+    use command_group::AsyncCommandGroup;
+    use tokio::{io::AsyncReadExt, process::Command};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn orphaned_grandchildren_are_stopped_after_the_leader_exits() {
+        let mut leader = Command::new("sh")
+            .args(["-c", "sleep 30 >/dev/null & echo $!"])
+            .stdout(Stdio::piped())
+            .group_spawn()
+            .unwrap();
+        let pgid = leader.id();
+        let mut output = String::new();
+        let mut stdout = leader.inner().stdout.take().unwrap();
+        stdout.read_to_string(&mut output).await.unwrap();
+        leader.wait().await.unwrap();
+        let grandchild = output.trim().to_string();
+
+        kill_process_group(&mut leader, pgid).await.unwrap();
+
+        // Gone, or a zombie waiting for whoever adopted it to reap it
+        let stat = std::fs::read_to_string(format!("/proc/{grandchild}/stat"));
+        let running = stat.is_ok_and(|stat| {
+            stat.rsplit(") ")
+                .next()
+                .is_some_and(|rest| !rest.starts_with('Z'))
+        });
+        assert!(!running);
+    }
+}
@@ -9,10 +9,12 @@ use std::{
 
 use anyhow::anyhow;
 use async_trait::async_trait;
+use chrono::Utc;
 use command_group::AsyncGroupChild;
 use db::{
     DBService,
     models::{
+        attempt_usage,
         coding_agent_turn::CodingAgentTurn,
         execution_process::{
             ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
@@ -21,6 +23,7 @@ use db::{
         repo::Repo,
         scratch::{DraftFollowUpData, Scratch, ScratchType},
         task::{Task, TaskStatus},
+        task_event::{TaskEvent, TaskEventActor},
         workspace::Workspace,
         workspace_repo::WorkspaceRepo,
     },
@@ -34,9 +37,12 @@ use executors::{
     },
     approvals::{ExecutorApprovalService, NoopExecutorApprovalService},
     env::{ExecutionEnv, RepoContext},
-    executors::{BaseCodingAgent, ExecutorExitResult, ExecutorExitSignal, InterruptSender},
+    executors::{
+        BaseCodingAgent, ExecutorExitResult, ExecutorExitSignal, InterruptSender, ProcessIds,
+        StandardCodingAgentExecutor,
+    },
     logs::{NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch},
-    profile::ExecutorProfileId,
+    profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use futures::{FutureExt, TryStreamExt, stream::select};
 use serde_json::json;
@@ -51,6 +57,7 @@ use services::services::{
     notification::NotificationService,
     queued_message::QueuedMessageService,
     share::SharePublisher,
+    task_transitions,
     workspace_manager::{RepoWorkspaceInput, WorkspaceManager},
 };
 use tokio::{sync::RwLock, task::JoinHandle};
@@ -58,17 +65,42 @@ use tokio_util::io::ReaderStream;
 use utils::{
     log_msg::LogMsg,
     msg_store::MsgStore,
+    redact::Redactor,
     text::{git_branch_id, short_uuid, truncate_to_char_boundary},
 };
 use uuid::Uuid;
 
-use crate::{command, copy};
+use crate::{command, copy, heartbeat, scratch_dir};
+
+const DEFAULT_DELETED_TASK_RETENTION_DAYS: u32 = 30;
+const DEFAULT_TASK_EVENT_RETENTION_DAYS: u32 = 90;
+
+/// How long deleted tasks can be restored before they are purged
+fn deleted_task_retention(config: &Config) -> chrono::Duration {
+    chrono::Duration::days(
+        config
+            .deleted_task_retention_days
+            .unwrap_or(DEFAULT_DELETED_TASK_RETENTION_DAYS)
+            .into(),
+    )
+}
+
+/// How long task activity log entries are kept
+fn task_event_retention(config: &Config) -> chrono::Duration {
+    chrono::Duration::days(
+        config
+            .task_event_retention_days
+            .unwrap_or(DEFAULT_TASK_EVENT_RETENTION_DAYS)
+            .into(),
+    )
+}
 
 #[derive(Clone)]
 pub struct LocalContainerService {
     db: DBService,
     child_store: Arc<RwLock<HashMap<Uuid, Arc<RwLock<AsyncGroupChild>>>>>,
     interrupt_senders: Arc<RwLock<HashMap<Uuid, InterruptSender>>>,
+    process_ids: Arc<RwLock<HashMap<Uuid, ProcessIds>>>,
     msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
     config: Arc<RwLock<Config>>,
     git: GitService,
@@ -95,12 +127,14 @@ impl LocalContainerService {
     ) -> Self {
         let child_store = Arc::new(RwLock::new(HashMap::new()));
         let interrupt_senders = Arc::new(RwLock::new(HashMap::new()));
+        let process_ids = Arc::new(RwLock::new(HashMap::new()));
         let notification_service = NotificationService::new(config.clone());
 
         let container = LocalContainerService {
             db,
             child_store,
             interrupt_senders,
+            process_ids,
             msg_stores,
             config,
             git,
@@ -130,6 +164,15 @@ impl LocalContainerService {
     pub async fn remove_child_from_store(&self, id: &Uuid) {
         let mut map = self.child_store.write().await;
         map.remove(id);
+        self.process_ids.write().await.remove(id);
+    }
+
+    async fn recorded_pgid(&self, id: &Uuid) -> Option<u32> {
+        self.process_ids
+            .read()
+            .await
+            .get(id)
+            .and_then(|ids| ids.pgid)
     }
 
     async fn add_interrupt_sender(&self, id: Uuid, sender: InterruptSender) {
@@ -174,6 +217,8 @@ impl LocalContainerService {
                 });
         }
 
+        scratch_dir::remove(workspace.id).await;
+
         // Clear container_ref so this workspace won't be picked up again
         let _ = Workspace::clear_container_ref(&db.pool, workspace.id).await;
     }
@@ -194,9 +239,89 @@ impl LocalContainerService {
         Ok(())
     }
 
+    /// Permanently remove tasks deleted longer ago than the retention period,
+    /// with their worktrees, attempts and logs. The remote copy of a shared
+    /// task goes too, unless a live task has been linked to it since; a task
+    /// whose remote copy could not be deleted is kept for the next run.
+    pub async fn purge_deleted_tasks(
+        db: &DBService,
+        publisher: Option<&SharePublisher>,
+        retention: chrono::Duration,
+    ) -> Result<(), DeploymentError> {
+        let cutoff = Utc::now() - retention;
+        let tasks = Task::find_deleted_before(&db.pool, cutoff).await?;
+        if tasks.is_empty() {
+            return Ok(());
+        }
+        tracing::info!("Purging {} deleted tasks", tasks.len());
+
+        for task in &tasks {
+            if let Some(shared_task_id) = task.shared_task_id
+                && Task::find_by_shared_task_id(&db.pool, shared_task_id)
+                    .await?
+                    .is_none()
+            {
+                match publisher {
+                    Some(publisher) => {
+                        if let Err(e) = publisher.delete_shared_task(shared_task_id).await {
+                            tracing::warn!(
+                                "Keeping deleted task {} until its shared task {} is removed: {}",
+                                task.id,
+                                shared_task_id,
+                                e
+                            );
+                            continue;
+                        }
+                    }
+                    None => tracing::warn!(
+                        "Purging task {} without removing its shared task {}, sharing is not configured",
+                        task.id,
+                        shared_task_id
+                    ),
+                }
+            }
+
+            let workspaces = Workspace::fetch_all(&db.pool, Some(task.id)).await?;
+            for workspace in &workspaces {
+                Self::cleanup_workspace(db, workspace).await;
+            }
+
+            // Workspaces, sessions, execution processes and their logs go with
+            // the task through FK cascades
+            let mut tx = db.pool.begin().await?;
+            for workspace in &workspaces {
+                Task::nullify_children_by_workspace_id(&mut *tx, workspace.id).await?;
+            }
+            Task::delete(&mut *tx, task.id).await?;
+            tx.commit().await?;
+        }
+
+        let orphaned = Repo::delete_orphaned(&db.pool).await?;
+        if orphaned > 0 {
+            tracing::info!("Deleted {} orphaned repo records", orphaned);
+        }
+        Ok(())
+    }
+
+    pub async fn prune_task_events(
+        db: &DBService,
+        retention: chrono::Duration,
+    ) -> Result<(), DeploymentError> {
+        let cutoff = Utc::now() - retention;
+        let pruned = TaskEvent::delete_older_than(&db.pool, cutoff).await?;
+        if pruned > 0 {
+            tracing::info!("Pruned {} task activity events", pruned);
+        }
+        Ok(())
+    }
+
     pub fn spawn_workspace_cleanup(&self) {
         let db = self.db.clone();
+        let publisher = self.publisher.clone().ok();
+        let config = self.config.clone();
         let cleanup_expired = Self::cleanup_expired_workspaces;
+        let purge_deleted = Self::purge_deleted_tasks;
+        let prune_events = Self::prune_task_events;
         tokio::spawn(async move {
             WorkspaceManager::cleanup_orphan_workspaces(&db.pool).await;
 
@@ -205,9 +330,22 @@ impl LocalContainerService {
             loop {
                 cleanup_interval.tick().await;
                 tracing::info!("Starting periodic workspace cleanup...");
+                let (purge_after, prune_after) = {
+                    let config = config.read().await;
+                    (
+                        deleted_task_retention(&config),
+                        task_event_retention(&config),
+                    )
+                };
                 cleanup_expired(&db).await.unwrap_or_else(|e| {
                     tracing::error!("Failed to clean up expired workspaces: {}", e)
                 });
+                purge_deleted(&db, publisher.as_ref(), purge_after)
+                    .await
+                    .unwrap_or_else(|e| tracing::error!("Failed to purge deleted tasks: {}", e));
+                prune_events(&db, prune_after)
+                    .await
+                    .unwrap_or_else(|e| tracing::error!("Failed to prune task events: {}", e));
             }
         });
     }
@@ -377,6 +515,7 @@ impl LocalContainerService {
     ) -> JoinHandle<()> {
         let exec_id = *exec_id;
         let child_store = self.child_store.clone();
+        let process_ids = self.process_ids.clone();
         let msg_stores = self.msg_stores.clone();
         let db = self.db.clone();
         let config = self.config.clone();
@@ -401,8 +540,9 @@ impl LocalContainerService {
                 exit_result = &mut exit_signal_future => {
                     // Executor signaled completion: kill group and use the provided result
                     if let Some(child_lock) = child_store.read().await.get(&exec_id).cloned() {
+                        let pgid = process_ids.read().await.get(&exec_id).and_then(|ids| ids.pgid);
                         let mut child = child_lock.write().await ;
-                        if let Err(err) = command::kill_process_group(&mut child).await {
+                        if let Err(err) = command::kill_process_group(&mut child, pgid).await {
                             tracing::error!("Failed to kill process group after exit signal: {} {}", exec_id, err);
                         }
                     }
@@ -441,10 +581,17 @@ impl LocalContainerService {
             }
 
             if let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await {
+                let failed = matches!(ctx.execution_process.status, ExecutionProcessStatus::Failed);
+                let retain = failed && config.read().await.retain_scratch_on_failure;
+                scratch_dir::set_retained(ctx.workspace.id, retain).await;
+
                 // Update executor session summary if available
                 if let Err(e) = container.update_executor_session_summary(&exec_id).await {
                     tracing::warn!("Failed to update executor session summary: {}", e);
                 }
+                if let Err(e) = container.update_execution_summary(&ctx, exit_code).await {
+                    tracing::warn!("Failed to update execution summary: {}", e);
+                }
 
                 let success = matches!(
                     ctx.execution_process.status,
@@ -593,6 +740,7 @@ impl LocalContainerService {
 
             // Cleanup child handle
             child_store.write().await.remove(&exec_id);
+            process_ids.write().await.remove(&exec_id);
         })
     }
 
@@ -638,8 +786,15 @@ impl LocalContainerService {
         format!("{}-{}", short_uuid(workspace_id), task_title_id)
     }
 
-    async fn track_child_msgs_in_store(&self, id: Uuid, child: &mut AsyncGroupChild) {
+    async fn track_child_msgs_in_store(
+        &self,
+        id: Uuid,
+        child: &mut AsyncGroupChild,
+        redactor: Redactor,
+        profile_output_limit: Option<u64>,
+    ) {
         let store = Arc::new(MsgStore::new());
+        store.set_redactor(redactor);
 
         let out = child.inner().stdout.take().expect("no stdout");
         let err = child.inner().stderr.take().expect("no stderr");
@@ -656,7 +811,52 @@ impl LocalContainerService {
 
         // Merge and forward into the store
         let merged = select(out, err); // Stream<Item = Result<LogMsg, io::Error>>
-        store.clone().spawn_forwarder(merged);
+        let (output_limit, kill_on_overflow, heartbeat_secs) = {
+            let config = self.config.read().await;
+            (
+                profile_output_limit.or(config.execution_output_limit_bytes),
+                config.kill_on_output_overflow,
+                config.execution_heartbeat_secs,
+            )
+        };
+        if let Some(secs) = heartbeat_secs.filter(|secs| *secs > 0) {
+            heartbeat::spawn(store.clone(), Duration::from_secs(secs));
+        }
+        match output_limit {
+            Some(limit) => {
+                let child_store = self.child_store.clone();
+                let process_ids = self.process_ids.clone();
+                store.clone().spawn_limited_forwarder(
+                    merged,
+                    usize::try_from(limit).unwrap_or(usize::MAX),
+                    move || {
+                        tracing::warn!("Execution {} exceeded output limit of {} bytes", id, limit);
+                        if !kill_on_overflow {
+                            return;
+                        }
+                        tokio::spawn(async move {
+                            if let Some(child_lock) = child_store.read().await.get(&id).cloned() {
+                                let pgid =
+                                    process_ids.read().await.get(&id).and_then(|ids| ids.pgid);
+                                let mut child = child_lock.write().await;
+                                if let Err(err) =
+                                    command::kill_process_group(&mut child, pgid).await
+                                {
+                                    tracing::error!(
+                                        "Failed to kill process group after output overflow: {} {}",
+                                        id,
+                                        err
+                                    );
+                                }
+                            }
+                        });
+                    },
+                );
+            }
+            None => {
+                store.clone().spawn_forwarder(merged);
+            }
+        }
 
         let mut map = self.msg_stores().write().await;
         map.insert(id, store);
@@ -723,6 +923,45 @@ impl LocalContainerService {
         Ok(())
     }
 
+    /// Persist the executor's structured summary of a finished coding agent run
+    /// and what the run used
+    async fn update_execution_summary(
+        &self,
+        ctx: &ExecutionContext,
+        exit_code: Option<i64>,
+    ) -> Result<(), anyhow::Error> {
+        let exec_id = ctx.execution_process.id;
+        let Some(profile_id) = ctx
+            .execution_process
+            .executor_action()?
+            .executor_profile_id()
+        else {
+            return Ok(());
+        };
+        let Some(msg_store) = self.get_msg_store_by_id(&exec_id).await else {
+            return Ok(());
+        };
+
+        let executor = ExecutorConfigs::get_cached().get_coding_agent_or_default(profile_id);
+        let mut summary = executor.summarize(msg_store);
+        summary.exit_code = exit_code;
+        CodingAgentTurn::update_execution_summary(&self.db.pool, exec_id, &summary).await?;
+
+        let process = &ctx.execution_process;
+        let duration = process.completed_at.unwrap_or_else(Utc::now) - process.started_at;
+        attempt_usage::record(
+            &self.db.pool,
+            exec_id,
+            summary
+                .token_usage
+                .map(|usage| i64::from(usage.total_tokens)),
+            duration.num_milliseconds().max(0),
+        )
+        .await?;
+
+        Ok(())
+    }
+
     /// Copy project files and images to the workspace.
     /// Skips files/images that already exist (fast no-op if all exist).
     async fn copy_files_and_images(
@@ -1133,9 +1372,14 @@ impl ContainerService for LocalContainerService {
         env.insert("VK_PROJECT_NAME", &project.name);
         env.insert("VK_PROJECT_ID", project.id.to_string());
         env.insert("VK_TASK_ID", task.id.to_string());
+        env.insert("VK_TASK_TITLE", &task.title);
         env.insert("VK_WORKSPACE_ID", workspace.id.to_string());
         env.insert("VK_WORKSPACE_BRANCH", &workspace.branch);
 
+        // Follow-ups in the same workspace share its scratch directory
+        let scratch = scratch_dir::ensure(workspace.id).await?;
+        env.insert("VK_SCRATCH_DIR", scratch.to_string_lossy());
+
         // Create the child and stream, add to execution tracker with timeout
         let mut spawned = tokio::time::timeout(
             Duration::from_secs(30),
@@ -1148,8 +1392,27 @@ impl ContainerService for LocalContainerService {
             ))
         })??;
 
-        self.track_child_msgs_in_store(execution_process.id, &mut spawned.child)
-            .await;
+        if let Some(ids) = spawned.process_ids() {
+            self.process_ids
+                .write()
+                .await
+                .insert(execution_process.id, ids);
+        }
+
+        let redactor = Redactor::new(executor_action.secret_values(&env).await);
+        self.track_child_msgs_in_store(
+            execution_process.id,
+            &mut spawned.child,
+            redactor,
+            executor_action.output_limit_bytes(),
+        )
+        .await;
+
+        if let Some(limit) = self.config.read().await.scratch_dir_limit_bytes
+            && let Some(store) = self.get_msg_store_by_id(&execution_process.id).await
+        {
+            scratch_dir::spawn_size_watch(store, scratch, limit);
+        }
 
         self.add_child_to_store(execution_process.id, spawned.child)
             .await;
@@ -1218,8 +1481,9 @@ impl ContainerService for LocalContainerService {
 
         // Kill the child process and remove from the store
         {
+            let pgid = self.recorded_pgid(&execution_process.id).await;
             let mut child_guard = child.write().await;
-            if let Err(e) = command::kill_process_group(&mut child_guard).await {
+            if let Err(e) = command::kill_process_group(&mut child_guard, pgid).await {
                 tracing::error!(
                     "Failed to stop execution process {}: {}",
                     execution_process.id,
@@ -1242,7 +1506,14 @@ impl ContainerService for LocalContainerService {
                 ExecutionProcessRunReason::DevServer
             )
         {
-            match Task::update_status(&self.db.pool, ctx.task.id, TaskStatus::InReview).await {
+            match task_transitions::set_status(
+                &self.db.pool,
+                ctx.task.id,
+                TaskStatus::InReview,
+                TaskEventActor::System,
+            )
+            .await
+            {
                 Ok(_) => {
                     if let Some(publisher) = self.share_publisher()
                         && let Err(err) = publisher.update_shared_task_by_id(ctx.task.id).await
@@ -1271,6 +1542,10 @@ impl ContainerService for LocalContainerService {
         Ok(())
     }
 
+    async fn process_ids(&self, execution_id: &Uuid) -> Option<ProcessIds> {
+        self.process_ids.read().await.get(execution_id).copied()
+    }
+
     async fn stream_diff(
         &self,
         workspace: &Workspace,
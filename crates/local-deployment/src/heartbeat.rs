@@ -0,0 +1,89 @@
+use std::{sync::Arc, time::Duration};
+
+use executors::logs::{NormalizedEntry, NormalizedEntryType, utils::patch::ConversationPatch};
+use tokio::{
+    sync::broadcast::error::RecvError,
+    task::JoinHandle,
+    time::{Instant, sleep_until},
+};
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+
+/// Watch `store` and, while the child has been silent for `interval`, push a
+/// heartbeat entry every `interval` until output resumes or the run finishes.
+/// Heartbeats are sent with [`MsgStore::push_transient`], so they never reach
+/// history or the persisted logs.
+pub fn spawn(store: Arc<MsgStore>, interval: Duration) -> JoinHandle<()> {
+    let mut rx = store.get_receiver();
+    tokio::spawn(async move {
+        let started = Instant::now();
+        let mut last_output = started;
+        let mut next_beat = started + interval;
+        let mut showing = false;
+
+        loop {
+            tokio::select! {
+                msg = rx.recv() => match msg {
+                    Ok(LogMsg::Stdout(_) | LogMsg::Stderr(_)) => {
+                        last_output = Instant::now();
+                        next_beat = last_output + interval;
+                        if showing {
+                            store.push_transient(LogMsg::JsonPatch(
+                                ConversationPatch::clear_heartbeat(),
+                            ));
+                            showing = false;
+                        }
+                    }
+                    Ok(LogMsg::Finished) | Err(RecvError::Closed) => break,
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                },
+                _ = sleep_until(next_beat) => {
+                    let now = Instant::now();
+                    let entry = heartbeat_entry(now - started, now - last_output);
+                    store.push_transient(LogMsg::JsonPatch(
+                        ConversationPatch::set_heartbeat(entry),
+                    ));
+                    showing = true;
+                    next_beat = now + interval;
+                }
+            }
+        }
+
+        if showing {
+            store.push_transient(LogMsg::JsonPatch(ConversationPatch::clear_heartbeat()));
+        }
+    })
+}
+
+fn heartbeat_entry(elapsed: Duration, silent: Duration) -> NormalizedEntry {
+    NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::Heartbeat,
+        content: format!(
+            "Still running, {} elapsed, no output for {}",
+            format_duration(elapsed),
+            format_duration(silent)
+        ),
+        metadata: None,
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs % 3600 / 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m{s:02}s"),
+        (h, m, s) => format!("{h}h{m:02}m{s:02}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_durations_compactly() {
+        assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+        assert_eq!(format_duration(Duration::from_secs(192)), "3m12s");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "1h02m05s");
+    }
+}
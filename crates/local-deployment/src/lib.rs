@@ -1,7 +1,7 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
-use db::DBService;
+use db::{DBOptions, DBService};
 use deployment::{Deployment, DeploymentError, RemoteClientNotConfigured};
 use executors::profile::ExecutorConfigs;
 use services::services::{
@@ -10,6 +10,7 @@ use services::services::{
     auth::AuthContext,
     config::{Config, load_config_from_file, save_config_to_file},
     container::ContainerService,
+    event_bus::EventBus,
     events::EventService,
     file_search::FileSearchCache,
     filesystem::FilesystemService,
@@ -34,7 +35,9 @@ use crate::{container::LocalContainerService, pty::PtyService};
 mod command;
 pub mod container;
 mod copy;
+mod heartbeat;
 pub mod pty;
+mod scratch_dir;
 
 #[derive(Clone)]
 pub struct LocalDeployment {
@@ -49,6 +52,7 @@ pub struct LocalDeployment {
     image: ImageService,
     filesystem: FilesystemService,
     events: EventService,
+    event_bus: EventBus,
     file_search_cache: Arc<FileSearchCache>,
     approvals: Approvals,
     queued_message_service: QueuedMessageService,
@@ -66,6 +70,19 @@ struct PendingHandoff {
     app_verifier: String,
 }
 
+/// Database settings from the config, falling back to the defaults
+fn db_options(config: &Config) -> DBOptions {
+    let defaults = DBOptions::default();
+    DBOptions {
+        max_connections: config
+            .database_max_connections
+            .unwrap_or(defaults.max_connections),
+        busy_timeout: config
+            .database_busy_timeout_ms
+            .map_or(defaults.busy_timeout, Duration::from_millis),
+    }
+}
+
 #[async_trait]
 impl Deployment for LocalDeployment {
     async fn new() -> Result<Self, DeploymentError> {
@@ -93,6 +110,7 @@ impl Deployment for LocalDeployment {
         // Always save config (may have been migrated or version updated)
         save_config_to_file(&raw_config, &config_path()).await?;
 
+        let db_options = db_options(&raw_config);
         let config = Arc::new(RwLock::new(raw_config));
         let user_id = generate_user_id();
         let analytics = AnalyticsConfig::new().map(AnalyticsService::new);
@@ -111,9 +129,9 @@ impl Deployment for LocalDeployment {
             let hook = EventService::create_hook(
                 events_msg_store.clone(),
                 events_entry_count.clone(),
-                DBService::new().await?, // Temporary DB service for the hook
+                DBService::new(&db_options).await?, // Temporary DB service for the hook
             );
-            DBService::new_with_after_connect(hook).await?
+            DBService::new_with_after_connect(&db_options, hook).await?
         };
 
         let image = ImageService::new(db.clone().pool)?;
@@ -205,6 +223,7 @@ impl Deployment for LocalDeployment {
             image,
             filesystem,
             events,
+            event_bus: EventBus::new(),
             file_search_cache,
             approvals,
             queued_message_service,
@@ -263,6 +282,10 @@ impl Deployment for LocalDeployment {
         &self.events
     }
 
+    fn event_bus(&self) -> &EventBus {
+        &self.event_bus
+    }
+
     fn file_search_cache(&self) -> &Arc<FileSearchCache> {
         &self.file_search_cache
     }
@@ -0,0 +1,130 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use tokio::sync::broadcast::error::RecvError;
+use utils::{log_msg::LogMsg, msg_store::MsgStore, path::get_vibe_kanban_temp_dir};
+use uuid::Uuid;
+
+/// Present in a scratch directory that should survive workspace cleanup
+const RETAIN_MARKER: &str = ".vk-retain";
+
+const SIZE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Scratch directory shared by every execution of a workspace, kept outside
+/// the worktree so its contents never show up in diffs
+pub fn path_for(workspace_id: Uuid) -> PathBuf {
+    get_vibe_kanban_temp_dir()
+        .join("scratch")
+        .join(workspace_id.to_string())
+}
+
+pub async fn ensure(workspace_id: Uuid) -> std::io::Result<PathBuf> {
+    let dir = path_for(workspace_id);
+    tokio::fs::create_dir_all(&dir).await?;
+    Ok(dir)
+}
+
+/// Mark the directory to be kept (after a failed run) or released again
+/// (after a later run succeeds)
+pub async fn set_retained(workspace_id: Uuid, retain: bool) {
+    let marker = path_for(workspace_id).join(RETAIN_MARKER);
+    let result = if retain {
+        tokio::fs::write(&marker, b"").await
+    } else {
+        match tokio::fs::remove_file(&marker).await {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            other => other,
+        }
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to update scratch retain marker {:?}: {}", marker, e);
+    }
+}
+
+/// Remove the workspace's scratch directory unless a failed run asked to keep it
+pub async fn remove(workspace_id: Uuid) {
+    let dir = path_for(workspace_id);
+    if !dir.exists() {
+        return;
+    }
+    if dir.join(RETAIN_MARKER).exists() {
+        tracing::info!("Keeping scratch directory {:?} of failed workspace", dir);
+        return;
+    }
+    if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+        tracing::warn!("Failed to remove scratch directory {:?}: {}", dir, e);
+    }
+}
+
+/// Total size of the regular files below `dir`. Symlinks are not followed.
+pub fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(ft) if ft.is_dir() => dir_size(&entry.path()),
+            Ok(ft) if ft.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Check the scratch directory periodically while the execution runs and
+/// report once in its logs if it grows beyond `limit` bytes
+pub fn spawn_size_watch(store: Arc<MsgStore>, dir: PathBuf, limit: u64) {
+    let mut rx = store.get_receiver();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SIZE_CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                msg = rx.recv() => match msg {
+                    Ok(LogMsg::Finished) | Err(RecvError::Closed) => break,
+                    _ => {}
+                },
+                _ = interval.tick() => {
+                    let measured = dir.clone();
+                    let size = tokio::task::spawn_blocking(move || dir_size(&measured))
+                        .await
+                        .unwrap_or(0);
+                    if size > limit {
+                        tracing::warn!(
+                            "Scratch directory {:?} holds {} bytes, over the {} byte limit",
+                            dir,
+                            size,
+                            limit
+                        );
+                        store.push_stderr(format!(
+                            "\n[scratch directory {} holds {size} bytes, over the {limit} byte \
+                             limit]\n",
+                            dir.display()
+                        ));
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dir_size_counts_nested_files() {
+        let dir = std::env::temp_dir().join(format!("vk-scratch-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("a.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.join("nested").join("b.bin"), vec![0u8; 28]).unwrap();
+
+        let size = dir_size(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(size, 128);
+    }
+}
@@ -18,6 +18,7 @@ fn generate_types_content() -> String {
         db::models::project::Project::decl(),
         db::models::project::CreateProject::decl(),
         db::models::project::UpdateProject::decl(),
+        db::models::project_settings::ProjectSettings::decl(),
         db::models::project::SearchResult::decl(),
         db::models::project::SearchMatchType::decl(),
         db::models::repo::Repo::decl(),
@@ -31,11 +32,53 @@ fn generate_types_content() -> String {
         db::models::tag::CreateTag::decl(),
         db::models::tag::UpdateTag::decl(),
         db::models::task::TaskStatus::decl(),
+        db::models::project_status::ProjectStatus::decl(),
+        db::models::project_status::CreateProjectStatus::decl(),
+        db::models::project_status::UpdateProjectStatus::decl(),
+        db::models::project_status::StatusOccupant::decl(),
+        db::models::project_status::StatusOccupancy::decl(),
+        db::models::project_stats::StatusCount::decl(),
+        db::models::project_stats::StatusDuration::decl(),
+        db::models::project_stats::DurationPercentiles::decl(),
+        db::models::project_stats::WeeklyThroughput::decl(),
+        db::models::attempt_usage::UsageTotals::decl(),
+        db::models::attempt_usage::AttemptUsage::decl(),
+        db::models::attempt_usage::TaskUsage::decl(),
+        db::models::attempt_usage::MonthlyUsage::decl(),
+        db::models::attempt_usage::ProjectUsage::decl(),
+        db::models::task::TaskPriority::decl(),
         db::models::task::Task::decl(),
         db::models::task::TaskWithAttemptStatus::decl(),
+        db::models::task::TaskSearchResult::decl(),
         db::models::task::TaskRelationships::decl(),
         db::models::task::CreateTask::decl(),
         db::models::task::UpdateTask::decl(),
+        db::models::task::MoveTask::decl(),
+        db::models::task::MoveTaskToProject::decl(),
+        db::models::task::DuplicateTask::decl(),
+        db::models::task::DuplicatedTask::decl(),
+        db::models::task::AssigneeTaskCount::decl(),
+        db::models::task::DueDateChange::decl(),
+        db::models::task::DueTask::decl(),
+        db::models::task::TaskBatchItemError::decl(),
+        db::models::task_dependency::TaskDependency::decl(),
+        db::models::task_dependency::CreateTaskDependency::decl(),
+        db::models::task_dependency::TaskDependencies::decl(),
+        db::models::task_event::TaskEventType::decl(),
+        db::models::task_event::TaskEventActor::decl(),
+        db::models::task_event::TaskEvent::decl(),
+        db::models::task_external_link::ExternalProvider::decl(),
+        db::models::task_external_link::ExternalIssueState::decl(),
+        db::models::task_external_link::TaskExternalLink::decl(),
+        db::models::task_template::TaskTemplate::decl(),
+        db::models::task_template::CreateTaskTemplate::decl(),
+        db::models::task_template::UpdateTaskTemplate::decl(),
+        db::models::webhook::WebhookEventType::decl(),
+        db::models::webhook::WebhookDeliveryStatus::decl(),
+        db::models::webhook::Webhook::decl(),
+        db::models::webhook::CreateWebhook::decl(),
+        db::models::webhook::UpdateWebhook::decl(),
+        db::models::webhook::WebhookDelivery::decl(),
         db::models::scratch::DraftFollowUpData::decl(),
         db::models::scratch::DraftWorkspaceData::decl(),
         db::models::scratch::DraftWorkspaceRepo::decl(),
@@ -54,6 +97,9 @@ fn generate_types_content() -> String {
         db::models::execution_process::ExecutionProcessStatus::decl(),
         db::models::execution_process::ExecutionProcessRunReason::decl(),
         db::models::execution_process_repo_state::ExecutionProcessRepoState::decl(),
+        db::models::approval_event::ApprovalDecision::decl(),
+        db::models::approval_event::ApprovalDecider::decl(),
+        db::models::approval_event::ApprovalEvent::decl(),
         db::models::merge::Merge::decl(),
         db::models::merge::DirectMerge::decl(),
         db::models::merge::PrMerge::decl(),
@@ -65,6 +111,8 @@ fn generate_types_content() -> String {
         utils::diff::Diff::decl(),
         utils::diff::DiffChangeKind::decl(),
         utils::response::ApiResponse::<()>::decl(),
+        utils::msg_store::IndexedLogMsg::decl(),
+        utils::msg_store::LogPage::decl(),
         utils::api::oauth::LoginStatus::decl(),
         utils::api::oauth::ProfileResponse::decl(),
         utils::api::oauth::ProviderProfile::decl(),
@@ -95,9 +143,16 @@ fn generate_types_content() -> String {
         utils::api::projects::RemoteProjectMembersResponse::decl(),
         server::routes::projects::CreateRemoteProjectRequest::decl(),
         server::routes::projects::LinkToExistingRequest::decl(),
+        server::routes::projects::ImportGitHubIssuesRequest::decl(),
         server::routes::repo::RegisterRepoRequest::decl(),
         server::routes::repo::InitRepoRequest::decl(),
         server::routes::tags::TagSearchParams::decl(),
+        server::routes::task_templates::TaskTemplateQuery::decl(),
+        server::routes::approvals::RespondToPendingApprovalsRequest::decl(),
+        server::routes::approvals::RespondToPendingApprovalsResponse::decl(),
+        services::services::approvals::PendingApprovalInfo::decl(),
+        server::routes::acp_sessions::CleanupAcpSessionsRequest::decl(),
+        server::routes::acp_sessions::ListAcpSessionsQuery::decl(),
         server::routes::oauth::TokenResponse::decl(),
         server::routes::config::UserSystemInfo::decl(),
         server::routes::config::Environment::decl(),
@@ -121,6 +176,7 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::OpenEditorResponse::decl(),
         server::routes::shared_tasks::AssignSharedTaskRequest::decl(),
         server::routes::tasks::ShareTaskResponse::decl(),
+        server::routes::tasks::DueTasks::decl(),
         server::routes::tasks::CreateAndStartTaskRequest::decl(),
         server::routes::task_attempts::pr::CreatePrApiRequest::decl(),
         server::routes::images::ImageResponse::decl(),
@@ -144,6 +200,17 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::pr::GetPrCommentsQuery::decl(),
         services::services::git_host::UnifiedPrComment::decl(),
         services::services::git_host::ProviderKind::decl(),
+        services::services::wip_limits::WipLimitOverride::decl(),
+        services::services::project_bundle::NameConflict::decl(),
+        services::services::project_bundle::ProjectImport::decl(),
+        services::services::github_issues::IssueImportReport::decl(),
+        services::services::task_import::TaskImportFormat::decl(),
+        services::services::task_import::ColumnMapping::decl(),
+        services::services::task_import::TaskImportOptions::decl(),
+        services::services::task_import::TaskImportRowError::decl(),
+        services::services::task_import::TaskImportReport::decl(),
+        services::services::event_bus::ProjectChange::decl(),
+        services::services::project_stats::ProjectStats::decl(),
         server::routes::task_attempts::RepoBranchStatus::decl(),
         server::routes::task_attempts::UpdateWorkspace::decl(),
         server::routes::task_attempts::workspace_summary::WorkspaceSummaryRequest::decl(),
@@ -168,6 +235,9 @@ fn generate_types_content() -> String {
         services::services::queued_message::QueuedMessage::decl(),
         services::services::queued_message::QueueStatus::decl(),
         services::services::git::ConflictOp::decl(),
+        services::services::acp_sessions::AcpSessionSummary::decl(),
+        services::services::acp_sessions::AcpSessionPage::decl(),
+        services::services::acp_sessions::AcpSessionTranscript::decl(),
         executors::actions::ExecutorAction::decl(),
         executors::mcp_config::McpConfig::decl(),
         executors::actions::ExecutorActionType::decl(),
@@ -177,10 +247,16 @@ fn generate_types_content() -> String {
         executors::executors::BaseCodingAgent::decl(),
         executors::executors::CodingAgent::decl(),
         executors::executors::AvailabilityInfo::decl(),
+        executors::executors::ProcessIds::decl(),
+        executors::executors::ExecutorErrorKind::decl(),
+        executors::executors::acp::SessionFileInfo::decl(),
         executors::command::CommandBuilder::decl(),
         executors::profile::ExecutorProfileId::decl(),
         executors::profile::ExecutorConfig::decl(),
         executors::profile::ExecutorConfigs::decl(),
+        executors::profile::ProfileIssueSeverity::decl(),
+        executors::profile::ProfileIssue::decl(),
+        executors::profile::ProfilesReport::decl(),
         executors::executors::BaseAgentCapability::decl(),
         executors::executors::claude::ClaudeCode::decl(),
         executors::executors::gemini::Gemini::decl(),
@@ -216,6 +292,8 @@ fn generate_types_content() -> String {
         executors::logs::ToolResultValueType::decl(),
         executors::logs::ToolStatus::decl(),
         executors::logs::utils::patch::PatchType::decl(),
+        executors::logs::summary::ExecutionSummary::decl(),
+        executors::logs::summary::CommandSummary::decl(),
         serde_json::Value::decl(),
     ];
 
@@ -5,24 +5,46 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use db::models::{
-    execution_process::ExecutionProcessError, project::ProjectError,
-    project_repo::ProjectRepoError, repo::RepoError, scratch::ScratchError, session::SessionError,
+    execution_process::ExecutionProcessError,
+    project::ProjectError,
+    project_repo::ProjectRepoError,
+    project_settings::ProjectSettingsError,
+    project_status::ProjectStatusError,
+    repo::RepoError,
+    scratch::ScratchError,
+    session::SessionError,
+    task::{TaskBatchError, TaskMoveError, TaskProjectMoveError},
+    task_dependency::TaskDependencyError,
+    task_recurrence::RecurrenceError,
+    task_schedule::TaskScheduleError,
     workspace::WorkspaceError,
 };
 use deployment::{DeploymentError, RemoteClientNotConfigured};
-use executors::{command::CommandBuildError, executors::ExecutorError};
+use executors::{
+    command::CommandBuildError,
+    executors::{ExecutorError, ExecutorErrorKind},
+};
 use git2::Error as Git2Error;
 use local_deployment::pty::PtyError;
 use services::services::{
+    acp_sessions::AcpSessionError,
     config::{ConfigError, EditorOpenError},
     container::ContainerError,
     git::GitServiceError,
     git_host::GitHostError,
+    github_issues::GitHubIssuesError,
     image::ImageError,
     project::ProjectServiceError,
+    project_bundle::ProjectBundleError,
+    project_stats::ProjectStatsError,
     remote_client::RemoteClientError,
     repo::RepoError as RepoServiceError,
     share::ShareError,
+    task_import::TaskImportError,
+    task_transitions::TaskTransitionError,
+    tasks::{AttemptStartError, TaskCreateError, TaskUpdateError},
+    webhooks::WebhookError,
+    wip_limits::WipLimitError,
     worktree_manager::WorktreeError,
 };
 use thiserror::Error;
@@ -44,6 +66,36 @@ pub enum ApiError {
     #[error(transparent)]
     ExecutionProcess(#[from] ExecutionProcessError),
     #[error(transparent)]
+    TaskDependency(#[from] TaskDependencyError),
+    #[error(transparent)]
+    TaskTransition(TaskTransitionError),
+    #[error(transparent)]
+    TaskMove(#[from] TaskMoveError),
+    #[error(transparent)]
+    TaskProjectMove(#[from] TaskProjectMoveError),
+    #[error(transparent)]
+    TaskBatch(#[from] TaskBatchError),
+    #[error(transparent)]
+    WipLimit(#[from] WipLimitError),
+    #[error(transparent)]
+    ProjectStatus(#[from] ProjectStatusError),
+    #[error(transparent)]
+    ProjectSettings(#[from] ProjectSettingsError),
+    #[error(transparent)]
+    ProjectBundle(#[from] ProjectBundleError),
+    #[error(transparent)]
+    ProjectStats(#[from] ProjectStatsError),
+    #[error(transparent)]
+    TaskImport(#[from] TaskImportError),
+    #[error(transparent)]
+    GitHubIssues(#[from] GitHubIssuesError),
+    #[error(transparent)]
+    Webhook(#[from] WebhookError),
+    #[error(transparent)]
+    TaskSchedule(#[from] TaskScheduleError),
+    #[error(transparent)]
+    Recurrence(#[from] RecurrenceError),
+    #[error(transparent)]
     GitService(#[from] GitServiceError),
     #[error(transparent)]
     GitHost(#[from] GitHostError),
@@ -81,6 +133,8 @@ pub enum ApiError {
     CommandBuilder(#[from] CommandBuildError),
     #[error(transparent)]
     Pty(#[from] PtyError),
+    #[error(transparent)]
+    AcpSession(#[from] AcpSessionError),
 }
 
 impl From<&'static str> for ApiError {
@@ -127,6 +181,16 @@ impl IntoResponse for ApiError {
             },
             ApiError::GitHost(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHostError"),
             ApiError::Deployment(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DeploymentError"),
+            // A task refusing the move to In Progress when an attempt starts
+            ApiError::Container(ContainerError::TaskTransition(err))
+                if !matches!(
+                    err,
+                    TaskTransitionError::Database(_)
+                        | TaskTransitionError::WipLimit(WipLimitError::Database(_))
+                ) =>
+            {
+                (StatusCode::CONFLICT, "TaskTransitionError")
+            }
             ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
             ApiError::CommandBuilder(_) => (StatusCode::INTERNAL_SERVER_ERROR, "CommandBuildError"),
@@ -179,6 +243,118 @@ impl IntoResponse for ApiError {
                     (StatusCode::BAD_REQUEST, "RemoteClientError")
                 }
             },
+            ApiError::TaskDependency(err) => match err {
+                TaskDependencyError::SelfDependency => {
+                    (StatusCode::BAD_REQUEST, "TaskDependencyError")
+                }
+                TaskDependencyError::BlockerNotFound => {
+                    (StatusCode::NOT_FOUND, "TaskDependencyError")
+                }
+                TaskDependencyError::Cycle(_) => (StatusCode::CONFLICT, "TaskDependencyError"),
+                TaskDependencyError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "TaskDependencyError")
+                }
+            },
+            ApiError::TaskTransition(err) => match err {
+                TaskTransitionError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "TaskTransitionError")
+                }
+                _ => (StatusCode::CONFLICT, "TaskTransitionError"),
+            },
+            ApiError::TaskMove(err) => match err {
+                TaskMoveError::NotFound => (StatusCode::NOT_FOUND, "TaskMoveError"),
+                TaskMoveError::AnchorNotInColumn(_) => (StatusCode::BAD_REQUEST, "TaskMoveError"),
+                TaskMoveError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "TaskMoveError"),
+            },
+            ApiError::TaskProjectMove(err) => match err {
+                TaskProjectMoveError::NotFound => (StatusCode::NOT_FOUND, "TaskProjectMoveError"),
+                TaskProjectMoveError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "TaskProjectMoveError")
+                }
+                _ => (StatusCode::BAD_REQUEST, "TaskProjectMoveError"),
+            },
+            ApiError::TaskBatch(err) => match err {
+                TaskBatchError::Invalid(_) => (StatusCode::UNPROCESSABLE_ENTITY, "TaskBatchError"),
+                TaskBatchError::TooLarge(_) => (StatusCode::BAD_REQUEST, "TaskBatchError"),
+                TaskBatchError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "TaskBatchError")
+                }
+            },
+            ApiError::WipLimit(err) => match err {
+                WipLimitError::Exceeded { .. } => (StatusCode::CONFLICT, "WipLimitError"),
+                WipLimitError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "WipLimitError"),
+            },
+            ApiError::ProjectSettings(err) => match err {
+                ProjectSettingsError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "ProjectSettingsError")
+                }
+                ProjectSettingsError::ProjectNotFound => {
+                    (StatusCode::NOT_FOUND, "ProjectSettingsError")
+                }
+                ProjectSettingsError::Invalid(_) => {
+                    (StatusCode::BAD_REQUEST, "ProjectSettingsError")
+                }
+            },
+            ApiError::ProjectBundle(err) => match err {
+                ProjectBundleError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "ProjectBundleError")
+                }
+                ProjectBundleError::ProjectNotFound => {
+                    (StatusCode::NOT_FOUND, "ProjectBundleError")
+                }
+                ProjectBundleError::UnsupportedVersion(_) => {
+                    (StatusCode::BAD_REQUEST, "ProjectBundleError")
+                }
+            },
+            ApiError::ProjectStats(err) => match err {
+                ProjectStatsError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "ProjectStatsError")
+                }
+                ProjectStatsError::InvalidRange => (StatusCode::BAD_REQUEST, "ProjectStatsError"),
+            },
+            ApiError::TaskImport(err) => match err {
+                TaskImportError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "TaskImportError")
+                }
+                TaskImportError::ProjectArchived => (StatusCode::CONFLICT, "TaskImportError"),
+                _ => (StatusCode::BAD_REQUEST, "TaskImportError"),
+            },
+            ApiError::GitHubIssues(err) => match err {
+                GitHubIssuesError::InvalidRepository(_) => {
+                    (StatusCode::BAD_REQUEST, "GitHubIssuesError")
+                }
+                GitHubIssuesError::Api { .. } | GitHubIssuesError::Http(_) => {
+                    (StatusCode::BAD_GATEWAY, "GitHubIssuesError")
+                }
+                GitHubIssuesError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "GitHubIssuesError")
+                }
+                GitHubIssuesError::ProjectArchived => (StatusCode::CONFLICT, "GitHubIssuesError"),
+            },
+            ApiError::Webhook(err) => match err {
+                WebhookError::NotFound => (StatusCode::NOT_FOUND, "WebhookError"),
+                WebhookError::InvalidUrl(_) => (StatusCode::BAD_REQUEST, "WebhookError"),
+                WebhookError::Database(_) | WebhookError::Http(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "WebhookError")
+                }
+            },
+            ApiError::ProjectStatus(err) => match err {
+                ProjectStatusError::NotFound => (StatusCode::NOT_FOUND, "ProjectStatusError"),
+                ProjectStatusError::DuplicateKey(_) | ProjectStatusError::InUse(_) => {
+                    (StatusCode::CONFLICT, "ProjectStatusError")
+                }
+                ProjectStatusError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "ProjectStatusError")
+                }
+                _ => (StatusCode::BAD_REQUEST, "ProjectStatusError"),
+            },
+            ApiError::TaskSchedule(err) => match err {
+                TaskScheduleError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "TaskScheduleError")
+                }
+                _ => (StatusCode::BAD_REQUEST, "TaskScheduleError"),
+            },
+            ApiError::Recurrence(_) => (StatusCode::BAD_REQUEST, "RecurrenceError"),
             ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized"),
             ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BadRequest"),
             ApiError::Conflict(_) => (StatusCode::CONFLICT, "ConflictError"),
@@ -188,6 +364,12 @@ impl IntoResponse for ApiError {
                 PtyError::SessionClosed => (StatusCode::GONE, "PtyError"),
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, "PtyError"),
             },
+            ApiError::AcpSession(err) => match err {
+                AcpSessionError::UnknownNamespace(_) | AcpSessionError::SessionNotFound(_) => {
+                    (StatusCode::NOT_FOUND, "AcpSessionError")
+                }
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "AcpSessionError"),
+            },
         };
 
         let error_message = match &self {
@@ -260,12 +442,95 @@ impl IntoResponse for ApiError {
                 RemoteClientError::Serde(_) => "Unexpected response from remote service.".to_string(),
                 RemoteClientError::Url(_) => "Remote service URL is invalid.".to_string(),
             },
+            ApiError::TaskDependency(
+                err @ (TaskDependencyError::SelfDependency
+                | TaskDependencyError::BlockerNotFound
+                | TaskDependencyError::Cycle(_)),
+            ) => err.to_string(),
+            ApiError::TaskTransition(err) | ApiError::Container(ContainerError::TaskTransition(err))
+                if !matches!(
+                    err,
+                    TaskTransitionError::Database(_)
+                        | TaskTransitionError::WipLimit(WipLimitError::Database(_))
+                ) =>
+            {
+                err.to_string()
+            }
+            ApiError::TaskMove(
+                err @ (TaskMoveError::NotFound | TaskMoveError::AnchorNotInColumn(_)),
+            ) => err.to_string(),
+            ApiError::TaskProjectMove(err) if !matches!(err, TaskProjectMoveError::Database(_)) => {
+                err.to_string()
+            }
+            ApiError::TaskBatch(err @ TaskBatchError::Invalid(_)) => {
+                format!("{err}, nothing was created")
+            }
+            ApiError::TaskBatch(err @ TaskBatchError::TooLarge(_)) => err.to_string(),
+            ApiError::WipLimit(err @ WipLimitError::Exceeded { .. }) => err.to_string(),
+            ApiError::ProjectSettings(err) if !matches!(err, ProjectSettingsError::Database(_)) => {
+                err.to_string()
+            }
+            ApiError::ProjectBundle(err) if !matches!(err, ProjectBundleError::Database(_)) => {
+                err.to_string()
+            }
+            ApiError::ProjectStats(err) if !matches!(err, ProjectStatsError::Database(_)) => {
+                err.to_string()
+            }
+            ApiError::TaskImport(err) if !matches!(err, TaskImportError::Database(_)) => {
+                err.to_string()
+            }
+            ApiError::GitHubIssues(err) if !matches!(err, GitHubIssuesError::Database(_)) => {
+                err.to_string()
+            }
+            ApiError::Webhook(err) if !matches!(err, WebhookError::Database(_)) => err.to_string(),
+            ApiError::ProjectStatus(err) if !matches!(err, ProjectStatusError::Database(_)) => {
+                err.to_string()
+            }
+            ApiError::TaskSchedule(err) if !matches!(err, TaskScheduleError::Database(_)) => {
+                err.to_string()
+            }
+            ApiError::Recurrence(err) => err.to_string(),
             ApiError::Unauthorized => "Unauthorized. Please sign in again.".to_string(),
             ApiError::BadRequest(msg) => msg.clone(),
             ApiError::Conflict(msg) => msg.clone(),
             ApiError::Forbidden(msg) => msg.clone(),
             _ => format!("{}: {}", error_type, self),
         };
+        // Executor failures carry their kind so the UI can suggest a fix
+        let executor_error = match &self {
+            ApiError::Executor(err) | ApiError::Container(ContainerError::ExecutorError(err)) => {
+                Some(err)
+            }
+            _ => None,
+        };
+        if let Some(err) = executor_error {
+            let response = ApiResponse::<(), ExecutorErrorKind>::error_with_message_and_data(
+                &error_message,
+                err.kind(),
+            );
+            return (status_code, Json(response)).into_response();
+        }
+
+        // So the UI can point at the rejected tasks
+        if let ApiError::TaskBatch(TaskBatchError::Invalid(errors)) = &self {
+            let response =
+                ApiResponse::<(), _>::error_with_message_and_data(&error_message, errors.clone());
+            return (status_code, Json(response)).into_response();
+        }
+
+        // So the UI can show which tasks fill the column
+        if let ApiError::WipLimit(WipLimitError::Exceeded { occupants, .. })
+        | ApiError::Container(ContainerError::TaskTransition(TaskTransitionError::WipLimit(
+            WipLimitError::Exceeded { occupants, .. },
+        ))) = &self
+        {
+            let response = ApiResponse::<(), _>::error_with_message_and_data(
+                &error_message,
+                occupants.clone(),
+            );
+            return (status_code, Json(response)).into_response();
+        }
+
         let response = ApiResponse::<()>::error(&error_message);
         (status_code, Json(response)).into_response()
     }
@@ -330,6 +595,7 @@ impl From<ProjectServiceError> for ApiError {
             ProjectServiceError::Database(db_err) => ApiError::Database(db_err),
             ProjectServiceError::Io(io_err) => ApiError::Io(io_err),
             ProjectServiceError::Project(proj_err) => ApiError::Project(proj_err),
+            ProjectServiceError::Settings(settings_err) => ApiError::ProjectSettings(settings_err),
             ProjectServiceError::Share(share_err) => ApiError::from(share_err),
             ProjectServiceError::PathNotFound(path) => {
                 ApiError::BadRequest(format!("Path does not exist: {}", path.display()))
@@ -346,6 +612,9 @@ impl From<ProjectServiceError> for ApiError {
             ProjectServiceError::DuplicateRepositoryName => ApiError::Conflict(
                 "A repository with this name already exists in the project".to_string(),
             ),
+            ProjectServiceError::ProjectArchived => ApiError::Conflict(
+                "Project is archived, unarchive it before adding tasks or attempts".to_string(),
+            ),
             ProjectServiceError::RepositoryNotFound => {
                 ApiError::BadRequest("Repository not found".to_string())
             }
@@ -359,6 +628,52 @@ impl From<ProjectServiceError> for ApiError {
     }
 }
 
+impl From<TaskCreateError> for ApiError {
+    fn from(err: TaskCreateError) -> Self {
+        match err {
+            TaskCreateError::Database(db_err)
+            | TaskCreateError::Batch(TaskBatchError::Database(db_err)) => {
+                ApiError::Database(db_err)
+            }
+            TaskCreateError::Batch(batch_err) => ApiError::TaskBatch(batch_err),
+            err @ TaskCreateError::ProjectArchived => ApiError::Conflict(err.to_string()),
+        }
+    }
+}
+
+impl From<TaskUpdateError> for ApiError {
+    fn from(err: TaskUpdateError) -> Self {
+        match err {
+            TaskUpdateError::Database(db_err) => ApiError::Database(db_err),
+            TaskUpdateError::Move(move_err) => ApiError::TaskMove(move_err),
+            TaskUpdateError::Schedule(schedule_err) => ApiError::TaskSchedule(schedule_err),
+            TaskUpdateError::Transition(transition_err) => ApiError::from(transition_err),
+            TaskUpdateError::ProjectMove(move_err) => ApiError::TaskProjectMove(move_err),
+        }
+    }
+}
+
+impl From<TaskTransitionError> for ApiError {
+    fn from(err: TaskTransitionError) -> Self {
+        match err {
+            TaskTransitionError::Database(db_err) => ApiError::Database(db_err),
+            TaskTransitionError::WipLimit(wip_err) => ApiError::WipLimit(wip_err),
+            err => ApiError::TaskTransition(err),
+        }
+    }
+}
+
+impl From<AttemptStartError> for ApiError {
+    fn from(err: AttemptStartError) -> Self {
+        match err {
+            AttemptStartError::Database(db_err) => ApiError::Database(db_err),
+            err @ AttemptStartError::ProjectArchived => ApiError::Conflict(err.to_string()),
+            AttemptStartError::Workspace(workspace_err) => ApiError::Workspace(workspace_err),
+            AttemptStartError::Transition(transition_err) => ApiError::from(transition_err),
+        }
+    }
+}
+
 impl From<RepoServiceError> for ApiError {
     fn from(err: RepoServiceError) -> Self {
         match err {
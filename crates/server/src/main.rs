@@ -68,6 +68,11 @@ async fn main() -> Result<(), VibeKanbanError> {
         .await
         .map_err(DeploymentError::from)?;
     deployment.spawn_pr_monitor_service().await;
+    deployment.spawn_acp_session_gc_service().await;
+    deployment.spawn_task_template_scheduler().await;
+    deployment.spawn_github_issue_sync_service().await;
+    deployment.spawn_webhook_dispatcher().await;
+    deployment.spawn_event_bus_feeder().await;
     deployment
         .track_if_analytics_allowed("session_start", serde_json::json!({}))
         .await;
@@ -787,6 +787,14 @@ impl TaskServer {
             status,
             parent_workspace_id: None,
             image_ids: None,
+            ignore_blockers: None,
+            priority: None,
+            propagate_priority: None,
+            assignee: None,
+            due_date: None,
+            schedule_weight: None,
+            redistribute_due_dates: None,
+            override_wip_limit: None,
         };
         let url = self.url(&format!("/api/tasks/{}", task_id));
         let updated_task: Task = match self.send_json(self.client.put(&url).json(&payload)).await {
@@ -6,7 +6,7 @@ use axum::{
 };
 use db::models::{
     execution_process::ExecutionProcess, project::Project, session::Session, tag::Tag, task::Task,
-    workspace::Workspace,
+    task_template::TaskTemplate, workspace::Workspace,
 };
 use deployment::Deployment;
 use uuid::Uuid;
@@ -47,7 +47,7 @@ pub async fn load_task_middleware(
     next: Next,
 ) -> Result<Response, StatusCode> {
     // Load the task and validate it belongs to the project
-    let task = match Task::find_by_id(&deployment.db().pool, task_id).await {
+    let task = match Task::find_live_by_id(&deployment.db().pool, task_id).await {
         Ok(Some(task)) => task,
         Ok(None) => {
             tracing::warn!("Task {} not found", task_id);
@@ -148,6 +148,29 @@ pub async fn load_tag_middleware(
     Ok(next.run(request).await)
 }
 
+// Middleware that loads and injects TaskTemplate based on the template_id path parameter
+pub async fn load_task_template_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(template_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let template = match TaskTemplate::find_by_id(&deployment.db().pool, template_id).await {
+        Ok(Some(template)) => template,
+        Ok(None) => {
+            tracing::warn!("Task template {} not found", template_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch task template {}: {}", template_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(template);
+    Ok(next.run(request).await)
+}
+
 pub async fn load_session_middleware(
     State(deployment): State<DeploymentImpl>,
     Path(session_id): Path<Uuid>,
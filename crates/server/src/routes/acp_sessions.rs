@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::acp_sessions::{
+    self, AcpSessionPage, AcpSessionTranscript, KEEP_LATEST_PER_TASK, MAX_SESSION_AGE,
+    collect_stale_sessions,
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Default, Deserialize, TS)]
+pub struct CleanupAcpSessionsRequest {
+    pub older_than_days: Option<u32>,
+    pub keep_latest_per_task: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ListAcpSessionsQuery {
+    pub offset: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+pub async fn list_acp_sessions(
+    State(deployment): State<DeploymentImpl>,
+    Path(namespace): Path<String>,
+    Query(query): Query<ListAcpSessionsQuery>,
+) -> Result<ResponseJson<ApiResponse<AcpSessionPage>>, ApiError> {
+    let page = acp_sessions::list_sessions(
+        deployment.db(),
+        &namespace,
+        query.offset.unwrap_or(0) as usize,
+        query.limit.map(|limit| limit as usize),
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(page)))
+}
+
+pub async fn get_acp_session(
+    State(deployment): State<DeploymentImpl>,
+    Path((namespace, session_id)): Path<(String, String)>,
+) -> Result<ResponseJson<ApiResponse<AcpSessionTranscript>>, ApiError> {
+    let transcript = acp_sessions::get_session(deployment.db(), &namespace, &session_id).await?;
+    Ok(ResponseJson(ApiResponse::success(transcript)))
+}
+
+pub async fn cleanup_acp_sessions(
+    State(deployment): State<DeploymentImpl>,
+    ResponseJson(payload): ResponseJson<CleanupAcpSessionsRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<String>>>, ApiError> {
+    let older_than = payload
+        .older_than_days
+        .map(|days| Duration::from_secs(u64::from(days) * 24 * 60 * 60))
+        .unwrap_or(MAX_SESSION_AGE);
+    let keep_latest_per_task = payload
+        .keep_latest_per_task
+        .map(|n| n as usize)
+        .unwrap_or(KEEP_LATEST_PER_TASK);
+
+    let deleted = collect_stale_sessions(deployment.db(), older_than, keep_latest_per_task).await?;
+    Ok(ResponseJson(ApiResponse::success(deleted)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/acp-sessions/cleanup", post(cleanup_acp_sessions))
+        .route("/acp-sessions/{namespace}", get(list_acp_sessions))
+        .route(
+            "/acp-sessions/{namespace}/{session_id}",
+            get(get_acp_session),
+        )
+}
@@ -1,17 +1,36 @@
 use axum::{
-    Router,
+    Extension, Router,
     extract::{Path, State},
     http::StatusCode,
     response::Json as ResponseJson,
     routing::post,
 };
+use db::models::{execution_process::ExecutionProcess, session::Session, workspace::Workspace};
 use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::approvals::PendingApprovalInfo;
+use sqlx::SqlitePool;
+use ts_rs::TS;
 use utils::{
     approvals::{ApprovalResponse, ApprovalStatus},
     response::ApiResponse,
 };
+use uuid::Uuid;
 
-use crate::DeploymentImpl;
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct RespondToPendingApprovalsRequest {
+    pub status: ApprovalStatus,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct RespondToPendingApprovalsResponse {
+    /// Approval ids resolved by this call
+    pub resolved: Vec<String>,
+    /// Approval ids that were resolved elsewhere (or timed out) first
+    pub skipped: Vec<String>,
+}
 
 pub async fn respond_to_approval(
     State(deployment): State<DeploymentImpl>,
@@ -43,6 +62,84 @@ pub async fn respond_to_approval(
     }
 }
 
+async fn workspace_execution_process_ids(
+    pool: &SqlitePool,
+    workspace_id: Uuid,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let mut ids = Vec::new();
+    for session in Session::find_by_workspace_id(pool, workspace_id).await? {
+        ids.extend(
+            ExecutionProcess::find_by_session_id(pool, session.id, false)
+                .await?
+                .into_iter()
+                .map(|process| process.id),
+        );
+    }
+    Ok(ids)
+}
+
+pub async fn get_pending_approvals(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<PendingApprovalInfo>>>, ApiError> {
+    let ids = workspace_execution_process_ids(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(
+        deployment.approvals().pending(&ids),
+    )))
+}
+
+pub async fn respond_to_pending_approvals(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    ResponseJson(request): ResponseJson<RespondToPendingApprovalsRequest>,
+) -> Result<ResponseJson<ApiResponse<RespondToPendingApprovalsResponse>>, ApiError> {
+    if matches!(request.status, ApprovalStatus::Pending) {
+        return Err(ApiError::BadRequest(
+            "Approvals cannot be resolved as pending".to_string(),
+        ));
+    }
+
+    let pool = &deployment.db().pool;
+    let ids = workspace_execution_process_ids(pool, workspace.id).await?;
+    let approval_ids: Vec<String> = deployment
+        .approvals()
+        .pending(&ids)
+        .into_iter()
+        .map(|pending| pending.approval_id)
+        .collect();
+
+    let mut response = RespondToPendingApprovalsResponse {
+        resolved: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for (id, result) in deployment
+        .approvals()
+        .respond_many(pool, &approval_ids, request.status.clone())
+        .await
+    {
+        match result {
+            Ok(_) => response.resolved.push(id),
+            Err(e) => {
+                tracing::debug!("Skipped approval {} in batch response: {}", id, e);
+                response.skipped.push(id);
+            }
+        }
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "approvals_batch_responded",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "status": format!("{:?}", request.status),
+                "resolved_count": response.resolved.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(response)))
+}
+
 pub fn router() -> Router<DeploymentImpl> {
     Router::new().route("/approvals/{id}/respond", post(respond_to_approval))
 }
@@ -6,7 +6,7 @@ use axum::{
     extract::{Path, Query, State},
     http,
     response::{Json as ResponseJson, Response},
-    routing::{get, put},
+    routing::{get, post, put},
 };
 use deployment::{Deployment, DeploymentError};
 use executors::{
@@ -14,7 +14,7 @@ use executors::{
         AvailabilityInfo, BaseAgentCapability, BaseCodingAgent, StandardCodingAgentExecutor,
     },
     mcp_config::{McpConfig, read_agent_config, write_agent_config},
-    profile::{ExecutorConfigs, ExecutorProfileId},
+    profile::{ExecutorConfigs, ExecutorProfileId, ProfilesReport},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -36,6 +36,8 @@ pub fn router() -> Router<DeploymentImpl> {
         .route("/sounds/{sound}", get(get_sound))
         .route("/mcp-config", get(get_mcp_servers).post(update_mcp_servers))
         .route("/profiles", get(get_profiles).put(update_profiles))
+        .route("/profiles/report", get(get_profiles_report))
+        .route("/profiles/reload", post(reload_profiles))
         .route(
             "/editors/check-availability",
             get(check_editor_availability),
@@ -405,6 +407,29 @@ async fn get_profiles(
     }))
 }
 
+/// Problems found in profiles.json when it was last loaded
+async fn get_profiles_report(
+    State(_deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<ProfilesReport>> {
+    ResponseJson(ApiResponse::success(ExecutorConfigs::load_report()))
+}
+
+/// Re-read profiles.json after it was edited by hand. Invalid files leave the
+/// current profiles active and return the report as error data.
+async fn reload_profiles(
+    State(_deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<ProfilesReport>> {
+    let report = ExecutorConfigs::reload_checked();
+    if report.has_errors() {
+        ResponseJson(ApiResponse::error_with_message_and_data(
+            "profiles.json has errors, keeping the current profiles",
+            report,
+        ))
+    } else {
+        ResponseJson(ApiResponse::success(report))
+    }
+}
+
 async fn update_profiles(
     State(_deployment): State<DeploymentImpl>,
     body: String,
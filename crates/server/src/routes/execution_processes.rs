@@ -10,14 +10,20 @@ use axum::{
     routing::{get, post},
 };
 use db::models::{
+    coding_agent_turn::CodingAgentTurn,
     execution_process::{ExecutionProcess, ExecutionProcessError, ExecutionProcessStatus},
     execution_process_repo_state::ExecutionProcessRepoState,
 };
 use deployment::Deployment;
+use executors::{executors::ProcessIds, logs::summary::ExecutionSummary};
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::Deserialize;
 use services::services::container::ContainerService;
-use utils::{log_msg::LogMsg, response::ApiResponse};
+use utils::{
+    log_msg::LogMsg,
+    msg_store::{LogFilter, LogPage},
+    response::ApiResponse,
+};
 use uuid::Uuid;
 
 use crate::{DeploymentImpl, error::ApiError, middleware::load_execution_process_middleware};
@@ -30,6 +36,39 @@ pub struct SessionExecutionProcessQuery {
     pub show_soft_deleted: Option<bool>,
 }
 
+const DEFAULT_LOG_SEARCH_LIMIT: usize = 200;
+const MAX_LOG_SEARCH_LIMIT: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct LogSearchQuery {
+    /// Comma-separated message kinds, e.g. `tool_use,error_message,stderr`
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Case-insensitive text the message must contain
+    #[serde(default)]
+    pub q: Option<String>,
+    #[serde(default)]
+    pub cursor: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl LogSearchQuery {
+    fn filter(&self) -> LogFilter {
+        LogFilter {
+            kinds: self
+                .kind
+                .iter()
+                .flat_map(|kinds| kinds.split(','))
+                .map(str::trim)
+                .filter(|kind| !kind.is_empty())
+                .map(str::to_string)
+                .collect(),
+            text_contains: self.q.clone().filter(|q| !q.is_empty()),
+        }
+    }
+}
+
 pub async fn get_execution_process_by_id(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(_deployment): State<DeploymentImpl>,
@@ -166,6 +205,50 @@ async fn handle_normalized_logs_ws(
     Ok(())
 }
 
+pub async fn search_logs(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<LogSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<LogPage>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LOG_SEARCH_LIMIT)
+        .clamp(1, MAX_LOG_SEARCH_LIMIT);
+    let page = deployment
+        .container()
+        .search_logs(&execution_process.id, &query.filter(), query.cursor, limit)
+        .await
+        .ok_or_else(|| {
+            ApiError::ExecutionProcess(ExecutionProcessError::ExecutionProcessNotFound)
+        })?;
+    Ok(ResponseJson(ApiResponse::success(page)))
+}
+
+pub async fn stream_filtered_logs_ws(
+    ws: WebSocketUpgrade,
+    State(deployment): State<DeploymentImpl>,
+    Path(exec_id): Path<Uuid>,
+    Query(query): Query<LogSearchQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let store = deployment
+        .container()
+        .log_store_for_search(&exec_id)
+        .await
+        .ok_or_else(|| {
+            ApiError::ExecutionProcess(ExecutionProcessError::ExecutionProcessNotFound)
+        })?;
+    let stream = store
+        .history_plus_stream_filtered(query.filter())
+        .err_into::<anyhow::Error>()
+        .into_stream();
+
+    Ok(ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_normalized_logs_ws(socket, stream).await {
+            tracing::warn!("filtered logs WS closed: {}", e);
+        }
+    }))
+}
+
 pub async fn stop_execution_process(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(deployment): State<DeploymentImpl>,
@@ -178,6 +261,17 @@ pub async fn stop_execution_process(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+pub async fn get_execution_process_ids(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProcessIds>>>, ApiError> {
+    let process_ids = deployment
+        .container()
+        .process_ids(&execution_process.id)
+        .await;
+    Ok(ResponseJson(ApiResponse::success(process_ids)))
+}
+
 pub async fn stream_execution_processes_by_session_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
@@ -243,13 +337,27 @@ pub async fn get_execution_process_repo_states(
     Ok(ResponseJson(ApiResponse::success(repo_states)))
 }
 
+pub async fn get_execution_summary(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ExecutionSummary>>>, ApiError> {
+    let summary =
+        CodingAgentTurn::find_execution_summary(&deployment.db().pool, execution_process.id)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let workspace_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
         .route("/stop", post(stop_execution_process))
+        .route("/process-ids", get(get_execution_process_ids))
         .route("/repo-states", get(get_execution_process_repo_states))
+        .route("/summary", get(get_execution_summary))
         .route("/raw-logs/ws", get(stream_raw_logs_ws))
         .route("/normalized-logs/ws", get(stream_normalized_logs_ws))
+        .route("/logs/search", get(search_logs))
+        .route("/logs/search/ws", get(stream_filtered_logs_ws))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_execution_process_middleware,
@@ -130,7 +130,7 @@ pub async fn upload_task_image(
     State(deployment): State<DeploymentImpl>,
     multipart: Multipart,
 ) -> Result<ResponseJson<ApiResponse<ImageResponse>>, ApiError> {
-    Task::find_by_id(&deployment.db().pool, task_id)
+    Task::find_live_by_id(&deployment.db().pool, task_id)
         .await?
         .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
 
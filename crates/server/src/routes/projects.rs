@@ -4,25 +4,44 @@ use anyhow;
 use axum::{
     Extension, Json, Router,
     extract::{
-        Path, Query, State,
+        DefaultBodyLimit, Multipart, Path, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
     middleware::from_fn_with_state,
-    response::{IntoResponse, Json as ResponseJson},
-    routing::{get, post},
+    response::{
+        IntoResponse, Json as ResponseJson, Sse,
+        sse::{Event, KeepAlive},
+    },
+    routing::{get, post, put},
 };
+use chrono::{DateTime, Utc};
 use db::models::{
+    attempt_usage::{self, ProjectUsage},
     project::{CreateProject, Project, ProjectError, SearchResult, UpdateProject},
+    project_bundle::ProjectBundle,
     project_repo::{CreateProjectRepo, ProjectRepo},
+    project_settings::ProjectSettings,
+    project_stats::StatsRange,
+    project_status::{CreateProjectStatus, ProjectStatus, StatusOccupancy, UpdateProjectStatus},
     repo::Repo,
+    webhook::{CreateWebhook, UpdateWebhook, Webhook, WebhookDelivery},
 };
 use deployment::Deployment;
-use futures_util::{SinkExt, StreamExt, TryStreamExt};
+use futures_util::{SinkExt, Stream, StreamExt, TryStreamExt};
 use serde::Deserialize;
+use serde_json::{Map, Value};
 use services::services::{
-    file_search::SearchQuery, project::ProjectServiceError,
+    event_bus::BusMessage,
+    file_search::SearchQuery,
+    github_issues::{self, GitHubIssuesClient, IssueImportReport, IssueRepo},
+    project::ProjectServiceError,
+    project_bundle::{self, NameConflict, ProjectImport},
+    project_stats::{self, ProjectStats},
     remote_client::CreateRemoteProjectPayload,
+    task_import::{self, TaskImportFormat, TaskImportOptions, TaskImportReport},
+    webhooks::{self, WebhookError},
+    wip_limits,
 };
 use ts_rs::TS;
 use utils::{
@@ -44,10 +63,60 @@ pub struct CreateRemoteProjectRequest {
     pub name: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ProjectsQuery {
+    /// List archived projects instead of the active ones
+    #[serde(default)]
+    pub archived: bool,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ImportGitHubIssuesRequest {
+    /// `owner/name` or a github.com URL
+    pub repository: String,
+    /// Also import closed issues, as Done tasks
+    #[serde(default)]
+    #[ts(optional)]
+    pub include_closed: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportProjectQuery {
+    /// Also bundle sessions, execution processes and their logs
+    #[serde(default)]
+    pub include_execution_history: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportProjectQuery {
+    #[serde(default)]
+    pub on_name_conflict: NameConflict,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectStatsQuery {
+    /// Only count changes from this time on
+    pub from: Option<DateTime<Utc>>,
+    /// Only count changes before this time
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveriesQuery {
+    /// Defaults to 50
+    pub limit: Option<i64>,
+}
+
 pub async fn get_projects(
     State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ProjectsQuery>,
 ) -> Result<ResponseJson<ApiResponse<Vec<Project>>>, ApiError> {
-    let projects = Project::find_all(&deployment.db().pool).await?;
+    let pool = &deployment.db().pool;
+    let projects = if query.archived {
+        Project::find_archived(pool).await?
+    } else {
+        Project::find_unarchived(pool).await?
+    };
     Ok(ResponseJson(ApiResponse::success(projects)))
 }
 
@@ -279,6 +348,198 @@ pub async fn update_project(
     }
 }
 
+pub async fn get_project_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ProjectSettings>>, ApiError> {
+    let settings = Project::settings(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+/// Change only the settings present in the body; `null` resets one to its
+/// default
+pub async fn update_project_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<Map<String, Value>>,
+) -> Result<ResponseJson<ApiResponse<ProjectSettings>>, ApiError> {
+    let settings = Project::update_settings(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_settings_updated",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "keys": payload.keys().collect::<Vec<_>>(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+/// Hide the project from listings and stop new work in it. Its tasks are
+/// kept as they are.
+pub async fn archive_project(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if Project::archive(&deployment.db().pool, project.id).await? > 0 {
+        deployment
+            .track_if_analytics_allowed(
+                "project_archived",
+                serde_json::json!({
+                    "project_id": project.id.to_string(),
+                }),
+            )
+            .await;
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn unarchive_project(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    Project::unarchive(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Download the project as a bundle another instance can import
+pub async fn export_project(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ExportProjectQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let bundle = project_bundle::export(
+        &deployment.db().pool,
+        project.id,
+        query.include_execution_history,
+    )
+    .await?;
+    let disposition = format!("attachment; filename=\"project-{}.json\"", project.id);
+    Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(bundle)))
+}
+
+pub async fn import_project(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ImportProjectQuery>,
+    Json(bundle): Json<ProjectBundle>,
+) -> Result<ResponseJson<ApiResponse<ProjectImport>>, ApiError> {
+    let outcome =
+        project_bundle::import(&deployment.db().pool, &bundle, query.on_name_conflict).await?;
+
+    if let ProjectImport::Imported { project_id, .. } = &outcome {
+        deployment
+            .track_if_analytics_allowed(
+                "project_imported",
+                serde_json::json!({
+                    "project_id": project_id.to_string(),
+                    "task_count": bundle.tasks.len(),
+                    "with_execution_history": bundle.execution_history.is_some(),
+                }),
+            )
+            .await;
+    }
+    Ok(ResponseJson(ApiResponse::success(outcome)))
+}
+
+/// Create tasks from an uploaded backlog file. The multipart body holds the
+/// `file` and optionally `options`, a JSON [`TaskImportOptions`].
+pub async fn import_project_tasks(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    mut multipart: Multipart,
+) -> Result<ResponseJson<ApiResponse<TaskImportReport>>, ApiError> {
+    let mut file = None;
+    let mut options = TaskImportOptions::default();
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("file") => {
+                let file_name = field.file_name().map(|s| s.to_string());
+                file = Some((file_name, field.bytes().await?));
+            }
+            Some("options") => {
+                options = serde_json::from_slice(&field.bytes().await?)
+                    .map_err(|e| ApiError::BadRequest(format!("Invalid import options: {e}")))?;
+            }
+            _ => {}
+        }
+    }
+    let (file_name, data) =
+        file.ok_or_else(|| ApiError::BadRequest("No file was uploaded".to_string()))?;
+    let format = options
+        .format
+        .or_else(|| {
+            file_name
+                .as_deref()
+                .and_then(TaskImportFormat::from_file_name)
+        })
+        .ok_or_else(|| {
+            ApiError::BadRequest("Set the format, or upload a .csv or .json file".to_string())
+        })?;
+
+    let pool = &deployment.db().pool;
+    let rows = task_import::parse_rows(&data, format)?;
+    let report = task_import::import_rows(pool, project.id, &rows, &options).await?;
+
+    if !report.dry_run {
+        deployment
+            .track_if_analytics_allowed(
+                "tasks_imported",
+                serde_json::json!({
+                    "project_id": project.id.to_string(),
+                    "row_count": report.total_rows,
+                    "task_count": report.imported,
+                    "error_count": report.errors.len(),
+                }),
+            )
+            .await;
+    }
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+/// Create tasks from the issues of a GitHub repository, using the token
+/// from the GitHub settings
+pub async fn import_github_issues(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ImportGitHubIssuesRequest>,
+) -> Result<ResponseJson<ApiResponse<IssueImportReport>>, ApiError> {
+    let repo = IssueRepo::parse(&payload.repository)?;
+    let token = deployment
+        .config()
+        .read()
+        .await
+        .github
+        .token()
+        .ok_or_else(|| {
+            ApiError::BadRequest("Add a GitHub token in settings to import issues".to_string())
+        })?;
+    let pool = &deployment.db().pool;
+
+    let client = GitHubIssuesClient::new(token)?;
+    let report = github_issues::import_issues(
+        pool,
+        &client,
+        project.id,
+        &repo,
+        payload.include_closed.unwrap_or(false),
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "github_issues_imported",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "task_count": report.task_ids.len(),
+            }),
+        )
+        .await;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
 pub async fn delete_project(
     Extension(project): Extension<Project>,
     State(deployment): State<DeploymentImpl>,
@@ -568,6 +829,196 @@ pub async fn get_project_repository(
     }
 }
 
+pub async fn get_project_statuses(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectStatus>>>, ApiError> {
+    let statuses = ProjectStatus::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(statuses)))
+}
+
+/// Status counts, time in status, cycle and lead times and weekly
+/// throughput of the project
+pub async fn get_project_stats(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ProjectStatsQuery>,
+) -> Result<ResponseJson<ApiResponse<ProjectStats>>, ApiError> {
+    let range = StatsRange {
+        from: query.from,
+        to: query.to,
+    };
+    let stats = project_stats::project_stats(&deployment.db().pool, project.id, range).await?;
+    Ok(ResponseJson(ApiResponse::success(stats)))
+}
+
+/// Token usage and run time of the project's coding agent runs, by month
+pub async fn get_project_usage(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ProjectUsage>>, ApiError> {
+    let usage = attempt_usage::project_usage(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
+/// Task counts against each column's WIP limit
+pub async fn get_project_wip_occupancy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<StatusOccupancy>>>, ApiError> {
+    let occupancy = wip_limits::occupancy(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(occupancy)))
+}
+
+pub async fn create_project_status(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateProjectStatus>,
+) -> Result<ResponseJson<ApiResponse<ProjectStatus>>, ApiError> {
+    if payload.name.trim().is_empty() {
+        return Err(ApiError::BadRequest("Name must not be empty".to_string()));
+    }
+    let status = ProjectStatus::create(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_status_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "category": status.category.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+pub async fn update_project_status(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, key)): Path<(Uuid, String)>,
+    Json(payload): Json<UpdateProjectStatus>,
+) -> Result<ResponseJson<ApiResponse<ProjectStatus>>, ApiError> {
+    let status = ProjectStatus::update(&deployment.db().pool, project_id, &key, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+pub async fn delete_project_status(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, key)): Path<(Uuid, String)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ProjectStatus::delete(&deployment.db().pool, project_id, &key).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Live task and attempt changes of the project as server-sent events,
+/// named after the change. A client reconnecting with `Last-Event-ID`
+/// gets the changes it missed; a `resync` event means some are no longer
+/// available and the client should reload.
+pub async fn stream_project_events(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+    let stream = deployment
+        .event_bus()
+        .subscribe(project.id, last_event_id)
+        .map(|message| match message {
+            BusMessage::Change(change) => Event::default()
+                .id(change.id.to_string())
+                .event(&change.event_type)
+                .json_data(&change),
+            BusMessage::Resync => Ok(Event::default().event("resync").data("{}")),
+        });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+pub async fn get_project_webhooks(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Webhook>>>, ApiError> {
+    let webhooks = Webhook::find_by_project(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(webhooks)))
+}
+
+pub async fn create_project_webhook(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateWebhook>,
+) -> Result<ResponseJson<ApiResponse<Webhook>>, ApiError> {
+    let webhook = webhooks::create_webhook(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_webhook_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "event_count": webhook.events.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(webhook)))
+}
+
+async fn find_project_webhook(
+    deployment: &DeploymentImpl,
+    project_id: Uuid,
+    webhook_id: Uuid,
+) -> Result<Webhook, ApiError> {
+    Ok(
+        Webhook::find_in_project(&deployment.db().pool, project_id, webhook_id)
+            .await?
+            .ok_or(WebhookError::NotFound)?,
+    )
+}
+
+pub async fn update_project_webhook(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, webhook_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateWebhook>,
+) -> Result<ResponseJson<ApiResponse<Webhook>>, ApiError> {
+    find_project_webhook(&deployment, project_id, webhook_id).await?;
+    let webhook = webhooks::update_webhook(&deployment.db().pool, webhook_id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(webhook)))
+}
+
+pub async fn delete_project_webhook(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, webhook_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    find_project_webhook(&deployment, project_id, webhook_id).await?;
+    Webhook::delete(&deployment.db().pool, webhook_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Send a `webhook.test` event right away and return its delivery
+pub async fn test_project_webhook(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, webhook_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<WebhookDelivery>>, ApiError> {
+    let webhook = find_project_webhook(&deployment, project_id, webhook_id).await?;
+    let client = webhooks::client()?;
+    let delivery = webhooks::send_test_event(&deployment.db().pool, &client, &webhook).await?;
+    Ok(ResponseJson(ApiResponse::success(delivery)))
+}
+
+/// Latest deliveries of the webhook, newest first
+pub async fn get_project_webhook_deliveries(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, webhook_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<WebhookDeliveriesQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<WebhookDelivery>>>, ApiError> {
+    find_project_webhook(&deployment, project_id, webhook_id).await?;
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let deliveries =
+        WebhookDelivery::find_by_webhook(&deployment.db().pool, webhook_id, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(deliveries)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let project_id_router = Router::new()
         .route(
@@ -577,6 +1028,14 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/remote/members", get(get_project_remote_members))
         .route("/search", get(search_project_files))
         .route("/open-editor", post(open_project_in_editor))
+        .route("/archive", post(archive_project))
+        .route("/unarchive", post(unarchive_project))
+        .route("/export", get(export_project))
+        .route("/github/import", post(import_github_issues))
+        .route(
+            "/tasks/import",
+            post(import_project_tasks).layer(DefaultBodyLimit::max(20 * 1024 * 1024)),
+        )
         .route(
             "/link",
             post(link_project_to_existing_remote).delete(unlink_project),
@@ -586,6 +1045,22 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             "/repositories",
             get(get_project_repositories).post(add_project_repository),
         )
+        .route(
+            "/statuses",
+            get(get_project_statuses).post(create_project_status),
+        )
+        .route("/wip", get(get_project_wip_occupancy))
+        .route("/stats", get(get_project_stats))
+        .route("/usage", get(get_project_usage))
+        .route("/events", get(stream_project_events))
+        .route(
+            "/settings",
+            get(get_project_settings).patch(update_project_settings),
+        )
+        .route(
+            "/webhooks",
+            get(get_project_webhooks).post(create_project_webhook),
+        )
         .layer(from_fn_with_state(
             deployment.clone(),
             load_project_middleware,
@@ -597,6 +1072,26 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             "/{project_id}/repositories/{repo_id}",
             get(get_project_repository).delete(delete_project_repository),
         )
+        .route(
+            "/{project_id}/statuses/{key}",
+            put(update_project_status).delete(delete_project_status),
+        )
+        .route(
+            "/{project_id}/webhooks/{webhook_id}",
+            put(update_project_webhook).delete(delete_project_webhook),
+        )
+        .route(
+            "/{project_id}/webhooks/{webhook_id}/test",
+            post(test_project_webhook),
+        )
+        .route(
+            "/{project_id}/webhooks/{webhook_id}/deliveries",
+            get(get_project_webhook_deliveries),
+        )
+        .route(
+            "/import",
+            post(import_project).layer(DefaultBodyLimit::max(100 * 1024 * 1024)),
+        )
         .route("/stream/ws", get(stream_projects_ws))
         .nest("/{id}", project_id_router);
 
@@ -23,6 +23,7 @@ use axum::{
     routing::{get, post, put},
 };
 use db::models::{
+    approval_event::ApprovalEvent,
     coding_agent_turn::CodingAgentTurn,
     execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
     merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
@@ -30,6 +31,7 @@ use db::models::{
     repo::{Repo, RepoError},
     session::{CreateSession, Session},
     task::{Task, TaskRelationships, TaskStatus},
+    task_event::TaskEventActor,
     workspace::{CreateWorkspace, Workspace, WorkspaceError},
     workspace_repo::{CreateWorkspaceRepo, RepoWithTargetBranch, WorkspaceRepo},
 };
@@ -48,6 +50,7 @@ use services::services::{
     container::ContainerService,
     file_search::SearchQuery,
     git::{ConflictOp, GitCliError, GitServiceError},
+    task_transitions, tasks,
     workspace_manager::WorkspaceManager,
 };
 use sqlx::Error as SqlxError;
@@ -56,8 +59,10 @@ use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use crate::{
-    DeploymentImpl, error::ApiError, middleware::load_workspace_middleware,
-    routes::task_attempts::gh_cli_setup::GhCliSetupError,
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_workspace_middleware,
+    routes::{approvals, task_attempts::gh_cli_setup::GhCliSetupError},
 };
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -182,7 +187,8 @@ pub async fn create_task_attempt(
     }
 
     let pool = &deployment.db().pool;
-    let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
+    // Deleted tasks are read-only until restored
+    let task = Task::find_live_by_id(pool, payload.task_id)
         .await?
         .ok_or(SqlxError::RowNotFound)?;
 
@@ -204,14 +210,14 @@ pub async fn create_task_attempt(
         .git_branch_from_workspace(&attempt_id, &task.title)
         .await;
 
-    let workspace = Workspace::create(
+    let workspace = tasks::start_attempt(
         pool,
+        task.id,
         &CreateWorkspace {
             branch: git_branch_name.clone(),
             agent_working_dir,
         },
         attempt_id,
-        payload.task_id,
     )
     .await?;
 
@@ -469,7 +475,7 @@ pub async fn merge_task_attempt(
         &merge_commit_id,
     )
     .await?;
-    Task::update_status(pool, task.id, TaskStatus::Done).await?;
+    task_transitions::set_status(pool, task.id, TaskStatus::Done, TaskEventActor::System).await?;
     if !workspace.pinned {
         Workspace::set_archived(pool, workspace.id, true).await?;
     }
@@ -1553,7 +1559,7 @@ pub async fn gh_cli_setup_handler(
 
             Ok(ResponseJson(ApiResponse::success(execution_process)))
         }
-        Err(ApiError::Executor(ExecutorError::ExecutableNotFound { program }))
+        Err(ApiError::Executor(ExecutorError::ExecutableNotFound { program, .. }))
             if program == "brew" =>
         {
             Ok(ResponseJson(ApiResponse::error_with_data(
@@ -1632,6 +1638,14 @@ pub async fn get_first_user_message(
     Ok(ResponseJson(ApiResponse::success(message)))
 }
 
+pub async fn get_approval_events(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ApprovalEvent>>>, ApiError> {
+    let events = ApprovalEvent::find_by_task_attempt(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(events)))
+}
+
 pub async fn delete_workspace(
     Extension(workspace): Extension<Workspace>,
     State(deployment): State<DeploymentImpl>,
@@ -1779,6 +1793,12 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/repos", get(get_task_attempt_repos))
         .route("/search", get(search_workspace_files))
         .route("/first-message", get(get_first_user_message))
+        .route("/approval-events", get(get_approval_events))
+        .route("/approvals/pending", get(approvals::get_pending_approvals))
+        .route(
+            "/approvals/respond",
+            post(approvals::respond_to_pending_approvals),
+        )
         .route("/mark-seen", put(mark_seen))
         .layer(from_fn_with_state(
             deployment.clone(),
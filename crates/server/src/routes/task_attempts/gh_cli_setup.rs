@@ -74,9 +74,9 @@ async fn get_gh_cli_setup_helper_action() -> Result<ExecutorAction, ApiError> {
         use utils::shell::resolve_executable_path;
 
         if resolve_executable_path("brew").await.is_none() {
-            return Err(ApiError::Executor(ExecutorError::ExecutableNotFound {
-                program: "brew".to_string(),
-            }));
+            return Err(ApiError::Executor(ExecutorError::executable_not_found(
+                "brew",
+            )));
         }
 
         // Install script
@@ -39,7 +39,7 @@ pub async fn upload_image(
     multipart: Multipart,
 ) -> Result<ResponseJson<ApiResponse<ImageResponse>>, ApiError> {
     // Get the task for this attempt
-    let task = Task::find_by_id(&deployment.db().pool, workspace.task_id)
+    let task = Task::find_live_by_id(&deployment.db().pool, workspace.task_id)
         .await?
         .ok_or_else(|| ApiError::Image(ImageError::NotFound))?;
 
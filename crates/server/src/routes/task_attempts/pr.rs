@@ -10,7 +10,8 @@ use db::models::{
     merge::{Merge, MergeStatus},
     repo::{Repo, RepoError},
     session::{CreateSession, Session},
-    task::{Task, TaskStatus},
+    task::TaskStatus,
+    task_event::TaskEventActor,
     workspace::{Workspace, WorkspaceError},
     workspace_repo::WorkspaceRepo,
 };
@@ -26,6 +27,7 @@ use services::services::{
     git_host::{
         self, CreatePrRequest, GitHostError, GitHostProvider, ProviderKind, UnifiedPrComment,
     },
+    task_transitions,
 };
 use ts_rs::TS;
 use utils::response::ApiResponse;
@@ -482,7 +484,8 @@ pub async fn attach_existing_pr(
 
         // If PR is merged, mark task as done and archive workspace
         if matches!(pr_info.status, MergeStatus::Merged) {
-            Task::update_status(pool, task.id, TaskStatus::Done).await?;
+            task_transitions::set_status(pool, task.id, TaskStatus::Done, TaskEventActor::System)
+                .await?;
             if !workspace.pinned {
                 Workspace::set_archived(pool, workspace.id, true).await?;
             }
@@ -0,0 +1,140 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Query, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use db::models::{
+    task::Task,
+    task_event::TaskEventActor,
+    task_template::{CreateTaskTemplate, TaskTemplate, UpdateTaskTemplate, parse_recurrence},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::project::ensure_not_archived;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_task_template_middleware};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct TaskTemplateQuery {
+    pub project_id: Uuid,
+}
+
+pub async fn get_task_templates(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TaskTemplateQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskTemplate>>>, ApiError> {
+    let templates =
+        TaskTemplate::find_by_project_id(&deployment.db().pool, query.project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(templates)))
+}
+
+pub async fn get_task_template(
+    Extension(template): Extension<TaskTemplate>,
+) -> Result<ResponseJson<ApiResponse<TaskTemplate>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(template)))
+}
+
+pub async fn create_task_template(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskTemplate>,
+) -> Result<ResponseJson<ApiResponse<TaskTemplate>>, ApiError> {
+    if payload.title.trim().is_empty() {
+        return Err(ApiError::BadRequest("Title must not be empty".to_string()));
+    }
+    parse_recurrence(payload.recurrence.as_deref())?;
+
+    let template = TaskTemplate::create(&deployment.db().pool, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_template_created",
+            serde_json::json!({
+                "template_id": template.id.to_string(),
+                "project_id": template.project_id.to_string(),
+                "recurring": template.recurrence.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(template)))
+}
+
+pub async fn update_task_template(
+    Extension(template): Extension<TaskTemplate>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateTaskTemplate>,
+) -> Result<ResponseJson<ApiResponse<TaskTemplate>>, ApiError> {
+    if payload
+        .title
+        .as_deref()
+        .is_some_and(|title| title.trim().is_empty())
+    {
+        return Err(ApiError::BadRequest("Title must not be empty".to_string()));
+    }
+    parse_recurrence(payload.recurrence.as_deref())?;
+
+    let template = TaskTemplate::update(&deployment.db().pool, template.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(template)))
+}
+
+pub async fn delete_task_template(
+    Extension(template): Extension<TaskTemplate>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = TaskTemplate::delete(&deployment.db().pool, template.id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+/// Create a task from the template right away, outside its schedule
+pub async fn instantiate_task_template(
+    Extension(template): Extension<TaskTemplate>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    ensure_not_archived(&deployment.db().pool, template.project_id).await?;
+    let task =
+        TaskTemplate::instantiate(&deployment.db().pool, &template, None, TaskEventActor::User)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_template_instantiated",
+            serde_json::json!({
+                "template_id": template.id.to_string(),
+                "task_id": task.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let template_router = Router::new()
+        .route(
+            "/",
+            get(get_task_template)
+                .put(update_task_template)
+                .delete(delete_task_template),
+        )
+        .route("/instantiate", post(instantiate_task_template))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_task_template_middleware,
+        ));
+
+    let inner = Router::new()
+        .route("/", get(get_task_templates).post(create_task_template))
+        .nest("/{template_id}", template_router);
+
+    Router::new().nest("/task-templates", inner)
+}
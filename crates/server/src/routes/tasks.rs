@@ -1,21 +1,28 @@
-use std::path::PathBuf;
-
 use anyhow;
 use axum::{
     Extension, Json, Router,
     extract::{
-        Query, State,
+        Path, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
-    http::StatusCode,
     middleware::from_fn_with_state,
     response::{IntoResponse, Json as ResponseJson},
     routing::{delete, get, post, put},
 };
+use chrono::{NaiveDate, Utc};
 use db::models::{
-    image::TaskImage,
+    attempt_usage::{self, TaskUsage},
+    project_status::ProjectStatus,
     repo::{Repo, RepoError},
-    task::{CreateTask, Task, TaskWithAttemptStatus, UpdateTask},
+    task::{
+        AssigneeTaskCount, CreateTask, DueDateChange, DueTask, DuplicateTask, DuplicatedTask,
+        MoveTask, MoveTaskToProject, Task, TaskPriority, TaskRestoreError, TaskSearchResult,
+        TaskStatus, TaskWithAttemptStatus, UpdateTask,
+    },
+    task_dependency::{CreateTaskDependency, TaskDependencies, TaskDependency},
+    task_event::TaskEvent,
+    task_external_link::TaskExternalLink,
+    task_schedule,
     workspace::{CreateWorkspace, Workspace},
     workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
 };
@@ -24,7 +31,7 @@ use executors::profile::ExecutorProfileId;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use services::services::{
-    container::ContainerService, share::ShareError, workspace_manager::WorkspaceManager,
+    container::ContainerService, project::ensure_not_archived, share::ShareError, tasks,
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
@@ -39,19 +46,154 @@ use crate::{
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TaskQuery {
     pub project_id: Uuid,
+    #[serde(default)]
+    pub include_archived: bool,
+    pub priority: Option<TaskPriority>,
+    /// Only tasks assigned to this name, or to the signed-in user for `me`
+    pub assignee: Option<String>,
+    #[serde(default)]
+    pub unassigned: bool,
+    /// Only open tasks whose due date has passed
+    #[serde(default)]
+    pub overdue: bool,
+    /// Only open tasks due between today and this many days from now
+    pub due_within: Option<u32>,
+}
+
+fn is_open(task: &Task) -> bool {
+    !matches!(task.status, TaskStatus::Done | TaskStatus::Cancelled)
+}
+
+/// The name tasks are assigned to for the signed-in user
+async fn current_assignee(deployment: &DeploymentImpl) -> Result<String, ApiError> {
+    match deployment.get_login_status().await {
+        LoginStatus::LoggedIn { profile } => Ok(profile.username.unwrap_or(profile.email)),
+        LoginStatus::LoggedOut => Err(ApiError::BadRequest(
+            "Sign in to filter by your own tasks".to_string(),
+        )),
+    }
 }
 
 pub async fn get_tasks(
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskQuery>,
 ) -> Result<ResponseJson<ApiResponse<Vec<TaskWithAttemptStatus>>>, ApiError> {
-    let tasks =
-        Task::find_by_project_id_with_attempt_status(&deployment.db().pool, query.project_id)
-            .await?;
+    let mut tasks = Task::find_by_project_id_with_attempt_status(
+        &deployment.db().pool,
+        query.project_id,
+        query.include_archived,
+    )
+    .await?;
+    if let Some(priority) = query.priority {
+        tasks.retain(|task| task.priority == priority);
+    }
+    if query.unassigned {
+        tasks.retain(|task| task.assignee.is_none());
+    } else if let Some(assignee) = query.assignee {
+        let assignee = if assignee == "me" {
+            current_assignee(&deployment).await?
+        } else {
+            assignee
+        };
+        tasks.retain(|task| task.assignee.as_deref() == Some(assignee.as_str()));
+    }
+    let today = Utc::now().date_naive();
+    if query.overdue {
+        tasks.retain(|task| is_open(task) && task.due_date.is_some_and(|due| due < today));
+    }
+    if let Some(days) = query.due_within {
+        let horizon = today
+            .checked_add_days(chrono::Days::new(days.into()))
+            .unwrap_or(NaiveDate::MAX);
+        tasks.retain(|task| {
+            is_open(task)
+                && task
+                    .due_date
+                    .is_some_and(|due| today <= due && due <= horizon)
+        });
+    }
 
     Ok(ResponseJson(ApiResponse::success(tasks)))
 }
 
+const DEFAULT_DUE_WITHIN_DAYS: u32 = 7;
+const MAX_DUE_WITHIN_DAYS: u32 = 365;
+
+#[derive(Debug, Deserialize)]
+pub struct DueTasksQuery {
+    pub within_days: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct DueTasks {
+    pub overdue: Vec<DueTask>,
+    /// Due today or within the requested number of days
+    pub upcoming: Vec<DueTask>,
+}
+
+pub async fn get_due_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<DueTasksQuery>,
+) -> Result<ResponseJson<ApiResponse<DueTasks>>, ApiError> {
+    let within_days = query
+        .within_days
+        .unwrap_or(DEFAULT_DUE_WITHIN_DAYS)
+        .min(MAX_DUE_WITHIN_DAYS);
+    let today = Utc::now().date_naive();
+    let (overdue, upcoming) = Task::find_due(&deployment.db().pool, today, within_days)
+        .await?
+        .into_iter()
+        .partition(|due| due.task.due_date.is_some_and(|date| date < today));
+    Ok(ResponseJson(ApiResponse::success(DueTasks {
+        overdue,
+        upcoming,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssigneeCountsQuery {
+    pub project_id: Option<Uuid>,
+}
+
+pub async fn get_assignee_counts(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<AssigneeCountsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<AssigneeTaskCount>>>, ApiError> {
+    let counts = Task::open_counts_by_assignee(&deployment.db().pool, query.project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(counts)))
+}
+
+const DEFAULT_TASK_SEARCH_LIMIT: i64 = 20;
+const MAX_TASK_SEARCH_LIMIT: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct TaskSearchQuery {
+    pub project_id: Uuid,
+    pub q: String,
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+pub async fn search_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TaskSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskSearchResult>>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_TASK_SEARCH_LIMIT)
+        .clamp(1, MAX_TASK_SEARCH_LIMIT);
+    let results = Task::search(
+        &deployment.db().pool,
+        query.project_id,
+        &query.q,
+        limit,
+        query.include_archived,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(results)))
+}
+
 pub async fn stream_tasks_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
@@ -106,6 +248,16 @@ pub async fn get_task(
     Ok(ResponseJson(ApiResponse::success(task)))
 }
 
+fn check_task_schedule(data: &CreateTask) -> Result<(), ApiError> {
+    if let Some(due_date) = data.due_date {
+        task_schedule::check_due_date(due_date, Utc::now())?;
+    }
+    if let Some(weight) = data.schedule_weight {
+        task_schedule::check_weight(weight)?;
+    }
+    Ok(())
+}
+
 pub async fn create_task(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateTask>,
@@ -118,11 +270,8 @@ pub async fn create_task(
         payload.project_id
     );
 
-    let task = Task::create(&deployment.db().pool, &payload, id).await?;
-
-    if let Some(image_ids) = &payload.image_ids {
-        TaskImage::associate_many_dedup(&deployment.db().pool, task.id, image_ids).await?;
-    }
+    check_task_schedule(&payload)?;
+    let task = tasks::create(&deployment.db().pool, &payload, id).await?;
 
     deployment
         .track_if_analytics_allowed(
@@ -139,6 +288,50 @@ pub async fn create_task(
     Ok(ResponseJson(ApiResponse::success(task)))
 }
 
+pub async fn create_tasks_bulk(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<Vec<CreateTask>>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    tracing::debug!("Creating {} tasks in one batch", payload.len());
+
+    let tasks = tasks::create_many(&deployment.db().pool, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "tasks_bulk_created",
+            serde_json::json!({
+                "task_count": tasks.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(tasks)))
+}
+
+pub async fn duplicate_task(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<DuplicateTask>,
+) -> Result<ResponseJson<ApiResponse<DuplicatedTask>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let duplicated = tasks::duplicate(pool, task.id, &payload)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_duplicated",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "copy_id": duplicated.task.id.to_string(),
+                "child_count": duplicated.children.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(duplicated)))
+}
+
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateAndStartTaskRequest {
     pub task: CreateTask,
@@ -158,12 +351,9 @@ pub async fn create_task_and_start(
 
     let pool = &deployment.db().pool;
 
+    check_task_schedule(&payload.task)?;
     let task_id = Uuid::new_v4();
-    let task = Task::create(pool, &payload.task, task_id).await?;
-
-    if let Some(image_ids) = &payload.task.image_ids {
-        TaskImage::associate_many_dedup(pool, task.id, image_ids).await?;
-    }
+    let task = tasks::create(pool, &payload.task, task_id).await?;
 
     deployment
         .track_if_analytics_allowed(
@@ -195,14 +385,14 @@ pub async fn create_task_and_start(
         None
     };
 
-    let workspace = Workspace::create(
+    let workspace = tasks::start_attempt(
         pool,
+        task.id,
         &CreateWorkspace {
             branch: git_branch_name,
             agent_working_dir,
         },
         attempt_id,
-        task.id,
     )
     .await?;
 
@@ -234,7 +424,7 @@ pub async fn create_task_and_start(
         )
         .await;
 
-    let task = Task::find_by_id(pool, task.id)
+    let task = Task::find_live_by_id(pool, task.id)
         .await?
         .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
 
@@ -244,6 +434,8 @@ pub async fn create_task_and_start(
         has_in_progress_attempt: is_attempt_running,
         last_attempt_failed: false,
         executor: payload.executor_profile_id.executor.to_string(),
+        blocked: false,
+        archived_at: None,
     })))
 }
 
@@ -255,34 +447,15 @@ pub async fn update_task(
 ) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
     ensure_shared_task_auth(&existing_task, &deployment).await?;
 
-    // Use existing values if not provided in update
-    let title = payload.title.unwrap_or(existing_task.title);
-    let description = match payload.description {
-        Some(s) if s.trim().is_empty() => None, // Empty string = clear description
-        Some(s) => Some(s),                     // Non-empty string = update description
-        None => existing_task.description,      // Field omitted = keep existing
-    };
-    let status = payload.status.unwrap_or(existing_task.status);
-    let parent_workspace_id = payload
-        .parent_workspace_id
-        .or(existing_task.parent_workspace_id);
-
-    let task = Task::update(
-        &deployment.db().pool,
-        existing_task.id,
-        existing_task.project_id,
-        title,
-        description,
-        status,
-        parent_workspace_id,
-    )
-    .await?;
-
-    if let Some(image_ids) = &payload.image_ids {
-        TaskImage::delete_by_task_id(&deployment.db().pool, task.id).await?;
-        TaskImage::associate_many_dedup(&deployment.db().pool, task.id, image_ids).await?;
+    if let Some(Some(due_date)) = payload.due_date {
+        task_schedule::check_due_date(due_date, existing_task.created_at)?;
+    }
+    if let Some(weight) = payload.schedule_weight {
+        task_schedule::check_weight(weight)?;
     }
 
+    let task = tasks::update(&deployment.db().pool, existing_task.id, &payload).await?;
+
     // If task has been shared, broadcast update
     if task.shared_task_id.is_some() {
         let Ok(publisher) = deployment.share_publisher() else {
@@ -294,6 +467,108 @@ pub async fn update_task(
     Ok(ResponseJson(ApiResponse::success(task)))
 }
 
+pub async fn distribute_due_dates(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<DueDateChange>>>, ApiError> {
+    let changes = tasks::distribute_due_dates(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(changes)))
+}
+
+pub async fn move_task(
+    Extension(existing_task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<MoveTask>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    ensure_shared_task_auth(&existing_task, &deployment).await?;
+    let pool = &deployment.db().pool;
+    if let Some(key) = &payload.status_key {
+        ProjectStatus::resolve(pool, existing_task.project_id, key, &payload.status).await?;
+    }
+
+    let task = tasks::move_to(pool, existing_task.id, &payload).await?;
+
+    if task.status != existing_task.status && task.shared_task_id.is_some() {
+        let Ok(publisher) = deployment.share_publisher() else {
+            return Err(ShareError::MissingConfig("share publisher unavailable").into());
+        };
+        publisher.update_shared_task(&task).await?;
+    }
+
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+pub async fn move_task_to_project(
+    Extension(existing_task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<MoveTaskToProject>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    ensure_shared_task_auth(&existing_task, &deployment).await?;
+    let pool = &deployment.db().pool;
+    ensure_not_archived(pool, payload.project_id).await?;
+
+    let moved = tasks::move_to_project(
+        pool,
+        existing_task.id,
+        payload.project_id,
+        payload.sever_links,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_moved_to_project",
+            serde_json::json!({
+                "task_id": existing_task.id.to_string(),
+                "project_id": payload.project_id.to_string(),
+                "task_count": moved.task_ids.len(),
+                "sever_links": payload.sever_links,
+            }),
+        )
+        .await;
+
+    let task = Task::find_live_by_id(pool, existing_task.id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+pub async fn get_task_dependencies(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskDependencies>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let dependencies = TaskDependencies {
+        blockers: Task::blockers(pool, task.id).await?,
+        dependents: Task::dependents(pool, task.id).await?,
+    };
+    Ok(ResponseJson(ApiResponse::success(dependencies)))
+}
+
+pub async fn add_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskDependency>,
+) -> Result<ResponseJson<ApiResponse<TaskDependency>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let dependency = tasks::add_dependency(pool, payload.blocker_id, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(dependency)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskDependencyQuery {
+    pub blocker_id: Uuid,
+}
+
+pub async fn remove_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TaskDependencyQuery>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    tasks::remove_dependency(&deployment.db().pool, query.blocker_id, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
 async fn ensure_shared_task_auth(
     existing_task: &Task,
     deployment: &local_deployment::LocalDeployment,
@@ -312,69 +587,30 @@ async fn ensure_shared_task_auth(
 pub async fn delete_task(
     Extension(task): Extension<Task>,
     State(deployment): State<DeploymentImpl>,
-) -> Result<(StatusCode, ResponseJson<ApiResponse<()>>), ApiError> {
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
     ensure_shared_task_auth(&task, &deployment).await?;
 
     let pool = &deployment.db().pool;
 
-    // Gather task attempts data needed for background cleanup
+    // Stop any running execution processes; worktrees and history are kept
+    // until the deleted task is purged
     let attempts = Workspace::fetch_all(pool, Some(task.id))
         .await
         .map_err(|e| {
             tracing::error!("Failed to fetch task attempts for task {}: {}", task.id, e);
             ApiError::Workspace(e)
         })?;
-
-    // Stop any running execution processes before deletion
     for workspace in &attempts {
         deployment.container().try_stop(workspace, true).await;
     }
 
-    let repositories = WorkspaceRepo::find_unique_repos_for_task(pool, task.id).await?;
-
-    // Collect workspace directories that need cleanup
-    let workspace_dirs: Vec<PathBuf> = attempts
-        .iter()
-        .filter_map(|attempt| attempt.container_ref.as_ref().map(PathBuf::from))
-        .collect();
-
-    if let Some(shared_task_id) = task.shared_task_id {
-        let Ok(publisher) = deployment.share_publisher() else {
-            return Err(ShareError::MissingConfig("share publisher unavailable").into());
-        };
-        publisher.delete_shared_task(shared_task_id).await?;
-    }
-
-    // Use a transaction to ensure atomicity: either all operations succeed or all are rolled back
-    let mut tx = pool.begin().await?;
-
-    // Nullify parent_workspace_id for all child tasks before deletion
-    // This breaks parent-child relationships to avoid foreign key constraint violations
-    let mut total_children_affected = 0u64;
-    for attempt in &attempts {
-        let children_affected =
-            Task::nullify_children_by_workspace_id(&mut *tx, attempt.id).await?;
-        total_children_affected += children_affected;
-    }
-
-    // Delete task from database (FK CASCADE will handle task_attempts)
-    let rows_affected = Task::delete(&mut *tx, task.id).await?;
-
+    // A shared task stays linked so a restore keeps it; the remote copy is
+    // removed when the task is purged
+    let rows_affected = tasks::delete(pool, task.id).await?;
     if rows_affected == 0 {
         return Err(ApiError::Database(SqlxError::RowNotFound));
     }
 
-    // Commit the transaction - if this fails, all changes are rolled back
-    tx.commit().await?;
-
-    if total_children_affected > 0 {
-        tracing::info!(
-            "Nullified {} child task references before deleting task {}",
-            total_children_affected,
-            task.id
-        );
-    }
-
     deployment
         .track_if_analytics_allowed(
             "task_deleted",
@@ -382,47 +618,85 @@ pub async fn delete_task(
                 "task_id": task.id.to_string(),
                 "project_id": task.project_id.to_string(),
                 "attempt_count": attempts.len(),
+                "deleted_task_count": rows_affected,
             }),
         )
         .await;
 
-    let task_id = task.id;
-    let pool = pool.clone();
-    tokio::spawn(async move {
-        tracing::info!(
-            "Starting background cleanup for task {} ({} workspaces, {} repos)",
-            task_id,
-            workspace_dirs.len(),
-            repositories.len()
-        );
+    Ok(ResponseJson(ApiResponse::success(())))
+}
 
-        for workspace_dir in &workspace_dirs {
-            if let Err(e) = WorkspaceManager::cleanup_workspace(workspace_dir, &repositories).await
-            {
-                tracing::error!(
-                    "Background workspace cleanup failed for task {} at {}: {}",
-                    task_id,
-                    workspace_dir.display(),
-                    e
-                );
-            }
-        }
+#[derive(Debug, Deserialize)]
+pub struct DeletedTasksQuery {
+    pub project_id: Uuid,
+}
 
-        match Repo::delete_orphaned(&pool).await {
-            Ok(count) if count > 0 => {
-                tracing::info!("Deleted {} orphaned repo records", count);
-            }
-            Err(e) => {
-                tracing::error!("Failed to delete orphaned repos: {}", e);
-            }
-            _ => {}
+pub async fn get_deleted_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<DeletedTasksQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let tasks = Task::find_deleted_by_project_id(&deployment.db().pool, query.project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(tasks)))
+}
+
+pub async fn restore_task(
+    State(deployment): State<DeploymentImpl>,
+    Path(task_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let pool = &deployment.db().pool;
+    match tasks::restore(pool, task_id).await {
+        Ok(_) => {}
+        Err(TaskRestoreError::Database(e)) => return Err(e.into()),
+        Err(TaskRestoreError::NotFound) => return Err(ApiError::Database(SqlxError::RowNotFound)),
+        Err(err @ TaskRestoreError::ParentDeleted(_)) => {
+            return Err(ApiError::Conflict(err.to_string()));
         }
+    }
+    let task = Task::find_by_id(pool, task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
 
-        tracing::info!("Background cleanup completed for task {}", task_id);
-    });
+const DEFAULT_TASK_EVENTS_LIMIT: i64 = 50;
+const MAX_TASK_EVENTS_LIMIT: i64 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct TaskEventsQuery {
+    /// Only return events older than this event id
+    pub before: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+pub async fn get_task_external_links(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskExternalLink>>>, ApiError> {
+    let links = TaskExternalLink::find_by_task(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(links)))
+}
 
-    // Return 202 Accepted to indicate deletion was scheduled
-    Ok((StatusCode::ACCEPTED, ResponseJson(ApiResponse::success(()))))
+/// Token usage and run time of the task's coding agent runs, per attempt
+pub async fn get_task_usage(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskUsage>>, ApiError> {
+    let usage = attempt_usage::task_usage(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
+pub async fn get_task_events(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TaskEventsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskEvent>>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_TASK_EVENTS_LIMIT)
+        .clamp(1, MAX_TASK_EVENTS_LIMIT);
+    let events =
+        TaskEvent::find_by_task(&deployment.db().pool, task.id, query.before, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(events)))
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -430,6 +704,28 @@ pub struct ShareTaskResponse {
     pub shared_task_id: Uuid,
 }
 
+pub async fn archive_task(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let archived = tasks::archive(pool, task.id).await?;
+    tracing::debug!(
+        "Archived task {} and {} descendants",
+        task.id,
+        archived.saturating_sub(1)
+    );
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn unarchive_task(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    tasks::unarchive(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
 pub async fn share_task(
     Extension(task): Extension<Task>,
     State(deployment): State<DeploymentImpl>,
@@ -461,7 +757,22 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_actions_router = Router::new()
         .route("/", put(update_task))
         .route("/", delete(delete_task))
-        .route("/share", post(share_task));
+        .route("/move", post(move_task))
+        .route("/duplicate", post(duplicate_task))
+        .route("/move-to-project", post(move_task_to_project))
+        .route("/distribute-due-dates", post(distribute_due_dates))
+        .route("/share", post(share_task))
+        .route("/events", get(get_task_events))
+        .route("/external-links", get(get_task_external_links))
+        .route("/usage", get(get_task_usage))
+        .route("/archive", post(archive_task))
+        .route("/unarchive", post(unarchive_task))
+        .route(
+            "/dependencies",
+            get(get_task_dependencies)
+                .post(add_task_dependency)
+                .delete(remove_task_dependency),
+        );
 
     let task_id_router = Router::new()
         .route("/", get(get_task))
@@ -471,6 +782,12 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let inner = Router::new()
         .route("/", get(get_tasks).post(create_task))
         .route("/stream/ws", get(stream_tasks_ws))
+        .route("/search", get(search_tasks))
+        .route("/assignee-counts", get(get_assignee_counts))
+        .route("/due", get(get_due_tasks))
+        .route("/bulk", post(create_tasks_bulk))
+        .route("/deleted", get(get_deleted_tasks))
+        .route("/{task_id}/restore", post(restore_task))
         .route("/create-and-start", post(create_task_and_start))
         .nest("/{task_id}", task_id_router);
 
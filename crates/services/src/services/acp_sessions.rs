@@ -0,0 +1,264 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use db::{
+    DBService,
+    models::{
+        coding_agent_turn::{AgentSessionRef, CodingAgentTurn},
+        task::TaskStatus,
+    },
+};
+use executors::executors::acp::{AcpAgentHarness, SESSION_NAMESPACES, SessionFileInfo};
+use serde::Serialize;
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{error, info};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Sessions untouched for this long are eligible for removal
+pub const MAX_SESSION_AGE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+/// Newest sessions kept for every task, even closed ones
+pub const KEEP_LATEST_PER_TASK: usize = 1;
+/// Page size used when listing sessions
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+#[derive(Debug, Error)]
+pub enum AcpSessionError {
+    #[error(transparent)]
+    Database(#[from] SqlxError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Unknown ACP session namespace: {0}")]
+    UnknownNamespace(String),
+    #[error("ACP session not found: {0}")]
+    SessionNotFound(String),
+}
+
+/// A stored ACP session and the workspace (task attempt) that created it
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AcpSessionSummary {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub info: SessionFileInfo,
+    pub workspace_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AcpSessionPage {
+    pub sessions: Vec<AcpSessionSummary>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AcpSessionTranscript {
+    pub session: AcpSessionSummary,
+    pub events: Vec<serde_json::Value>,
+}
+
+/// Service that periodically deletes stale ACP session files
+pub struct AcpSessionGcService {
+    db: DBService,
+    poll_interval: Duration,
+}
+
+impl AcpSessionGcService {
+    pub async fn spawn(db: DBService) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            poll_interval: Duration::from_secs(6 * 60 * 60),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            match collect_stale_sessions(&self.db, MAX_SESSION_AGE, KEEP_LATEST_PER_TASK).await {
+                Ok(deleted) if !deleted.is_empty() => {
+                    info!("Removed {} stale ACP sessions", deleted.len())
+                }
+                Ok(_) => {}
+                Err(e) => error!("Error cleaning up ACP sessions: {}", e),
+            }
+        }
+    }
+}
+
+/// Delete ACP session files in every namespace that were not modified within
+/// `older_than`. Sessions of tasks that are still open, and the newest
+/// `keep_latest_per_task` sessions of every task, are always kept.
+pub async fn collect_stale_sessions(
+    db: &DBService,
+    older_than: Duration,
+    keep_latest_per_task: usize,
+) -> Result<Vec<String>, AcpSessionError> {
+    let refs = CodingAgentTurn::find_agent_session_refs(&db.pool).await?;
+    let protected = protected_sessions(&refs, keep_latest_per_task);
+
+    let deleted = tokio::task::spawn_blocking(move || {
+        let mut deleted = Vec::new();
+        for namespace in SESSION_NAMESPACES {
+            deleted.extend(AcpAgentHarness::cleanup_sessions(
+                namespace, older_than, &protected,
+            )?);
+        }
+        Ok::<_, std::io::Error>(deleted)
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    Ok(deleted)
+}
+
+/// List the sessions of a namespace, most recently used first
+pub async fn list_sessions(
+    db: &DBService,
+    namespace: &str,
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<AcpSessionPage, AcpSessionError> {
+    let namespace = known_namespace(namespace)?;
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+
+    let sessions = tokio::task::spawn_blocking(move || AcpAgentHarness::list_sessions(namespace))
+        .await
+        .map_err(std::io::Error::other)??;
+    let total = sessions.len();
+
+    let refs = latest_refs(CodingAgentTurn::find_agent_session_refs(&db.pool).await?);
+    let sessions = sessions
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .map(|info| summarize(info, &refs))
+        .collect();
+
+    Ok(AcpSessionPage {
+        sessions,
+        total,
+        offset,
+        limit,
+    })
+}
+
+/// Metadata and normalized transcript of a single session
+pub async fn get_session(
+    db: &DBService,
+    namespace: &str,
+    session_id: &str,
+) -> Result<AcpSessionTranscript, AcpSessionError> {
+    let namespace = known_namespace(namespace)?;
+    // Session ids become file names; refuse anything that could leave the namespace
+    if session_id.is_empty() || session_id.contains(['/', '\\']) || session_id.contains("..") {
+        return Err(AcpSessionError::SessionNotFound(session_id.to_string()));
+    }
+
+    let id = session_id.to_string();
+    let (info, events) =
+        tokio::task::spawn_blocking(move || AcpAgentHarness::read_session(namespace, &id))
+            .await
+            .map_err(std::io::Error::other)??
+            .ok_or_else(|| AcpSessionError::SessionNotFound(session_id.to_string()))?;
+
+    let refs = latest_refs(CodingAgentTurn::find_agent_session_refs(&db.pool).await?);
+    Ok(AcpSessionTranscript {
+        session: summarize(info, &refs),
+        events,
+    })
+}
+
+fn known_namespace(namespace: &str) -> Result<&'static str, AcpSessionError> {
+    SESSION_NAMESPACES
+        .iter()
+        .copied()
+        .find(|known| *known == namespace)
+        .ok_or_else(|| AcpSessionError::UnknownNamespace(namespace.to_string()))
+}
+
+/// Index session refs by id, keeping the newest ref for each session
+fn latest_refs(refs: Vec<AgentSessionRef>) -> HashMap<String, AgentSessionRef> {
+    let mut by_id = HashMap::new();
+    for session in refs {
+        by_id
+            .entry(session.agent_session_id.clone())
+            .or_insert(session);
+    }
+    by_id
+}
+
+fn summarize(info: SessionFileInfo, refs: &HashMap<String, AgentSessionRef>) -> AcpSessionSummary {
+    let session_ref = refs.get(&info.session_id);
+    AcpSessionSummary {
+        workspace_id: session_ref.map(|r| r.workspace_id),
+        task_id: session_ref.map(|r| r.task_id),
+        info,
+    }
+}
+
+/// Session ids that must survive cleanup. `refs` is expected newest first.
+fn protected_sessions(refs: &[AgentSessionRef], keep_latest_per_task: usize) -> HashSet<String> {
+    let mut kept_per_task: HashMap<_, usize> = HashMap::new();
+    let mut protected = HashSet::new();
+
+    for session in refs {
+        let open = !matches!(
+            session.task_status,
+            TaskStatus::Done | TaskStatus::Cancelled
+        );
+        let kept = kept_per_task.entry(session.task_id).or_default();
+        if open || *kept < keep_latest_per_task {
+            protected.insert(session.agent_session_id.clone());
+        }
+        *kept += 1;
+    }
+    protected
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn session_ref(id: &str, task_id: Uuid, task_status: TaskStatus) -> AgentSessionRef {
+        AgentSessionRef {
+            agent_session_id: id.to_string(),
+            workspace_id: Uuid::new_v4(),
+            task_id,
+            task_status,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn open_tasks_and_latest_sessions_are_protected() {
+        let open_task = Uuid::new_v4();
+        let done_task = Uuid::new_v4();
+        let refs = vec![
+            session_ref("open-new", open_task, TaskStatus::InProgress),
+            session_ref("done-new", done_task, TaskStatus::Done),
+            session_ref("open-old", open_task, TaskStatus::InProgress),
+            session_ref("done-old", done_task, TaskStatus::Done),
+        ];
+
+        let protected = protected_sessions(&refs, 1);
+
+        assert!(protected.contains("open-new"));
+        assert!(protected.contains("open-old"));
+        assert!(protected.contains("done-new"));
+        assert!(!protected.contains("done-old"));
+    }
+}
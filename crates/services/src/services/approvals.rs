@@ -8,8 +8,7 @@ use std::{
 
 use dashmap::DashMap;
 use db::models::{
-    execution_process::ExecutionProcess,
-    task::{Task, TaskStatus},
+    execution_process::ExecutionProcess, task::TaskStatus, task_event::TaskEventActor,
 };
 use executors::{
     approvals::ToolCallMetadata,
@@ -19,9 +18,11 @@ use executors::{
     },
 };
 use futures::future::{BoxFuture, FutureExt, Shared};
+use serde::Serialize;
 use sqlx::{Error as SqlxError, SqlitePool};
 use thiserror::Error;
 use tokio::sync::{RwLock, oneshot};
+use ts_rs::TS;
 use utils::{
     approvals::{ApprovalRequest, ApprovalResponse, ApprovalStatus},
     log_msg::LogMsg,
@@ -29,6 +30,8 @@ use utils::{
 };
 use uuid::Uuid;
 
+use crate::services::task_transitions;
+
 #[derive(Debug)]
 struct PendingApproval {
     entry_index: usize,
@@ -46,6 +49,14 @@ pub struct ToolContext {
     pub execution_process_id: Uuid,
 }
 
+/// An approval request still waiting for a decision
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct PendingApprovalInfo {
+    pub approval_id: String,
+    pub execution_process_id: Uuid,
+    pub tool_name: String,
+}
+
 #[derive(Clone)]
 pub struct Approvals {
     pending: Arc<DashMap<String, PendingApproval>>,
@@ -144,6 +155,8 @@ impl Approvals {
         id: &str,
         req: ApprovalResponse,
     ) -> Result<(ApprovalStatus, ToolContext), ApprovalError> {
+        // Removing from `pending` is the single point of resolution, so concurrent
+        // responders for the same id can never both succeed.
         if let Some((_, p)) = self.pending.remove(id) {
             self.completed.insert(id.to_string(), req.status.clone());
             let _ = p.response_tx.send(req.status.clone());
@@ -177,7 +190,13 @@ impl Approvals {
             ) && let Ok(ctx) =
                 ExecutionProcess::load_context(pool, tool_ctx.execution_process_id).await
                 && ctx.task.status == TaskStatus::InReview
-                && let Err(e) = Task::update_status(pool, ctx.task.id, TaskStatus::InProgress).await
+                && let Err(e) = task_transitions::set_status(
+                    pool,
+                    ctx.task.id,
+                    TaskStatus::InProgress,
+                    TaskEventActor::System,
+                )
+                .await
             {
                 tracing::warn!(
                     "Failed to update task status to InProgress after approval response: {}",
@@ -193,6 +212,51 @@ impl Approvals {
         }
     }
 
+    /// Resolve several approvals with the same status. Each request is resolved
+    /// (and its waiting agent notified) individually; ids that are unknown or
+    /// already resolved are returned with their error.
+    pub async fn respond_many(
+        &self,
+        pool: &SqlitePool,
+        ids: &[String],
+        status: ApprovalStatus,
+    ) -> Vec<(String, Result<ToolContext, ApprovalError>)> {
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            let Some(execution_process_id) = self.pending.get(id).map(|p| p.execution_process_id)
+            else {
+                let err = if self.completed.contains_key(id) {
+                    ApprovalError::AlreadyCompleted
+                } else {
+                    ApprovalError::NotFound
+                };
+                results.push((id.clone(), Err(err)));
+                continue;
+            };
+            let req = ApprovalResponse {
+                execution_process_id,
+                status: status.clone(),
+            };
+            let result = self.respond(pool, id, req).await.map(|(_, ctx)| ctx);
+            results.push((id.clone(), result));
+        }
+        results
+    }
+
+    /// Pending approvals belonging to any of the given execution processes
+    pub fn pending(&self, execution_process_ids: &[Uuid]) -> Vec<PendingApprovalInfo> {
+        let id_set: HashSet<_> = execution_process_ids.iter().collect();
+        self.pending
+            .iter()
+            .filter(|entry| id_set.contains(&entry.value().execution_process_id))
+            .map(|entry| PendingApprovalInfo {
+                approval_id: entry.key().clone(),
+                execution_process_id: entry.value().execution_process_id,
+                tool_name: entry.value().tool_name.clone(),
+            })
+            .collect()
+    }
+
     #[tracing::instrument(skip(self, id, timeout_at, waiter))]
     fn spawn_timeout_watcher(
         &self,
@@ -285,7 +349,13 @@ impl Approvals {
 pub(crate) async fn ensure_task_in_review(pool: &SqlitePool, execution_process_id: Uuid) {
     if let Ok(ctx) = ExecutionProcess::load_context(pool, execution_process_id).await
         && ctx.task.status == TaskStatus::InProgress
-        && let Err(e) = Task::update_status(pool, ctx.task.id, TaskStatus::InReview).await
+        && let Err(e) = task_transitions::set_status(
+            pool,
+            ctx.task.id,
+            TaskStatus::InReview,
+            TaskEventActor::System,
+        )
+        .await
     {
         tracing::warn!(
             "Failed to update task status to InReview for approval request: {}",
@@ -422,4 +492,59 @@ mod tests {
             "Should not match different tool ids"
         );
     }
+
+    #[tokio::test]
+    async fn racing_batch_resolvers_resolve_each_request_once() {
+        let store = Arc::new(MsgStore::new());
+        let execution_process_id = Uuid::new_v4();
+        let call_ids = ["write-a", "write-b", "write-c"];
+        for (idx, call_id) in call_ids.iter().enumerate() {
+            let entry = create_tool_use_entry("Write", "file.rs", call_id, ToolStatus::Created);
+            store.push_patch(
+                executors::logs::utils::patch::ConversationPatch::add_normalized_entry(idx, entry),
+            );
+        }
+        let msg_stores = Arc::new(RwLock::new(HashMap::from([(
+            execution_process_id,
+            store.clone(),
+        )])));
+        let approvals = Approvals::new(msg_stores);
+
+        let mut approval_ids = Vec::new();
+        let mut waiters = Vec::new();
+        for call_id in call_ids {
+            let request = ApprovalRequest::from_create(
+                utils::approvals::CreateApprovalRequest {
+                    tool_name: "Write".to_string(),
+                    tool_input: serde_json::json!({}),
+                    tool_call_id: call_id.to_string(),
+                },
+                execution_process_id,
+            );
+            let (request, waiter) = approvals.create_with_waiter(request).await.unwrap();
+            approval_ids.push(request.id);
+            waiters.push(waiter);
+        }
+        assert_eq!(approvals.pending(&[execution_process_id]).len(), 3);
+
+        // No tables exist, so the task status update is skipped
+        let pool = SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let (first, second) = tokio::join!(
+            approvals.respond_many(&pool, &approval_ids, ApprovalStatus::Approved),
+            approvals.respond_many(&pool, &approval_ids, ApprovalStatus::Approved),
+        );
+
+        let results: Vec<_> = first.into_iter().chain(second).collect();
+        assert_eq!(results.iter().filter(|(_, r)| r.is_ok()).count(), 3);
+        assert!(
+            results
+                .iter()
+                .filter_map(|(_, r)| r.as_ref().err())
+                .all(|e| matches!(e, ApprovalError::AlreadyCompleted))
+        );
+        for waiter in waiters {
+            assert!(matches!(waiter.await, ApprovalStatus::Approved));
+        }
+        assert!(approvals.pending(&[execution_process_id]).is_empty());
+    }
 }
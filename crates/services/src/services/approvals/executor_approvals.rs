@@ -1,8 +1,18 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use db::{self, DBService, models::execution_process::ExecutionProcess};
-use executors::approvals::{ExecutorApprovalError, ExecutorApprovalService};
+use chrono::Utc;
+use db::{
+    self, DBService,
+    models::{
+        approval_event::{ApprovalDecider, ApprovalDecision, ApprovalEvent, CreateApprovalEvent},
+        execution_process::{ExecutionContext, ExecutionProcess},
+    },
+};
+use executors::{
+    actions::ExecutorActionType,
+    approvals::{ExecutorApprovalError, ExecutorApprovalService},
+};
 use serde_json::Value;
 use utils::approvals::{ApprovalRequest, ApprovalStatus, CreateApprovalRequest};
 use uuid::Uuid;
@@ -30,6 +40,82 @@ impl ExecutorApprovalBridge {
             execution_process_id,
         })
     }
+
+    /// Write the resolution of `request` to the approvals audit log
+    async fn record_resolution(&self, request: &ApprovalRequest, status: &ApprovalStatus) {
+        let (decision, decider, reason) = match status {
+            ApprovalStatus::Approved => (ApprovalDecision::Approved, ApprovalDecider::User, None),
+            ApprovalStatus::Denied { reason } => (
+                ApprovalDecision::Denied,
+                ApprovalDecider::User,
+                reason.clone(),
+            ),
+            ApprovalStatus::TimedOut => {
+                (ApprovalDecision::TimedOut, ApprovalDecider::Timeout, None)
+            }
+            ApprovalStatus::Pending => return,
+        };
+
+        let ctx =
+            match ExecutionProcess::load_context(&self.db.pool, self.execution_process_id).await {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    tracing::warn!("Failed to load context for approval audit log: {}", e);
+                    return;
+                }
+            };
+
+        let event = CreateApprovalEvent {
+            workspace_id: ctx.workspace.id,
+            execution_process_id: self.execution_process_id,
+            executor: executor_profile(&ctx),
+            tool_name: request.tool_name.clone(),
+            tool_call_id: request.tool_call_id.clone(),
+            tool_summary: summarize_tool_input(&request.tool_input),
+            decision,
+            decider,
+            reason,
+            requested_at: request.created_at,
+            resolved_at: Utc::now(),
+        };
+        if let Err(e) = ApprovalEvent::create(&self.db.pool, &event).await {
+            tracing::warn!("Failed to record approval event: {}", e);
+        }
+    }
+}
+
+/// Executor profile that ran the process, falling back to the session executor
+fn executor_profile(ctx: &ExecutionContext) -> Option<String> {
+    let profile = ctx
+        .execution_process
+        .executor_action()
+        .ok()
+        .and_then(|action| match &action.typ {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                Some(&request.executor_profile_id)
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                Some(&request.executor_profile_id)
+            }
+            ExecutorActionType::ReviewRequest(request) => Some(&request.executor_profile_id),
+            _ => None,
+        });
+    profile
+        .map(ToString::to_string)
+        .or_else(|| ctx.session.executor.clone())
+}
+
+const TOOL_SUMMARY_MAX_CHARS: usize = 500;
+
+/// Compact, bounded rendering of a tool call's input for the audit log
+fn summarize_tool_input(tool_input: &Value) -> String {
+    let rendered = tool_input.to_string();
+    if rendered.chars().count() <= TOOL_SUMMARY_MAX_CHARS {
+        return rendered;
+    }
+    let mut summary: String = rendered.chars().take(TOOL_SUMMARY_MAX_CHARS).collect();
+    summary.push('…');
+    summary
 }
 
 #[async_trait]
@@ -51,7 +137,7 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
             self.execution_process_id,
         );
 
-        let (_, waiter) = self
+        let (request, waiter) = self
             .approvals
             .create_with_waiter(request)
             .await
@@ -77,6 +163,168 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
             ));
         }
 
+        self.record_resolution(&request, &status).await;
+
         Ok(status)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use db::{
+        models::{
+            execution_process::{CreateExecutionProcess, ExecutionProcessRunReason},
+            session::{CreateSession, Session},
+            workspace::{CreateWorkspace, Workspace},
+        },
+        test_support::{create_task, pool_with_project},
+    };
+    use executors::{
+        actions::{
+            ExecutorAction,
+            script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+        },
+        approvals::ToolCallMetadata,
+        logs::{
+            ActionType, NormalizedEntry, NormalizedEntryType, ToolStatus,
+            utils::patch::ConversationPatch,
+        },
+    };
+    use sqlx::SqlitePool;
+    use tokio::sync::RwLock;
+    use utils::{approvals::ApprovalResponse, msg_store::MsgStore};
+
+    use super::*;
+    use crate::services::config::Config;
+
+    /// A running process of a fresh attempt whose log holds one tool call
+    /// waiting for approval
+    async fn process_with_tool_call(
+        tool_call_id: &str,
+    ) -> (SqlitePool, Workspace, Uuid, Arc<MsgStore>) {
+        let (pool, project_id) = pool_with_project().await;
+        let task = create_task(&pool, project_id, "Guarded").await;
+        let workspace = CreateWorkspace {
+            branch: "guarded".to_string(),
+            agent_working_dir: None,
+        };
+        let workspace = Workspace::create(&pool, &workspace, Uuid::new_v4(), task.id)
+            .await
+            .unwrap();
+        let session = CreateSession {
+            executor: Some("CLAUDE_CODE".to_string()),
+        };
+        let session = Session::create(&pool, &session, Uuid::new_v4(), workspace.id)
+            .await
+            .unwrap();
+        let action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: "true".to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::SetupScript,
+                working_dir: None,
+            }),
+            None,
+        );
+        let process = CreateExecutionProcess {
+            session_id: session.id,
+            executor_action: action,
+            run_reason: ExecutionProcessRunReason::CodingAgent,
+        };
+        let process = ExecutionProcess::create(&pool, &process, Uuid::new_v4(), &[])
+            .await
+            .unwrap();
+
+        let store = Arc::new(MsgStore::new());
+        let entry = NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "Bash".to_string(),
+                action_type: ActionType::Other {
+                    description: "rm -rf target".to_string(),
+                },
+                status: ToolStatus::Created,
+            },
+            content: "rm -rf target".to_string(),
+            metadata: Some(
+                serde_json::to_value(ToolCallMetadata {
+                    tool_call_id: tool_call_id.to_string(),
+                })
+                .unwrap(),
+            ),
+        };
+        store.push_patch(ConversationPatch::add_normalized_entry(0, entry));
+        (pool, workspace, process.id, store)
+    }
+
+    #[tokio::test]
+    async fn resolved_approvals_are_recorded_for_the_attempt() {
+        let (pool, workspace, process_id, store) = process_with_tool_call("call-1").await;
+        let approvals = Approvals::new(Arc::new(RwLock::new(HashMap::from([(process_id, store)]))));
+        let mut config = Config::default();
+        config.notifications.sound_enabled = false;
+        config.notifications.push_enabled = false;
+        let bridge = ExecutorApprovalBridge::new(
+            approvals.clone(),
+            DBService { pool: pool.clone() },
+            NotificationService::new(Arc::new(RwLock::new(config))),
+            process_id,
+        );
+
+        let request = tokio::spawn(async move {
+            bridge
+                .request_tool_approval(
+                    "Bash",
+                    serde_json::json!({"command": "rm -rf target"}),
+                    "call-1",
+                )
+                .await
+        });
+        let approval_id = loop {
+            if let Some(pending) = approvals.pending(&[process_id]).pop() {
+                break pending.approval_id;
+            }
+            tokio::task::yield_now().await;
+        };
+        let denial = ApprovalStatus::Denied {
+            reason: Some("Not in this repo".to_string()),
+        };
+        let response = ApprovalResponse {
+            execution_process_id: process_id,
+            status: denial,
+        };
+        approvals
+            .respond(&pool, &approval_id, response)
+            .await
+            .unwrap();
+        let status = request.await.unwrap().unwrap();
+        assert!(matches!(status, ApprovalStatus::Denied { .. }));
+
+        let events = ApprovalEvent::find_by_task_attempt(&pool, workspace.id)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.execution_process_id, process_id);
+        assert_eq!(event.executor.as_deref(), Some("CLAUDE_CODE"));
+        assert_eq!(event.tool_name, "Bash");
+        assert_eq!(event.tool_call_id, "call-1");
+        assert_eq!(event.tool_summary, r#"{"command":"rm -rf target"}"#);
+        assert_eq!(event.decision, ApprovalDecision::Denied);
+        assert_eq!(event.decider, ApprovalDecider::User);
+        assert_eq!(event.reason.as_deref(), Some("Not in this repo"));
+    }
+
+    #[test]
+    fn tool_summary_is_bounded() {
+        let short = serde_json::json!({"command": "npm test"});
+        assert_eq!(summarize_tool_input(&short), r#"{"command":"npm test"}"#);
+
+        let long = serde_json::json!({"content": "x".repeat(2 * TOOL_SUMMARY_MAX_CHARS)});
+        let summary = summarize_tool_input(&long);
+        assert_eq!(summary.chars().count(), TOOL_SUMMARY_MAX_CHARS + 1);
+        assert!(summary.ends_with('…'));
+    }
+}
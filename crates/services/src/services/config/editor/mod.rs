@@ -111,10 +111,12 @@ impl EditorConfig {
                 })?;
 
         let (executable, args) = command_parts.into_resolved().await.map_err(|e| match e {
-            ExecutorError::ExecutableNotFound { program } => EditorOpenError::ExecutableNotFound {
-                executable: program,
-                editor_type: self.editor_type.clone(),
-            },
+            ExecutorError::ExecutableNotFound { program, .. } => {
+                EditorOpenError::ExecutableNotFound {
+                    executable: program,
+                    editor_type: self.editor_type.clone(),
+                }
+            }
             _ => EditorOpenError::InvalidCommand {
                 details: e.to_string(),
                 editor_type: self.editor_type.clone(),
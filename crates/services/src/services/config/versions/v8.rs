@@ -17,6 +17,10 @@ fn default_pr_auto_description_enabled() -> bool {
     true
 }
 
+fn default_execution_heartbeat_secs() -> Option<u64> {
+    Some(30)
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
 pub struct Config {
     pub config_version: String,
@@ -47,6 +51,30 @@ pub struct Config {
     pub beta_workspaces_invitation_sent: bool,
     #[serde(default)]
     pub commit_reminder: bool,
+    #[serde(default)]
+    pub execution_output_limit_bytes: Option<u64>,
+    #[serde(default)]
+    pub kill_on_output_overflow: bool,
+    #[serde(default = "default_execution_heartbeat_secs")]
+    pub execution_heartbeat_secs: Option<u64>,
+    #[serde(default)]
+    pub retain_scratch_on_failure: bool,
+    #[serde(default)]
+    pub scratch_dir_limit_bytes: Option<u64>,
+    /// Connections the app keeps to its database, the default when unset
+    #[serde(default)]
+    pub database_max_connections: Option<u32>,
+    /// How long a database write waits for another one to finish before
+    /// failing, the default when unset
+    #[serde(default)]
+    pub database_busy_timeout_ms: Option<u64>,
+    /// Days a deleted task can be restored before it is purged, the default
+    /// when unset
+    #[serde(default)]
+    pub deleted_task_retention_days: Option<u32>,
+    /// Days task activity log entries are kept, the default when unset
+    #[serde(default)]
+    pub task_event_retention_days: Option<u32>,
 }
 
 impl Config {
@@ -75,6 +103,15 @@ impl Config {
             beta_workspaces: false,
             beta_workspaces_invitation_sent: false,
             commit_reminder: false,
+            execution_output_limit_bytes: None,
+            kill_on_output_overflow: false,
+            execution_heartbeat_secs: default_execution_heartbeat_secs(),
+            retain_scratch_on_failure: false,
+            scratch_dir_limit_bytes: None,
+            database_max_connections: None,
+            database_busy_timeout_ms: None,
+            deleted_task_retention_days: None,
+            task_event_retention_days: None,
         }
     }
 
@@ -128,6 +165,15 @@ impl Default for Config {
             beta_workspaces: false,
             beta_workspaces_invitation_sent: false,
             commit_reminder: false,
+            execution_output_limit_bytes: None,
+            kill_on_output_overflow: false,
+            execution_heartbeat_secs: default_execution_heartbeat_secs(),
+            retain_scratch_on_failure: false,
+            scratch_dir_limit_bytes: None,
+            database_max_connections: None,
+            database_busy_timeout_ms: None,
+            deleted_task_retention_days: None,
+            task_event_retention_days: None,
         }
     }
 }
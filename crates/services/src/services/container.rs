@@ -20,7 +20,8 @@ use db::{
         },
         repo::Repo,
         session::{CreateSession, Session, SessionError},
-        task::{Task, TaskStatus},
+        task::TaskStatus,
+        task_event::TaskEventActor,
         workspace::{Workspace, WorkspaceError},
         workspace_repo::WorkspaceRepo,
     },
@@ -35,7 +36,8 @@ use executors::{
         coding_agent_initial::CodingAgentInitialRequest,
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
     },
-    executors::{ExecutorError, StandardCodingAgentExecutor},
+    env::{ExecutionEnv, RepoContext},
+    executors::{ExecutorError, ProcessIds, StandardCodingAgentExecutor},
     logs::{NormalizedEntry, NormalizedEntryError, NormalizedEntryType, utils::ConversationPatch},
     profile::ExecutorProfileId,
 };
@@ -45,7 +47,8 @@ use thiserror::Error;
 use tokio::{sync::RwLock, task::JoinHandle};
 use utils::{
     log_msg::LogMsg,
-    msg_store::MsgStore,
+    msg_store::{LogFilter, LogPage, MsgStore, Overflow},
+    redact::Redactor,
     text::{git_branch_id, short_uuid},
 };
 use uuid::Uuid;
@@ -54,6 +57,7 @@ use crate::services::{
     git::{GitService, GitServiceError},
     notification::NotificationService,
     share::SharePublisher,
+    task_transitions::{self, TaskTransitionError},
     workspace_manager::WorkspaceError as WorkspaceManagerError,
     worktree_manager::WorktreeError,
 };
@@ -75,6 +79,8 @@ pub enum ContainerError {
     WorkspaceManager(#[from] WorkspaceManagerError),
     #[error(transparent)]
     Session(#[from] SessionError),
+    #[error(transparent)]
+    TaskTransition(#[from] TaskTransitionError),
     #[error("Io error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Failed to kill process: {0}")]
@@ -169,7 +175,14 @@ pub trait ContainerService {
         share_publisher: Option<&SharePublisher>,
         ctx: &ExecutionContext,
     ) {
-        match Task::update_status(&self.db().pool, ctx.task.id, TaskStatus::InReview).await {
+        match task_transitions::set_status(
+            &self.db().pool,
+            ctx.task.id,
+            TaskStatus::InReview,
+            TaskEventActor::System,
+        )
+        .await
+        {
             Ok(_) => {
                 if let Some(publisher) = share_publisher
                     && let Err(err) = publisher.update_shared_task_by_id(ctx.task.id).await
@@ -276,7 +289,14 @@ pub trait ContainerService {
                     Workspace::find_by_id(&self.db().pool, session.workspace_id).await
                 && let Ok(Some(task)) = workspace.parent_task(&self.db().pool).await
             {
-                match Task::update_status(&self.db().pool, task.id, TaskStatus::InReview).await {
+                match task_transitions::set_status(
+                    &self.db().pool,
+                    task.id,
+                    TaskStatus::InReview,
+                    TaskEventActor::System,
+                )
+                .await
+                {
                     Ok(_) => {
                         if let Some(publisher) = self.share_publisher()
                             && let Err(err) = publisher.update_shared_task_by_id(task.id).await
@@ -539,6 +559,9 @@ pub trait ContainerService {
         status: ExecutionProcessStatus,
     ) -> Result<(), ContainerError>;
 
+    /// PID and process-group ID of a running execution; `None` once it has exited.
+    async fn process_ids(&self, execution_id: &Uuid) -> Option<ProcessIds>;
+
     async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError>;
 
     async fn copy_project_files(
@@ -579,10 +602,11 @@ pub trait ContainerService {
         id: &Uuid,
     ) -> Option<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>> {
         if let Some(store) = self.get_msg_store_by_id(id).await {
-            // First try in-memory store
+            // First try in-memory store. Raw output can arrive much faster than
+            // a viewer reads it, so merge backed-up chunks instead of dropping them.
             return Some(
                 store
-                    .history_plus_stream()
+                    .history_plus_stream_with(Overflow::CoalesceRaw)
                     .filter(|msg| {
                         future::ready(matches!(
                             msg,
@@ -797,6 +821,50 @@ pub trait ContainerService {
         }
     }
 
+    /// The live store of a running execution, or for a finished one a store
+    /// rebuilt from its persisted raw logs followed by their normalization
+    async fn log_store_for_search(&self, id: &Uuid) -> Option<Arc<MsgStore>> {
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            return Some(store);
+        }
+
+        let raw: Vec<LogMsg> = self
+            .stream_raw_logs(id)
+            .await?
+            .filter_map(|msg| future::ready(msg.ok()))
+            .collect()
+            .await;
+        let normalized: Vec<LogMsg> = match self.stream_normalized_logs(id).await {
+            Some(stream) => {
+                stream
+                    .filter_map(|msg| future::ready(msg.ok()))
+                    .collect()
+                    .await
+            }
+            None => Vec::new(),
+        };
+
+        let store = MsgStore::new();
+        for msg in raw.into_iter().chain(normalized) {
+            if !matches!(msg, LogMsg::Finished) {
+                store.push(msg);
+            }
+        }
+        store.push_finished();
+        Some(Arc::new(store))
+    }
+
+    async fn search_logs(
+        &self,
+        id: &Uuid,
+        filter: &LogFilter,
+        cursor: usize,
+        limit: usize,
+    ) -> Option<LogPage> {
+        let store = self.log_store_for_search(id).await?;
+        Some(store.history_filtered(filter, cursor, limit))
+    }
+
     fn spawn_stream_raw_logs_to_db(&self, execution_id: &Uuid) -> JoinHandle<()> {
         let execution_id = *execution_id;
         let msg_stores = self.msg_stores().clone();
@@ -978,7 +1046,13 @@ pub trait ContainerService {
         if task.status != TaskStatus::InProgress
             && run_reason != &ExecutionProcessRunReason::DevServer
         {
-            Task::update_status(&self.db().pool, task.id, TaskStatus::InProgress).await?;
+            task_transitions::set_status(
+                &self.db().pool,
+                task.id,
+                TaskStatus::InProgress,
+                TaskEventActor::System,
+            )
+            .await?;
 
             if let Some(publisher) = self.share_publisher()
                 && let Err(err) = publisher.update_shared_task_by_id(task.id).await
@@ -1079,10 +1153,26 @@ pub trait ContainerService {
                     update_error
                 );
             }
-            Task::update_status(&self.db().pool, task.id, TaskStatus::InReview).await?;
+            if let Err(e) = task_transitions::set_status(
+                &self.db().pool,
+                task.id,
+                TaskStatus::InReview,
+                TaskEventActor::System,
+            )
+            .await
+            {
+                tracing::error!("Failed to update task status to InReview after start error: {e}");
+            }
 
-            // Emit stderr error message
-            let log_message = LogMsg::Stderr(format!("Failed to start execution: {start_error}"));
+            // Emit stderr error message, without any secrets from the command context
+            let env = ExecutionEnv::new(
+                RepoContext::new(self.workspace_to_current_dir(workspace), Vec::new()),
+                false,
+            );
+            let redactor = Redactor::new(executor_action.secret_values(&env).await);
+            let log_message = LogMsg::Stderr(
+                redactor.redact(&format!("Failed to start execution: {start_error}")),
+            );
             if let Ok(json_line) = serde_json::to_string(&log_message) {
                 let _ = ExecutionProcessLogs::append_log_line(
                     &self.db().pool,
@@ -1093,8 +1183,10 @@ pub trait ContainerService {
             }
 
             // Emit NextAction with failure context for coding agent requests
-            if let ContainerError::ExecutorError(ExecutorError::ExecutableNotFound { program }) =
-                &start_error
+            if let ContainerError::ExecutorError(ExecutorError::ExecutableNotFound {
+                program,
+                ..
+            }) = &start_error
             {
                 let help_text = format!("The required executable `{program}` is not installed.");
                 let error_message = NormalizedEntry {
@@ -0,0 +1,404 @@
+//! Task and attempt changes published to live subscribers, such as the
+//! per-project server-sent events stream.
+//!
+//! [`EventBusFeeder`] follows the task activity log and publishes each
+//! entry once the transaction that wrote it has committed, so subscribers
+//! never see a change that is later rolled back. The bus keeps the most
+//! recent changes in a ring buffer, letting a reconnecting subscriber
+//! resume from the last change it saw.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::task_event::{ProjectTaskEvent, TaskEvent, TaskEventActor, TaskEventType},
+};
+use futures::{Stream, StreamExt, stream};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::SqlitePool;
+use tokio::{sync::broadcast, time::interval};
+use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
+use tracing::error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Changes kept for subscribers resuming after a disconnect
+const RING_CAPACITY: usize = 1024;
+/// Log entries published per poll
+const BATCH_SIZE: i64 = 500;
+
+/// One change to a task of a project
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct ProjectChange {
+    /// Position on the bus, sent as the SSE event id
+    #[ts(type = "number")]
+    pub id: u64,
+    pub project_id: Uuid,
+    /// `task.status_changed`, `attempt.started`, ...
+    pub event_type: String,
+    pub task_id: Uuid,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+    pub actor: TaskEventActor,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// What a subscriber receives
+#[derive(Debug, Clone, PartialEq)]
+pub enum BusMessage {
+    Change(ProjectChange),
+    /// Changes were missed, either because the resume point is no longer
+    /// buffered or because the subscriber fell behind. The subscriber
+    /// should reload what it shows.
+    Resync,
+}
+
+/// Name of the change a log entry records
+pub fn event_name(event_type: TaskEventType) -> &'static str {
+    match event_type {
+        TaskEventType::Created => "task.created",
+        TaskEventType::StatusChange => "task.status_changed",
+        TaskEventType::PriorityChange => "task.priority_changed",
+        TaskEventType::AssigneeChange => "task.assignee_changed",
+        TaskEventType::DueDateChange => "task.due_date_changed",
+        TaskEventType::ProjectChange => "task.project_changed",
+        TaskEventType::AttemptStarted => "attempt.started",
+        TaskEventType::AttemptFinished => "attempt.finished",
+        TaskEventType::DependencyAdded => "task.dependency_added",
+        TaskEventType::DependencyRemoved => "task.dependency_removed",
+        TaskEventType::Archived => "task.archived",
+        TaskEventType::Unarchived => "task.unarchived",
+        TaskEventType::Deleted => "task.deleted",
+        TaskEventType::Restored => "task.restored",
+        TaskEventType::WipLimitOverride => "task.wip_limit_override",
+        TaskEventType::ExternalConflict => "task.external_conflict",
+    }
+}
+
+struct Ring {
+    next_id: u64,
+    recent: VecDeque<ProjectChange>,
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    ring: Arc<Mutex<Ring>>,
+    sender: broadcast::Sender<ProjectChange>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(RING_CAPACITY);
+        Self {
+            ring: Arc::new(Mutex::new(Ring {
+                next_id: 1,
+                recent: VecDeque::with_capacity(RING_CAPACITY),
+            })),
+            sender,
+        }
+    }
+
+    /// Publish a committed change, numbering it after the previous one
+    pub fn publish(&self, event: &ProjectTaskEvent) -> ProjectChange {
+        let mut ring = self.ring.lock().unwrap();
+        let change = ProjectChange {
+            id: ring.next_id,
+            project_id: event.project_id,
+            event_type: event_name(event.event.event_type).to_string(),
+            task_id: event.event.task_id,
+            old_value: event.event.old_value.clone(),
+            new_value: event.event.new_value.clone(),
+            actor: event.event.actor,
+            occurred_at: event.event.created_at,
+        };
+        ring.next_id += 1;
+        if ring.recent.len() == RING_CAPACITY {
+            ring.recent.pop_front();
+        }
+        ring.recent.push_back(change.clone());
+        // No subscribers is fine
+        let _ = self.sender.send(change.clone());
+        change
+    }
+
+    /// Changes of the project from now on, preceded by those after
+    /// `last_event_id` when resuming. Starts with [`BusMessage::Resync`]
+    /// instead when the changes to resume from are no longer buffered.
+    pub fn subscribe(
+        &self,
+        project_id: Uuid,
+        last_event_id: Option<u64>,
+    ) -> impl Stream<Item = BusMessage> + Send + 'static {
+        // Holding the ring while subscribing keeps a change from being
+        // both replayed and received live, or neither
+        let ring = self.ring.lock().unwrap();
+        let receiver = self.sender.subscribe();
+        let replay = match last_event_id {
+            None => vec![],
+            Some(last) => {
+                let oldest = ring.recent.front().map_or(ring.next_id, |change| change.id);
+                if last + 1 < oldest || last >= ring.next_id {
+                    vec![BusMessage::Resync]
+                } else {
+                    ring.recent
+                        .iter()
+                        .filter(|change| change.id > last && change.project_id == project_id)
+                        .cloned()
+                        .map(BusMessage::Change)
+                        .collect()
+                }
+            }
+        };
+        drop(ring);
+
+        let live = BroadcastStream::new(receiver).filter_map(move |received| async move {
+            match received {
+                Ok(change) if change.project_id == project_id => Some(BusMessage::Change(change)),
+                Ok(_) => None,
+                Err(BroadcastStreamRecvError::Lagged(_)) => Some(BusMessage::Resync),
+            }
+        });
+        stream::iter(replay).chain(live)
+    }
+}
+
+/// Publish the log entries written after `cursor`, moving it past them.
+/// Returns the number published.
+pub async fn publish_new_events(
+    pool: &SqlitePool,
+    bus: &EventBus,
+    cursor: &mut i64,
+) -> Result<usize, sqlx::Error> {
+    let events = TaskEvent::find_after(pool, *cursor, BATCH_SIZE).await?;
+    for event in &events {
+        bus.publish(event);
+        *cursor = event.event.id;
+    }
+    Ok(events.len())
+}
+
+/// Follows the task activity log and publishes new entries on the bus
+pub struct EventBusFeeder {
+    db: DBService,
+    bus: EventBus,
+    poll_interval: Duration,
+}
+
+impl EventBusFeeder {
+    pub async fn spawn(db: DBService, bus: EventBus) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            bus,
+            poll_interval: Duration::from_millis(250),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        // Only changes from now on; history is in the activity log
+        let mut cursor = match TaskEvent::latest_id(&self.db.pool).await {
+            Ok(id) => id,
+            Err(e) => {
+                error!("Error reading the task activity log: {}", e);
+                return;
+            }
+        };
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            loop {
+                match publish_new_events(&self.db.pool, &self.bus, &mut cursor).await {
+                    Ok(published) if published as i64 == BATCH_SIZE => continue,
+                    Ok(_) => break,
+                    Err(e) => {
+                        error!("Error publishing task changes: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use db::{
+        models::{
+            task::{CreateTask, Task, TaskStatus},
+            task_event::CreateTaskEvent,
+        },
+        test_support::create_project,
+    };
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+    use super::*;
+    use crate::services::task_transitions::set_status;
+
+    /// A file database, so a reader can run while a writer is mid
+    /// transaction
+    async fn pool_with_project(dir: &tempfile::TempDir) -> (SqlitePool, Uuid) {
+        let options = SqliteConnectOptions::new()
+            .filename(dir.path().join("db.sqlite"))
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(2)
+            .connect_with(options)
+            .await
+            .unwrap();
+        sqlx::migrate!("../db/migrations").run(&pool).await.unwrap();
+        let project_id = create_project(&pool, "Live").await;
+        (pool, project_id)
+    }
+
+    async fn create_task(pool: &SqlitePool, project_id: Uuid, title: &str) -> Task {
+        let data = CreateTask::from_title_description(project_id, title.to_string(), None);
+        let task = Task::create(pool, &data, Uuid::new_v4()).await.unwrap();
+        let created = CreateTaskEvent::new(task.id, TaskEventType::Created, TaskEventActor::User);
+        TaskEvent::create(pool, &created).await.unwrap();
+        task
+    }
+
+    /// Everything the stream has ready, without waiting for more
+    async fn drain(stream: &mut (impl Stream<Item = BusMessage> + Unpin)) -> Vec<BusMessage> {
+        let mut messages = vec![];
+        while let Ok(Some(message)) =
+            tokio::time::timeout(Duration::from_millis(50), stream.next()).await
+        {
+            messages.push(message);
+        }
+        messages
+    }
+
+    fn names(messages: &[BusMessage]) -> Vec<String> {
+        messages
+            .iter()
+            .map(|message| match message {
+                BusMessage::Change(change) => change.event_type.clone(),
+                BusMessage::Resync => "resync".to_string(),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn streams_committed_changes_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pool, project_id) = pool_with_project(&dir).await;
+        let bus = EventBus::new();
+        let mut cursor = TaskEvent::latest_id(&pool).await.unwrap();
+        let mut live = Box::pin(bus.subscribe(project_id, None));
+        let mut elsewhere = Box::pin(bus.subscribe(Uuid::new_v4(), None));
+
+        let task = create_task(&pool, project_id, "Stream me").await;
+        set_status(&pool, task.id, TaskStatus::InProgress, TaskEventActor::User)
+            .await
+            .unwrap();
+        set_status(&pool, task.id, TaskStatus::InReview, TaskEventActor::System)
+            .await
+            .unwrap();
+
+        // Written but not committed yet: nothing to publish
+        let mut tx = pool.begin().await.unwrap();
+        let pending =
+            CreateTaskEvent::new(task.id, TaskEventType::StatusChange, TaskEventActor::User)
+                .with_change(Some(Value::from("inreview")), Some(Value::from("done")));
+        TaskEvent::create(&mut *tx, &pending).await.unwrap();
+        assert_eq!(
+            publish_new_events(&pool, &bus, &mut cursor).await.unwrap(),
+            3
+        );
+        assert_eq!(
+            names(&drain(&mut live).await),
+            ["task.created", "task.status_changed", "task.status_changed"]
+        );
+        tx.commit().await.unwrap();
+
+        assert_eq!(
+            publish_new_events(&pool, &bus, &mut cursor).await.unwrap(),
+            1
+        );
+        let messages = drain(&mut live).await;
+        let [BusMessage::Change(change)] = &messages[..] else {
+            panic!("one change expected, got {messages:?}");
+        };
+        assert_eq!(change.id, 4);
+        assert_eq!(change.task_id, task.id);
+        assert_eq!(change.new_value, Some(Value::from("done")));
+        assert!(drain(&mut elsewhere).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn resumes_after_the_last_seen_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pool, project_id) = pool_with_project(&dir).await;
+        let bus = EventBus::new();
+        let mut cursor = 0;
+        for title in ["One", "Two", "Three"] {
+            create_task(&pool, project_id, title).await;
+        }
+        publish_new_events(&pool, &bus, &mut cursor).await.unwrap();
+
+        // Reconnecting after the first change replays the other two, then
+        // goes on live
+        let mut resumed = Box::pin(bus.subscribe(project_id, Some(1)));
+        let task = create_task(&pool, project_id, "Four").await;
+        publish_new_events(&pool, &bus, &mut cursor).await.unwrap();
+        let messages = drain(&mut resumed).await;
+        let ids: Vec<_> = messages
+            .iter()
+            .map(|message| match message {
+                BusMessage::Change(change) => change.id,
+                BusMessage::Resync => 0,
+            })
+            .collect();
+        assert_eq!(ids, [2, 3, 4]);
+        assert!(matches!(&messages[2], BusMessage::Change(c) if c.task_id == task.id));
+
+        // Up to date: nothing to replay
+        let mut current = Box::pin(bus.subscribe(project_id, Some(4)));
+        assert!(drain(&mut current).await.is_empty());
+        // Ids from before a restart are unknown
+        let mut stale = Box::pin(bus.subscribe(project_id, Some(40)));
+        assert_eq!(drain(&mut stale).await, [BusMessage::Resync]);
+    }
+
+    #[tokio::test]
+    async fn asks_for_a_resync_once_the_resume_point_is_gone() {
+        let bus = EventBus::new();
+        let project_id = Uuid::new_v4();
+        let event = ProjectTaskEvent {
+            event: TaskEvent {
+                id: 1,
+                task_id: Uuid::new_v4(),
+                event_type: TaskEventType::PriorityChange,
+                old_value: None,
+                new_value: None,
+                actor: TaskEventActor::User,
+                created_at: Utc::now(),
+            },
+            project_id,
+        };
+        for _ in 0..RING_CAPACITY + 1 {
+            bus.publish(&event);
+        }
+
+        let mut resumed = Box::pin(bus.subscribe(project_id, Some(0)));
+        assert_eq!(drain(&mut resumed).await, [BusMessage::Resync]);
+        let mut recent = Box::pin(bus.subscribe(project_id, Some(1)));
+        assert_eq!(drain(&mut recent).await.len(), RING_CAPACITY);
+    }
+}
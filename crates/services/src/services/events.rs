@@ -4,7 +4,7 @@ use db::{
     DBService,
     models::{
         execution_process::ExecutionProcess, project::Project, scratch::Scratch, session::Session,
-        task::Task, workspace::Workspace,
+        task::Task, task_dependency::TaskDependency, workspace::Workspace,
     },
 };
 use serde_json::json;
@@ -49,7 +49,8 @@ impl EventService {
         task_id: Uuid,
     ) -> Result<(), SqlxError> {
         if let Some(task) = Task::find_by_id(pool, task_id).await? {
-            let tasks = Task::find_by_project_id_with_attempt_status(pool, task.project_id).await?;
+            let tasks =
+                Task::find_by_project_id_with_attempt_status(pool, task.project_id, false).await?;
 
             if let Some(task_with_status) = tasks
                 .into_iter()
@@ -276,21 +277,45 @@ impl EventService {
                                         Task::find_by_project_id_with_attempt_status(
                                             &db.pool,
                                             task.project_id,
+                                            false,
                                         )
                                         .await
                                         && let Some(task_with_status) =
-                                            task_list.into_iter().find(|t| t.id == task.id)
+                                            task_list.iter().find(|t| t.id == task.id)
                                     {
                                         let patch = match hook.operation {
                                             SqliteOperation::Insert => {
-                                                task_patch::add(&task_with_status)
+                                                task_patch::add(task_with_status)
                                             }
+                                            // Add rather than replace so unarchived tasks
+                                            // reappear on boards that dropped them
                                             SqliteOperation::Update => {
-                                                task_patch::replace(&task_with_status)
+                                                task_patch::add(task_with_status)
                                             }
-                                            _ => task_patch::replace(&task_with_status), // fallback
+                                            _ => task_patch::replace(task_with_status), // fallback
                                         };
                                         msg_store_for_hook.push_patch(patch);
+
+                                        // A status change may block or unblock dependents
+                                        if matches!(hook.operation, SqliteOperation::Update)
+                                            && let Ok(dependents) =
+                                                TaskDependency::find_dependents(&db.pool, task.id)
+                                                    .await
+                                        {
+                                            for dependent in task_list.iter().filter(|t| {
+                                                dependents.iter().any(|d| d.id == t.id)
+                                            }) {
+                                                msg_store_for_hook
+                                                    .push_patch(task_patch::replace(dependent));
+                                            }
+                                        }
+                                        return;
+                                    }
+                                    // Archived and deleted tasks drop off the board
+                                    if matches!(hook.operation, SqliteOperation::Update)
+                                        && let Ok(true) = Task::is_hidden(&db.pool, task.id).await
+                                    {
+                                        msg_store_for_hook.push_patch(task_patch::remove(task.id));
                                         return;
                                     }
                                 }
@@ -303,9 +328,20 @@ impl EventService {
                                     return;
                                 }
                                 RecordTypes::Project(project) => {
+                                    // Archived projects drop out of the project list
+                                    if matches!(hook.operation, SqliteOperation::Update)
+                                        && let Ok(true) =
+                                            Project::is_archived(&db.pool, project.id).await
+                                    {
+                                        msg_store_for_hook
+                                            .push_patch(project_patch::remove(project.id));
+                                        return;
+                                    }
                                     let patch = match hook.operation {
                                         SqliteOperation::Insert => project_patch::add(project),
-                                        SqliteOperation::Update => project_patch::replace(project),
+                                        // Add rather than replace so unarchived projects
+                                        // reappear in lists that dropped them
+                                        SqliteOperation::Update => project_patch::add(project),
                                         _ => project_patch::replace(project),
                                     };
                                     msg_store_for_hook.push_patch(patch);
@@ -351,6 +387,7 @@ impl EventService {
                                             Task::find_by_project_id_with_attempt_status(
                                                 &db.pool,
                                                 task.project_id,
+                                                false,
                                             )
                                             .await
                                         && let Some(task_with_status) =
@@ -372,6 +409,7 @@ impl EventService {
                                             Task::find_by_project_id_with_attempt_status(
                                                 &db.pool,
                                                 task.project_id,
+                                                false,
                                             )
                                             .await
                                         && let Some(task_with_status) =
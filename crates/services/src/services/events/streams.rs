@@ -25,7 +25,8 @@ impl EventService {
     ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, EventError>
     {
         // Get initial snapshot of tasks
-        let tasks = Task::find_by_project_id_with_attempt_status(&self.db.pool, project_id).await?;
+        let tasks =
+            Task::find_by_project_id_with_attempt_status(&self.db.pool, project_id, false).await?;
 
         // Convert task array to object keyed by task ID
         let tasks_map: serde_json::Map<String, serde_json::Value> = tasks
@@ -175,8 +176,8 @@ impl EventService {
             LogMsg::JsonPatch(serde_json::from_value(patch).unwrap())
         }
 
-        // Get initial snapshot of projects
-        let projects = Project::find_all(&self.db.pool).await?;
+        // Get initial snapshot of projects, leaving out archived ones
+        let projects = Project::find_unarchived(&self.db.pool).await?;
         let initial_msg = build_projects_snapshot(projects);
 
         let db_pool = self.db.pool.clone();
@@ -202,7 +203,7 @@ impl EventService {
                                 "projects stream lagged; resyncing snapshot"
                             );
 
-                            match Project::find_all(&db_pool).await {
+                            match Project::find_unarchived(&db_pool).await {
                                 Ok(projects) => Some(Ok(build_projects_snapshot(projects))),
                                 Err(err) => {
                                     tracing::error!(
@@ -0,0 +1,697 @@
+//! Importing GitHub issues as tasks, and pushing task status back to them.
+//!
+//! Imported tasks keep a [`TaskExternalLink`] to their issue. When a linked
+//! task reaches Done or Cancelled its issue is closed, and moving it back
+//! reopens the issue. An issue changed upstream since the last sync in a
+//! way that disagrees with its task is left alone and logged on the task
+//! as an `external_conflict` event instead.
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use db::{
+    DBService,
+    models::{
+        project::Project,
+        task::{CreateTask, Task, TaskStatus},
+        task_event::{CreateTaskEvent, TaskEvent, TaskEventActor, TaskEventType},
+        task_external_link::{ExternalIssueState, ExternalProvider, TaskExternalLink},
+    },
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{error, info};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::config::Config;
+
+const GITHUB_API_BASE: &str = "https://api.github.com";
+const USER_AGENT: &str = concat!("vibe-kanban/", env!("CARGO_PKG_VERSION"));
+/// Most issues fetched in one import
+const MAX_IMPORTED_ISSUES: usize = 1000;
+
+#[derive(Debug, Error)]
+pub enum GitHubIssuesError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("GitHub API error: {status} - {message}")]
+    Api { status: u16, message: String },
+    #[error("Not a GitHub repository: {0}, expected owner/name")]
+    InvalidRepository(String),
+    #[error("Project is archived, unarchive it before adding tasks or attempts")]
+    ProjectArchived,
+}
+
+/// A GitHub repository as `owner/name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueRepo {
+    pub owner: String,
+    pub name: String,
+}
+
+impl IssueRepo {
+    /// Accepts `owner/name` or a github.com URL
+    pub fn parse(value: &str) -> Result<Self, GitHubIssuesError> {
+        let path = value
+            .trim()
+            .trim_start_matches("https://")
+            .trim_start_matches("github.com/")
+            .trim_end_matches('/')
+            .trim_end_matches(".git");
+        match path.split('/').collect::<Vec<_>>()[..] {
+            [owner, name] if !owner.is_empty() && !name.is_empty() => Ok(Self {
+                owner: owner.to_string(),
+                name: name.to_string(),
+            }),
+            _ => Err(GitHubIssuesError::InvalidRepository(value.to_string())),
+        }
+    }
+
+    pub fn full_name(&self) -> String {
+        format!("{}/{}", self.owner, self.name)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitHubIssue {
+    pub number: i64,
+    pub title: String,
+    pub body: Option<String>,
+    pub state: ExternalIssueState,
+    pub html_url: String,
+    /// Set when the issue is a pull request
+    #[serde(default)]
+    pub pull_request: Option<serde_json::Value>,
+}
+
+/// The parts of the GitHub API used for issue sync
+#[async_trait]
+pub trait IssueClient: Send + Sync {
+    /// Issues of the repository, pull requests included
+    async fn list_issues(
+        &self,
+        repo: &IssueRepo,
+        include_closed: bool,
+    ) -> Result<Vec<GitHubIssue>, GitHubIssuesError>;
+
+    async fn get_issue(
+        &self,
+        repo: &IssueRepo,
+        number: i64,
+    ) -> Result<GitHubIssue, GitHubIssuesError>;
+
+    async fn set_issue_state(
+        &self,
+        repo: &IssueRepo,
+        number: i64,
+        state: ExternalIssueState,
+        state_reason: &str,
+    ) -> Result<(), GitHubIssuesError>;
+
+    async fn create_comment(
+        &self,
+        repo: &IssueRepo,
+        number: i64,
+        body: &str,
+    ) -> Result<(), GitHubIssuesError>;
+}
+
+/// [`IssueClient`] over the GitHub REST API
+pub struct GitHubIssuesClient {
+    http: Client,
+    token: String,
+}
+
+impl GitHubIssuesClient {
+    pub fn new(token: String) -> Result<Self, GitHubIssuesError> {
+        let http = Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self { http, token })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.http
+            .request(method, format!("{GITHUB_API_BASE}{path}"))
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+    }
+
+    async fn check(response: reqwest::Response) -> Result<reqwest::Response, GitHubIssuesError> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        Err(GitHubIssuesError::Api {
+            status: response.status().as_u16(),
+            message: response.text().await.unwrap_or_default(),
+        })
+    }
+}
+
+#[async_trait]
+impl IssueClient for GitHubIssuesClient {
+    async fn list_issues(
+        &self,
+        repo: &IssueRepo,
+        include_closed: bool,
+    ) -> Result<Vec<GitHubIssue>, GitHubIssuesError> {
+        const PER_PAGE: usize = 100;
+        let state = if include_closed { "all" } else { "open" };
+        let mut issues = Vec::new();
+        for page in 1.. {
+            let path = format!(
+                "/repos/{}/{}/issues?state={state}&per_page={PER_PAGE}&page={page}",
+                repo.owner, repo.name
+            );
+            let response = self.request(reqwest::Method::GET, &path).send().await?;
+            let batch: Vec<GitHubIssue> = Self::check(response).await?.json().await?;
+            let done = batch.len() < PER_PAGE;
+            issues.extend(batch);
+            if done || issues.len() >= MAX_IMPORTED_ISSUES {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+
+    async fn get_issue(
+        &self,
+        repo: &IssueRepo,
+        number: i64,
+    ) -> Result<GitHubIssue, GitHubIssuesError> {
+        let path = format!("/repos/{}/{}/issues/{number}", repo.owner, repo.name);
+        let response = self.request(reqwest::Method::GET, &path).send().await?;
+        Ok(Self::check(response).await?.json().await?)
+    }
+
+    async fn set_issue_state(
+        &self,
+        repo: &IssueRepo,
+        number: i64,
+        state: ExternalIssueState,
+        state_reason: &str,
+    ) -> Result<(), GitHubIssuesError> {
+        let path = format!("/repos/{}/{}/issues/{number}", repo.owner, repo.name);
+        let response = self
+            .request(reqwest::Method::PATCH, &path)
+            .json(&json!({ "state": state, "state_reason": state_reason }))
+            .send()
+            .await?;
+        Self::check(response).await?;
+        Ok(())
+    }
+
+    async fn create_comment(
+        &self,
+        repo: &IssueRepo,
+        number: i64,
+        body: &str,
+    ) -> Result<(), GitHubIssuesError> {
+        let path = format!(
+            "/repos/{}/{}/issues/{number}/comments",
+            repo.owner, repo.name
+        );
+        let response = self
+            .request(reqwest::Method::POST, &path)
+            .json(&json!({ "body": body }))
+            .send()
+            .await?;
+        Self::check(response).await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct IssueImportReport {
+    /// Tasks created, one per issue
+    pub task_ids: Vec<Uuid>,
+    /// Issues already imported into the project
+    pub already_linked: usize,
+}
+
+/// Create a task for each issue of `repo` not yet imported into the
+/// project. Closed issues, when included, come in as Done tasks.
+pub async fn import_issues(
+    pool: &SqlitePool,
+    client: &dyn IssueClient,
+    project_id: Uuid,
+    repo: &IssueRepo,
+    include_closed: bool,
+) -> Result<IssueImportReport, GitHubIssuesError> {
+    let repository = repo.full_name();
+    let linked: HashSet<String> =
+        TaskExternalLink::find_linked_ids(pool, project_id, ExternalProvider::GitHub, &repository)
+            .await?
+            .into_iter()
+            .collect();
+    let issues = client.list_issues(repo, include_closed).await?;
+
+    let mut tx = pool.begin().await?;
+    if Project::is_archived(&mut *tx, project_id).await? {
+        return Err(GitHubIssuesError::ProjectArchived);
+    }
+    let mut task_ids = Vec::new();
+    let mut already_linked = 0;
+    for issue in issues.iter().filter(|issue| issue.pull_request.is_none()) {
+        let external_id = issue.number.to_string();
+        if linked.contains(&external_id) {
+            already_linked += 1;
+            continue;
+        }
+        let mut data = CreateTask::from_title_description(
+            project_id,
+            issue.title.clone(),
+            issue.body.clone().filter(|body| !body.trim().is_empty()),
+        );
+        if issue.state == ExternalIssueState::Closed {
+            data.status = Some(TaskStatus::Done);
+        }
+        let task = Task::insert(&mut tx, Uuid::new_v4(), &data).await?;
+        TaskEvent::create(
+            &mut *tx,
+            &CreateTaskEvent::new(task.id, TaskEventType::Created, TaskEventActor::System),
+        )
+        .await?;
+        TaskExternalLink::create(
+            &mut *tx,
+            task.id,
+            ExternalProvider::GitHub,
+            &repository,
+            &external_id,
+            &issue.html_url,
+            issue.state,
+        )
+        .await?;
+        task_ids.push(task.id);
+    }
+    tx.commit().await?;
+
+    Ok(IssueImportReport {
+        task_ids,
+        already_linked,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyncOutcome {
+    /// The issue already matched the task
+    Unchanged,
+    Closed,
+    Reopened,
+    /// The issue changed upstream and disagrees with the task; it was left
+    /// as it is and the conflict logged on the task
+    Conflict,
+}
+
+/// Bring the linked issue in line with the task's `status`
+pub async fn sync_link(
+    pool: &SqlitePool,
+    client: &dyn IssueClient,
+    link: &TaskExternalLink,
+    status: &TaskStatus,
+    event_id: i64,
+) -> Result<SyncOutcome, GitHubIssuesError> {
+    let repo = IssueRepo::parse(&link.repository)?;
+    let number: i64 = link
+        .external_id
+        .parse()
+        .map_err(|_| GitHubIssuesError::InvalidRepository(link.url.clone()))?;
+    let remote = client.get_issue(&repo, number).await?.state;
+    let wanted = match status {
+        TaskStatus::Done | TaskStatus::Cancelled => ExternalIssueState::Closed,
+        _ => ExternalIssueState::Open,
+    };
+
+    let (outcome, state) = if remote == wanted {
+        (SyncOutcome::Unchanged, remote)
+    } else if remote != link.remote_state {
+        let event = CreateTaskEvent::new(
+            link.task_id,
+            TaskEventType::ExternalConflict,
+            TaskEventActor::System,
+        )
+        .with_change(
+            Some(json!({ "task_status": status, "issue_state": link.remote_state })),
+            Some(json!({ "issue_state": remote, "url": link.url })),
+        );
+        TaskEvent::create(pool, &event).await?;
+        (SyncOutcome::Conflict, remote)
+    } else {
+        let (reason, comment, outcome) = match (wanted, status) {
+            (ExternalIssueState::Closed, TaskStatus::Cancelled) => (
+                "not_planned",
+                "Closed as not planned, the task was cancelled in Vibe Kanban.",
+                SyncOutcome::Closed,
+            ),
+            (ExternalIssueState::Closed, _) => (
+                "completed",
+                "Closed as completed, the task was moved to Done in Vibe Kanban.",
+                SyncOutcome::Closed,
+            ),
+            (ExternalIssueState::Open, _) => (
+                "reopened",
+                "Reopened, the task was moved back to work in Vibe Kanban.",
+                SyncOutcome::Reopened,
+            ),
+        };
+        client
+            .set_issue_state(&repo, number, wanted, reason)
+            .await?;
+        client.create_comment(&repo, number, comment).await?;
+        (outcome, wanted)
+    };
+
+    TaskExternalLink::mark_synced(pool, link.id, event_id, state).await?;
+    Ok(outcome)
+}
+
+/// Push the status changes of linked tasks not synced yet
+pub async fn sync_pending(
+    pool: &SqlitePool,
+    client: &dyn IssueClient,
+) -> Result<usize, GitHubIssuesError> {
+    let pending = TaskExternalLink::find_unsynced(pool, ExternalProvider::GitHub).await?;
+    let mut synced = 0;
+    for unsynced in pending {
+        let Some(task) = Task::find_by_id(pool, unsynced.link.task_id).await? else {
+            continue;
+        };
+        match sync_link(
+            pool,
+            client,
+            &unsynced.link,
+            &task.status,
+            unsynced.event_id,
+        )
+        .await
+        {
+            Ok(_) => synced += 1,
+            // Left unsynced to be tried again on the next run
+            Err(e) => error!("Failed to sync issue {}: {}", unsynced.link.url, e),
+        }
+    }
+    Ok(synced)
+}
+
+/// Periodically pushes task status changes to linked GitHub issues, with
+/// the token from the user's GitHub settings
+pub struct GitHubIssueSyncService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    poll_interval: Duration,
+}
+
+impl GitHubIssueSyncService {
+    pub async fn spawn(db: DBService, config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            poll_interval: Duration::from_secs(30),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            let Some(token) = self.config.read().await.github.token() else {
+                continue;
+            };
+            let client = match GitHubIssuesClient::new(token) {
+                Ok(client) => client,
+                Err(e) => {
+                    error!("Error creating GitHub client: {}", e);
+                    continue;
+                }
+            };
+            match sync_pending(&self.db.pool, &client).await {
+                Ok(synced) if synced > 0 => info!("Synced {} tasks to GitHub issues", synced),
+                Ok(_) => {}
+                Err(e) => error!("Error syncing GitHub issues: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use db::test_support::pool_with_project;
+
+    use super::*;
+
+    /// Issues of one repository kept in memory, recording every write
+    #[derive(Default)]
+    struct MockIssues {
+        issues: Mutex<HashMap<i64, GitHubIssue>>,
+        comments: Mutex<Vec<(i64, String)>>,
+    }
+
+    impl MockIssues {
+        fn with(issues: Vec<(i64, &str, ExternalIssueState, bool)>) -> Self {
+            let mock = Self::default();
+            for (number, title, state, pull_request) in issues {
+                mock.issues.lock().unwrap().insert(
+                    number,
+                    GitHubIssue {
+                        number,
+                        title: title.to_string(),
+                        body: Some(format!("Body of {title}")),
+                        state,
+                        html_url: format!("https://github.com/octo/repo/issues/{number}"),
+                        pull_request: pull_request.then(|| json!({})),
+                    },
+                );
+            }
+            mock
+        }
+
+        fn state(&self, number: i64) -> ExternalIssueState {
+            self.issues.lock().unwrap()[&number].state
+        }
+
+        fn set_state(&self, number: i64, state: ExternalIssueState) {
+            self.issues.lock().unwrap().get_mut(&number).unwrap().state = state;
+        }
+    }
+
+    #[async_trait]
+    impl IssueClient for MockIssues {
+        async fn list_issues(
+            &self,
+            _repo: &IssueRepo,
+            include_closed: bool,
+        ) -> Result<Vec<GitHubIssue>, GitHubIssuesError> {
+            let mut issues: Vec<_> = self
+                .issues
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|issue| include_closed || issue.state == ExternalIssueState::Open)
+                .cloned()
+                .collect();
+            issues.sort_by_key(|issue| issue.number);
+            Ok(issues)
+        }
+
+        async fn get_issue(
+            &self,
+            _repo: &IssueRepo,
+            number: i64,
+        ) -> Result<GitHubIssue, GitHubIssuesError> {
+            Ok(self.issues.lock().unwrap()[&number].clone())
+        }
+
+        async fn set_issue_state(
+            &self,
+            _repo: &IssueRepo,
+            number: i64,
+            state: ExternalIssueState,
+            _state_reason: &str,
+        ) -> Result<(), GitHubIssuesError> {
+            self.set_state(number, state);
+            Ok(())
+        }
+
+        async fn create_comment(
+            &self,
+            _repo: &IssueRepo,
+            number: i64,
+            body: &str,
+        ) -> Result<(), GitHubIssuesError> {
+            self.comments
+                .lock()
+                .unwrap()
+                .push((number, body.to_string()));
+            Ok(())
+        }
+    }
+
+    fn repo() -> IssueRepo {
+        IssueRepo::parse("https://github.com/octo/repo").unwrap()
+    }
+
+    /// Change the task's status the way the app does, logging the change
+    async fn move_task(pool: &SqlitePool, task_id: Uuid, status: TaskStatus) {
+        crate::services::task_transitions::set_status(
+            pool,
+            task_id,
+            status,
+            TaskEventActor::System,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn parses_repositories() {
+        assert_eq!(repo().full_name(), "octo/repo");
+        assert_eq!(IssueRepo::parse("octo/repo.git").unwrap(), repo());
+        assert!(IssueRepo::parse("octo").is_err());
+    }
+
+    #[tokio::test]
+    async fn imports_issues_once_and_skips_pull_requests() {
+        let (pool, project_id) = pool_with_project().await;
+        let github = MockIssues::with(vec![
+            (1, "Crash on start", ExternalIssueState::Open, false),
+            (2, "Fix the crash", ExternalIssueState::Open, true),
+            (3, "Old bug", ExternalIssueState::Closed, false),
+        ]);
+
+        let report = import_issues(&pool, &github, project_id, &repo(), true)
+            .await
+            .unwrap();
+        assert_eq!(report.task_ids.len(), 2);
+        let crash = Task::find_by_id(&pool, report.task_ids[0])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(crash.title, "Crash on start");
+        assert_eq!(crash.description.as_deref(), Some("Body of Crash on start"));
+        let old = Task::find_by_id(&pool, report.task_ids[1])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(old.status, TaskStatus::Done);
+        let links = TaskExternalLink::find_by_task(&pool, crash.id)
+            .await
+            .unwrap();
+        assert_eq!(links[0].repository, "octo/repo");
+        assert_eq!(links[0].external_id, "1");
+        let events = TaskEvent::find_by_task(&pool, crash.id, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(events[0].event_type, TaskEventType::Created);
+        assert_eq!(events[0].actor, TaskEventActor::System);
+
+        let again = import_issues(&pool, &github, project_id, &repo(), true)
+            .await
+            .unwrap();
+        assert!(again.task_ids.is_empty());
+        assert_eq!(again.already_linked, 2);
+    }
+
+    #[tokio::test]
+    async fn archived_projects_refuse_imports() {
+        let (pool, project_id) = pool_with_project().await;
+        Project::archive(&pool, project_id).await.unwrap();
+        let github = MockIssues::with(vec![(1, "Crash", ExternalIssueState::Open, false)]);
+
+        let result = import_issues(&pool, &github, project_id, &repo(), false).await;
+        assert!(matches!(result, Err(GitHubIssuesError::ProjectArchived)));
+        assert!(
+            TaskExternalLink::find_linked_ids(
+                &pool,
+                project_id,
+                ExternalProvider::GitHub,
+                "octo/repo"
+            )
+            .await
+            .unwrap()
+            .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn done_closes_the_issue_and_reopening_reopens_it() {
+        let (pool, project_id) = pool_with_project().await;
+        let github = MockIssues::with(vec![(1, "Crash", ExternalIssueState::Open, false)]);
+        let task_id = import_issues(&pool, &github, project_id, &repo(), false)
+            .await
+            .unwrap()
+            .task_ids[0];
+        assert_eq!(sync_pending(&pool, &github).await.unwrap(), 0);
+
+        move_task(&pool, task_id, TaskStatus::InProgress).await;
+        move_task(&pool, task_id, TaskStatus::InReview).await;
+        move_task(&pool, task_id, TaskStatus::Done).await;
+        assert_eq!(sync_pending(&pool, &github).await.unwrap(), 1);
+        assert_eq!(github.state(1), ExternalIssueState::Closed);
+        assert!(github.comments.lock().unwrap()[0].1.contains("Done"));
+        assert_eq!(sync_pending(&pool, &github).await.unwrap(), 0);
+
+        move_task(&pool, task_id, TaskStatus::InProgress).await;
+        sync_pending(&pool, &github).await.unwrap();
+        assert_eq!(github.state(1), ExternalIssueState::Open);
+        assert_eq!(github.comments.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn upstream_changes_become_conflicts() {
+        let (pool, project_id) = pool_with_project().await;
+        let github = MockIssues::with(vec![(1, "Crash", ExternalIssueState::Open, false)]);
+        let task_id = import_issues(&pool, &github, project_id, &repo(), false)
+            .await
+            .unwrap()
+            .task_ids[0];
+
+        // Closed on GitHub while the task is still being worked on
+        github.set_state(1, ExternalIssueState::Closed);
+        move_task(&pool, task_id, TaskStatus::InProgress).await;
+        let link = &TaskExternalLink::find_unsynced(&pool, ExternalProvider::GitHub)
+            .await
+            .unwrap()[0];
+        let outcome = sync_link(
+            &pool,
+            &github,
+            &link.link,
+            &TaskStatus::InProgress,
+            link.event_id,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(outcome, SyncOutcome::Conflict);
+        assert_eq!(github.state(1), ExternalIssueState::Closed);
+        assert!(github.comments.lock().unwrap().is_empty());
+        let events = TaskEvent::find_by_task(&pool, task_id, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(events[0].event_type, TaskEventType::ExternalConflict);
+        let links = TaskExternalLink::find_by_task(&pool, task_id)
+            .await
+            .unwrap();
+        assert_eq!(links[0].remote_state, ExternalIssueState::Closed);
+
+        // Once the task is done the two agree again
+        move_task(&pool, task_id, TaskStatus::InReview).await;
+        move_task(&pool, task_id, TaskStatus::Done).await;
+        sync_pending(&pool, &github).await.unwrap();
+        assert!(github.comments.lock().unwrap().is_empty());
+    }
+}
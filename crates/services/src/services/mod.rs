@@ -1,9 +1,11 @@
+pub mod acp_sessions;
 pub mod analytics;
 pub mod approvals;
 pub mod auth;
 pub mod config;
 pub mod container;
 pub mod diff_stream;
+pub mod event_bus;
 pub mod events;
 pub mod file_ranker;
 pub mod file_search;
@@ -11,16 +13,25 @@ pub mod filesystem;
 pub mod filesystem_watcher;
 pub mod git;
 pub mod git_host;
+pub mod github_issues;
 pub mod image;
 pub mod notification;
 pub mod oauth_credentials;
 pub mod pr_monitor;
 pub mod project;
+pub mod project_bundle;
+pub mod project_stats;
 #[cfg(feature = "qa-mode")]
 pub mod qa_repos;
 pub mod queued_message;
 pub mod remote_client;
 pub mod repo;
 pub mod share;
+pub mod task_import;
+pub mod task_templates;
+pub mod task_transitions;
+pub mod tasks;
+pub mod webhooks;
+pub mod wip_limits;
 pub mod workspace_manager;
 pub mod worktree_manager;
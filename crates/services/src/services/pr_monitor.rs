@@ -5,6 +5,7 @@ use db::{
     models::{
         merge::{Merge, MergeStatus, PrMerge},
         task::{Task, TaskStatus},
+        task_event::TaskEventActor,
         workspace::{Workspace, WorkspaceError},
     },
 };
@@ -18,6 +19,7 @@ use crate::services::{
     analytics::AnalyticsContext,
     git_host::{self, GitHostError, GitHostProvider},
     share::SharePublisher,
+    task_transitions::{self, TaskTransitionError},
 };
 
 #[derive(Debug, Error)]
@@ -28,6 +30,8 @@ enum PrMonitorError {
     WorkspaceError(#[from] WorkspaceError),
     #[error(transparent)]
     Sqlx(#[from] SqlxError),
+    #[error(transparent)]
+    TaskTransition(#[from] TaskTransitionError),
 }
 
 /// Service to monitor PRs and update task status when they are merged
@@ -123,7 +127,13 @@ impl PrMonitorService {
                     "PR #{} was merged, updating task {} to done and archiving workspace",
                     pr_merge.pr_info.number, workspace.task_id
                 );
-                Task::update_status(&self.db.pool, workspace.task_id, TaskStatus::Done).await?;
+                task_transitions::set_status(
+                    &self.db.pool,
+                    workspace.task_id,
+                    TaskStatus::Done,
+                    TaskEventActor::System,
+                )
+                .await?;
 
                 // Archive workspace unless pinned
                 if !workspace.pinned {
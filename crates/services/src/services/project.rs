@@ -6,9 +6,11 @@ use std::{
 use db::models::{
     project::{CreateProject, Project, ProjectError, SearchMatchType, SearchResult, UpdateProject},
     project_repo::{CreateProjectRepo, ProjectRepo},
+    project_settings::ProjectSettingsError,
     repo::Repo,
     task::Task,
 };
+use serde_json::Map;
 use sqlx::SqlitePool;
 use thiserror::Error;
 use utils::api::projects::RemoteProject;
@@ -29,6 +31,8 @@ pub enum ProjectServiceError {
     #[error(transparent)]
     Project(#[from] ProjectError),
     #[error(transparent)]
+    Settings(#[from] ProjectSettingsError),
+    #[error(transparent)]
     Share(#[from] ShareError),
     #[error("Path does not exist: {0}")]
     PathNotFound(PathBuf),
@@ -42,6 +46,8 @@ pub enum ProjectServiceError {
     DuplicateRepositoryName,
     #[error("Repository not found")]
     RepositoryNotFound,
+    #[error("Project is archived")]
+    ProjectArchived,
     #[error("Git operation failed: {0}")]
     GitError(String),
     #[error("Remote client error: {0}")]
@@ -50,6 +56,14 @@ pub enum ProjectServiceError {
 
 pub type Result<T> = std::result::Result<T, ProjectServiceError>;
 
+/// Refuse new tasks and attempts in an archived project
+pub async fn ensure_not_archived(pool: &SqlitePool, project_id: Uuid) -> Result<()> {
+    if Project::is_archived(pool, project_id).await? {
+        return Err(ProjectServiceError::ProjectArchived);
+    }
+    Ok(())
+}
+
 impl From<RepoError> for ProjectServiceError {
     fn from(e: RepoError) -> Self {
         match e {
@@ -125,6 +139,19 @@ impl ProjectService {
         payload: UpdateProject,
     ) -> Result<Project> {
         let project = Project::update(pool, existing.id, &payload).await?;
+        let mut settings = Map::new();
+        if let Some(relaxed) = payload.relaxed_status_transitions {
+            settings.insert("relaxed_status_transitions".to_string(), relaxed.into());
+        }
+        if let Some(excludes) = payload.wip_limit_excludes_child_tasks {
+            settings.insert(
+                "wip_limit_excludes_child_tasks".to_string(),
+                excludes.into(),
+            );
+        }
+        if !settings.is_empty() {
+            Project::update_settings(pool, existing.id, &settings).await?;
+        }
 
         Ok(project)
     }
@@ -319,3 +346,24 @@ impl ProjectService {
         Ok(all_results)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use db::test_support::pool_with_project;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn archived_projects_refuse_new_work() {
+        let (pool, project_id) = pool_with_project().await;
+
+        ensure_not_archived(&pool, project_id).await.unwrap();
+        Project::archive(&pool, project_id).await.unwrap();
+        assert!(matches!(
+            ensure_not_archived(&pool, project_id).await,
+            Err(ProjectServiceError::ProjectArchived)
+        ));
+        Project::unarchive(&pool, project_id).await.unwrap();
+        ensure_not_archived(&pool, project_id).await.unwrap();
+    }
+}
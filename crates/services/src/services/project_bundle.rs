@@ -0,0 +1,312 @@
+//! Moving a project between databases as a JSON bundle, see
+//! [`db::models::project_bundle`] for what a bundle holds.
+
+use db::models::{
+    project::Project,
+    project_bundle::{PROJECT_BUNDLE_VERSION, ProjectBundle},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum ProjectBundleError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Project not found")]
+    ProjectNotFound,
+    #[error("Bundle version {0} is not supported, expected {PROJECT_BUNDLE_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// What to do when a project with the bundle's name already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum NameConflict {
+    /// Import under the name with the first free ` (n)` suffix
+    #[default]
+    Rename,
+    /// Leave the existing project alone and import nothing
+    Skip,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+#[ts(tag = "outcome", rename_all = "snake_case")]
+pub enum ProjectImport {
+    Imported { project_id: Uuid, name: String },
+    Skipped { existing_project_id: Uuid },
+}
+
+pub async fn export(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    include_execution_history: bool,
+) -> Result<ProjectBundle, ProjectBundleError> {
+    ProjectBundle::load(pool, project_id, include_execution_history)
+        .await?
+        .ok_or(ProjectBundleError::ProjectNotFound)
+}
+
+/// Recreate the bundle's project in one transaction, so a failed import
+/// leaves nothing behind
+pub async fn import(
+    pool: &SqlitePool,
+    bundle: &ProjectBundle,
+    on_name_conflict: NameConflict,
+) -> Result<ProjectImport, ProjectBundleError> {
+    if bundle.version != PROJECT_BUNDLE_VERSION {
+        return Err(ProjectBundleError::UnsupportedVersion(bundle.version));
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut name = bundle.project.name.clone();
+    if let Some(existing_project_id) = Project::find_id_by_name(&mut *tx, &name).await? {
+        if on_name_conflict == NameConflict::Skip {
+            return Ok(ProjectImport::Skipped {
+                existing_project_id,
+            });
+        }
+        for suffix in 2.. {
+            name = format!("{} ({suffix})", bundle.project.name);
+            if Project::find_id_by_name(&mut *tx, &name).await?.is_none() {
+                break;
+            }
+        }
+    }
+
+    let project_id = bundle.insert(&mut tx, &name).await?;
+    tx.commit().await?;
+    tracing::info!(
+        "Imported project '{}' as {} with {} tasks",
+        name,
+        project_id,
+        bundle.tasks.len()
+    );
+    Ok(ProjectImport::Imported { project_id, name })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use db::{
+        models::{
+            project_status::{CreateProjectStatus, ProjectStatus},
+            task::{CreateTask, Task, TaskStatus},
+            task_dependency::TaskDependency,
+            task_event::{CreateTaskEvent, TaskEvent, TaskEventActor, TaskEventType},
+            task_template::{CreateTaskTemplate, TaskTemplate},
+            workspace::{CreateWorkspace, Workspace},
+        },
+        test_support::{create_project, create_task, test_pool},
+    };
+    use serde_json::{Map, Value, json};
+
+    use super::*;
+
+    /// A project using most of what a bundle carries
+    async fn seeded_project(pool: &SqlitePool) -> Uuid {
+        let project_id = create_project(pool, "Laptop project").await;
+        let settings = json!({ "relaxed_status_transitions": true });
+        Project::update_settings(pool, project_id, settings.as_object().unwrap())
+            .await
+            .unwrap();
+        ProjectStatus::create(
+            pool,
+            project_id,
+            &CreateProjectStatus {
+                key: "blocked".to_string(),
+                name: "Blocked".to_string(),
+                category: TaskStatus::InProgress,
+                sort_order: None,
+                wip_limit: Some(2),
+            },
+        )
+        .await
+        .unwrap();
+
+        let parent = create_task(pool, project_id, "Design").await;
+        TaskEvent::create(
+            pool,
+            &CreateTaskEvent::new(parent.id, TaskEventType::Created, TaskEventActor::User),
+        )
+        .await
+        .unwrap();
+        let workspace = Workspace::create(
+            pool,
+            &CreateWorkspace {
+                branch: "design".to_string(),
+                agent_working_dir: None,
+            },
+            Uuid::new_v4(),
+            parent.id,
+        )
+        .await
+        .unwrap();
+        let mut child = CreateTask::from_title_description(project_id, "Build".into(), None);
+        child.parent_workspace_id = Some(workspace.id);
+        let child = Task::create(pool, &child, Uuid::new_v4()).await.unwrap();
+        Task::update_status(pool, child.id, TaskStatus::InProgress)
+            .await
+            .unwrap();
+        Task::set_status_key(pool, child.id, Some("blocked"))
+            .await
+            .unwrap();
+        TaskDependency::create(pool, parent.id, child.id)
+            .await
+            .unwrap();
+        let archived = create_task(pool, project_id, "Old idea").await;
+        Task::archive(pool, archived.id).await.unwrap();
+
+        let session_id = Uuid::new_v4();
+        let process_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO sessions (id, workspace_id, executor) VALUES ($1, $2, 'CLAUDE_CODE')",
+        )
+        .bind(session_id)
+        .bind(workspace.id)
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"INSERT INTO execution_processes (id, session_id, run_reason, status, exit_code)
+               VALUES ($1, $2, 'codingagent', 'completed', 0)"#,
+        )
+        .bind(process_id)
+        .bind(session_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"INSERT INTO execution_process_logs (execution_id, logs, byte_size)
+               VALUES ($1, '{"Stdout":"done"}', 17)"#,
+        )
+        .bind(process_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        TaskTemplate::create(
+            pool,
+            &CreateTaskTemplate {
+                project_id,
+                title: "Weekly review".to_string(),
+                description: None,
+                priority: None,
+                assignee: None,
+                recurrence: Some("@weekly".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+        project_id
+    }
+
+    /// The bundle as JSON with ids numbered by first appearance, and the
+    /// values an import does not keep removed
+    fn normalized(bundle: &ProjectBundle) -> Value {
+        fn walk(value: &mut Value, ids: &mut HashMap<Uuid, usize>) {
+            match value {
+                Value::String(text) => {
+                    if let Ok(id) = text.parse::<Uuid>() {
+                        let next = ids.len();
+                        *text = format!("id-{}", ids.entry(id).or_insert(next));
+                    }
+                }
+                Value::Array(items) => items.iter_mut().for_each(|item| walk(item, ids)),
+                Value::Object(fields) => fields.values_mut().for_each(|field| walk(field, ids)),
+                _ => {}
+            }
+        }
+        fn strip(value: &mut Value, list: &str, keys: &[&str]) {
+            for item in value[list].as_array_mut().unwrap() {
+                let fields: &mut Map<String, Value> = item.as_object_mut().unwrap();
+                for key in keys {
+                    fields.remove(*key);
+                }
+            }
+        }
+
+        let mut value = serde_json::to_value(bundle).unwrap();
+        value.as_object_mut().unwrap().remove("exported_at");
+        value["project"].as_object_mut().unwrap().remove("name");
+        strip(&mut value, "statuses", &["created_at", "updated_at"]);
+        strip(&mut value, "templates", &["created_at", "updated_at"]);
+        strip(&mut value, "task_events", &["id"]);
+        strip(&mut value, "project_events", &["id"]);
+        walk(&mut value, &mut HashMap::new());
+        value
+    }
+
+    #[tokio::test]
+    async fn round_trips_into_another_database() {
+        let source = test_pool().await;
+        let project_id = seeded_project(&source).await;
+        let bundle = export(&source, project_id, true).await.unwrap();
+        assert_eq!(bundle.tasks.len(), 3);
+        assert_eq!(bundle.execution_history.as_ref().unwrap().logs.len(), 1);
+        // Through JSON, as it would travel
+        let bundle: ProjectBundle =
+            serde_json::from_str(&serde_json::to_string(&bundle).unwrap()).unwrap();
+
+        let target = test_pool().await;
+        let ProjectImport::Imported {
+            project_id: imported_id,
+            name,
+        } = import(&target, &bundle, NameConflict::Rename)
+            .await
+            .unwrap()
+        else {
+            panic!("nothing to conflict with");
+        };
+        assert_eq!(name, "Laptop project");
+        assert_ne!(imported_id, project_id);
+
+        let reexported = export(&target, imported_id, true).await.unwrap();
+        assert_eq!(normalized(&reexported), normalized(&bundle));
+
+        // Without the flag the history stays behind
+        let slim = export(&source, project_id, false).await.unwrap();
+        assert!(slim.execution_history.is_none());
+        assert!(
+            !serde_json::to_string(&slim)
+                .unwrap()
+                .contains("execution_history")
+        );
+    }
+
+    #[tokio::test]
+    async fn name_collisions_skip_or_rename() {
+        let pool = test_pool().await;
+        let project_id = seeded_project(&pool).await;
+        let bundle = export(&pool, project_id, false).await.unwrap();
+
+        assert_eq!(
+            import(&pool, &bundle, NameConflict::Skip).await.unwrap(),
+            ProjectImport::Skipped {
+                existing_project_id: project_id
+            }
+        );
+        for expected in ["Laptop project (2)", "Laptop project (3)"] {
+            let ProjectImport::Imported { name, .. } =
+                import(&pool, &bundle, NameConflict::Rename).await.unwrap()
+            else {
+                panic!("renaming never skips");
+            };
+            assert_eq!(name, expected);
+        }
+
+        let future = ProjectBundle {
+            version: PROJECT_BUNDLE_VERSION + 1,
+            ..bundle
+        };
+        assert!(matches!(
+            import(&pool, &future, NameConflict::Rename).await,
+            Err(ProjectBundleError::UnsupportedVersion(_))
+        ));
+    }
+}
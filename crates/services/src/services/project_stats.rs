@@ -0,0 +1,102 @@
+//! Project progress for dashboards: where tasks are, how long they stay in
+//! each status and how fast they get done. The numbers come from
+//! [`db::models::project_stats`].
+
+use db::models::project_stats::{
+    self, DurationPercentiles, StatsRange, StatusCount, StatusDuration, WeeklyThroughput,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum ProjectStatsError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("The start of the range must be before its end")]
+    InvalidRange,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProjectStats {
+    /// Current tasks per status, regardless of the range
+    pub status_counts: Vec<StatusCount>,
+    pub status_durations: Vec<StatusDuration>,
+    /// From first start to completion
+    pub cycle_time: DurationPercentiles,
+    /// From creation to completion
+    pub lead_time: DurationPercentiles,
+    /// Every week from the first to the last with completions
+    pub weekly_throughput: Vec<WeeklyThroughput>,
+}
+
+pub async fn project_stats(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    range: StatsRange,
+) -> Result<ProjectStats, ProjectStatsError> {
+    if let (Some(from), Some(to)) = (range.from, range.to)
+        && from >= to
+    {
+        return Err(ProjectStatsError::InvalidRange);
+    }
+
+    Ok(ProjectStats {
+        status_counts: project_stats::status_counts(pool, project_id).await?,
+        status_durations: project_stats::status_durations(pool, project_id, range).await?,
+        cycle_time: project_stats::cycle_times(pool, project_id, range).await?,
+        lead_time: project_stats::lead_times(pool, project_id, range).await?,
+        weekly_throughput: with_empty_weeks(
+            project_stats::weekly_throughput(pool, project_id, range).await?,
+        ),
+    })
+}
+
+/// Add the weeks without completions between those with some, so a chart
+/// shows the quiet weeks
+fn with_empty_weeks(weeks: Vec<WeeklyThroughput>) -> Vec<WeeklyThroughput> {
+    let (Some(first), Some(last)) = (weeks.first(), weeks.last()) else {
+        return weeks;
+    };
+    let mut listed = weeks.iter().peekable();
+    let mut filled = Vec::new();
+    for week_start in first.week_start.iter_weeks() {
+        if week_start > last.week_start {
+            break;
+        }
+        match listed.peek() {
+            Some(week) if week.week_start == week_start => {
+                filled.push((*week).clone());
+                listed.next();
+            }
+            _ => filled.push(WeeklyThroughput {
+                week_start,
+                completed: 0,
+            }),
+        }
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn week(day: u32, completed: i64) -> WeeklyThroughput {
+        WeeklyThroughput {
+            week_start: NaiveDate::from_ymd_opt(2026, 3, day).unwrap(),
+            completed,
+        }
+    }
+
+    #[test]
+    fn fills_quiet_weeks() {
+        let filled = with_empty_weeks(vec![week(2, 3), week(23, 1)]);
+        assert_eq!(filled, [week(2, 3), week(9, 0), week(16, 0), week(23, 1)]);
+        assert!(with_empty_weeks(vec![]).is_empty());
+    }
+}
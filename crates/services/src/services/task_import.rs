@@ -0,0 +1,444 @@
+//! Creating tasks from a backlog exported by another tracker, as a CSV file
+//! with a header row or a JSON array of objects.
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use db::models::{
+    project::Project,
+    project_status::ProjectStatus,
+    task::{CreateTask, Task, TaskPriority},
+    task_event::{CreateTaskEvent, TaskEvent, TaskEventActor, TaskEventType},
+    task_schedule,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Largest number of rows accepted in one file
+pub const MAX_IMPORT_ROWS: usize = 5000;
+
+#[derive(Debug, Error)]
+pub enum TaskImportError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Could not read the file: {0}")]
+    Parse(String),
+    #[error("File has {0} rows, the limit is {MAX_IMPORT_ROWS}")]
+    TooLarge(usize),
+    #[error("Project is archived, unarchive it before adding tasks or attempts")]
+    ProjectArchived,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(rename_all = "lowercase")]
+pub enum TaskImportFormat {
+    Csv,
+    Json,
+}
+
+impl TaskImportFormat {
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let (_, extension) = name.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// The column (CSV) or key (JSON) each task field is read from, matched
+/// ignoring case. Fields whose column is missing from the file are left
+/// at their defaults.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(default)]
+pub struct ColumnMapping {
+    pub title: String,
+    pub description: String,
+    /// Key or name of one of the project's statuses
+    pub status: String,
+    pub priority: String,
+    pub assignee: String,
+    /// As YYYY-MM-DD
+    pub due_date: String,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        Self {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            status: "status".to_string(),
+            priority: "priority".to_string(),
+            assignee: "assignee".to_string(),
+            due_date: "due_date".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+#[serde(default)]
+pub struct TaskImportOptions {
+    /// Taken from the file name when unset
+    #[ts(optional)]
+    pub format: Option<TaskImportFormat>,
+    pub mapping: ColumnMapping,
+    /// Create nothing if any row is invalid
+    pub all_or_nothing: bool,
+    /// Report what the import would do without creating anything
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct TaskImportRowError {
+    /// 1-based position among the file's rows, not counting a CSV header
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TaskImportReport {
+    pub total_rows: usize,
+    /// Tasks created, or that would have been in a dry run
+    pub imported: usize,
+    /// Ids of the created tasks, empty in a dry run
+    pub task_ids: Vec<Uuid>,
+    pub errors: Vec<TaskImportRowError>,
+    pub dry_run: bool,
+}
+
+/// A row of the file, by lowercased column name
+type Row = HashMap<String, String>;
+
+/// Read the file's rows, keeping only non-empty values
+pub fn parse_rows(data: &[u8], format: TaskImportFormat) -> Result<Vec<Row>, TaskImportError> {
+    let rows = match format {
+        TaskImportFormat::Csv => parse_csv(data)?,
+        TaskImportFormat::Json => parse_json(data)?,
+    };
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err(TaskImportError::TooLarge(rows.len()));
+    }
+    Ok(rows)
+}
+
+fn parse_csv(data: &[u8]) -> Result<Vec<Row>, TaskImportError> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(data);
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| TaskImportError::Parse(e.to_string()))?
+        .iter()
+        .map(|header| header.trim().to_lowercase())
+        .collect();
+    reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|e| TaskImportError::Parse(e.to_string()))?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .map(|(header, value)| (header.clone(), value.trim().to_string()))
+                .filter(|(_, value)| !value.is_empty())
+                .collect())
+        })
+        .collect()
+}
+
+fn parse_json(data: &[u8]) -> Result<Vec<Row>, TaskImportError> {
+    let items: Vec<serde_json::Map<String, Value>> =
+        serde_json::from_slice(data).map_err(|e| TaskImportError::Parse(e.to_string()))?;
+    Ok(items
+        .into_iter()
+        .map(|item| {
+            item.into_iter()
+                .filter_map(|(key, value)| {
+                    let value = match value {
+                        Value::Null => return None,
+                        Value::String(text) => text.trim().to_string(),
+                        other => other.to_string(),
+                    };
+                    (!value.is_empty()).then(|| (key.trim().to_lowercase(), value))
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// A row turned into a task, along with the custom column it goes in
+struct ImportedTask {
+    data: CreateTask,
+    status_key: Option<String>,
+}
+
+fn map_row(
+    project_id: Uuid,
+    row: &Row,
+    mapping: &ColumnMapping,
+    statuses: &[ProjectStatus],
+) -> Result<ImportedTask, String> {
+    let field = |column: &str| row.get(&column.trim().to_lowercase());
+
+    let title = field(&mapping.title).ok_or_else(|| "Title must not be empty".to_string())?;
+    let mut data = CreateTask::from_title_description(
+        project_id,
+        title.clone(),
+        field(&mapping.description).cloned(),
+    );
+
+    let mut status_key = None;
+    if let Some(value) = field(&mapping.status) {
+        let status = statuses
+            .iter()
+            .find(|status| {
+                status.key.eq_ignore_ascii_case(value) || status.name.eq_ignore_ascii_case(value)
+            })
+            .ok_or_else(|| format!("Unknown status '{value}'"))?;
+        data.status = Some(status.category.clone());
+        status_key = (!status.is_built_in()).then(|| status.key.clone());
+    }
+    if let Some(value) = field(&mapping.priority) {
+        data.priority = Some(
+            value
+                .to_lowercase()
+                .parse::<TaskPriority>()
+                .map_err(|_| format!("Unknown priority '{value}'"))?,
+        );
+    }
+    data.assignee = field(&mapping.assignee).cloned();
+    if let Some(value) = field(&mapping.due_date) {
+        let due = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| format!("Due date '{value}' is not a YYYY-MM-DD date"))?;
+        task_schedule::check_due_date(due, Utc::now()).map_err(|e| e.to_string())?;
+        data.due_date = Some(due);
+    }
+
+    Ok(ImportedTask { data, status_key })
+}
+
+/// Create a task for every valid row in one transaction, reporting the
+/// invalid ones. A dry run does the same work and rolls it back, so its
+/// report matches what the real import would give.
+pub async fn import_rows(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    rows: &[Row],
+    options: &TaskImportOptions,
+) -> Result<TaskImportReport, TaskImportError> {
+    let statuses = ProjectStatus::find_by_project_id(pool, project_id).await?;
+
+    let mut tasks = Vec::new();
+    let mut errors = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        match map_row(project_id, row, &options.mapping, &statuses) {
+            Ok(task) => tasks.push(task),
+            Err(message) => errors.push(TaskImportRowError {
+                row: index + 1,
+                message,
+            }),
+        }
+    }
+    if options.all_or_nothing && !errors.is_empty() {
+        tasks.clear();
+    }
+
+    let mut tx = pool.begin().await?;
+    if Project::is_archived(&mut *tx, project_id).await? {
+        return Err(TaskImportError::ProjectArchived);
+    }
+    let mut task_ids = Vec::with_capacity(tasks.len());
+    for task in &tasks {
+        let created = Task::insert(&mut tx, Uuid::new_v4(), &task.data).await?;
+        if task.status_key.is_some() {
+            Task::set_status_key(&mut *tx, created.id, task.status_key.as_deref()).await?;
+        }
+        TaskEvent::create(
+            &mut *tx,
+            &CreateTaskEvent::new(created.id, TaskEventType::Created, TaskEventActor::User),
+        )
+        .await?;
+        task_ids.push(created.id);
+    }
+    if options.dry_run {
+        tx.rollback().await?;
+        task_ids.clear();
+    } else {
+        tx.commit().await?;
+    }
+
+    Ok(TaskImportReport {
+        total_rows: rows.len(),
+        imported: tasks.len(),
+        task_ids,
+        errors,
+        dry_run: options.dry_run,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use db::{
+        models::{project_status::CreateProjectStatus, task::TaskStatus},
+        test_support,
+    };
+
+    use super::*;
+
+    /// A project with a custom Blocked column
+    async fn pool_with_project() -> (SqlitePool, Uuid) {
+        let (pool, project_id) = test_support::pool_with_project().await;
+        ProjectStatus::create(
+            &pool,
+            project_id,
+            &CreateProjectStatus {
+                key: "blocked".to_string(),
+                name: "Blocked".to_string(),
+                category: TaskStatus::InProgress,
+                sort_order: None,
+                wip_limit: None,
+            },
+        )
+        .await
+        .unwrap();
+        (pool, project_id)
+    }
+
+    const BACKLOG: &str = "\
+Summary,Details,State,Priority,Due
+Write docs,For the API,In Progress,high,2999-01-31
+Fix login,,blocked,URGENT,
+,Missing its title,todo,,
+Tidy up,,Someday,,
+Ship it,,Done,low,31/01/2999
+";
+
+    fn tracker_mapping() -> TaskImportOptions {
+        TaskImportOptions {
+            mapping: ColumnMapping {
+                title: "Summary".to_string(),
+                description: "details".to_string(),
+                status: "state".to_string(),
+                due_date: "due".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reads_csv_and_json_alike() {
+        let csv = parse_rows(b"Title,Priority\nA,high\n", TaskImportFormat::Csv).unwrap();
+        let json = parse_rows(
+            br#"[{"title": "A", "priority": "high", "assignee": null}]"#,
+            TaskImportFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(csv, json);
+        assert_eq!(
+            TaskImportFormat::from_file_name("backlog.CSV"),
+            Some(TaskImportFormat::Csv)
+        );
+    }
+
+    #[tokio::test]
+    async fn maps_columns_and_reports_bad_rows() {
+        let (pool, project_id) = pool_with_project().await;
+        let rows = parse_rows(BACKLOG.as_bytes(), TaskImportFormat::Csv).unwrap();
+        let report = import_rows(&pool, project_id, &rows, &tracker_mapping())
+            .await
+            .unwrap();
+
+        assert_eq!(report.total_rows, 5);
+        assert_eq!(report.imported, 2);
+        assert_eq!(
+            report.errors.iter().map(|e| e.row).collect::<Vec<_>>(),
+            [3, 4, 5]
+        );
+        assert_eq!(report.errors[1].message, "Unknown status 'Someday'");
+
+        let docs = Task::find_by_id(&pool, report.task_ids[0])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(docs.title, "Write docs");
+        assert_eq!(docs.description.as_deref(), Some("For the API"));
+        assert_eq!(docs.status, TaskStatus::InProgress);
+        assert_eq!(docs.priority, TaskPriority::High);
+        assert_eq!(docs.due_date, NaiveDate::from_ymd_opt(2999, 1, 31));
+        let login = Task::find_by_id(&pool, report.task_ids[1])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(login.status_key, "blocked");
+        assert_eq!(login.priority, TaskPriority::Urgent);
+    }
+
+    #[tokio::test]
+    async fn all_or_nothing_creates_nothing_on_errors() {
+        let (pool, project_id) = pool_with_project().await;
+        let rows = parse_rows(BACKLOG.as_bytes(), TaskImportFormat::Csv).unwrap();
+        let options = TaskImportOptions {
+            all_or_nothing: true,
+            ..tracker_mapping()
+        };
+        let report = import_rows(&pool, project_id, &rows, &options)
+            .await
+            .unwrap();
+        assert_eq!(report.imported, 0);
+        assert_eq!(report.errors.len(), 3);
+        assert!(
+            Task::find_by_project_id_with_attempt_status(&pool, project_id, true)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_reports_what_the_import_does() {
+        let (pool, project_id) = pool_with_project().await;
+        let rows = parse_rows(BACKLOG.as_bytes(), TaskImportFormat::Csv).unwrap();
+        let dry_run = TaskImportOptions {
+            dry_run: true,
+            ..tracker_mapping()
+        };
+        let preview = import_rows(&pool, project_id, &rows, &dry_run)
+            .await
+            .unwrap();
+        assert!(preview.task_ids.is_empty());
+        assert!(
+            Task::find_by_project_id_with_attempt_status(&pool, project_id, true)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let report = import_rows(&pool, project_id, &rows, &tracker_mapping())
+            .await
+            .unwrap();
+        assert_eq!(preview.total_rows, report.total_rows);
+        assert_eq!(preview.imported, report.imported);
+        assert_eq!(preview.errors, report.errors);
+        assert_eq!(report.task_ids.len(), report.imported);
+    }
+
+    #[tokio::test]
+    async fn archived_projects_refuse_imports() {
+        let (pool, project_id) = pool_with_project().await;
+        Project::archive(&pool, project_id).await.unwrap();
+        let rows = parse_rows(BACKLOG.as_bytes(), TaskImportFormat::Csv).unwrap();
+
+        let result = import_rows(&pool, project_id, &rows, &tracker_mapping()).await;
+        assert!(matches!(result, Err(TaskImportError::ProjectArchived)));
+        assert!(
+            Task::find_by_project_id_with_attempt_status(&pool, project_id, true)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{task::Task, task_event::TaskEventActor, task_template::TaskTemplate},
+};
+use sqlx::error::Error as SqlxError;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+/// Creates tasks from recurring templates as their occurrences come due
+pub struct TaskTemplateScheduler {
+    db: DBService,
+    poll_interval: Duration,
+}
+
+impl TaskTemplateScheduler {
+    pub async fn spawn(db: DBService) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            poll_interval: Duration::from_secs(60),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            match materialize_due_templates(&self.db, Utc::now()).await {
+                Ok(tasks) if !tasks.is_empty() => {
+                    info!("Created {} tasks from recurring templates", tasks.len())
+                }
+                Ok(_) => {}
+                Err(e) => error!("Error materializing task templates: {}", e),
+            }
+        }
+    }
+}
+
+/// Create a task for every scheduled template with an occurrence due at
+/// `now`. Occurrences already claimed, for instance by a run interrupted
+/// before a restart, are skipped.
+pub async fn materialize_due_templates(
+    db: &DBService,
+    now: DateTime<Utc>,
+) -> Result<Vec<Task>, SqlxError> {
+    let mut created = Vec::new();
+    for template in TaskTemplate::find_scheduled(&db.pool).await? {
+        if let Err(e) = template.schedule() {
+            warn!("Skipping task template {}: {}", template.id, e);
+            continue;
+        }
+        let Some(occurrence) = template.due_occurrence(now) else {
+            continue;
+        };
+        if let Some(task) = TaskTemplate::instantiate(
+            &db.pool,
+            &template,
+            Some(occurrence),
+            TaskEventActor::System,
+        )
+        .await?
+        {
+            created.push(task);
+        }
+    }
+    Ok(created)
+}
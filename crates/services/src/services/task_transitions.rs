@@ -0,0 +1,355 @@
+use db::{
+    models::{
+        project::Project,
+        task::{Task, TaskStatus},
+        task_dependency::TaskDependency,
+        task_event::{CreateTaskEvent, TaskEvent, TaskEventActor, TaskEventType},
+    },
+    retry::retry_on_busy,
+};
+use serde_json::Value;
+use sqlx::{Executor, Sqlite, SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::wip_limits::{self, WipLimitError, WipLimitOverride};
+
+#[derive(Debug, Error)]
+pub enum TaskTransitionError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Task is archived, unarchive it before making changes")]
+    TaskArchived,
+    #[error("Cannot move a task from {from} to {to}")]
+    InvalidTransition { from: TaskStatus, to: TaskStatus },
+    #[error("Task is blocked by unfinished tasks: {}", .0.join(", "))]
+    Blocked(Vec<String>),
+    #[error(transparent)]
+    WipLimit(#[from] WipLimitError),
+}
+
+/// Checks that may be skipped when changing a task's status
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TransitionOverrides {
+    /// Move outside the regular lifecycle, see [`is_allowed`]
+    pub ignore_lifecycle: bool,
+    /// Start the task while tasks blocking it are unfinished
+    pub ignore_blockers: bool,
+    /// Move past the target column's WIP limit, logging that it was passed
+    pub override_wip_limit: bool,
+}
+
+impl TransitionOverrides {
+    /// Skip every check but the archived one
+    pub const ALL: Self = Self {
+        ignore_lifecycle: true,
+        ignore_blockers: true,
+        override_wip_limit: true,
+    };
+}
+
+/// Whether `from -> to` is part of the regular task lifecycle:
+/// Todo -> InProgress -> InReview -> Done, with InReview -> InProgress for
+/// rework, Done -> InProgress to reopen, Cancelled -> Todo to revive and
+/// anything -> Cancelled. Staying in place is always allowed.
+pub fn is_allowed(from: &TaskStatus, to: &TaskStatus) -> bool {
+    use TaskStatus::*;
+
+    from == to
+        || matches!(
+            (from, to),
+            (_, Cancelled)
+                | (Todo, InProgress)
+                | (InProgress, InReview)
+                | (InReview, InProgress)
+                | (InReview, Done)
+                | (Done, InProgress)
+                | (Cancelled, Todo)
+        )
+}
+
+/// Check a status change, skipping the rules when the project relaxed them
+pub fn validate(
+    from: &TaskStatus,
+    to: &TaskStatus,
+    relaxed: bool,
+) -> Result<(), TaskTransitionError> {
+    if relaxed || is_allowed(from, to) {
+        Ok(())
+    } else {
+        Err(TaskTransitionError::InvalidTransition {
+            from: from.clone(),
+            to: to.clone(),
+        })
+    }
+}
+
+/// Refuse changes to an archived task, or to one created from an archived
+/// task, until it is unarchived
+pub async fn ensure_not_archived(
+    conn: &mut SqliteConnection,
+    task_id: Uuid,
+) -> Result<(), TaskTransitionError> {
+    if Task::is_archived(&mut *conn, task_id).await? {
+        return Err(TaskTransitionError::TaskArchived);
+    }
+    Ok(())
+}
+
+/// Check moving `task` to `status` under its project's rules, inside the
+/// transaction that makes the change. The task lands in the column
+/// `status_key`, or without one in the status's built-in column when the
+/// status changes. Returns the WIP limit the move was allowed to pass, if
+/// any, for the caller to log with the move.
+pub async fn check(
+    conn: &mut SqliteConnection,
+    task: &Task,
+    status: &TaskStatus,
+    status_key: Option<&str>,
+    overrides: TransitionOverrides,
+) -> Result<Option<WipLimitOverride>, TaskTransitionError> {
+    ensure_not_archived(conn, task.id).await?;
+    let relaxed = Project::relaxed_status_transitions(&mut *conn, task.project_id).await?;
+    validate(&task.status, status, relaxed || overrides.ignore_lifecycle)?;
+    if *status == TaskStatus::InProgress
+        && task.status != TaskStatus::InProgress
+        && !overrides.ignore_blockers
+    {
+        let open_blockers = TaskDependency::find_open_blockers(&mut *conn, task.id).await?;
+        if !open_blockers.is_empty() {
+            let titles = open_blockers.into_iter().map(|t| t.title).collect();
+            return Err(TaskTransitionError::Blocked(titles));
+        }
+    }
+
+    let to_key = match status_key {
+        Some(key) => key.to_string(),
+        None if *status == task.status => return Ok(None),
+        None => status.to_string(),
+    };
+    Ok(wip_limits::check(conn, task, &to_key, overrides.override_wip_limit).await?)
+}
+
+/// Record a status change in the task's activity log, alongside the change
+/// itself when given its transaction
+pub async fn record<'e, E>(
+    executor: E,
+    task_id: Uuid,
+    from: &TaskStatus,
+    to: &TaskStatus,
+    actor: TaskEventActor,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    if from == to {
+        return Ok(());
+    }
+    let event = CreateTaskEvent::new(task_id, TaskEventType::StatusChange, actor).with_change(
+        Some(Value::from(from.to_string())),
+        Some(Value::from(to.to_string())),
+    );
+    TaskEvent::create(executor, &event).await
+}
+
+/// Move a task to `status` on the app's own initiative, such as an attempt
+/// starting or a PR merging, and log the change in the same transaction.
+/// These moves follow something that already happened, so the lifecycle,
+/// blockers and WIP limits do not stop them; a WIP limit passed is logged
+/// like a user's override. Only archived tasks refuse them.
+pub async fn set_status(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    status: TaskStatus,
+    actor: TaskEventActor,
+) -> Result<(), TaskTransitionError> {
+    let status = &status;
+    retry_on_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        let Some(task) = Task::find_by_id(&mut *tx, task_id).await? else {
+            return Ok(Ok(()));
+        };
+        // Only a locked database is worth another try, a refused move is final
+        let wip_override = match check(&mut tx, &task, status, None, TransitionOverrides::ALL).await
+        {
+            Ok(wip_override) => wip_override,
+            Err(
+                TaskTransitionError::Database(err)
+                | TaskTransitionError::WipLimit(WipLimitError::Database(err)),
+            ) => return Err(err),
+            Err(err) => return Ok(Err(err)),
+        };
+        Task::update_status(&mut *tx, task_id, status.clone()).await?;
+        record(&mut *tx, task_id, &task.status, status, actor).await?;
+        if let Some(wip_override) = &wip_override {
+            wip_limits::record_override(&mut *tx, task_id, wip_override, actor).await?;
+        }
+        tx.commit().await?;
+        Ok(Ok(()))
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use db::{
+        models::project_status::{ProjectStatus, UpdateProjectStatus},
+        test_support::{create_task, pool_with_project},
+    };
+
+    use super::*;
+
+    const ALL: [TaskStatus; 5] = [
+        TaskStatus::Todo,
+        TaskStatus::InProgress,
+        TaskStatus::InReview,
+        TaskStatus::Done,
+        TaskStatus::Cancelled,
+    ];
+
+    #[test]
+    fn transition_matrix() {
+        use TaskStatus::*;
+
+        // Rows are `from`, columns are `to`, both in `ALL` order
+        let expected = [
+            [true, true, false, false, true],  // Todo
+            [false, true, true, false, true],  // InProgress
+            [false, true, true, true, true],   // InReview
+            [false, true, false, true, true],  // Done
+            [true, false, false, false, true], // Cancelled
+        ];
+        for (from, row) in ALL.iter().zip(expected) {
+            for (to, allowed) in ALL.iter().zip(row) {
+                assert_eq!(is_allowed(from, to), allowed, "{from} -> {to}");
+                assert_eq!(validate(from, to, false).is_ok(), allowed, "{from} -> {to}");
+            }
+        }
+
+        let Err(TaskTransitionError::InvalidTransition { from, to }) =
+            validate(&Done, &Todo, false)
+        else {
+            panic!("Done -> Todo should be rejected");
+        };
+        assert_eq!((from, to), (Done, Todo));
+    }
+
+    #[test]
+    fn relaxed_projects_allow_every_transition() {
+        for from in &ALL {
+            for to in &ALL {
+                assert!(validate(from, to, true).is_ok(), "{from} -> {to}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn set_status_logs_only_real_changes() {
+        let (pool, project_id) = pool_with_project().await;
+        let task = create_task(&pool, project_id, "Audited").await;
+
+        set_status(
+            &pool,
+            task.id,
+            TaskStatus::InProgress,
+            TaskEventActor::System,
+        )
+        .await
+        .unwrap();
+        set_status(
+            &pool,
+            task.id,
+            TaskStatus::InProgress,
+            TaskEventActor::System,
+        )
+        .await
+        .unwrap();
+
+        let events = TaskEvent::find_by_task(&pool, task.id, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, TaskEventType::StatusChange);
+        assert_eq!(events[0].actor, TaskEventActor::System);
+        assert_eq!(events[0].old_value, Some(Value::from("todo")));
+        assert_eq!(events[0].new_value, Some(Value::from("inprogress")));
+    }
+
+    #[tokio::test]
+    async fn merges_finish_tasks_from_any_status() {
+        let (pool, project_id) = pool_with_project().await;
+        let task = create_task(&pool, project_id, "Skips review").await;
+        assert!(!is_allowed(&TaskStatus::Todo, &TaskStatus::Done));
+
+        set_status(&pool, task.id, TaskStatus::Done, TaskEventActor::System)
+            .await
+            .unwrap();
+        let done = Task::find_by_id(&pool, task.id).await.unwrap().unwrap();
+        assert_eq!(done.status, TaskStatus::Done);
+    }
+
+    #[tokio::test]
+    async fn attempts_start_blocked_tasks_and_pass_full_columns() {
+        let (pool, project_id) = pool_with_project().await;
+        let blocker = create_task(&pool, project_id, "Blocker").await;
+        let blocked = create_task(&pool, project_id, "Blocked").await;
+        Task::add_dependency(&pool, blocked.id, blocker.id)
+            .await
+            .unwrap();
+        let limit = UpdateProjectStatus {
+            name: None,
+            sort_order: None,
+            wip_limit: Some(Some(1)),
+        };
+        ProjectStatus::update(&pool, project_id, "inprogress", &limit)
+            .await
+            .unwrap();
+
+        // As an attempt starting on each task does
+        for task in [&blocker, &blocked] {
+            set_status(
+                &pool,
+                task.id,
+                TaskStatus::InProgress,
+                TaskEventActor::System,
+            )
+            .await
+            .unwrap();
+            let started = Task::find_by_id(&pool, task.id).await.unwrap().unwrap();
+            assert_eq!(started.status, TaskStatus::InProgress);
+        }
+
+        let events = TaskEvent::find_by_task(&pool, blocked.id, None, 10)
+            .await
+            .unwrap();
+        let logged: Vec<_> = events
+            .iter()
+            .map(|event| (event.event_type, event.actor))
+            .collect();
+        assert_eq!(
+            logged,
+            [
+                (TaskEventType::WipLimitOverride, TaskEventActor::System),
+                (TaskEventType::StatusChange, TaskEventActor::System),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn archived_tasks_keep_their_status() {
+        let (pool, project_id) = pool_with_project().await;
+        let task = create_task(&pool, project_id, "Shelved").await;
+        Task::archive(&pool, task.id).await.unwrap();
+
+        let result = set_status(
+            &pool,
+            task.id,
+            TaskStatus::InProgress,
+            TaskEventActor::System,
+        )
+        .await;
+        assert!(matches!(result, Err(TaskTransitionError::TaskArchived)));
+        let unchanged = Task::find_by_id(&pool, task.id).await.unwrap().unwrap();
+        assert_eq!(unchanged.status, TaskStatus::Todo);
+    }
+}
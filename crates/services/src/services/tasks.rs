@@ -0,0 +1,838 @@
+//! Changes to tasks made on a user's behalf. Each one writes its entries in
+//! the task's activity log in the same transaction as the change, so the log
+//! never claims a change that was rolled back or misses one that landed.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use db::models::{
+    image::TaskImage,
+    project::Project,
+    task::{
+        CreateTask, DueDateChange, DuplicateTask, DuplicatedTask, MoveTask, ProjectMove, Task,
+        TaskBatchError, TaskMoveError, TaskProjectMoveError, TaskRestoreError, UpdateTask,
+    },
+    task_dependency::{TaskDependency, TaskDependencyError},
+    task_event::{CreateTaskEvent, TaskEvent, TaskEventActor, TaskEventType},
+    task_schedule::TaskScheduleError,
+    workspace::{CreateWorkspace, Workspace, WorkspaceError},
+};
+use serde_json::{Value, json};
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::{
+    task_transitions::{self, TaskTransitionError, TransitionOverrides},
+    wip_limits,
+};
+
+#[derive(Debug, Error)]
+pub enum TaskUpdateError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Move(#[from] TaskMoveError),
+    #[error(transparent)]
+    Schedule(#[from] TaskScheduleError),
+    #[error(transparent)]
+    Transition(#[from] TaskTransitionError),
+    #[error(transparent)]
+    ProjectMove(#[from] TaskProjectMoveError),
+}
+
+#[derive(Debug, Error)]
+pub enum TaskCreateError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Batch(#[from] TaskBatchError),
+    #[error("Project is archived, unarchive it before adding tasks or attempts")]
+    ProjectArchived,
+}
+
+#[derive(Debug, Error)]
+pub enum AttemptStartError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Project is archived, unarchive it before adding tasks or attempts")]
+    ProjectArchived,
+    #[error(transparent)]
+    Workspace(#[from] WorkspaceError),
+    #[error(transparent)]
+    Transition(#[from] TaskTransitionError),
+}
+
+async fn log(conn: &mut SqliteConnection, event: CreateTaskEvent) -> Result<(), sqlx::Error> {
+    TaskEvent::create(conn, &event).await
+}
+
+async fn log_created(conn: &mut SqliteConnection, task_id: Uuid) -> Result<(), sqlx::Error> {
+    log(
+        conn,
+        CreateTaskEvent::new(task_id, TaskEventType::Created, TaskEventActor::User),
+    )
+    .await
+}
+
+/// Log due dates set on the app's initiative, such as a parent's deadline
+/// being spread over its children
+async fn log_due_date_changes(
+    conn: &mut SqliteConnection,
+    changes: &[DueDateChange],
+) -> Result<(), sqlx::Error> {
+    for change in changes {
+        log(
+            conn,
+            CreateTaskEvent::new(
+                change.task_id,
+                TaskEventType::DueDateChange,
+                TaskEventActor::System,
+            )
+            .with_change(
+                change.previous.map(|date| json!(date)),
+                Some(json!(change.due_date)),
+            ),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn create(
+    pool: &SqlitePool,
+    data: &CreateTask,
+    id: Uuid,
+) -> Result<Task, TaskCreateError> {
+    let mut tx = pool.begin().await?;
+    if Project::is_archived(&mut *tx, data.project_id).await? {
+        return Err(TaskCreateError::ProjectArchived);
+    }
+    let task = Task::insert(&mut tx, id, data).await?;
+    log_created(&mut tx, task.id).await?;
+    tx.commit().await?;
+    Ok(task)
+}
+
+pub async fn create_many(
+    pool: &SqlitePool,
+    data: &[CreateTask],
+) -> Result<Vec<Task>, TaskCreateError> {
+    let mut tx = pool.begin().await?;
+    let project_ids: HashSet<Uuid> = data.iter().map(|task| task.project_id).collect();
+    for project_id in project_ids {
+        if Project::is_archived(&mut *tx, project_id).await? {
+            return Err(TaskCreateError::ProjectArchived);
+        }
+    }
+    let tasks = Task::create_many(&mut *tx, data).await?;
+    for task in &tasks {
+        log_created(&mut tx, task.id).await?;
+    }
+    tx.commit().await?;
+    Ok(tasks)
+}
+
+pub async fn duplicate(
+    pool: &SqlitePool,
+    id: Uuid,
+    options: &DuplicateTask,
+) -> Result<Option<DuplicatedTask>, TaskCreateError> {
+    let mut tx = pool.begin().await?;
+    let Some(original) = Task::find_live_by_id(&mut *tx, id).await? else {
+        return Ok(None);
+    };
+    if Project::is_archived(&mut *tx, original.project_id).await? {
+        return Err(TaskCreateError::ProjectArchived);
+    }
+    let Some(duplicated) = Task::duplicate(&mut *tx, id, options).await? else {
+        return Ok(None);
+    };
+    for created in std::iter::once(&duplicated.task).chain(&duplicated.children) {
+        log_created(&mut tx, created.id).await?;
+    }
+    tx.commit().await?;
+    Ok(Some(duplicated))
+}
+
+/// Create the workspace for a new attempt at the task, unless it or its
+/// project is archived
+pub async fn start_attempt(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    data: &CreateWorkspace,
+    workspace_id: Uuid,
+) -> Result<Workspace, AttemptStartError> {
+    let mut tx = pool.begin().await?;
+    task_transitions::ensure_not_archived(&mut tx, task_id).await?;
+    let task = Task::find_live_by_id(&mut *tx, task_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    if Project::is_archived(&mut *tx, task.project_id).await? {
+        return Err(AttemptStartError::ProjectArchived);
+    }
+    let workspace = Workspace::create(&mut *tx, data, workspace_id, task_id).await?;
+    log(
+        &mut tx,
+        CreateTaskEvent::new(task_id, TaskEventType::AttemptStarted, TaskEventActor::User)
+            .with_change(None, Some(json!(workspace.id))),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(workspace)
+}
+
+/// Apply `changes` to task `id`, fields left out keeping their value. A new
+/// status is checked against the project's rules and WIP limits, see
+/// [`task_transitions::check`].
+pub async fn update(
+    pool: &SqlitePool,
+    id: Uuid,
+    changes: &UpdateTask,
+) -> Result<Task, TaskUpdateError> {
+    let mut tx = pool.begin().await?;
+    // Checked and logged against the row as it is now, not as the caller saw it
+    let existing = &Task::find_live_by_id(&mut *tx, id)
+        .await?
+        .ok_or(TaskMoveError::NotFound)?;
+    let title = changes
+        .title
+        .clone()
+        .unwrap_or_else(|| existing.title.clone());
+    let description = match &changes.description {
+        Some(s) if s.trim().is_empty() => None, // Empty string = clear description
+        Some(s) => Some(s.clone()),             // Non-empty string = update description
+        None => existing.description.clone(),   // Field omitted = keep existing
+    };
+    let status = changes
+        .status
+        .clone()
+        .unwrap_or_else(|| existing.status.clone());
+    let parent_workspace_id = changes.parent_workspace_id.or(existing.parent_workspace_id);
+
+    let overrides = TransitionOverrides {
+        ignore_blockers: changes.ignore_blockers.unwrap_or(false),
+        override_wip_limit: changes.override_wip_limit.unwrap_or(false),
+        ..Default::default()
+    };
+    let wip_override = task_transitions::check(&mut tx, existing, &status, None, overrides).await?;
+    let task = Task::update(
+        &mut *tx,
+        existing.id,
+        existing.project_id,
+        title,
+        description,
+        status,
+        parent_workspace_id,
+    )
+    .await?;
+    task_transitions::record(
+        &mut *tx,
+        task.id,
+        &existing.status,
+        &task.status,
+        TaskEventActor::User,
+    )
+    .await?;
+    // A task entering another column starts at its top
+    if task.status != existing.status {
+        Task::move_to(&mut *tx, task.id, task.status.clone(), None).await?;
+    }
+    if let Some(wip_override) = &wip_override {
+        wip_limits::record_override(&mut *tx, task.id, wip_override, TaskEventActor::User).await?;
+    }
+
+    if let Some(priority) = changes.priority {
+        let changed = Task::set_priority(
+            &mut *tx,
+            task.id,
+            priority,
+            changes.propagate_priority.unwrap_or(false),
+        )
+        .await?;
+        for id in changed {
+            // Descendants may have held any priority before following the parent
+            let old_value = (id == task.id).then(|| json!(existing.priority));
+            let actor = if id == task.id {
+                TaskEventActor::User
+            } else {
+                TaskEventActor::System
+            };
+            log(
+                &mut tx,
+                CreateTaskEvent::new(id, TaskEventType::PriorityChange, actor)
+                    .with_change(old_value, Some(json!(priority))),
+            )
+            .await?;
+        }
+    }
+
+    if let Some(assignee) = &changes.assignee {
+        let current = Task::set_assignee(&mut *tx, task.id, Some(assignee)).await?;
+        if current != existing.assignee {
+            log(
+                &mut tx,
+                CreateTaskEvent::new(task.id, TaskEventType::AssigneeChange, TaskEventActor::User)
+                    .with_change(
+                        existing.assignee.clone().map(Value::from),
+                        current.map(Value::from),
+                    ),
+            )
+            .await?;
+        }
+    }
+
+    if let Some(due_date) = changes.due_date
+        && due_date != existing.due_date
+    {
+        Task::set_due_date(&mut *tx, task.id, due_date).await?;
+        log(
+            &mut tx,
+            CreateTaskEvent::new(task.id, TaskEventType::DueDateChange, TaskEventActor::User)
+                .with_change(
+                    existing.due_date.map(|date| json!(date)),
+                    due_date.map(|date| json!(date)),
+                ),
+        )
+        .await?;
+    }
+    if let Some(weight) = changes.schedule_weight {
+        Task::set_schedule_weight(&mut *tx, task.id, weight).await?;
+    }
+    if changes.redistribute_due_dates.unwrap_or(false) {
+        let due_date_changes =
+            Task::distribute_due_dates(&mut *tx, task.id, Utc::now().date_naive()).await?;
+        log_due_date_changes(&mut tx, &due_date_changes).await?;
+    }
+
+    if let Some(image_ids) = &changes.image_ids {
+        TaskImage::delete_by_task_id(&mut *tx, task.id).await?;
+        TaskImage::associate_many_dedup(&mut *tx, task.id, image_ids).await?;
+    }
+
+    // Priority, assignee and schedule changes land after the update itself
+    let task = Task::find_by_id(&mut *tx, task.id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    tx.commit().await?;
+    Ok(task)
+}
+
+pub async fn distribute_due_dates(
+    pool: &SqlitePool,
+    id: Uuid,
+) -> Result<Vec<DueDateChange>, TaskScheduleError> {
+    let mut tx = pool.begin().await?;
+    let changes = Task::distribute_due_dates(&mut *tx, id, Utc::now().date_naive()).await?;
+    log_due_date_changes(&mut tx, &changes).await?;
+    tx.commit().await?;
+    Ok(changes)
+}
+
+/// Place task `id` where `target` says, checking the move as for [`update`]
+pub async fn move_to(
+    pool: &SqlitePool,
+    id: Uuid,
+    target: &MoveTask,
+) -> Result<Task, TaskUpdateError> {
+    let to_key = target
+        .status_key
+        .clone()
+        .unwrap_or_else(|| target.status.to_string());
+    let overrides = TransitionOverrides {
+        override_wip_limit: target.override_wip_limit,
+        ..Default::default()
+    };
+
+    let mut tx = pool.begin().await?;
+    let existing = &Task::find_live_by_id(&mut *tx, id)
+        .await?
+        .ok_or(TaskMoveError::NotFound)?;
+    let wip_override =
+        task_transitions::check(&mut tx, existing, &target.status, Some(&to_key), overrides)
+            .await?;
+    let task = Task::move_to(
+        &mut *tx,
+        existing.id,
+        target.status.clone(),
+        target.after_task_id,
+    )
+    .await?;
+    Task::set_status_key(&mut *tx, task.id, target.status_key.as_deref()).await?;
+    if let Some(wip_override) = &wip_override {
+        wip_limits::record_override(&mut *tx, task.id, wip_override, TaskEventActor::User).await?;
+    }
+    task_transitions::record(
+        &mut *tx,
+        task.id,
+        &existing.status,
+        &task.status,
+        TaskEventActor::User,
+    )
+    .await?;
+    let task = Task::find_by_id(&mut *tx, task.id)
+        .await?
+        .ok_or(TaskMoveError::NotFound)?;
+    tx.commit().await?;
+    Ok(task)
+}
+
+pub async fn move_to_project(
+    pool: &SqlitePool,
+    id: Uuid,
+    project_id: Uuid,
+    sever_links: bool,
+) -> Result<ProjectMove, TaskUpdateError> {
+    let mut tx = pool.begin().await?;
+    task_transitions::ensure_not_archived(&mut tx, id).await?;
+    let moved = Task::move_to_project(&mut *tx, id, project_id, sever_links).await?;
+    for moved_id in &moved.task_ids {
+        log(
+            &mut tx,
+            CreateTaskEvent::new(
+                *moved_id,
+                TaskEventType::ProjectChange,
+                TaskEventActor::User,
+            )
+            .with_change(
+                Some(json!(moved.previous_project_id)),
+                Some(json!(project_id)),
+            ),
+        )
+        .await?;
+    }
+    for (blocker_id, blocked_id) in &moved.severed_dependencies {
+        log(
+            &mut tx,
+            CreateTaskEvent::new(
+                *blocked_id,
+                TaskEventType::DependencyRemoved,
+                TaskEventActor::User,
+            )
+            .with_change(Some(json!(blocker_id)), None),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(moved)
+}
+
+pub async fn add_dependency(
+    pool: &SqlitePool,
+    blocker_id: Uuid,
+    blocked_id: Uuid,
+) -> Result<TaskDependency, TaskDependencyError> {
+    let mut tx = pool.begin().await?;
+    if Task::find_live_by_id(&mut *tx, blocker_id).await?.is_none() {
+        return Err(TaskDependencyError::BlockerNotFound);
+    }
+    let dependency = Task::add_dependency(&mut *tx, blocked_id, blocker_id).await?;
+    log(
+        &mut tx,
+        CreateTaskEvent::new(
+            blocked_id,
+            TaskEventType::DependencyAdded,
+            TaskEventActor::User,
+        )
+        .with_change(None, Some(json!(blocker_id))),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(dependency)
+}
+
+pub async fn remove_dependency(
+    pool: &SqlitePool,
+    blocker_id: Uuid,
+    blocked_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    if TaskDependency::delete(&mut *tx, blocker_id, blocked_id).await? > 0 {
+        log(
+            &mut tx,
+            CreateTaskEvent::new(
+                blocked_id,
+                TaskEventType::DependencyRemoved,
+                TaskEventActor::User,
+            )
+            .with_change(Some(json!(blocker_id)), None),
+        )
+        .await?;
+    }
+    tx.commit().await
+}
+
+/// Soft-delete the task with its descendants. Returns how many were deleted.
+pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let deleted = Task::soft_delete(&mut *tx, id).await?;
+    if deleted > 0 {
+        log(
+            &mut tx,
+            CreateTaskEvent::new(id, TaskEventType::Deleted, TaskEventActor::User),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+pub async fn restore(pool: &SqlitePool, id: Uuid) -> Result<u64, TaskRestoreError> {
+    let mut tx = pool.begin().await?;
+    let restored = Task::restore(&mut *tx, id).await?;
+    if restored > 0 {
+        log(
+            &mut tx,
+            CreateTaskEvent::new(id, TaskEventType::Restored, TaskEventActor::User),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(restored)
+}
+
+pub async fn archive(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let archived = Task::archive(&mut *tx, id).await?;
+    if archived > 0 {
+        log(
+            &mut tx,
+            CreateTaskEvent::new(id, TaskEventType::Archived, TaskEventActor::User),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(archived)
+}
+
+pub async fn unarchive(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let unarchived = Task::unarchive(&mut *tx, id).await?;
+    if unarchived > 0 {
+        log(
+            &mut tx,
+            CreateTaskEvent::new(id, TaskEventType::Unarchived, TaskEventActor::User),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(unarchived)
+}
+
+#[cfg(test)]
+mod tests {
+    use db::{
+        models::{
+            project_status::{ProjectStatus, UpdateProjectStatus},
+            task::TaskStatus,
+        },
+        test_support::pool_with_project,
+    };
+
+    use super::*;
+    use crate::services::wip_limits::WipLimitError;
+
+    async fn pool_with_task() -> (SqlitePool, Task) {
+        let (pool, project_id) = pool_with_project().await;
+        let data = CreateTask::from_title_description(project_id, "Logged".to_string(), None);
+        let task = create(&pool, &data, Uuid::new_v4()).await.unwrap();
+        (pool, task)
+    }
+
+    async fn event_types(pool: &SqlitePool, task_id: Uuid) -> Vec<TaskEventType> {
+        let mut events = TaskEvent::find_by_task(pool, task_id, None, 50)
+            .await
+            .unwrap();
+        events.reverse();
+        events.into_iter().map(|event| event.event_type).collect()
+    }
+
+    #[tokio::test]
+    async fn update_logs_each_change_with_it() {
+        let (pool, task) = pool_with_task().await;
+        let changes = UpdateTask {
+            status: Some(TaskStatus::InProgress),
+            assignee: Some("ana".to_string()),
+            ..Default::default()
+        };
+        let updated = update(&pool, task.id, &changes).await.unwrap();
+        assert_eq!(updated.status, TaskStatus::InProgress);
+        assert_eq!(updated.assignee.as_deref(), Some("ana"));
+        assert_eq!(
+            event_types(&pool, task.id).await,
+            [
+                TaskEventType::Created,
+                TaskEventType::StatusChange,
+                TaskEventType::AssigneeChange,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn status_changes_follow_the_lifecycle() {
+        let (pool, task) = pool_with_task().await;
+        let skip_ahead = UpdateTask {
+            status: Some(TaskStatus::Done),
+            ..Default::default()
+        };
+        assert!(matches!(
+            update(&pool, task.id, &skip_ahead).await,
+            Err(TaskUpdateError::Transition(
+                TaskTransitionError::InvalidTransition { .. }
+            ))
+        ));
+        let target = MoveTask {
+            status: TaskStatus::InReview,
+            status_key: None,
+            after_task_id: None,
+            override_wip_limit: false,
+        };
+        assert!(matches!(
+            move_to(&pool, task.id, &target).await,
+            Err(TaskUpdateError::Transition(
+                TaskTransitionError::InvalidTransition { .. }
+            ))
+        ));
+        assert_eq!(event_types(&pool, task.id).await, [TaskEventType::Created]);
+
+        let target = MoveTask {
+            status: TaskStatus::InProgress,
+            ..target
+        };
+        let moved = move_to(&pool, task.id, &target).await.unwrap();
+        assert_eq!(moved.status, TaskStatus::InProgress);
+    }
+
+    #[tokio::test]
+    async fn updates_check_the_status_the_task_has_now() {
+        let (pool, task) = pool_with_task().await;
+        // Moved on since the caller loaded it
+        task_transitions::set_status(
+            &pool,
+            task.id,
+            TaskStatus::InProgress,
+            TaskEventActor::System,
+        )
+        .await
+        .unwrap();
+
+        let review = UpdateTask {
+            status: Some(TaskStatus::InReview),
+            ..Default::default()
+        };
+        let reviewed = update(&pool, task.id, &review).await.unwrap();
+        assert_eq!(reviewed.status, TaskStatus::InReview);
+        let latest = TaskEvent::find_by_task(&pool, task.id, None, 1)
+            .await
+            .unwrap();
+        assert_eq!(latest[0].old_value, Some(Value::from("inprogress")));
+
+        assert!(matches!(
+            update(&pool, Uuid::new_v4(), &review).await,
+            Err(TaskUpdateError::Move(TaskMoveError::NotFound))
+        ));
+    }
+
+    #[tokio::test]
+    async fn archived_tasks_refuse_changes_and_attempts() {
+        let (pool, parent) = pool_with_task().await;
+        let workspace = CreateWorkspace {
+            branch: "parent".to_string(),
+            agent_working_dir: None,
+        };
+        let workspace = start_attempt(&pool, parent.id, &workspace, Uuid::new_v4())
+            .await
+            .unwrap();
+        archive(&pool, parent.id).await.unwrap();
+        // Created by the parent's attempt after the parent was archived
+        let mut data = CreateTask::from_title_description(parent.project_id, "Late".into(), None);
+        data.parent_workspace_id = Some(workspace.id);
+        let child = create(&pool, &data, Uuid::new_v4()).await.unwrap();
+
+        let start = UpdateTask {
+            status: Some(TaskStatus::InProgress),
+            ..Default::default()
+        };
+        for task in [&parent, &child] {
+            assert!(matches!(
+                update(&pool, task.id, &start).await,
+                Err(TaskUpdateError::Transition(
+                    TaskTransitionError::TaskArchived
+                ))
+            ));
+        }
+        let retry = CreateWorkspace {
+            branch: "retry".to_string(),
+            agent_working_dir: None,
+        };
+        assert!(matches!(
+            start_attempt(&pool, child.id, &retry, Uuid::new_v4()).await,
+            Err(AttemptStartError::Transition(
+                TaskTransitionError::TaskArchived
+            ))
+        ));
+
+        unarchive(&pool, parent.id).await.unwrap();
+        let started = update(&pool, child.id, &start).await.unwrap();
+        assert_eq!(started.status, TaskStatus::InProgress);
+    }
+
+    #[tokio::test]
+    async fn archived_projects_refuse_new_tasks_and_attempts() {
+        let (pool, task) = pool_with_task().await;
+        let workspace = CreateWorkspace {
+            branch: "first".to_string(),
+            agent_working_dir: None,
+        };
+        let workspace = start_attempt(&pool, task.id, &workspace, Uuid::new_v4())
+            .await
+            .unwrap();
+        Project::archive(&pool, task.project_id).await.unwrap();
+
+        let data = CreateTask::from_title_description(task.project_id, "New".into(), None);
+        assert!(matches!(
+            create(&pool, &data, Uuid::new_v4()).await,
+            Err(TaskCreateError::ProjectArchived)
+        ));
+        // Created by the task's attempt after the project was archived
+        let mut child = data.clone();
+        child.parent_workspace_id = Some(workspace.id);
+        assert!(matches!(
+            create(&pool, &child, Uuid::new_v4()).await,
+            Err(TaskCreateError::ProjectArchived)
+        ));
+        assert!(matches!(
+            create_many(&pool, &[data, child]).await,
+            Err(TaskCreateError::ProjectArchived)
+        ));
+        assert!(matches!(
+            duplicate(&pool, task.id, &DuplicateTask::default()).await,
+            Err(TaskCreateError::ProjectArchived)
+        ));
+        let retry = CreateWorkspace {
+            branch: "retry".to_string(),
+            agent_working_dir: None,
+        };
+        assert!(matches!(
+            start_attempt(&pool, task.id, &retry, Uuid::new_v4()).await,
+            Err(AttemptStartError::ProjectArchived)
+        ));
+        let tasks = Task::find_by_project_id_with_attempt_status(&pool, task.project_id, true)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(event_types(&pool, task.id).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn full_columns_refuse_tasks_unless_overridden() {
+        let (pool, first) = pool_with_task().await;
+        let limit = UpdateProjectStatus {
+            name: None,
+            sort_order: None,
+            wip_limit: Some(Some(1)),
+        };
+        ProjectStatus::update(&pool, first.project_id, "inprogress", &limit)
+            .await
+            .unwrap();
+        let start = UpdateTask {
+            status: Some(TaskStatus::InProgress),
+            ..Default::default()
+        };
+        update(&pool, first.id, &start).await.unwrap();
+
+        let data = CreateTask::from_title_description(first.project_id, "Second".into(), None);
+        let second = create(&pool, &data, Uuid::new_v4()).await.unwrap();
+        let Err(TaskUpdateError::Transition(TaskTransitionError::WipLimit(
+            WipLimitError::Exceeded { occupants, .. },
+        ))) = update(&pool, second.id, &start).await
+        else {
+            panic!("the column is full");
+        };
+        assert_eq!(occupants.len(), 1);
+        assert_eq!(occupants[0].id, first.id);
+
+        let forced = UpdateTask {
+            override_wip_limit: Some(true),
+            ..start
+        };
+        let moved = update(&pool, second.id, &forced).await.unwrap();
+        assert_eq!(moved.status, TaskStatus::InProgress);
+        assert_eq!(
+            event_types(&pool, second.id).await,
+            [
+                TaskEventType::Created,
+                TaskEventType::StatusChange,
+                TaskEventType::WipLimitOverride,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn blocked_tasks_start_only_when_told_to() {
+        let (pool, blocker) = pool_with_task().await;
+        let data = CreateTask::from_title_description(blocker.project_id, "Waits".into(), None);
+        let task = create(&pool, &data, Uuid::new_v4()).await.unwrap();
+        add_dependency(&pool, blocker.id, task.id).await.unwrap();
+
+        let start = UpdateTask {
+            status: Some(TaskStatus::InProgress),
+            ..Default::default()
+        };
+        let Err(TaskUpdateError::Transition(TaskTransitionError::Blocked(titles))) =
+            update(&pool, task.id, &start).await
+        else {
+            panic!("the blocker is not done");
+        };
+        assert_eq!(titles, ["Logged"]);
+
+        let anyway = UpdateTask {
+            ignore_blockers: Some(true),
+            ..start
+        };
+        let started = update(&pool, task.id, &anyway).await.unwrap();
+        assert_eq!(started.status, TaskStatus::InProgress);
+    }
+
+    #[tokio::test]
+    async fn failed_changes_leave_no_event() {
+        let (pool, task) = pool_with_task().await;
+        let changes = UpdateTask {
+            assignee: Some("ana".to_string()),
+            schedule_weight: Some(-1.0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            update(&pool, task.id, &changes).await,
+            Err(TaskUpdateError::Schedule(_))
+        ));
+        assert_eq!(
+            Task::find_by_id(&pool, task.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .assignee,
+            None
+        );
+
+        assert!(matches!(
+            add_dependency(&pool, task.id, task.id).await,
+            Err(TaskDependencyError::SelfDependency)
+        ));
+        assert_eq!(event_types(&pool, task.id).await, [TaskEventType::Created]);
+    }
+
+    #[tokio::test]
+    async fn deleted_tasks_cannot_block_others() {
+        let (pool, task) = pool_with_task().await;
+        let data = CreateTask::from_title_description(task.project_id, "Gone".to_string(), None);
+        let blocker = create(&pool, &data, Uuid::new_v4()).await.unwrap();
+        delete(&pool, blocker.id).await.unwrap();
+
+        assert!(matches!(
+            add_dependency(&pool, blocker.id, task.id).await,
+            Err(TaskDependencyError::BlockerNotFound)
+        ));
+        assert!(Task::blockers(&pool, task.id).await.unwrap().is_empty());
+    }
+}
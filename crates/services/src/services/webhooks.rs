@@ -0,0 +1,475 @@
+//! Outbound webhooks for task and attempt changes.
+//!
+//! Database triggers queue changes in `webhook_outbox` as part of the
+//! transaction that made them, so a mutation never waits on the network.
+//! [`WebhookDispatcher`] turns queued changes into one delivery per
+//! subscribed webhook and posts them as JSON, signed with the webhook's
+//! secret in `X-Webhook-Signature: sha256=<hex>`. Failed deliveries are
+//! retried with exponential backoff until [`MAX_DELIVERY_ATTEMPTS`], and
+//! every delivery stays in the log for debugging.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{
+        task::Task,
+        webhook::{
+            CreateWebhook, UpdateWebhook, Webhook, WebhookDelivery, WebhookDeliveryStatus,
+            WebhookEventType, WebhookOutboxEvent,
+        },
+    },
+};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde_json::{Value, json};
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Attempts before a delivery is given up on
+pub const MAX_DELIVERY_ATTEMPTS: i64 = 5;
+/// Wait before the first retry, doubled for each one after it
+const RETRY_BASE_DELAY: chrono::Duration = chrono::Duration::seconds(30);
+/// Queued changes and due deliveries handled per run
+const BATCH_SIZE: i64 = 100;
+
+const USER_AGENT: &str = concat!("vibe-kanban-webhooks/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("HTTP client error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Invalid webhook URL: {0}")]
+    InvalidUrl(String),
+    #[error("Webhook not found")]
+    NotFound,
+}
+
+fn validate_url(value: &str) -> Result<(), WebhookError> {
+    match url::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+        _ => Err(WebhookError::InvalidUrl(value.to_string())),
+    }
+}
+
+/// `sha256=<hex>` HMAC of the body, as sent in `X-Webhook-Signature`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+pub fn client() -> Result<Client, WebhookError> {
+    Ok(Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(Duration::from_secs(10))
+        .build()?)
+}
+
+pub async fn create_webhook(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    data: &CreateWebhook,
+) -> Result<Webhook, WebhookError> {
+    validate_url(&data.url)?;
+    let secret = match &data.secret {
+        Some(secret) => secret.clone(),
+        None => format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+    };
+    Ok(Webhook::create(pool, project_id, data, &secret).await?)
+}
+
+pub async fn update_webhook(
+    pool: &SqlitePool,
+    id: Uuid,
+    data: &UpdateWebhook,
+) -> Result<Webhook, WebhookError> {
+    if let Some(url) = &data.url {
+        validate_url(url)?;
+    }
+    Ok(Webhook::update(pool, id, data).await?)
+}
+
+fn event_payload(event: &WebhookOutboxEvent, task: Option<&Task>) -> Value {
+    json!({
+        "event": event.event_type,
+        "occurred_at": event.created_at,
+        "project_id": event.project_id,
+        "task": task.map(|task| json!({
+            "id": task.id,
+            "title": task.title,
+            "status": task.status,
+        })),
+        "task_id": event.task_id,
+        "change": {
+            "from": event.old_value.as_deref(),
+            "to": event.new_value.as_deref(),
+        },
+        "execution_process_id": event.execution_process_id,
+    })
+}
+
+/// Turn queued changes into deliveries for the webhooks subscribed to
+/// them. Returns the number of deliveries created.
+pub async fn fan_out(pool: &SqlitePool) -> Result<usize, WebhookError> {
+    let mut created = 0;
+    for event in WebhookOutboxEvent::find_batch(pool, BATCH_SIZE).await? {
+        let task = Task::find_by_id(pool, event.task_id).await?;
+        let payload = event_payload(&event, task.as_ref());
+        let mut tx = pool.begin().await?;
+        for webhook in Webhook::find_enabled_by_project(&mut *tx, event.project_id).await? {
+            if webhook.subscribes_to(&event.event_type) {
+                WebhookDelivery::create(&mut *tx, webhook.id, &event.event_type, &payload).await?;
+                created += 1;
+            }
+        }
+        WebhookOutboxEvent::delete(&mut *tx, event.id).await?;
+        tx.commit().await?;
+    }
+    Ok(created)
+}
+
+/// Post the delivery once and record the outcome. Failures are retried
+/// later until the delivery runs out of attempts.
+pub async fn deliver(
+    pool: &SqlitePool,
+    client: &Client,
+    delivery: &WebhookDelivery,
+    now: DateTime<Utc>,
+) -> Result<WebhookDelivery, WebhookError> {
+    let Some(webhook) = Webhook::find_by_id(pool, delivery.webhook_id).await? else {
+        // Deleted along with its deliveries in the meantime
+        return Ok(delivery.clone());
+    };
+
+    let body = serde_json::to_vec(&delivery.payload.0).unwrap_or_default();
+    let result = client
+        .post(&webhook.url)
+        .header("Content-Type", "application/json")
+        .header("X-Webhook-Event", &delivery.event_type)
+        .header("X-Webhook-Delivery", delivery.id.to_string())
+        .header("X-Webhook-Signature", sign(&webhook.secret, &body))
+        .body(body)
+        .send()
+        .await;
+    let (response_status, error) = match result {
+        Ok(response) if response.status().is_success() => {
+            (Some(response.status().as_u16() as i64), None)
+        }
+        Ok(response) => (
+            Some(response.status().as_u16() as i64),
+            Some(format!("Receiver answered {}", response.status())),
+        ),
+        Err(e) => (None, Some(e.to_string())),
+    };
+
+    let attempts = delivery.attempts + 1;
+    let (status, next_attempt_at) = match &error {
+        None => (WebhookDeliveryStatus::Delivered, now),
+        Some(_) if attempts >= MAX_DELIVERY_ATTEMPTS => (WebhookDeliveryStatus::Failed, now),
+        Some(_) => (
+            WebhookDeliveryStatus::Pending,
+            now + RETRY_BASE_DELAY * 2i32.pow(attempts as u32 - 1),
+        ),
+    };
+    if let Some(error) = &error {
+        warn!(
+            "Webhook delivery {} to {} failed (attempt {}): {}",
+            delivery.id, webhook.url, attempts, error
+        );
+    }
+    Ok(WebhookDelivery::record_attempt(
+        pool,
+        delivery.id,
+        status,
+        response_status,
+        error.as_deref(),
+        next_attempt_at,
+    )
+    .await?)
+}
+
+/// Attempt the deliveries due at `now`. Returns how many were delivered.
+pub async fn dispatch_due(
+    pool: &SqlitePool,
+    client: &Client,
+    now: DateTime<Utc>,
+) -> Result<usize, WebhookError> {
+    let mut delivered = 0;
+    for delivery in WebhookDelivery::find_due(pool, now, BATCH_SIZE).await? {
+        let delivery = deliver(pool, client, &delivery, now).await?;
+        if delivery.status == WebhookDeliveryStatus::Delivered {
+            delivered += 1;
+        }
+    }
+    Ok(delivered)
+}
+
+/// Send a `webhook.test` event to the webhook right away, whatever its
+/// filter, and return the logged delivery
+pub async fn send_test_event(
+    pool: &SqlitePool,
+    client: &Client,
+    webhook: &Webhook,
+) -> Result<WebhookDelivery, WebhookError> {
+    let event_type = WebhookEventType::Test.to_string();
+    let payload = json!({
+        "event": event_type,
+        "occurred_at": Utc::now(),
+        "project_id": webhook.project_id,
+        "webhook_id": webhook.id,
+    });
+    let delivery = WebhookDelivery::create(pool, webhook.id, &event_type, &payload).await?;
+    deliver(pool, client, &delivery, Utc::now()).await
+}
+
+/// Periodically fans out queued changes and posts due deliveries
+pub struct WebhookDispatcher {
+    db: DBService,
+    client: Client,
+    poll_interval: Duration,
+}
+
+impl WebhookDispatcher {
+    pub async fn spawn(db: DBService) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let client = match client() {
+                Ok(client) => client,
+                Err(e) => {
+                    error!("Error creating webhook client: {}", e);
+                    return;
+                }
+            };
+            let service = Self {
+                db,
+                client,
+                poll_interval: Duration::from_secs(5),
+            };
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = fan_out(&self.db.pool).await {
+                error!("Error queueing webhook deliveries: {}", e);
+            }
+            if let Err(e) = dispatch_due(&self.db.pool, &self.client, Utc::now()).await {
+                error!("Error dispatching webhooks: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        Router,
+        extract::State,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+    };
+    use db::{
+        models::{
+            task::{CreateTask, TaskStatus},
+            task_event::TaskEventActor,
+        },
+        test_support::pool_with_project,
+    };
+    use remote::github_app::verify_webhook_signature;
+
+    use super::*;
+
+    /// What the mock receiver got, and the statuses it answers with in turn
+    /// before settling on 200
+    #[derive(Clone, Default)]
+    struct Receiver {
+        received: Arc<Mutex<Vec<(HeaderMap, Vec<u8>)>>>,
+        answers: Arc<Mutex<Vec<StatusCode>>>,
+    }
+
+    async fn receive(
+        State(receiver): State<Receiver>,
+        headers: HeaderMap,
+        body: String,
+    ) -> StatusCode {
+        receiver
+            .received
+            .lock()
+            .unwrap()
+            .push((headers, body.into_bytes()));
+        let mut answers = receiver.answers.lock().unwrap();
+        if answers.is_empty() {
+            StatusCode::OK
+        } else {
+            answers.remove(0)
+        }
+    }
+
+    /// A local HTTP server standing in for the webhook's receiver
+    async fn mock_receiver(answers: Vec<StatusCode>) -> (String, Receiver) {
+        let receiver = Receiver {
+            answers: Arc::new(Mutex::new(answers)),
+            ..Default::default()
+        };
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .with_state(receiver.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, receiver)
+    }
+
+    async fn webhook(pool: &SqlitePool, project_id: Uuid, url: &str) -> Webhook {
+        let data = CreateWebhook {
+            url: url.to_string(),
+            secret: Some("s3cret".to_string()),
+            events: vec![WebhookEventType::TaskStatusChanged],
+            enabled: None,
+        };
+        create_webhook(pool, project_id, &data).await.unwrap()
+    }
+
+    /// Add a task and have the app move it to `status`
+    async fn move_new_task(pool: &SqlitePool, project_id: Uuid, status: TaskStatus) -> Task {
+        let data = CreateTask::from_title_description(project_id, "Ship it".to_string(), None);
+        let task = Task::create(pool, &data, Uuid::new_v4()).await.unwrap();
+        crate::services::task_transitions::set_status(
+            pool,
+            task.id,
+            status,
+            TaskEventActor::System,
+        )
+        .await
+        .unwrap();
+        task
+    }
+
+    #[tokio::test]
+    async fn posts_signed_status_changes() {
+        let (pool, project_id) = pool_with_project().await;
+        let (url, receiver) = mock_receiver(vec![]).await;
+        webhook(&pool, project_id, &url).await;
+        let task = move_new_task(&pool, project_id, TaskStatus::InProgress).await;
+
+        assert_eq!(fan_out(&pool).await.unwrap(), 1);
+        assert_eq!(fan_out(&pool).await.unwrap(), 0);
+        let client = client().unwrap();
+        assert_eq!(dispatch_due(&pool, &client, Utc::now()).await.unwrap(), 1);
+
+        let received = receiver.received.lock().unwrap();
+        let (headers, body) = &received[0];
+        assert_eq!(headers["x-webhook-event"], "task.status_changed");
+        let signature = headers["x-webhook-signature"].to_str().unwrap();
+        assert!(verify_webhook_signature(b"s3cret", signature, body));
+        assert!(!verify_webhook_signature(b"other", signature, body));
+        let payload: Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload["task"]["id"], json!(task.id));
+        assert_eq!(
+            payload["change"]["to"],
+            json!(TaskStatus::InProgress.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_deliveries_back_off_and_give_up() {
+        let (pool, project_id) = pool_with_project().await;
+        let (url, receiver) = mock_receiver(vec![StatusCode::INTERNAL_SERVER_ERROR]).await;
+        let hook = webhook(&pool, project_id, &url).await;
+        move_new_task(&pool, project_id, TaskStatus::InProgress).await;
+        fan_out(&pool).await.unwrap();
+        let client = client().unwrap();
+
+        let start = Utc::now();
+        assert_eq!(dispatch_due(&pool, &client, start).await.unwrap(), 0);
+        let [delivery] = &WebhookDelivery::find_by_webhook(&pool, hook.id, 10)
+            .await
+            .unwrap()[..]
+        else {
+            panic!("one delivery expected");
+        };
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(delivery.response_status, Some(500));
+        assert_eq!(
+            (delivery.next_attempt_at - start).num_seconds(),
+            RETRY_BASE_DELAY.num_seconds()
+        );
+        // Not due yet
+        assert_eq!(dispatch_due(&pool, &client, start).await.unwrap(), 0);
+        assert_eq!(receiver.received.lock().unwrap().len(), 1);
+        // The retry goes through
+        let later = start + chrono::Duration::minutes(1);
+        assert_eq!(dispatch_due(&pool, &client, later).await.unwrap(), 1);
+        assert_eq!(receiver.received.lock().unwrap().len(), 2);
+
+        // A receiver that is gone uses up every attempt
+        let gone = UpdateWebhook {
+            url: Some("http://127.0.0.1:9/closed".to_string()),
+            secret: None,
+            events: None,
+            enabled: None,
+        };
+        update_webhook(&pool, hook.id, &gone).await.unwrap();
+        move_new_task(&pool, project_id, TaskStatus::Cancelled).await;
+        fan_out(&pool).await.unwrap();
+        let mut now = later;
+        for _ in 0..MAX_DELIVERY_ATTEMPTS {
+            now += chrono::Duration::hours(1);
+            dispatch_due(&pool, &client, now).await.unwrap();
+        }
+        let newest = &WebhookDelivery::find_by_webhook(&pool, hook.id, 10)
+            .await
+            .unwrap()[0];
+        assert_eq!(newest.status, WebhookDeliveryStatus::Failed);
+        assert_eq!(newest.attempts, MAX_DELIVERY_ATTEMPTS);
+        assert!(newest.error.is_some());
+        assert!(
+            WebhookDelivery::find_due(&pool, now + chrono::Duration::days(1), 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_events_ignore_the_filter() {
+        let (pool, project_id) = pool_with_project().await;
+        let (url, receiver) = mock_receiver(vec![]).await;
+        let hook = webhook(&pool, project_id, &url).await;
+        let delivery = send_test_event(&pool, &client().unwrap(), &hook)
+            .await
+            .unwrap();
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Delivered);
+        assert_eq!(
+            receiver.received.lock().unwrap()[0].0["x-webhook-event"],
+            "webhook.test"
+        );
+
+        let bad = CreateWebhook {
+            url: "ftp://example.com".to_string(),
+            secret: None,
+            events: vec![],
+            enabled: None,
+        };
+        assert!(matches!(
+            create_webhook(&pool, project_id, &bad).await,
+            Err(WebhookError::InvalidUrl(_))
+        ));
+    }
+}
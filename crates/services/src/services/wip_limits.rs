@@ -0,0 +1,191 @@
+use db::models::{
+    project::Project,
+    project_status::{ProjectStatus, StatusOccupancy, StatusOccupant},
+    task::Task,
+    task_event::{CreateTaskEvent, TaskEvent, TaskEventActor, TaskEventType},
+};
+use serde::Serialize;
+use sqlx::{Executor, Sqlite, SqliteConnection, SqlitePool};
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum WipLimitError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(
+        "{status} is at its WIP limit of {limit}: {}",
+        .occupants.iter().map(|t| t.title.as_str()).collect::<Vec<_>>().join(", ")
+    )]
+    Exceeded {
+        status: String,
+        limit: i32,
+        occupants: Vec<StatusOccupant>,
+    },
+}
+
+/// A move past a full column that was forced through
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct WipLimitOverride {
+    pub status_key: String,
+    pub limit: i32,
+    /// Tasks already in the column when the limit was overridden
+    pub task_count: usize,
+}
+
+/// Whether a task may join a column already holding `occupants`. A full
+/// column rejects it unless `force` is set, in which case the override is
+/// returned so it can be logged.
+pub fn evaluate(
+    status: &ProjectStatus,
+    occupants: Vec<StatusOccupant>,
+    force: bool,
+) -> Result<Option<WipLimitOverride>, WipLimitError> {
+    let Some(limit) = status.wip_limit else {
+        return Ok(None);
+    };
+    if occupants.len() < limit as usize {
+        return Ok(None);
+    }
+    if !force {
+        return Err(WipLimitError::Exceeded {
+            status: status.name.clone(),
+            limit,
+            occupants,
+        });
+    }
+    Ok(Some(WipLimitOverride {
+        status_key: status.key.clone(),
+        limit,
+        task_count: occupants.len(),
+    }))
+}
+
+/// Check moving `task` from its column into column `to_key` of its project
+/// against the target's WIP limit, inside the transaction making the move.
+/// Staying in the same column, and tasks the project leaves out of the count,
+/// always pass.
+pub async fn check(
+    conn: &mut SqliteConnection,
+    task: &Task,
+    to_key: &str,
+    force: bool,
+) -> Result<Option<WipLimitOverride>, WipLimitError> {
+    if task.status_key == to_key {
+        return Ok(None);
+    }
+    let Some(status) = ProjectStatus::find_by_key(&mut *conn, task.project_id, to_key).await?
+    else {
+        return Ok(None);
+    };
+    if status.wip_limit.is_none() {
+        return Ok(None);
+    }
+    let exclude_child_tasks =
+        Project::wip_limit_excludes_child_tasks(&mut *conn, task.project_id).await?;
+    if exclude_child_tasks && task.parent_workspace_id.is_some() {
+        return Ok(None);
+    }
+
+    let mut occupants =
+        ProjectStatus::occupants(&mut *conn, task.project_id, to_key, exclude_child_tasks).await?;
+    occupants.retain(|occupant| occupant.id != task.id);
+    evaluate(&status, occupants, force)
+}
+
+/// Record a forced move past a full column in the task's activity log
+pub async fn record_override<'e, E>(
+    executor: E,
+    task_id: Uuid,
+    over: &WipLimitOverride,
+    actor: TaskEventActor,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let event = CreateTaskEvent::new(task_id, TaskEventType::WipLimitOverride, actor)
+        .with_change(None, serde_json::to_value(over).ok());
+    TaskEvent::create(executor, &event).await
+}
+
+/// Current occupancy against the limit of every column in the project
+pub async fn occupancy(
+    pool: &SqlitePool,
+    project_id: Uuid,
+) -> Result<Vec<StatusOccupancy>, sqlx::Error> {
+    let exclude_child_tasks = Project::wip_limit_excludes_child_tasks(pool, project_id).await?;
+    ProjectStatus::occupancy(pool, project_id, exclude_child_tasks).await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use db::models::task::TaskStatus;
+
+    use super::*;
+
+    fn column(wip_limit: Option<i32>) -> ProjectStatus {
+        ProjectStatus {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            key: "inprogress".to_string(),
+            name: "In Progress".to_string(),
+            category: TaskStatus::InProgress,
+            sort_order: 1,
+            wip_limit,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn occupants(count: usize) -> Vec<StatusOccupant> {
+        (0..count)
+            .map(|i| StatusOccupant {
+                id: Uuid::new_v4(),
+                title: format!("Task {i}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn full_columns_reject_new_tasks() {
+        assert!(matches!(
+            evaluate(&column(Some(3)), occupants(2), false),
+            Ok(None)
+        ));
+        assert!(matches!(
+            evaluate(&column(None), occupants(50), false),
+            Ok(None)
+        ));
+
+        let Err(err) = evaluate(&column(Some(3)), occupants(3), false) else {
+            panic!("a fourth task should not fit");
+        };
+        assert_eq!(
+            err.to_string(),
+            "In Progress is at its WIP limit of 3: Task 0, Task 1, Task 2"
+        );
+        let WipLimitError::Exceeded { occupants, .. } = err else {
+            unreachable!();
+        };
+        assert_eq!(occupants.len(), 3);
+    }
+
+    #[test]
+    fn forcing_returns_the_override() {
+        assert_eq!(
+            evaluate(&column(Some(3)), occupants(4), true).unwrap(),
+            Some(WipLimitOverride {
+                status_key: "inprogress".to_string(),
+                limit: 3,
+                task_count: 4,
+            })
+        );
+        // Nothing to record when the column had room anyway
+        assert_eq!(
+            evaluate(&column(Some(3)), occupants(1), true).unwrap(),
+            None
+        );
+    }
+}
@@ -67,6 +67,27 @@ impl LogMsg {
         Message::Text(json.into())
     }
 
+    /// Kind used for filtering: the entry type (e.g. `tool_use`) for patches
+    /// carrying a normalized entry, otherwise the event name
+    pub fn kind(&self) -> String {
+        match self {
+            LogMsg::JsonPatch(patch) => normalized_entry(patch)
+                .and_then(|entry| entry["entry_type"]["type"].as_str().map(str::to_string))
+                .unwrap_or_else(|| EV_JSON_PATCH.to_string()),
+            msg => msg.name().to_string(),
+        }
+    }
+
+    /// Searchable text: raw output, or the content of a normalized entry
+    pub fn text(&self) -> Option<String> {
+        match self {
+            LogMsg::Stdout(s) | LogMsg::Stderr(s) => Some(s.clone()),
+            LogMsg::JsonPatch(patch) => normalized_entry(patch)
+                .and_then(|entry| entry["content"].as_str().map(str::to_string)),
+            _ => None,
+        }
+    }
+
     /// Rough size accounting for your byte‑budgeted history.
     pub fn approx_bytes(&self) -> usize {
         const OVERHEAD: usize = 8;
@@ -83,3 +104,14 @@ impl LogMsg {
         }
     }
 }
+
+/// The normalized entry added or replaced by the last operation of `patch`
+fn normalized_entry(patch: &Patch) -> Option<serde_json::Value> {
+    let ops = serde_json::to_value(patch).ok()?;
+    ops.as_array()?.iter().rev().find_map(|op| {
+        let value = op.get("value")?;
+        (value.get("type")?.as_str()? == "NORMALIZED_ENTRY")
+            .then(|| value.get("content").cloned())
+            .flatten()
+    })
+}
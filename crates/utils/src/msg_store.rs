@@ -1,18 +1,55 @@
 use std::{
     collections::VecDeque,
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
 };
 
 use axum::response::sse::Event;
 use futures::{StreamExt, TryStreamExt, future};
-use tokio::{sync::broadcast, task::JoinHandle};
-use tokio_stream::wrappers::BroadcastStream;
+use serde::Serialize;
+use tokio::{
+    sync::broadcast::{
+        self,
+        error::{RecvError, TryRecvError},
+    },
+    task::JoinHandle,
+};
+use ts_rs::TS;
 
-use crate::{log_msg::LogMsg, stream_lines::LinesStreamExt};
+use crate::{
+    log_msg::LogMsg,
+    redact::{Redactor, StreamRedactor},
+    stream_lines::LinesStreamExt,
+};
 
 // 100 MB Limit
 const HISTORY_BYTES: usize = 100000 * 1024;
 
+/// Messages buffered for live subscribers. The buffer is shared and never
+/// blocks the sender: a subscriber that falls this far behind skips ahead.
+const LIVE_BUFFER_MSGS: usize = 10000;
+
+/// Upper bound for one chunk merged by [`Overflow::CoalesceRaw`]
+const COALESCE_MAX_BYTES: usize = 64 * 1024;
+
+/// What a live subscriber does when it reads slower than messages arrive
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Skip the messages that fell out of the buffer and emit a gap marker
+    #[default]
+    DropOldest,
+    /// As [`Overflow::DropOldest`], but also merge consecutive raw chunks
+    /// already waiting in the buffer into one message, so a fast producer
+    /// costs the consumer fewer sends and overflows less often
+    CoalesceRaw,
+}
+
+/// Marker emitted in place of `skipped` messages a slow subscriber missed
+pub fn gap_marker(skipped: u64) -> LogMsg {
+    LogMsg::Stderr(format!(
+        "\n[{skipped} log messages skipped: reader fell behind]\n"
+    ))
+}
+
 #[derive(Clone)]
 struct StoredMsg {
     msg: LogMsg,
@@ -22,11 +59,100 @@ struct StoredMsg {
 struct Inner {
     history: VecDeque<StoredMsg>,
     total_bytes: usize,
+    /// Messages dropped from the front, so indices stay stable
+    evicted: usize,
+}
+
+/// Selects log messages by kind and text
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Accepted [`LogMsg::kind`] values; empty accepts every kind
+    pub kinds: Vec<String>,
+    /// Case-insensitive text the message must contain
+    pub text_contains: Option<String>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, msg: &LogMsg) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&msg.kind()) {
+            return false;
+        }
+        match &self.text_contains {
+            Some(needle) => msg
+                .text()
+                .is_some_and(|text| text.to_lowercase().contains(&needle.to_lowercase())),
+            None => true,
+        }
+    }
+}
+
+/// A log message with its position in the store's history
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct IndexedLogMsg {
+    pub index: usize,
+    #[ts(type = "JsonValue")]
+    pub msg: LogMsg,
+}
+
+/// One page of filtered history. Pass `next_cursor` back to continue.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LogPage {
+    pub entries: Vec<IndexedLogMsg>,
+    pub next_cursor: Option<usize>,
+}
+
+struct Redaction {
+    redactor: Redactor,
+    stdout: StreamRedactor,
+    stderr: StreamRedactor,
+}
+
+impl Redaction {
+    fn apply(&mut self, msg: LogMsg) -> Vec<LogMsg> {
+        match msg {
+            LogMsg::Stdout(s) => {
+                let out = self.stdout.feed(&self.redactor, &s);
+                (!out.is_empty())
+                    .then_some(LogMsg::Stdout(out))
+                    .into_iter()
+                    .collect()
+            }
+            LogMsg::Stderr(s) => {
+                let out = self.stderr.feed(&self.redactor, &s);
+                (!out.is_empty())
+                    .then_some(LogMsg::Stderr(out))
+                    .into_iter()
+                    .collect()
+            }
+            LogMsg::JsonPatch(patch) => {
+                let redacted = serde_json::to_value(&patch).ok().and_then(|mut value| {
+                    self.redactor.redact_json(&mut value);
+                    serde_json::from_value(value).ok()
+                });
+                vec![LogMsg::JsonPatch(redacted.unwrap_or(patch))]
+            }
+            LogMsg::Finished => {
+                let mut msgs = Vec::new();
+                let out = self.stdout.flush();
+                if !out.is_empty() {
+                    msgs.push(LogMsg::Stdout(out));
+                }
+                let err = self.stderr.flush();
+                if !err.is_empty() {
+                    msgs.push(LogMsg::Stderr(err));
+                }
+                msgs.push(LogMsg::Finished);
+                msgs
+            }
+            msg => vec![msg],
+        }
+    }
 }
 
 pub struct MsgStore {
     inner: RwLock<Inner>,
     sender: broadcast::Sender<LogMsg>,
+    redaction: Mutex<Option<Redaction>>,
 }
 
 impl Default for MsgStore {
@@ -37,17 +163,57 @@ impl Default for MsgStore {
 
 impl MsgStore {
     pub fn new() -> Self {
-        let (sender, _) = broadcast::channel(10000);
+        Self::with_live_buffer(LIVE_BUFFER_MSGS)
+    }
+
+    /// Store whose live subscribers buffer at most `capacity` messages
+    pub fn with_live_buffer(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
         Self {
             inner: RwLock::new(Inner {
                 history: VecDeque::with_capacity(32),
                 total_bytes: 0,
+                evicted: 0,
             }),
             sender,
+            redaction: Mutex::new(None),
         }
     }
 
+    /// Mask the redactor's secrets in every message pushed from now on,
+    /// before it reaches listeners or history.
+    pub fn set_redactor(&self, redactor: Redactor) {
+        let redaction = (!redactor.is_empty()).then(|| Redaction {
+            redactor,
+            stdout: StreamRedactor::default(),
+            stderr: StreamRedactor::default(),
+        });
+        *self.redaction.lock().unwrap() = redaction;
+    }
+
     pub fn push(&self, msg: LogMsg) {
+        let mut redaction = self.redaction.lock().unwrap();
+        match redaction.as_mut() {
+            // Keep the lock while pushing so redacted chunks stay in order
+            Some(redaction) => {
+                for msg in redaction.apply(msg) {
+                    self.push_unredacted(msg);
+                }
+            }
+            None => {
+                drop(redaction);
+                self.push_unredacted(msg);
+            }
+        }
+    }
+
+    /// Send `msg` to live listeners only. It is kept out of history, so late
+    /// subscribers and the persisted logs never see it.
+    pub fn push_transient(&self, msg: LogMsg) {
+        let _ = self.sender.send(msg);
+    }
+
+    fn push_unredacted(&self, msg: LogMsg) {
         let _ = self.sender.send(msg.clone()); // live listeners
         let bytes = msg.approx_bytes();
 
@@ -55,6 +221,7 @@ impl MsgStore {
         while inner.total_bytes.saturating_add(bytes) > HISTORY_BYTES {
             if let Some(front) = inner.history.pop_front() {
                 inner.total_bytes = inner.total_bytes.saturating_sub(front.bytes);
+                inner.evicted += 1;
             } else {
                 break;
             }
@@ -100,14 +267,68 @@ impl MsgStore {
     /// History then live, as `LogMsg`.
     pub fn history_plus_stream(
         &self,
+    ) -> futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>> {
+        self.history_plus_stream_with(Overflow::default())
+    }
+
+    /// History then live, handling a slow reader according to `overflow`
+    pub fn history_plus_stream_with(
+        &self,
+        overflow: Overflow,
     ) -> futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>> {
         let (history, rx) = (self.get_history(), self.get_receiver());
 
-        let hist = futures::stream::iter(history.into_iter().map(Ok::<_, std::io::Error>));
-        let live = BroadcastStream::new(rx)
-            .filter_map(|res| async move { res.ok().map(Ok::<_, std::io::Error>) });
+        let hist = futures::stream::iter(history);
+        let live = live_stream(rx, overflow);
+
+        Box::pin(hist.chain(live).map(Ok::<_, std::io::Error>))
+    }
+
+    /// Up to `limit` history messages matching `filter`, starting at index
+    /// `cursor`. Indices count from the first message ever pushed, so a
+    /// cursor stays valid after older messages are evicted.
+    pub fn history_filtered(&self, filter: &LogFilter, cursor: usize, limit: usize) -> LogPage {
+        let inner = self.inner.read().unwrap();
+        let mut entries = Vec::new();
+        let mut next_cursor = None;
+
+        let skip = cursor.saturating_sub(inner.evicted);
+        for (offset, stored) in inner.history.iter().enumerate().skip(skip) {
+            if !filter.matches(&stored.msg) {
+                continue;
+            }
+            let index = inner.evicted + offset;
+            if entries.len() == limit {
+                next_cursor = Some(index);
+                break;
+            }
+            entries.push(IndexedLogMsg {
+                index,
+                msg: stored.msg.clone(),
+            });
+        }
+
+        LogPage {
+            entries,
+            next_cursor,
+        }
+    }
 
-        Box::pin(hist.chain(live))
+    /// History then live, keeping only messages matching `filter`. `Finished`
+    /// always passes so consumers know when the stream ends.
+    pub fn history_plus_stream_filtered(
+        &self,
+        filter: LogFilter,
+    ) -> futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>> {
+        self.history_plus_stream()
+            .filter(move |res| {
+                future::ready(match res {
+                    Ok(LogMsg::Finished) => true,
+                    Ok(msg) => filter.matches(msg),
+                    Err(_) => true,
+                })
+            })
+            .boxed()
     }
 
     pub fn stdout_chunked_stream(
@@ -174,4 +395,321 @@ impl MsgStore {
             }
         })
     }
+
+    /// Like [`spawn_forwarder`](Self::spawn_forwarder), but stops storing raw
+    /// stdout/stderr once `max_bytes` of it has been seen. A single truncation
+    /// marker is pushed in its place and `on_overflow` is invoked once. The
+    /// source stream keeps being drained so the child never blocks on a full pipe.
+    pub fn spawn_limited_forwarder<S, E, F>(
+        self: Arc<Self>,
+        stream: S,
+        max_bytes: usize,
+        on_overflow: F,
+    ) -> JoinHandle<()>
+    where
+        S: futures::Stream<Item = Result<LogMsg, E>> + Send + 'static,
+        E: std::fmt::Display + Send + 'static,
+        F: FnOnce() + Send + 'static,
+    {
+        tokio::spawn(async move {
+            tokio::pin!(stream);
+
+            let mut seen = 0usize;
+            let mut truncated = false;
+            let mut on_overflow = Some(on_overflow);
+
+            while let Some(next) = stream.next().await {
+                let msg = match next {
+                    Ok(msg) => msg,
+                    Err(e) => LogMsg::Stderr(format!("stream error: {e}")),
+                };
+                let len = match &msg {
+                    LogMsg::Stdout(s) | LogMsg::Stderr(s) => s.len(),
+                    _ => {
+                        self.push(msg);
+                        continue;
+                    }
+                };
+
+                if truncated {
+                    continue;
+                }
+                if seen.saturating_add(len) <= max_bytes {
+                    seen += len;
+                    self.push(msg);
+                    continue;
+                }
+
+                truncated = true;
+                self.push(LogMsg::Stderr(format!(
+                    "\n[output truncated after {seen} bytes]\n"
+                )));
+                if let Some(f) = on_overflow.take() {
+                    f();
+                }
+            }
+        })
+    }
+}
+
+/// Live messages from `rx`. Lagging is reported with a [`gap_marker`]
+/// instead of being skipped silently.
+fn live_stream(
+    rx: broadcast::Receiver<LogMsg>,
+    overflow: Overflow,
+) -> futures::stream::BoxStream<'static, LogMsg> {
+    futures::stream::unfold((rx, None), move |(mut rx, pending)| async move {
+        let msg = match pending {
+            Some(msg) => msg,
+            None => match rx.recv().await {
+                Ok(msg) => msg,
+                Err(RecvError::Lagged(skipped)) => gap_marker(skipped),
+                Err(RecvError::Closed) => return None,
+            },
+        };
+        let (msg, pending) = match overflow {
+            Overflow::DropOldest => (msg, None),
+            Overflow::CoalesceRaw => coalesce_raw(msg, &mut rx),
+        };
+        Some((msg, (rx, pending)))
+    })
+    .boxed()
+}
+
+/// Append raw chunks of the same stream that are already buffered to `msg`.
+/// Returns the merged message and the first buffered message that did not fit.
+fn coalesce_raw(mut msg: LogMsg, rx: &mut broadcast::Receiver<LogMsg>) -> (LogMsg, Option<LogMsg>) {
+    loop {
+        let (LogMsg::Stdout(acc) | LogMsg::Stderr(acc)) = &mut msg else {
+            return (msg, None);
+        };
+        if acc.len() >= COALESCE_MAX_BYTES {
+            return (msg, None);
+        }
+        let next = match rx.try_recv() {
+            Ok(next) => next,
+            Err(TryRecvError::Lagged(skipped)) => gap_marker(skipped),
+            Err(TryRecvError::Empty | TryRecvError::Closed) => return (msg, None),
+        };
+        match (&mut msg, next) {
+            (LogMsg::Stdout(acc), LogMsg::Stdout(more))
+            | (LogMsg::Stderr(acc), LogMsg::Stderr(more)) => acc.push_str(&more),
+            (_, other) => return (msg, Some(other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_bytes(store: &MsgStore) -> usize {
+        store
+            .get_history()
+            .iter()
+            .map(|m| match m {
+                LogMsg::Stdout(s) | LogMsg::Stderr(s) => s.len(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn redactor_keeps_secret_out_of_store() {
+        let secret = "ghp_0123456789abcdef";
+        let store = MsgStore::new();
+        store.set_redactor(Redactor::new([secret.to_string()]));
+        let mut receiver = store.get_receiver();
+
+        store.push_stdout("GITHUB_TOKEN=ghp_01234");
+        store.push_stdout("56789abcdef\nok\n");
+        store.push_stderr("error: bad credentials ghp_0123456789");
+        store.push_patch(
+            serde_json::from_value(serde_json::json!([
+                {"op": "add", "path": "/entries/0", "value": {"content": secret}}
+            ]))
+            .unwrap(),
+        );
+        store.push_finished();
+
+        let history = store.get_history();
+        let mut live = Vec::new();
+        while let Ok(msg) = receiver.try_recv() {
+            live.push(msg);
+        }
+        for msgs in [&history, &live] {
+            let dump = serde_json::to_string(msgs).unwrap();
+            assert!(!dump.contains(secret));
+        }
+
+        let stdout: String = history
+            .iter()
+            .filter_map(|m| match m {
+                LogMsg::Stdout(s) => Some(s.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(stdout, "GITHUB_TOKEN=***\nok\n");
+        assert!(matches!(history.last(), Some(LogMsg::Finished)));
+    }
+
+    #[tokio::test]
+    async fn limited_forwarder_truncates_once() {
+        let store = Arc::new(MsgStore::new());
+        let chunks = futures::stream::iter(
+            (0..1000).map(|_| Ok::<_, std::io::Error>(LogMsg::Stdout("y\n".repeat(512)))),
+        );
+        let overflows = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = overflows.clone();
+
+        store
+            .clone()
+            .spawn_limited_forwarder(chunks, 64 * 1024, move || {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            })
+            .await
+            .unwrap();
+
+        let history = store.get_history();
+        let markers = history
+            .iter()
+            .filter(|m| matches!(m, LogMsg::Stderr(s) if s.contains("output truncated after")))
+            .count();
+        assert_eq!(markers, 1);
+        assert_eq!(overflows.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(raw_bytes(&store) <= 64 * 1024 + 64);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn limited_forwarder_bounds_yes_output() {
+        use tokio_util::io::ReaderStream;
+
+        let mut child = tokio::process::Command::new("yes")
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .expect("spawn yes");
+        let stdout = ReaderStream::new(child.stdout.take().unwrap())
+            .map_ok(|chunk| LogMsg::Stdout(String::from_utf8_lossy(&chunk).into_owned()));
+
+        let store = Arc::new(MsgStore::new());
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let forwarder = store
+            .clone()
+            .spawn_limited_forwarder(stdout, 1024 * 1024, move || {
+                let _ = tx.send(());
+            });
+
+        tokio::time::timeout(std::time::Duration::from_secs(30), rx)
+            .await
+            .expect("overflow not reached")
+            .unwrap();
+        child.kill().await.unwrap();
+        forwarder.await.unwrap();
+
+        assert!(raw_bytes(&store) <= 1024 * 1024 + 64);
+    }
+
+    #[test]
+    fn filters_and_pages_large_history() {
+        let store = MsgStore::new();
+        for i in 0..50_000 {
+            if i % 2 == 0 {
+                store.push_stdout(format!("line {i}\n"));
+            } else {
+                let tool = if i % 1000 == 1 { "cargo test" } else { "ls" };
+                store.push_patch(
+                    serde_json::from_value(serde_json::json!([{
+                        "op": "add",
+                        "path": format!("/entries/{i}"),
+                        "value": {"type": "NORMALIZED_ENTRY", "content": {
+                            "entry_type": {"type": "tool_use"},
+                            "content": format!("Run {tool}"),
+                        }},
+                    }]))
+                    .unwrap(),
+                );
+            }
+        }
+
+        let filter = LogFilter {
+            kinds: vec!["tool_use".to_string()],
+            text_contains: Some("CARGO".to_string()),
+        };
+        let first = store.history_filtered(&filter, 0, 20);
+        assert_eq!(first.entries.len(), 20);
+        assert_eq!(first.entries[0].index, 1);
+        assert!(first.entries.iter().all(|e| filter.matches(&e.msg)));
+
+        let cursor = first.next_cursor.unwrap();
+        let rest = store.history_filtered(&filter, cursor, 100);
+        assert_eq!(rest.entries.len(), 30);
+        assert_eq!(rest.entries[0].index, cursor);
+        assert_eq!(rest.next_cursor, None);
+
+        let stdout = LogFilter {
+            kinds: vec!["stdout".to_string()],
+            text_contains: None,
+        };
+        assert_eq!(
+            store.history_filtered(&stdout, 0, 50_000).entries.len(),
+            25_000
+        );
+    }
+
+    async fn drain(
+        mut stream: futures::stream::BoxStream<'static, std::io::Result<LogMsg>>,
+    ) -> Vec<LogMsg> {
+        let mut received = Vec::new();
+        while let Some(Ok(msg)) = stream.next().await {
+            if matches!(msg, LogMsg::Finished) {
+                break;
+            }
+            received.push(msg);
+            // Deliberately slow consumer
+            tokio::task::yield_now().await;
+        }
+        received
+    }
+
+    #[tokio::test]
+    async fn slow_subscriber_gets_gap_marker_without_blocking_producer() {
+        let store = Arc::new(MsgStore::with_live_buffer(64));
+        let stream = store.history_plus_stream();
+
+        // The producer never waits on the subscriber
+        for i in 0..50_000 {
+            store.push_stdout(format!("line {i}\n"));
+        }
+        store.push_finished();
+        assert!(store.sender.len() <= 64);
+
+        let received = drain(stream).await;
+        assert!(received.len() <= 64 + 1);
+        let LogMsg::Stderr(marker) = &received[0] else {
+            panic!("expected gap marker, got {:?}", received[0]);
+        };
+        assert!(marker.contains("log messages skipped"));
+        assert!(matches!(received.last(), Some(LogMsg::Stdout(s)) if s == "line 49999\n"));
+    }
+
+    #[tokio::test]
+    async fn coalescing_subscriber_merges_backed_up_chunks() {
+        let store = Arc::new(MsgStore::with_live_buffer(1024));
+        let stream = store.history_plus_stream_with(Overflow::CoalesceRaw);
+
+        for i in 0..1000 {
+            store.push_stdout(format!("{i},"));
+        }
+        store.push_stderr("done");
+        store.push_finished();
+
+        let received = drain(stream).await;
+        let expected: String = (0..1000).map(|i| format!("{i},")).collect();
+        assert!(
+            matches!(&received[..], [LogMsg::Stdout(out), LogMsg::Stderr(err)]
+            if *out == expected && err == "done")
+        );
+    }
 }
@@ -0,0 +1,135 @@
+use serde_json::Value;
+
+/// Replacement for masked secret values
+pub const REDACTED: &str = "***";
+
+/// Values shorter than this are too likely to appear in ordinary output
+const MIN_SECRET_LEN: usize = 8;
+
+/// Masks a fixed set of secret values in text and JSON
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    secrets: Vec<String>,
+}
+
+impl Redactor {
+    pub fn new(secrets: impl IntoIterator<Item = String>) -> Self {
+        let mut secrets: Vec<String> = secrets
+            .into_iter()
+            .filter(|s| s.len() >= MIN_SECRET_LEN)
+            .collect();
+        // Longest first, so a secret that contains another is masked whole
+        secrets.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        secrets.dedup();
+        Self { secrets }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for secret in &self.secrets {
+            if out.contains(secret.as_str()) {
+                out = out.replace(secret.as_str(), REDACTED);
+            }
+        }
+        out
+    }
+
+    /// Mask secrets in every string inside `value`
+    pub fn redact_json(&self, value: &mut Value) {
+        match value {
+            Value::String(s) => {
+                if self
+                    .secrets
+                    .iter()
+                    .any(|secret| s.contains(secret.as_str()))
+                {
+                    *s = self.redact(s);
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_json(item)),
+            Value::Object(map) => map.values_mut().for_each(|item| self.redact_json(item)),
+            _ => {}
+        }
+    }
+
+    /// Length of the longest suffix of `text` that could be the start of a secret
+    fn partial_match_len(&self, text: &str) -> usize {
+        let mut longest = 0;
+        for secret in &self.secrets {
+            let max = (secret.len() - 1).min(text.len());
+            for len in (longest + 1..=max).rev() {
+                let start = text.len() - len;
+                if text.is_char_boundary(start) && secret.starts_with(&text[start..]) {
+                    longest = len;
+                    break;
+                }
+            }
+        }
+        longest
+    }
+}
+
+/// Redacts a stream of text chunks. A trailing partial match is held back
+/// until the next chunk shows whether it completes a secret, so values split
+/// across chunk boundaries are still masked.
+#[derive(Debug, Default)]
+pub struct StreamRedactor {
+    pending: String,
+}
+
+impl StreamRedactor {
+    /// Redact `chunk` and return the text that is safe to emit now
+    pub fn feed(&mut self, redactor: &Redactor, chunk: &str) -> String {
+        self.pending.push_str(chunk);
+        let mut redacted = redactor.redact(&self.pending);
+        let held = redactor.partial_match_len(&redacted);
+        self.pending = redacted.split_off(redacted.len() - held);
+        redacted
+    }
+
+    /// Return whatever is still held back; call once the stream has ended
+    pub fn flush(&mut self) -> String {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_secrets_split_across_chunks() {
+        let redactor = Redactor::new(["sk-live-123456".to_string(), "short".to_string()]);
+        let mut stream = StreamRedactor::default();
+
+        let mut out = String::new();
+        for chunk in ["key=sk-li", "ve-12", "3456 done, sk-", "other short"] {
+            out.push_str(&stream.feed(&redactor, chunk));
+        }
+        out.push_str(&stream.flush());
+
+        assert_eq!(out, "key=*** done, sk-other short");
+    }
+
+    #[test]
+    fn masks_secrets_inside_json() {
+        let redactor = Redactor::new(["hunter2hunter2".to_string()]);
+        let mut value = serde_json::json!({
+            "content": "token is hunter2hunter2",
+            "nested": [{"env": "X=hunter2hunter2"}],
+        });
+        redactor.redact_json(&mut value);
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "content": "token is ***",
+                "nested": [{"env": "X=***"}],
+            })
+        );
+    }
+}
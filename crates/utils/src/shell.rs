@@ -70,6 +70,17 @@ pub async fn resolve_executable_path(executable: &str) -> Option<PathBuf> {
     None
 }
 
+/// Look up an executable by name in the given directories only, honouring
+/// platform executable extensions. Explicit paths are not searched.
+pub fn find_executable_in_dirs(executable: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    if dirs.is_empty() || Path::new(executable).components().count() != 1 {
+        return None;
+    }
+    let search_path = join_paths(dirs).ok()?;
+    let cwd = std::env::current_dir().ok()?;
+    which::which_in(executable, Some(search_path), cwd).ok()
+}
+
 pub fn resolve_executable_path_blocking(executable: &str) -> Option<PathBuf> {
     block_on(resolve_executable_path(executable))
 }
@@ -338,3 +349,33 @@ fn get_fresh_path_blocking() -> Option<String> {
         .reduce(|a, b| merge_paths(&a, &b))
         .map(|merged| merged.to_string_lossy().into_owned())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(windows)]
+    #[test]
+    fn finds_batch_shims_through_pathext() {
+        let dir = std::env::temp_dir().join(format!("vk-pathext-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("vk-shim.cmd"), "@echo off\r\n").unwrap();
+
+        let found = find_executable_in_dirs("vk-shim", std::slice::from_ref(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let found = found.expect("shim should resolve without its extension");
+        assert!(
+            found
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("cmd"))
+        );
+    }
+
+    #[test]
+    fn explicit_paths_are_not_searched_in_dirs() {
+        let dirs = [std::env::temp_dir()];
+        assert_eq!(find_executable_in_dirs("bin/agent", &dirs), None);
+        assert_eq!(find_executable_in_dirs("agent", &[]), None);
+    }
+}
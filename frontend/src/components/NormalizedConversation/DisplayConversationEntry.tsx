@@ -23,6 +23,7 @@ import {
   Edit,
   Eye,
   Globe,
+  Loader2,
   Plus,
   Search,
   Settings,
@@ -733,6 +734,7 @@ function DisplayConversationEntry({
   const isUserMessage = entryType.type === 'user_message';
   const isUserFeedback = entryType.type === 'user_feedback';
   const isLoading = entryType.type === 'loading';
+  const isHeartbeat = entryType.type === 'heartbeat';
   const isTokenUsage = entryType.type === 'token_usage_info';
   const isFileEdit = (a: ActionType): a is FileEditAction =>
     a.action === 'file_edit';
@@ -907,6 +909,15 @@ function DisplayConversationEntry({
     );
   }
 
+  if (isHeartbeat) {
+    return (
+      <div className="px-4 py-2 text-xs text-muted-foreground flex items-center gap-2">
+        <Loader2 className="h-3 w-3 animate-spin" />
+        <span>{entry.content}</span>
+      </div>
+    );
+  }
+
   if (entry.entry_type.type === 'next_action') {
     return (
       <div className="px-4 py-2 text-sm">
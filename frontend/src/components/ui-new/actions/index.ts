@@ -746,6 +746,8 @@ export const Actions = {
           has_in_progress_attempt: false,
           last_attempt_failed: false,
           executor: '',
+          blocked: false,
+          archived_at: null,
         },
         repoId,
         targetBranch: repo?.target_branch,
@@ -319,6 +319,7 @@ function NewDisplayConversationEntry(props: Props) {
 
     case 'user_feedback':
     case 'loading':
+    case 'heartbeat':
       // Fallback to legacy component for these entry types
       return (
         <DisplayConversationEntry
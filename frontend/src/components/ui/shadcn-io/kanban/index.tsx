@@ -97,10 +97,13 @@ export const KanbanCard = ({
       data: { index, parent },
       disabled: dragDisabled,
     });
+  // Cards are drop targets too, so a card can be dropped below another
+  const { setNodeRef: setDropRef } = useDroppable({ id, data: { parent } });
 
-  // Combine DnD ref and forwarded ref
+  // Combine DnD refs and forwarded ref
   const combinedRef = (node: HTMLDivElement | null) => {
     setNodeRef(node);
+    setDropRef(node);
     if (typeof forwardedRef === 'function') {
       forwardedRef(node);
     } else if (forwardedRef && typeof forwardedRef === 'object') {
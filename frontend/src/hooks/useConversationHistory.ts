@@ -161,7 +161,7 @@ export const useConversationHistory = ({
   const patchWithKey = (
     patch: PatchType,
     executionProcessId: string,
-    index: number | 'user'
+    index: number | 'user' | 'heartbeat'
   ) => {
     return {
       ...patch,
@@ -454,20 +454,29 @@ export const useConversationHistory = ({
         } else {
           url = `/api/execution-processes/${executionProcess.id}/normalized-logs/ws`;
         }
+        const storeEntries = (entries: PatchType[], heartbeat?: PatchType) => {
+          const patchesWithKey = entries.map((entry, index) =>
+            patchWithKey(entry, executionProcess.id, index)
+          );
+          if (heartbeat) {
+            patchesWithKey.push(
+              patchWithKey(heartbeat, executionProcess.id, 'heartbeat')
+            );
+          }
+          mergeIntoDisplayed((state) => {
+            state[executionProcess.id] = {
+              executionProcess,
+              entries: patchesWithKey,
+            };
+          });
+        };
         const controller = streamJsonPatchEntries<PatchType>(url, {
           onEntries(entries) {
-            const patchesWithKey = entries.map((entry, index) =>
-              patchWithKey(entry, executionProcess.id, index)
-            );
-            mergeIntoDisplayed((state) => {
-              state[executionProcess.id] = {
-                executionProcess,
-                entries: patchesWithKey,
-              };
-            });
+            storeEntries(entries, controller.getSnapshot().heartbeat);
             emitEntries(displayedExecutionProcesses.current, 'running', false);
           },
-          onFinished: () => {
+          onFinished: (entries) => {
+            storeEntries(entries);
             emitEntries(displayedExecutionProcesses.current, 'running', false);
             controller.close();
             resolve();
@@ -6,6 +6,7 @@ import { useLiveQuery, eq, isNull } from '@tanstack/react-db';
 import { sharedTasksCollection } from '@/lib/electric/sharedTasksCollection';
 import { useAssigneeUserNames } from './useAssigneeUserName';
 import { useAutoLinkSharedTasks } from './useAutoLinkSharedTasks';
+import { compareBoardOrder } from '@/utils/taskOrder';
 import type {
   SharedTask,
   TaskStatus,
@@ -136,11 +137,7 @@ export const useProjectTasks = (projectId: string): UseProjectTasksResult => {
     );
 
     (Object.values(byStatus) as TaskWithAttemptStatus[][]).forEach((list) => {
-      list.sort(
-        (a, b) =>
-          new Date(b.created_at as string).getTime() -
-          new Date(a.created_at as string).getTime()
-      );
+      list.sort(compareBoardOrder);
     });
 
     return { tasks: sorted, tasksById: merged, tasksByStatus: byStatus };
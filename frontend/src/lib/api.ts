@@ -15,6 +15,7 @@ import {
   DirectoryEntry,
   ExecutionProcess,
   ExecutionProcessRepoState,
+  ProcessIds,
   GitBranch,
   Project,
   Repo,
@@ -25,10 +26,45 @@ import {
   SearchMode,
   SearchResult,
   ShareTaskResponse,
+  AssigneeTaskCount,
+  DueDateChange,
+  DueTasks,
+  CreateTaskDependency,
+  MoveTask,
+  ProjectStatus,
+  CreateProjectStatus,
+  UpdateProjectStatus,
+  ProjectStats,
+  ProjectUsage,
+  Webhook,
+  CreateWebhook,
+  UpdateWebhook,
+  WebhookDelivery,
+  StatusOccupancy,
+  ProjectSettings,
+  ProjectImport,
+  NameConflict,
+  TaskImportOptions,
+  TaskImportReport,
+  ImportGitHubIssuesRequest,
+  IssueImportReport,
+  TaskExternalLink,
+  TaskUsage,
+  MoveTaskToProject,
+  DuplicateTask,
+  DuplicatedTask,
   Task,
+  TaskBatchItemError,
+  TaskDependencies,
+  TaskDependency,
+  TaskEvent,
+  TaskSearchResult,
   TaskRelationships,
   Tag,
   TagSearchParams,
+  TaskTemplate,
+  CreateTaskTemplate,
+  UpdateTaskTemplate,
   TaskWithAttemptStatus,
   UpdateProject,
   UpdateTask,
@@ -40,6 +76,8 @@ import {
   ImageResponse,
   GitOperationError,
   ApprovalResponse,
+  PendingApprovalInfo,
+  RespondToPendingApprovalsResponse,
   RebaseTaskAttemptRequest,
   ChangeTargetBranchRequest,
   ChangeTargetBranchResponse,
@@ -91,6 +129,7 @@ import {
   Workspace,
   StartReviewRequest,
   ReviewError,
+  ProfilesReport,
 } from 'shared/types';
 import type { WorkspaceWithSession } from '@/types/attempt';
 import { createWorkspaceWithSession } from '@/types/attempt';
@@ -135,21 +174,23 @@ const handleApiResponseAsResult = async <T, E>(
   response: Response
 ): Promise<Result<T, E>> => {
   if (!response.ok) {
-    // HTTP error - no structured error data
+    // HTTP error - structured error data when the body carries it
     let errorMessage = `Request failed with status ${response.status}`;
+    let errorData: E | undefined;
 
     try {
-      const errorData = await response.json();
-      if (errorData.message) {
-        errorMessage = errorData.message;
+      const body: ApiResponse<T, E> = await response.json();
+      if (body.message) {
+        errorMessage = body.message;
       }
+      errorData = body.error_data || undefined;
     } catch {
       errorMessage = response.statusText || errorMessage;
     }
 
     return {
       success: false,
-      error: undefined,
+      error: errorData,
       message: errorMessage,
     };
   }
@@ -270,6 +311,83 @@ export const projectsApi = {
     return handleApiResponse<void>(response);
   },
 
+  getArchived: async (): Promise<Project[]> => {
+    const response = await makeRequest('/api/projects?archived=true');
+    return handleApiResponse<Project[]>(response);
+  },
+
+  archive: async (id: string): Promise<void> => {
+    const response = await makeRequest(`/api/projects/${id}/archive`, {
+      method: 'POST',
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  unarchive: async (id: string): Promise<void> => {
+    const response = await makeRequest(`/api/projects/${id}/unarchive`, {
+      method: 'POST',
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  // The bundle is returned as parsed JSON, to be saved or passed to `import`
+  export: async (
+    id: string,
+    includeExecutionHistory = false
+  ): Promise<unknown> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/export?include_execution_history=${includeExecutionHistory}`
+    );
+    if (!response.ok) {
+      return handleApiResponse<unknown>(response);
+    }
+    return response.json();
+  },
+
+  import: async (
+    bundle: unknown,
+    onNameConflict: NameConflict = 'rename'
+  ): Promise<ProjectImport> => {
+    const response = await makeRequest(
+      `/api/projects/import?on_name_conflict=${onNameConflict}`,
+      {
+        method: 'POST',
+        body: JSON.stringify(bundle),
+      }
+    );
+    return handleApiResponse<ProjectImport>(response);
+  },
+
+  importTasks: async (
+    id: string,
+    file: File,
+    options?: TaskImportOptions
+  ): Promise<TaskImportReport> => {
+    const formData = new FormData();
+    formData.append('file', file);
+    if (options) {
+      formData.append('options', JSON.stringify(options));
+    }
+
+    const response = await fetch(`/api/projects/${id}/tasks/import`, {
+      method: 'POST',
+      body: formData,
+      credentials: 'include',
+    });
+    return handleApiResponse<TaskImportReport>(response);
+  },
+
+  importGitHubIssues: async (
+    id: string,
+    data: ImportGitHubIssuesRequest
+  ): Promise<IssueImportReport> => {
+    const response = await makeRequest(`/api/projects/${id}/github/import`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<IssueImportReport>(response);
+  },
+
   openEditor: async (
     id: string,
     data: OpenEditorRequest
@@ -360,6 +478,157 @@ export const projectsApi = {
     );
     return handleApiResponse<void>(response);
   },
+
+  getStatuses: async (projectId: string): Promise<ProjectStatus[]> => {
+    const response = await makeRequest(`/api/projects/${projectId}/statuses`);
+    return handleApiResponse<ProjectStatus[]>(response);
+  },
+
+  createStatus: async (
+    projectId: string,
+    data: CreateProjectStatus
+  ): Promise<ProjectStatus> => {
+    const response = await makeRequest(`/api/projects/${projectId}/statuses`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<ProjectStatus>(response);
+  },
+
+  updateStatus: async (
+    projectId: string,
+    key: string,
+    data: UpdateProjectStatus
+  ): Promise<ProjectStatus> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/statuses/${encodeURIComponent(key)}`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<ProjectStatus>(response);
+  },
+
+  deleteStatus: async (projectId: string, key: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/statuses/${encodeURIComponent(key)}`,
+      {
+        method: 'DELETE',
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  getWipOccupancy: async (projectId: string): Promise<StatusOccupancy[]> => {
+    const response = await makeRequest(`/api/projects/${projectId}/wip`);
+    return handleApiResponse<StatusOccupancy[]>(response);
+  },
+
+  // Both ends are optional ISO timestamps; `to` is exclusive
+  getStats: async (
+    projectId: string,
+    range: { from?: string; to?: string } = {}
+  ): Promise<ProjectStats> => {
+    const params = new URLSearchParams();
+    if (range.from) params.set('from', range.from);
+    if (range.to) params.set('to', range.to);
+    const query = params.toString();
+    const response = await makeRequest(
+      `/api/projects/${projectId}/stats${query ? `?${query}` : ''}`
+    );
+    return handleApiResponse<ProjectStats>(response);
+  },
+
+  getUsage: async (projectId: string): Promise<ProjectUsage> => {
+    const response = await makeRequest(`/api/projects/${projectId}/usage`);
+    return handleApiResponse<ProjectUsage>(response);
+  },
+
+  getSettings: async (projectId: string): Promise<ProjectSettings> => {
+    const response = await makeRequest(`/api/projects/${projectId}/settings`);
+    return handleApiResponse<ProjectSettings>(response);
+  },
+
+  // Only the given settings change; `null` resets one to its default
+  updateSettings: async (
+    projectId: string,
+    settings: { [K in keyof ProjectSettings]?: ProjectSettings[K] | null }
+  ): Promise<ProjectSettings> => {
+    const response = await makeRequest(`/api/projects/${projectId}/settings`, {
+      method: 'PATCH',
+      body: JSON.stringify(settings),
+    });
+    return handleApiResponse<ProjectSettings>(response);
+  },
+
+  getWebhooks: async (projectId: string): Promise<Webhook[]> => {
+    const response = await makeRequest(`/api/projects/${projectId}/webhooks`);
+    return handleApiResponse<Webhook[]>(response);
+  },
+
+  createWebhook: async (
+    projectId: string,
+    data: CreateWebhook
+  ): Promise<Webhook> => {
+    const response = await makeRequest(`/api/projects/${projectId}/webhooks`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Webhook>(response);
+  },
+
+  updateWebhook: async (
+    projectId: string,
+    webhookId: string,
+    data: UpdateWebhook
+  ): Promise<Webhook> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/webhooks/${webhookId}`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<Webhook>(response);
+  },
+
+  deleteWebhook: async (
+    projectId: string,
+    webhookId: string
+  ): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/webhooks/${webhookId}`,
+      {
+        method: 'DELETE',
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  // Sends a `webhook.test` event right away, whatever the webhook's filter
+  testWebhook: async (
+    projectId: string,
+    webhookId: string
+  ): Promise<WebhookDelivery> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/webhooks/${webhookId}/test`,
+      {
+        method: 'POST',
+      }
+    );
+    return handleApiResponse<WebhookDelivery>(response);
+  },
+
+  getWebhookDeliveries: async (
+    projectId: string,
+    webhookId: string
+  ): Promise<WebhookDelivery[]> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/webhooks/${webhookId}/deliveries`
+    );
+    return handleApiResponse<WebhookDelivery[]>(response);
+  },
 };
 
 // Task Management APIs
@@ -377,6 +646,16 @@ export const tasksApi = {
     return handleApiResponse<Task>(response);
   },
 
+  createBulk: async (
+    data: CreateTask[]
+  ): Promise<Result<Task[], TaskBatchItemError[]>> => {
+    const response = await makeRequest(`/api/tasks/bulk`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponseAsResult<Task[], TaskBatchItemError[]>(response);
+  },
+
   createAndStart: async (
     data: CreateAndStartTaskRequest
   ): Promise<TaskWithAttemptStatus> => {
@@ -395,6 +674,36 @@ export const tasksApi = {
     return handleApiResponse<Task>(response);
   },
 
+  move: async (taskId: string, data: MoveTask): Promise<Task> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/move`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Task>(response);
+  },
+
+  moveToProject: async (
+    taskId: string,
+    data: MoveTaskToProject
+  ): Promise<Task> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/move-to-project`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Task>(response);
+  },
+
+  duplicate: async (
+    taskId: string,
+    data: DuplicateTask
+  ): Promise<DuplicatedTask> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/duplicate`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<DuplicatedTask>(response);
+  },
+
   delete: async (taskId: string): Promise<void> => {
     const response = await makeRequest(`/api/tasks/${taskId}`, {
       method: 'DELETE',
@@ -402,6 +711,120 @@ export const tasksApi = {
     return handleApiResponse<void>(response);
   },
 
+  getDeleted: async (projectId: string): Promise<Task[]> => {
+    const response = await makeRequest(
+      `/api/tasks/deleted?project_id=${encodeURIComponent(projectId)}`
+    );
+    return handleApiResponse<Task[]>(response);
+  },
+
+  restore: async (taskId: string): Promise<Task> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/restore`, {
+      method: 'POST',
+    });
+    return handleApiResponse<Task>(response);
+  },
+
+  archive: async (taskId: string): Promise<void> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/archive`, {
+      method: 'POST',
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  unarchive: async (taskId: string): Promise<void> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/unarchive`, {
+      method: 'POST',
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  search: async (
+    projectId: string,
+    q: string,
+    options: { limit?: number; includeArchived?: boolean } = {}
+  ): Promise<TaskSearchResult[]> => {
+    const params = new URLSearchParams({ project_id: projectId, q });
+    if (options.limit !== undefined) params.set('limit', String(options.limit));
+    if (options.includeArchived) params.set('include_archived', 'true');
+    const response = await makeRequest(`/api/tasks/search?${params}`);
+    return handleApiResponse<TaskSearchResult[]>(response);
+  },
+
+  getAssigneeCounts: async (
+    projectId?: string
+  ): Promise<AssigneeTaskCount[]> => {
+    const query = projectId ? `?project_id=${projectId}` : '';
+    const response = await makeRequest(`/api/tasks/assignee-counts${query}`);
+    return handleApiResponse<AssigneeTaskCount[]>(response);
+  },
+
+  getDue: async (withinDays?: number): Promise<DueTasks> => {
+    const query = withinDays !== undefined ? `?within_days=${withinDays}` : '';
+    const response = await makeRequest(`/api/tasks/due${query}`);
+    return handleApiResponse<DueTasks>(response);
+  },
+
+  distributeDueDates: async (taskId: string): Promise<DueDateChange[]> => {
+    const response = await makeRequest(
+      `/api/tasks/${taskId}/distribute-due-dates`,
+      { method: 'POST' }
+    );
+    return handleApiResponse<DueDateChange[]>(response);
+  },
+
+  getEvents: async (
+    taskId: string,
+    before?: number,
+    limit?: number
+  ): Promise<TaskEvent[]> => {
+    const params = new URLSearchParams();
+    if (before !== undefined) params.set('before', String(before));
+    if (limit !== undefined) params.set('limit', String(limit));
+    const query = params.toString();
+    const response = await makeRequest(
+      `/api/tasks/${taskId}/events${query ? `?${query}` : ''}`
+    );
+    return handleApiResponse<TaskEvent[]>(response);
+  },
+
+  getExternalLinks: async (taskId: string): Promise<TaskExternalLink[]> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/external-links`);
+    return handleApiResponse<TaskExternalLink[]>(response);
+  },
+
+  getUsage: async (taskId: string): Promise<TaskUsage> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/usage`);
+    return handleApiResponse<TaskUsage>(response);
+  },
+
+  getDependencies: async (taskId: string): Promise<TaskDependencies> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/dependencies`);
+    return handleApiResponse<TaskDependencies>(response);
+  },
+
+  addDependency: async (
+    taskId: string,
+    data: CreateTaskDependency
+  ): Promise<TaskDependency> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/dependencies`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<TaskDependency>(response);
+  },
+
+  removeDependency: async (
+    taskId: string,
+    blockerId: string
+  ): Promise<void> => {
+    const response = await makeRequest(
+      `/api/tasks/${taskId}/dependencies?blocker_id=${encodeURIComponent(blockerId)}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<void>(response);
+  },
+
   share: async (taskId: string): Promise<ShareTaskResponse> => {
     const response = await makeRequest(`/api/tasks/${taskId}/share`, {
       method: 'POST',
@@ -819,6 +1242,13 @@ export const executionProcessesApi = {
     return handleApiResponse<ExecutionProcessRepoState[]>(response);
   },
 
+  getProcessIds: async (processId: string): Promise<ProcessIds | null> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/process-ids`
+    );
+    return handleApiResponse<ProcessIds | null>(response);
+  },
+
   stopExecutionProcess: async (processId: string): Promise<void> => {
     const response = await makeRequest(
       `/api/execution-processes/${processId}/stop`,
@@ -995,6 +1425,50 @@ export const tagsApi = {
   },
 };
 
+// Task Templates APIs
+export const taskTemplatesApi = {
+  list: async (projectId: string): Promise<TaskTemplate[]> => {
+    const response = await makeRequest(
+      `/api/task-templates?project_id=${encodeURIComponent(projectId)}`
+    );
+    return handleApiResponse<TaskTemplate[]>(response);
+  },
+
+  create: async (data: CreateTaskTemplate): Promise<TaskTemplate> => {
+    const response = await makeRequest('/api/task-templates', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<TaskTemplate>(response);
+  },
+
+  update: async (
+    templateId: string,
+    data: UpdateTaskTemplate
+  ): Promise<TaskTemplate> => {
+    const response = await makeRequest(`/api/task-templates/${templateId}`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<TaskTemplate>(response);
+  },
+
+  delete: async (templateId: string): Promise<void> => {
+    const response = await makeRequest(`/api/task-templates/${templateId}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  instantiate: async (templateId: string): Promise<Task> => {
+    const response = await makeRequest(
+      `/api/task-templates/${templateId}/instantiate`,
+      { method: 'POST' }
+    );
+    return handleApiResponse<Task>(response);
+  },
+};
+
 // MCP Servers APIs
 export const mcpServersApi = {
   load: async (query: McpServerQuery): Promise<GetMcpServerResponse> => {
@@ -1045,6 +1519,16 @@ export const profilesApi = {
     });
     return handleApiResponse<string>(response);
   },
+  report: async (): Promise<ProfilesReport> => {
+    const response = await makeRequest('/api/profiles/report');
+    return handleApiResponse<ProfilesReport>(response);
+  },
+  reload: async (): Promise<ProfilesReport> => {
+    const response = await makeRequest('/api/profiles/reload', {
+      method: 'POST',
+    });
+    return handleApiResponse<ProfilesReport>(response);
+  },
 };
 
 // Images API
@@ -1158,6 +1642,28 @@ export const approvalsApi = {
 
     return handleApiResponse<ApprovalStatus>(res);
   },
+
+  getPending: async (attemptId: string): Promise<PendingApprovalInfo[]> => {
+    const res = await makeRequest(
+      `/api/task-attempts/${attemptId}/approvals/pending`
+    );
+    return handleApiResponse<PendingApprovalInfo[]>(res);
+  },
+
+  respondToPending: async (
+    attemptId: string,
+    status: ApprovalStatus
+  ): Promise<RespondToPendingApprovalsResponse> => {
+    const res = await makeRequest(
+      `/api/task-attempts/${attemptId}/approvals/respond`,
+      {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ status }),
+      }
+    );
+    return handleApiResponse<RespondToPendingApprovalsResponse>(res);
+  },
 };
 
 // OAuth API
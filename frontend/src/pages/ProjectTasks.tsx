@@ -74,6 +74,7 @@ import { AttemptHeaderActions } from '@/components/panels/AttemptHeaderActions';
 import { TaskPanelHeaderActions } from '@/components/panels/TaskPanelHeaderActions';
 
 import type { TaskWithAttemptStatus, TaskStatus } from 'shared/types';
+import { compareBoardOrder, priorityRank } from '@/utils/taskOrder';
 
 type Task = TaskWithAttemptStatus;
 
@@ -145,6 +146,7 @@ export function ProjectTasks() {
   const isXL = useMediaQuery('(min-width: 1280px)');
   const isMobile = !isXL;
   const posthog = usePostHog();
+  const [moveError, setMoveError] = useState<string | null>(null);
   const [selectedSharedTaskId, setSelectedSharedTaskId] = useState<
     string | null
   >(null);
@@ -482,8 +484,19 @@ export function ProjectTasks() {
       return new Date(createdAt).getTime();
     };
 
+    // Shared tasks without a local copy carry no priority
+    const getPriorityRank = (item: KanbanColumnItem) =>
+      item.type === 'task'
+        ? priorityRank[item.task.priority]
+        : priorityRank.medium;
+
     TASK_STATUSES.forEach((status) => {
-      columns[status].sort((a, b) => getTimestamp(b) - getTimestamp(a));
+      columns[status].sort((a, b) =>
+        a.type === 'task' && b.type === 'task'
+          ? compareBoardOrder(a.task, b.task)
+          : getPriorityRank(b) - getPriorityRank(a) ||
+            getTimestamp(b) - getTimestamp(a)
+      );
     });
 
     return columns;
@@ -802,26 +815,39 @@ export function ProjectTasks() {
   const handleDragEnd = useCallback(
     async (event: DragEndEvent) => {
       const { active, over } = event;
-      if (!over || !active.data.current) return;
+      if (!over || !active.data.current || over.id === active.id) return;
 
       const draggedTaskId = active.id as string;
-      const newStatus = over.id as Task['status'];
       const task = tasksById[draggedTaskId];
-      if (!task || task.status === newStatus) return;
+      if (!task) return;
+
+      // Dropped on a card: go below it. Dropped on a column: go to its end.
+      const overTask = tasksById[over.id as string];
+      const newStatus = overTask
+        ? overTask.status
+        : (over.id as Task['status']);
+      let afterTaskId: string | null = overTask?.id ?? null;
+      if (!overTask) {
+        if (task.status === newStatus) return;
+        const ownCards = (kanbanColumns[newStatus] ?? []).filter(
+          (item) => item.type === 'task'
+        );
+        afterTaskId = ownCards[ownCards.length - 1]?.task.id ?? null;
+      }
 
+      setMoveError(null);
       try {
-        await tasksApi.update(draggedTaskId, {
-          title: task.title,
-          description: task.description,
+        await tasksApi.move(draggedTaskId, {
           status: newStatus,
-          parent_workspace_id: task.parent_workspace_id,
-          image_ids: null,
+          after_task_id: afterTaskId,
         });
       } catch (err) {
-        console.error('Failed to update task status:', err);
+        console.error('Failed to move task:', err);
+        // Rejected moves, like a transition the project's rules forbid, say why
+        setMoveError(err instanceof Error ? err.message : String(err));
       }
     },
-    [tasksById]
+    [tasksById, kanbanColumns]
   );
 
   const getSharedTask = useCallback(
@@ -904,6 +930,22 @@ export function ProjectTasks() {
       </div>
     ) : (
       <div className="w-full h-full overflow-x-auto overflow-y-auto overscroll-x-contain">
+        {moveError && (
+          <Alert variant="destructive" className="sticky left-0 top-0 z-20">
+            <AlertTriangle size="16" />
+            <AlertTitle className="flex items-center justify-between gap-2">
+              {t('kanban.moveFailed')}
+              <Button
+                variant="icon"
+                aria-label={t('common:buttons.close')}
+                onClick={() => setMoveError(null)}
+              >
+                <X size={16} />
+              </Button>
+            </AlertTitle>
+            <AlertDescription>{moveError}</AlertDescription>
+          </Alert>
+        )}
         <TaskKanbanBoard
           columns={kanbanColumns}
           onDragEnd={handleDragEnd}
@@ -1,7 +1,11 @@
 // streamJsonPatchEntries.ts - WebSocket JSON patch streaming utility
 import { applyPatch, type Operation } from 'rfc6902';
 
-type PatchContainer<E = unknown> = { entries: E[] };
+type PatchContainer<E = unknown> = {
+  entries: E[];
+  /** Transient keep-alive entry, set while a running agent is silent */
+  heartbeat?: E;
+};
 
 export interface StreamOptions<E = unknown> {
   initial?: PatchContainer<E>;
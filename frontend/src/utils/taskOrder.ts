@@ -0,0 +1,24 @@
+import { TaskPriority, TaskWithAttemptStatus } from 'shared/types';
+
+// Higher sorts first on the board
+export const priorityRank: Record<TaskPriority, number> = {
+  low: 0,
+  medium: 1,
+  high: 2,
+  urgent: 3,
+};
+
+// Priority first, then manual position, then newest first
+export const compareBoardOrder = (
+  a: TaskWithAttemptStatus,
+  b: TaskWithAttemptStatus
+): number => {
+  const byPriority = priorityRank[b.priority] - priorityRank[a.priority];
+  if (byPriority !== 0) return byPriority;
+  if (a.position !== b.position) {
+    if (a.position === null) return 1;
+    if (b.position === null) return -1;
+    return a.position < b.position ? -1 : 1;
+  }
+  return new Date(b.created_at).getTime() - new Date(a.created_at).getTime();
+};
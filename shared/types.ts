@@ -16,7 +16,25 @@ export type Project = { id: string, name: string, default_agent_working_dir: str
 
 export type CreateProject = { name: string, repositories: Array<CreateProjectRepo>, };
 
-export type UpdateProject = { name: string | null, };
+export type UpdateProject = { name: string | null, 
+/**
+ * Let tasks move between any statuses instead of following the lifecycle
+ */
+relaxed_status_transitions?: boolean, 
+/**
+ * Leave tasks created from another task's attempts out of WIP limits
+ */
+wip_limit_excludes_child_tasks?: boolean, };
+
+export type ProjectSettings = { 
+/**
+ * Let tasks move between any statuses instead of following the lifecycle
+ */
+relaxed_status_transitions: boolean, 
+/**
+ * Leave tasks created from another task's attempts out of WIP limits
+ */
+wip_limit_excludes_child_tasks: boolean, };
 
 export type SearchResult = { path: string, is_file: boolean, match_type: SearchMatchType, 
 /**
@@ -48,15 +66,420 @@ export type UpdateTag = { tag_name: string | null, content: string | null, };
 
 export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelled";
 
-export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, shared_task_id: string | null, created_at: string, updated_at: string, };
+/**
+ * A board column of a project. Its `category` decides how tasks in it
+ * behave; the built-in columns use their category as key.
+ */
+export type ProjectStatus = { id: string, project_id: string, key: string, name: string, category: TaskStatus, sort_order: number, 
+/**
+ * Most tasks the column should hold at once; unlimited when unset
+ */
+wip_limit: number | null, created_at: string, updated_at: string, };
+
+export type CreateProjectStatus = { key: string, name: string, category: TaskStatus, 
+/**
+ * Defaults to after the last column
+ */
+sort_order?: number, wip_limit?: number, };
+
+export type UpdateProjectStatus = { name: string | null, sort_order: number | null, 
+/**
+ * `null` removes the limit
+ */
+wip_limit?: number | null, };
+
+/**
+ * A task counted against a column's WIP limit
+ */
+export type StatusOccupant = { id: string, title: string, };
+
+export type StatusOccupancy = { status_key: string, name: string, wip_limit: number | null, task_count: number, };
+
+export type StatusCount = { status: TaskStatus, count: number, };
+
+/**
+ * Time tasks spent in a status before moving on. Tasks that skipped the
+ * status do not count; a task that came back to it counts once, with the
+ * time of all its visits added up.
+ */
+export type StatusDuration = { status: TaskStatus, 
+/**
+ * Tasks that left the status
+ */
+tasks: number, 
+/**
+ * Times tasks left the status, more than `tasks` when some came back
+ */
+visits: number, 
+/**
+ * Average per task
+ */
+avg_seconds: number, };
+
+/**
+ * Nearest-rank percentiles of a duration, empty when nothing was measured
+ */
+export type DurationPercentiles = { count: number, mean_seconds: number | null, p50_seconds: number | null, p75_seconds: number | null, p90_seconds: number | null, };
+
+export type WeeklyThroughput = { 
+/**
+ * Monday the week starts on, in UTC
+ */
+week_start: string, 
+/**
+ * Tasks that reached Done in the week and are still there
+ */
+completed: number, };
+
+export type UsageTotals = { 
+/**
+ * Finished coding agent runs
+ */
+runs: number, 
+/**
+ * Runs without reported token usage, left out of the sums below
+ */
+unreported_runs: number, 
+/**
+ * Tokens of the runs that reported usage, none when no run did
+ */
+total_tokens: number | null, 
+/**
+ * Time the runs with a usage row spent running
+ */
+duration_ms: number | null, };
+
+/**
+ * Usage of one attempt at a task
+ */
+export type AttemptUsage = { workspace_id: string, branch: string, usage: UsageTotals, };
+
+export type TaskUsage = { 
+/**
+ * All attempts added up
+ */
+total: UsageTotals, 
+/**
+ * Attempts with finished runs, oldest first
+ */
+attempts: Array<AttemptUsage>, };
+
+/**
+ * Usage of the runs started in a calendar month, in UTC
+ */
+export type MonthlyUsage = { 
+/**
+ * `YYYY-MM`
+ */
+month: string, usage: UsageTotals, };
+
+export type ProjectUsage = { 
+/**
+ * All tasks added up, archived and deleted ones included
+ */
+total: UsageTotals, 
+/**
+ * Months with finished runs, oldest first
+ */
+months: Array<MonthlyUsage>, };
+
+/**
+ * Declared from least to most urgent, so `Ord` follows urgency
+ */
+export type TaskPriority = "low" | "medium" | "high" | "urgent";
 
-export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, last_attempt_failed: boolean, executor: string, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, shared_task_id: string | null, created_at: string, updated_at: string, };
+export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, shared_task_id: string | null, priority: TaskPriority, 
+/**
+ * Manual order within the status column, compared as a plain string
+ */
+position: string | null, assignee: string | null, due_date: string | null, 
+/**
+ * Share of the parent's schedule when due dates are distributed
+ */
+schedule_weight: number, 
+/**
+ * Project status the task sits in, one whose category is `status`
+ */
+status_key: string, created_at: string, updated_at: string, };
+
+export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, last_attempt_failed: boolean, executor: string, 
+/**
+ * Some task blocking this one is not Done yet
+ */
+blocked: boolean, 
+/**
+ * Set while the task is archived and read-only
+ */
+archived_at: string | null, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, shared_task_id: string | null, priority: TaskPriority, 
+/**
+ * Manual order within the status column, compared as a plain string
+ */
+position: string | null, assignee: string | null, due_date: string | null, 
+/**
+ * Share of the parent's schedule when due dates are distributed
+ */
+schedule_weight: number, 
+/**
+ * Project status the task sits in, one whose category is `status`
+ */
+status_key: string, created_at: string, updated_at: string, };
+
+export type TaskSearchResult = { 
+/**
+ * Task owning the workspace this one was created from, to group
+ * results under their parent
+ */
+parent_task_id: string | null, 
+/**
+ * Title with matches wrapped in `<mark>` tags
+ */
+title_highlight: string, 
+/**
+ * Excerpt of the description around the matches, if any matched
+ */
+description_snippet: string | null, 
+/**
+ * BM25 score, lower is a better match
+ */
+rank: number, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, shared_task_id: string | null, priority: TaskPriority, 
+/**
+ * Manual order within the status column, compared as a plain string
+ */
+position: string | null, assignee: string | null, due_date: string | null, 
+/**
+ * Share of the parent's schedule when due dates are distributed
+ */
+schedule_weight: number, 
+/**
+ * Project status the task sits in, one whose category is `status`
+ */
+status_key: string, created_at: string, updated_at: string, };
 
 export type TaskRelationships = { parent_task: Task | null, current_workspace: Workspace, children: Array<Task>, };
 
-export type CreateTask = { project_id: string, title: string, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, image_ids: Array<string> | null, shared_task_id: string | null, };
+export type CreateTask = { project_id: string, title: string, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, image_ids: Array<string> | null, shared_task_id: string | null, 
+/**
+ * Defaults to the parent task's priority, or Medium without a parent
+ */
+priority?: TaskPriority, assignee?: string, 
+/**
+ * Must not be in the past
+ */
+due_date?: string, schedule_weight?: number, };
+
+export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, image_ids: Array<string> | null, 
+/**
+ * Move to In Progress even while blockers are not Done
+ */
+ignore_blockers?: boolean, priority?: TaskPriority, 
+/**
+ * Also apply a priority change to tasks created from this one's
+ * workspaces, and to theirs in turn
+ */
+propagate_priority?: boolean, 
+/**
+ * An empty string unassigns the task
+ */
+assignee?: string, 
+/**
+ * `null` clears the due date
+ */
+due_date?: string | null, schedule_weight?: number, 
+/**
+ * With a new due date, spread it over the tasks created from this one
+ */
+redistribute_due_dates?: boolean, 
+/**
+ * Change status even when the target column is at its WIP limit
+ */
+override_wip_limit?: boolean, };
+
+export type MoveTask = { status: TaskStatus, 
+/**
+ * Task to place this one directly below; the top of the column if unset
+ */
+after_task_id: string | null, 
+/**
+ * Custom project status within `status` to put the task in
+ */
+status_key?: string, 
+/**
+ * Move even when the target column is at its WIP limit
+ */
+override_wip_limit?: boolean, };
+
+export type MoveTaskToProject = { project_id: string, 
+/**
+ * Drop dependencies on tasks outside the target project and unlink
+ * shared tasks; both are kept otherwise
+ */
+sever_links?: boolean, };
+
+export type DuplicateTask = { 
+/**
+ * Also recreate the tasks created from this task's attempts
+ */
+include_children: boolean, 
+/**
+ * Carry each child's description over; otherwise children start blank
+ */
+copy_child_descriptions: boolean, };
+
+export type DuplicatedTask = { task: Task, 
+/**
+ * In the order the originals were created
+ */
+children: Array<Task>, };
+
+export type AssigneeTaskCount = { project_id: string, 
+/**
+ * `None` counts the project's unassigned tasks
+ */
+assignee: string | null, open_tasks: number, };
+
+/**
+ * A due date set on a task by [`Task::distribute_due_dates`]
+ */
+export type DueDateChange = { task_id: string, previous: string | null, due_date: string, };
+
+/**
+ * An open task with a due date, for the cross-project dashboard
+ */
+export type DueTask = { project_name: string, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, shared_task_id: string | null, priority: TaskPriority, 
+/**
+ * Manual order within the status column, compared as a plain string
+ */
+position: string | null, assignee: string | null, due_date: string | null, 
+/**
+ * Share of the parent's schedule when due dates are distributed
+ */
+schedule_weight: number, 
+/**
+ * Project status the task sits in, one whose category is `status`
+ */
+status_key: string, created_at: string, updated_at: string, };
+
+export type TaskBatchItemError = { 
+/**
+ * Position of the rejected task in the submitted batch
+ */
+index: number, message: string, };
+
+/**
+ * `blocker_id` must be Done before `blocked_id` may move to In Progress
+ */
+export type TaskDependency = { blocker_id: string, blocked_id: string, created_at: string, };
+
+export type CreateTaskDependency = { blocker_id: string, };
+
+export type TaskDependencies = { 
+/**
+ * Tasks that must be Done before this one can start
+ */
+blockers: Array<Task>, 
+/**
+ * Tasks waiting on this one
+ */
+dependents: Array<Task>, };
+
+export type TaskEventType = "created" | "status_change" | "priority_change" | "assignee_change" | "due_date_change" | "project_change" | "attempt_started" | "attempt_finished" | "dependency_added" | "dependency_removed" | "archived" | "unarchived" | "deleted" | "restored" | "wip_limit_override" | "external_conflict";
+
+/**
+ * Who or what caused a task mutation
+ */
+export type TaskEventActor = "user" | "system";
+
+export type TaskEvent = { id: number, task_id: string, event_type: TaskEventType, old_value: JsonValue | null, new_value: JsonValue | null, actor: TaskEventActor, created_at: Date, };
+
+export type ExternalProvider = "github";
+
+export type ExternalIssueState = "open" | "closed";
+
+/**
+ * A task tied to the issue in an external tracker it was imported from
+ */
+export type TaskExternalLink = { id: string, task_id: string, provider: ExternalProvider, 
+/**
+ * `owner/name` for GitHub
+ */
+repository: string, 
+/**
+ * The issue number for GitHub
+ */
+external_id: string, url: string, 
+/**
+ * Issue state as of the last import or sync
+ */
+remote_state: ExternalIssueState, 
+/**
+ * Last status change of the task pushed to the issue
+ */
+synced_event_id: number, created_at: string, updated_at: string, };
+
+export type TaskTemplate = { id: string, project_id: string, title: string, description: string | null, priority: TaskPriority, assignee: string | null, 
+/**
+ * Five-field cron expression in UTC; templates without one are only
+ * instantiated by hand
+ */
+recurrence: string | null, enabled: boolean, created_at: string, updated_at: string, 
+/**
+ * The latest scheduled occurrence a task was created for
+ */
+last_occurrence_at: string | null, };
+
+export type CreateTaskTemplate = { project_id: string, title: string, description: string | null, priority?: TaskPriority, assignee?: string, recurrence?: string, };
+
+export type UpdateTaskTemplate = { title: string | null, 
+/**
+ * Empty string clears the description
+ */
+description: string | null, priority: TaskPriority | null, 
+/**
+ * Empty string clears the assignee
+ */
+assignee: string | null, 
+/**
+ * Empty string removes the schedule
+ */
+recurrence: string | null, enabled: boolean | null, };
+
+/**
+ * Events a webhook can subscribe to
+ */
+export type WebhookEventType = "task.created" | "task.status_changed" | "attempt.started" | "attempt.failed" | "webhook.test";
+
+export type WebhookDeliveryStatus = "pending" | "delivered" | "failed";
+
+export type Webhook = { id: string, project_id: string, url: string, 
+/**
+ * Key of the HMAC-SHA256 signature sent with every payload
+ */
+secret: string, 
+/**
+ * Events sent to the webhook; empty sends all of them
+ */
+events: WebhookEventType[], enabled: boolean, created_at: string, updated_at: string, };
+
+export type CreateWebhook = { url: string, 
+/**
+ * Generated when left out
+ */
+secret: string | null, events: Array<WebhookEventType>, enabled: boolean | null, };
+
+export type UpdateWebhook = { url: string | null, secret: string | null, events: Array<WebhookEventType> | null, enabled: boolean | null, };
 
-export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, image_ids: Array<string> | null, };
+/**
+ * One payload for one webhook, kept after it is sent for debugging
+ */
+export type WebhookDelivery = { id: string, webhook_id: string, event_type: string, payload: JsonValue, status: WebhookDeliveryStatus, attempts: number, 
+/**
+ * HTTP status of the last attempt, when the receiver answered
+ */
+response_status: number | null, 
+/**
+ * Why the last attempt failed
+ */
+error: string | null, next_attempt_at: string, created_at: string, updated_at: string, };
 
 export type DraftFollowUpData = { message: string, variant: string | null, };
 
@@ -100,6 +523,12 @@ export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codin
 
 export type ExecutionProcessRepoState = { id: string, execution_process_id: string, repo_id: string, before_head_commit: string | null, after_head_commit: string | null, merge_commit: string | null, created_at: Date, updated_at: Date, };
 
+export type ApprovalDecision = "approved" | "denied" | "timed_out";
+
+export type ApprovalDecider = "user" | "auto_rule" | "timeout";
+
+export type ApprovalEvent = { id: string, workspace_id: string, execution_process_id: string, executor: string | null, tool_name: string, tool_call_id: string, tool_summary: string, decision: ApprovalDecision, decider: ApprovalDecider, reason: string | null, requested_at: Date, resolved_at: Date, created_at: Date, };
+
 export type Merge = { "type": "direct" } & DirectMerge | { "type": "pr" } & PrMerge;
 
 export type DirectMerge = { id: string, workspace_id: string, repo_id: string, merge_commit: string, target_branch_name: string, created_at: string, };
@@ -130,6 +559,10 @@ export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "cop
 
 export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, };
 
+export type IndexedLogMsg = { index: number, msg: JsonValue, };
+
+export type LogPage = { entries: Array<IndexedLogMsg>, next_cursor: number | null, };
+
 export type LoginStatus = { "status": "loggedout" } | { "status": "loggedin", profile: ProfileResponse, };
 
 export type ProfileResponse = { user_id: string, username: string | null, email: string, providers: Array<ProviderProfile>, };
@@ -190,12 +623,42 @@ export type CreateRemoteProjectRequest = { organization_id: string, name: string
 
 export type LinkToExistingRequest = { remote_project_id: string, };
 
+export type ImportGitHubIssuesRequest = { 
+/**
+ * `owner/name` or a github.com URL
+ */
+repository: string, 
+/**
+ * Also import closed issues, as Done tasks
+ */
+include_closed?: boolean, };
+
 export type RegisterRepoRequest = { path: string, display_name: string | null, };
 
 export type InitRepoRequest = { parent_path: string, folder_name: string, };
 
 export type TagSearchParams = { search: string | null, };
 
+export type TaskTemplateQuery = { project_id: string, };
+
+export type RespondToPendingApprovalsRequest = { status: ApprovalStatus, };
+
+export type RespondToPendingApprovalsResponse = { 
+/**
+ * Approval ids resolved by this call
+ */
+resolved: Array<string>, 
+/**
+ * Approval ids that were resolved elsewhere (or timed out) first
+ */
+skipped: Array<string>, };
+
+export type PendingApprovalInfo = { approval_id: string, execution_process_id: string, tool_name: string, };
+
+export type CleanupAcpSessionsRequest = { older_than_days: number | null, keep_latest_per_task: number | null, };
+
+export type ListAcpSessionsQuery = { offset: number | null, limit: number | null, };
+
 export type TokenResponse = { access_token: string, expires_at: string | null, };
 
 export type UserSystemInfo = { config: Config, analytics_user_id: string, login_status: LoginStatus, environment: Environment, 
@@ -246,6 +709,12 @@ export type AssignSharedTaskRequest = { new_assignee_user_id: string | null, };
 
 export type ShareTaskResponse = { shared_task_id: string, };
 
+export type DueTasks = { overdue: Array<DueTask>, 
+/**
+ * Due today or within the requested number of days
+ */
+upcoming: Array<DueTask>, };
+
 export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id: ExecutorProfileId, repos: Array<WorkspaceRepoInput>, };
 
 export type CreatePrApiRequest = { title: string, body: string | null, target_branch: string | null, draft: boolean | null, repo_id: string, auto_generate_description: boolean, };
@@ -304,6 +773,110 @@ export type UnifiedPrComment = { "comment_type": "general", id: string, author:
 
 export type ProviderKind = "git_hub" | "azure_dev_ops" | "unknown";
 
+/**
+ * A move past a full column that was forced through
+ */
+export type WipLimitOverride = { status_key: string, limit: number, 
+/**
+ * Tasks already in the column when the limit was overridden
+ */
+task_count: number, };
+
+/**
+ * What to do when a project with the bundle's name already exists
+ */
+export type NameConflict = "rename" | "skip";
+
+export type ProjectImport = { "outcome": "imported", project_id: string, name: string, } | { "outcome": "skipped", existing_project_id: string, };
+
+export type IssueImportReport = { 
+/**
+ * Tasks created, one per issue
+ */
+task_ids: Array<string>, 
+/**
+ * Issues already imported into the project
+ */
+already_linked: number, };
+
+export type TaskImportFormat = "csv" | "json";
+
+/**
+ * The column (CSV) or key (JSON) each task field is read from, matched
+ * ignoring case. Fields whose column is missing from the file are left
+ * at their defaults.
+ */
+export type ColumnMapping = { title: string, description: string, 
+/**
+ * Key or name of one of the project's statuses
+ */
+status: string, priority: string, assignee: string, 
+/**
+ * As YYYY-MM-DD
+ */
+due_date: string, };
+
+export type TaskImportOptions = { 
+/**
+ * Taken from the file name when unset
+ */
+format?: TaskImportFormat, mapping: ColumnMapping, 
+/**
+ * Create nothing if any row is invalid
+ */
+all_or_nothing: boolean, 
+/**
+ * Report what the import would do without creating anything
+ */
+dry_run: boolean, };
+
+export type TaskImportRowError = { 
+/**
+ * 1-based position among the file's rows, not counting a CSV header
+ */
+row: number, message: string, };
+
+export type TaskImportReport = { total_rows: number, 
+/**
+ * Tasks created, or that would have been in a dry run
+ */
+imported: number, 
+/**
+ * Ids of the created tasks, empty in a dry run
+ */
+task_ids: Array<string>, errors: Array<TaskImportRowError>, dry_run: boolean, };
+
+/**
+ * One change to a task of a project
+ */
+export type ProjectChange = { 
+/**
+ * Position on the bus, sent as the SSE event id
+ */
+id: number, project_id: string, 
+/**
+ * `task.status_changed`, `attempt.started`, ...
+ */
+event_type: string, task_id: string, old_value: JsonValue | null, new_value: JsonValue | null, actor: TaskEventActor, occurred_at: string, };
+
+export type ProjectStats = { 
+/**
+ * Current tasks per status, regardless of the range
+ */
+status_counts: Array<StatusCount>, status_durations: Array<StatusDuration>, 
+/**
+ * From first start to completion
+ */
+cycle_time: DurationPercentiles, 
+/**
+ * From creation to completion
+ */
+lead_time: DurationPercentiles, 
+/**
+ * Every week from the first to the last with completions
+ */
+weekly_throughput: Array<WeeklyThroughput>, };
+
 export type RepoBranchStatus = { repo_id: string, repo_name: string, commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, target_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, 
 /**
  * True if a `git rebase` is currently in progress in this worktree
@@ -374,7 +947,25 @@ export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_pa
 
 export type SearchMode = "taskform" | "settings";
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, beta_workspaces: boolean, beta_workspaces_invitation_sent: boolean, commit_reminder: boolean, };
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, beta_workspaces: boolean, beta_workspaces_invitation_sent: boolean, commit_reminder: boolean, execution_output_limit_bytes: bigint | null, kill_on_output_overflow: boolean, execution_heartbeat_secs: bigint | null, retain_scratch_on_failure: boolean, scratch_dir_limit_bytes: bigint | null, 
+/**
+ * Connections the app keeps to its database, the default when unset
+ */
+database_max_connections: number | null, 
+/**
+ * How long a database write waits for another one to finish before
+ * failing, the default when unset
+ */
+database_busy_timeout_ms: bigint | null, 
+/**
+ * Days a deleted task can be restored before it is purged, the default
+ * when unset
+ */
+deleted_task_retention_days: number | null, 
+/**
+ * Days task activity log entries are kept, the default when unset
+ */
+task_event_retention_days: number | null, };
 
 export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
 
@@ -416,6 +1007,12 @@ export type QueueStatus = { "status": "empty" } | { "status": "queued", message:
 
 export type ConflictOp = "rebase" | "merge" | "cherry_pick" | "revert";
 
+export type AcpSessionSummary = { workspace_id: string | null, task_id: string | null, session_id: string, size_bytes: bigint, created_at: string | null, modified_at: string, };
+
+export type AcpSessionPage = { sessions: Array<AcpSessionSummary>, total: number, offset: number, limit: number, };
+
+export type AcpSessionTranscript = { session: AcpSessionSummary, events: Array<JsonValue>, };
+
 export type ExecutorAction = { typ: ExecutorActionType, next_action: ExecutorAction | null, };
 
 export type McpConfig = { servers: { [key in string]?: JsonValue }, servers_path: Array<string>, template: JsonValue, preconfigured: JsonValue, is_toml_config: boolean, };
@@ -439,6 +1036,12 @@ export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GE
 
 export type AvailabilityInfo = { "type": "LOGIN_DETECTED", last_auth_timestamp: bigint, } | { "type": "INSTALLATION_FOUND" } | { "type": "NOT_FOUND" };
 
+export type ProcessIds = { pid: number, pgid: number | null, };
+
+export type ExecutorErrorKind = "invalid_config" | "command_not_found" | "permission_denied" | "auth_required" | "spawn_failed" | "other";
+
+export type SessionFileInfo = { session_id: string, size_bytes: bigint, created_at: string | null, modified_at: string, };
+
 export type CommandBuilder = { 
 /**
  * Base executable command (e.g., "npx -y @anthropic-ai/claude-code@latest")
@@ -463,15 +1066,25 @@ export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } |
 
 export type ExecutorConfigs = { executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
 
+export type ProfileIssueSeverity = "error" | "warning";
+
+export type ProfileIssue = { severity: ProfileIssueSeverity, 
+/**
+ * Executor key as written in the file; `None` for document-level problems
+ */
+executor: string | null, variant: string | null, message: string, };
+
+export type ProfilesReport = { issues: Array<ProfileIssue>, };
+
 export enum BaseAgentCapability { SESSION_FORK = "SESSION_FORK", SETUP_HELPER = "SETUP_HELPER" }
 
-export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, disable_api_key?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, disable_api_key?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, env_file?: string | null, env_remove?: Array<string> | null, env_clear?: boolean | null, path_prepend?: Array<string> | null, redact?: Array<string> | null, output_limit_bytes?: bigint | null, };
 
-export type Gemini = { append_prompt: AppendPrompt, model?: string | null, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Gemini = { append_prompt: AppendPrompt, model?: string | null, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, env_file?: string | null, env_remove?: Array<string> | null, env_clear?: boolean | null, path_prepend?: Array<string> | null, redact?: Array<string> | null, output_limit_bytes?: bigint | null, };
 
-export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, env_file?: string | null, env_remove?: Array<string> | null, env_clear?: boolean | null, path_prepend?: Array<string> | null, redact?: Array<string> | null, output_limit_bytes?: bigint | null, };
 
-export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_apply_patch_tool?: boolean | null, model_provider?: string | null, compact_prompt?: string | null, developer_instructions?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_apply_patch_tool?: boolean | null, model_provider?: string | null, compact_prompt?: string | null, developer_instructions?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, env_file?: string | null, env_remove?: Array<string> | null, env_clear?: boolean | null, path_prepend?: Array<string> | null, redact?: Array<string> | null, output_limit_bytes?: bigint | null, };
 
 export type SandboxMode = "auto" | "read-only" | "workspace-write" | "danger-full-access";
 
@@ -483,19 +1096,19 @@ export type ReasoningSummary = "auto" | "concise" | "detailed" | "none";
 
 export type ReasoningSummaryFormat = "none" | "experimental";
 
-export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, env_file?: string | null, env_remove?: Array<string> | null, env_clear?: boolean | null, path_prepend?: Array<string> | null, redact?: Array<string> | null, output_limit_bytes?: bigint | null, };
 
-export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, env_file?: string | null, env_remove?: Array<string> | null, env_clear?: boolean | null, path_prepend?: Array<string> | null, redact?: Array<string> | null, output_limit_bytes?: bigint | null, };
 
 export type Opencode = { append_prompt: AppendPrompt, model?: string | null, mode?: string | null, 
 /**
  * Auto-approve agent actions
  */
-auto_approve: boolean, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+auto_approve: boolean, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, env_file?: string | null, env_remove?: Array<string> | null, env_clear?: boolean | null, path_prepend?: Array<string> | null, redact?: Array<string> | null, output_limit_bytes?: bigint | null, };
 
-export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, env_file?: string | null, env_remove?: Array<string> | null, env_clear?: boolean | null, path_prepend?: Array<string> | null, redact?: Array<string> | null, output_limit_bytes?: bigint | null, };
 
-export type Droid = { append_prompt: AppendPrompt, autonomy: Autonomy, model?: string | null, reasoning_effort?: DroidReasoningEffort | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Droid = { append_prompt: AppendPrompt, autonomy: Autonomy, model?: string | null, reasoning_effort?: DroidReasoningEffort | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, env_file?: string | null, env_remove?: Array<string> | null, env_clear?: boolean | null, path_prepend?: Array<string> | null, redact?: Array<string> | null, output_limit_bytes?: bigint | null, };
 
 export type Autonomy = "normal" | "low" | "medium" | "high" | "skip-permissions-unsafe";
 
@@ -543,7 +1156,7 @@ export type CommandRunResult = { exit_status: CommandExitStatus | null, output:
 
 export type NormalizedEntry = { timestamp: string | null, entry_type: NormalizedEntryType, content: string, };
 
-export type NormalizedEntryType = { "type": "user_message" } | { "type": "user_feedback", denied_tool: string, } | { "type": "assistant_message" } | { "type": "tool_use", tool_name: string, action_type: ActionType, status: ToolStatus, } | { "type": "system_message" } | { "type": "error_message", error_type: NormalizedEntryError, } | { "type": "thinking" } | { "type": "loading" } | { "type": "next_action", failed: boolean, execution_processes: number, needs_setup: boolean, } | { "type": "token_usage_info" } & TokenUsageInfo;
+export type NormalizedEntryType = { "type": "user_message" } | { "type": "user_feedback", denied_tool: string, } | { "type": "assistant_message" } | { "type": "tool_use", tool_name: string, action_type: ActionType, status: ToolStatus, } | { "type": "system_message" } | { "type": "error_message", error_type: NormalizedEntryError, } | { "type": "thinking" } | { "type": "loading" } | { "type": "heartbeat" } | { "type": "next_action", failed: boolean, execution_processes: number, needs_setup: boolean, } | { "type": "token_usage_info" } & TokenUsageInfo;
 
 export type TokenUsageInfo = { total_tokens: number, model_context_window: number, };
 
@@ -575,6 +1188,22 @@ export type ToolStatus = { "status": "created" } | { "status": "success" } | { "
 
 export type PatchType = { "type": "NORMALIZED_ENTRY", "content": NormalizedEntry } | { "type": "STDOUT", "content": string } | { "type": "STDERR", "content": string } | { "type": "DIFF", "content": Diff };
 
+export type ExecutionSummary = { 
+/**
+ * Paths edited, written, deleted or renamed, in first-touched order
+ */
+files_changed: Array<string>, commands: Array<CommandSummary>, final_message: string | null, 
+/**
+ * Set by the container once the process has exited
+ */
+exit_code: bigint | null, 
+/**
+ * Last token usage the agent reported, none for executors that don't
+ */
+token_usage: TokenUsageInfo | null, };
+
+export type CommandSummary = { command: string, exit_status: CommandExitStatus | null, };
+
 export type JsonValue = number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null;
 
 export const DEFAULT_PR_DESCRIPTION_PROMPT = `Update the PR that was just created with a better title and description.